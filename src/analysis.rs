@@ -0,0 +1,753 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use makai_vcd_reader::parser::{VcdHeader, VcdScope};
+use makai_waveform_db::{
+    bitvector::{BitVector, Logic},
+    WaveformSearchMode, WaveformValueResult,
+};
+
+use crate::source::WaveformSource;
+
+#[cfg(test)]
+use makai_waveform_db::Waveform;
+
+/// Number of buckets the delays between transitions are split into
+const DELAY_BUCKETS: u64 = 8;
+
+/// How many value changes of a signal are looked at when checking for a clock,
+/// which keeps the detection cheap regardless of the length of the dump
+const CLOCK_SAMPLES: usize = 128;
+/// Fewest periods that need to be seen before a signal is considered a clock
+const CLOCK_MIN_PERIODS: usize = 4;
+
+/// A signal that toggles regularly enough to likely be a clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockInfo {
+    /// Timestamps between rising edges
+    pub period: u64,
+}
+
+/// Checks if a single-bit signal toggles regularly, allowing for some gaps
+/// from gating or reset where the clock does not toggle
+pub fn detect_clock(waveform: &(impl WaveformSource + ?Sized), idcode: usize) -> Option<ClockInfo> {
+    if waveform.get_vector_width(idcode) != Some(1) {
+        return None;
+    }
+    let mut rising_edges = Vec::new();
+    let mut last = Logic::Unknown;
+    for (timestamp, bitvector) in waveform.get_vector_changes(idcode, 0).take(CLOCK_SAMPLES) {
+        let bit = bitvector.get_bit(0);
+        if bit == Logic::One && last == Logic::Zero {
+            rising_edges.push(timestamp);
+        }
+        last = bit;
+    }
+    let periods = rising_edges
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .collect::<Vec<u64>>();
+    if periods.len() < CLOCK_MIN_PERIODS {
+        return None;
+    }
+    // The most common period has to account for most of the edges
+    let mut counts = HashMap::new();
+    for period in &periods {
+        *counts.entry(*period).or_insert(0) += 1;
+    }
+    let (period, count) = counts.into_iter().max_by_key(|(p, c)| (*c, *p))?;
+    (count * 4 >= periods.len() * 3).then_some(ClockInfo { period })
+}
+
+fn detect_scope_clocks(
+    scope: &VcdScope,
+    waveform: &(impl WaveformSource + ?Sized),
+    clocks: &mut HashMap<usize, ClockInfo>,
+) {
+    for variable in scope.get_variables() {
+        if variable.get_bit_width() != 1 || clocks.contains_key(&variable.get_idcode()) {
+            continue;
+        }
+        if let Some(clock) = detect_clock(waveform, variable.get_idcode()) {
+            clocks.insert(variable.get_idcode(), clock);
+        }
+    }
+    for scope in scope.get_scopes() {
+        detect_scope_clocks(scope, waveform, clocks);
+    }
+}
+
+/// Finds all of the single-bit signals in the dump that look like clocks
+pub fn detect_clocks(
+    header: &VcdHeader,
+    waveform: &(impl WaveformSource + ?Sized),
+) -> HashMap<usize, ClockInfo> {
+    let mut clocks = HashMap::new();
+    for scope in header.get_scopes() {
+        detect_scope_clocks(scope, waveform, &mut clocks);
+    }
+    clocks
+}
+
+/// Finds every value of a signal (or one bit of it) that held for less than
+/// `width` timestamps, returning the timestamp and width of each pulse
+pub fn find_glitches(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    width: u64,
+) -> Vec<(u64, u64)> {
+    let mut glitches = Vec::new();
+    // The first value has no known start, so it can never be a glitch
+    let mut last = None;
+    let mut start = None;
+    for (timestamp, bitvector) in waveform.get_vector_changes(idcode, 0) {
+        let value = match index {
+            Some(index) => BitVector::from(bitvector.get_bit(index)),
+            None => bitvector,
+        };
+        if last.as_ref() == Some(&value) {
+            continue;
+        }
+        if let Some(start) = start {
+            if timestamp - start < width {
+                glitches.push((start, timestamp - start));
+            }
+        }
+        if last.is_some() {
+            start = Some(timestamp);
+        }
+        last = Some(value);
+    }
+    glitches
+}
+
+/// Checks if a signal (or one bit of it) has any unknown bits at a timestamp
+pub fn is_unknown_at(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    timestamp: u64,
+) -> bool {
+    waveform
+        .search_timestamp(timestamp, WaveformSearchMode::Before)
+        .and_then(|timestamp_index| {
+            waveform.search_value_bit_index(
+                idcode,
+                timestamp_index,
+                WaveformSearchMode::Before,
+                index,
+            )
+        })
+        .is_some_and(|value| value.is_unknown())
+}
+
+/// Checks if a signal (or one bit of it) has any unknown or floating bits at a
+/// timestamp
+pub fn is_unknown_or_floating_at(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    timestamp: u64,
+) -> bool {
+    waveform
+        .search_timestamp(timestamp, WaveformSearchMode::Before)
+        .and_then(|timestamp_index| {
+            waveform.search_value_bit_index(
+                idcode,
+                timestamp_index,
+                WaveformSearchMode::Before,
+                index,
+            )
+        })
+        .is_some_and(|value| value.is_unknown() || value.is_high_impedance())
+}
+
+/// Finds the earliest timestamp of the unknown values a signal (or one bit of
+/// it) has held without a break up to the timestamp, if it is unknown there
+pub fn find_unknown_start(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    timestamp: u64,
+) -> Option<u64> {
+    let mut start = None;
+    for (change, bitvector) in waveform.get_vector_changes(idcode, 0) {
+        if change > timestamp {
+            break;
+        }
+        let unknown = match index {
+            Some(index) => bitvector.get_bit(index) == Logic::Unknown,
+            None => bitvector.is_unknown(),
+        };
+        start = match (unknown, start) {
+            (true, None) => Some(change),
+            (true, start) => start,
+            (false, _) => None,
+        };
+    }
+    start
+}
+
+/// Timestamps of every change in value of a signal (or one bit of it) inside
+/// of the range, not counting the value it starts the range with
+pub fn get_transitions(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    range: &Range<u64>,
+) -> Vec<u64> {
+    let mut transitions = Vec::new();
+    let mut last = None;
+    // Start from the value held going into the range
+    for (timestamp, bitvector) in waveform.get_vector_changes(idcode, range.start) {
+        if timestamp >= range.end {
+            break;
+        }
+        let value = match index {
+            Some(index) => BitVector::from(bitvector.get_bit(index)),
+            None => bitvector,
+        };
+        if last.as_ref() == Some(&value) {
+            continue;
+        }
+        if last.is_some() && timestamp >= range.start {
+            transitions.push(timestamp);
+        }
+        last = Some(value);
+    }
+    transitions
+}
+
+/// The value a signal (or one bit of it) holds at the start of the range and
+/// then every change in value inside of it, each with the timestamp it starts
+/// at (the start of the range for the first one if it began before)
+pub fn get_value_history(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    range: &Range<u64>,
+) -> Vec<(u64, BitVector)> {
+    let mut history: Vec<(u64, BitVector)> = Vec::new();
+    for (timestamp, bitvector) in waveform.get_vector_changes(idcode, range.start) {
+        if timestamp >= range.end {
+            break;
+        }
+        let value = match index {
+            Some(index) => BitVector::from(bitvector.get_bit(index)),
+            None => bitvector,
+        };
+        if history.last().is_some_and(|(_, last)| *last == value) {
+            continue;
+        }
+        history.push((timestamp.max(range.start), value));
+    }
+    history
+}
+
+/// Nearest change in value of a signal (or one bit of it) strictly after the
+/// timestamp, or strictly before it when searching backwards
+pub fn find_transition(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    timestamp: u64,
+    forward: bool,
+) -> Option<u64> {
+    let mut found = None;
+    let mut last = None;
+    for (current, bitvector) in waveform.get_vector_changes(idcode, 0) {
+        if !forward && current >= timestamp {
+            break;
+        }
+        let value = match index {
+            Some(index) => BitVector::from(bitvector.get_bit(index)),
+            None => bitvector,
+        };
+        if last.as_ref() == Some(&value) {
+            continue;
+        }
+        if last.is_some() {
+            if forward && current > timestamp {
+                return Some(current);
+            }
+            found = Some(current);
+        }
+        last = Some(value);
+    }
+    if forward {
+        None
+    } else {
+        found
+    }
+}
+
+/// The value of a search without the timestamp index it was found at
+fn get_search_value(result: &WaveformValueResult) -> (Option<&BitVector>, Option<f64>) {
+    match result {
+        WaveformValueResult::Vector(bv, _) => (Some(bv), None),
+        WaveformValueResult::Real(value, _) => (None, Some(*value)),
+    }
+}
+
+/// Timestamps of the change that gave a signal (or one bit of it) its value at
+/// the timestamp and of the next change after that, skipping over recorded
+/// values that repeat the one before. The first value of a signal is not a
+/// change, and there is nothing to find when the signal has no value yet.
+/// Searches outwards from the timestamp, so only the values in between are
+/// looked at instead of the whole history.
+pub fn find_surrounding_changes(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    timestamp: u64,
+) -> Option<(Option<u64>, Option<u64>)> {
+    if !waveform.has_history(idcode) {
+        return None;
+    }
+    let search = |timestamp_index, search_mode| {
+        waveform.search_value_bit_index(idcode, timestamp_index, search_mode, index)
+    };
+    let current = waveform
+        .search_timestamp(timestamp, WaveformSearchMode::Before)
+        .and_then(|timestamp_index| search(timestamp_index, WaveformSearchMode::Before))?;
+    let value = get_search_value(&current);
+    let mut last = current.get_timestamp_index();
+    let last = loop {
+        if last == 0 {
+            break None;
+        }
+        match search(last - 1, WaveformSearchMode::Before) {
+            Some(before) if get_search_value(&before) == value => {
+                last = before.get_timestamp_index()
+            }
+            Some(_) => break Some(last),
+            None => break None,
+        }
+    };
+    let mut next = current.get_timestamp_index();
+    let next = loop {
+        if waveform.get_timestamp(next + 1).is_none() {
+            break None;
+        }
+        match search(next + 1, WaveformSearchMode::After) {
+            Some(after) if get_search_value(&after) == value => next = after.get_timestamp_index(),
+            after => break after.map(|after| after.get_timestamp_index()),
+        }
+    };
+    Some((
+        last.and_then(|last| waveform.get_timestamp(last)),
+        next.and_then(|next| waveform.get_timestamp(next)),
+    ))
+}
+
+/// Spread of the delays from transitions of one signal to the next
+/// transition of another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayStatistics {
+    pub count: usize,
+    pub min: u64,
+    pub max: u64,
+    pub mean: u64,
+    /// Delays falling in each range, which together cover min to max
+    pub histogram: Vec<(Range<u64>, usize)>,
+}
+
+/// Measures the delay from each transition in `from` to the first transition
+/// in `to` at or after it, where both are sorted timestamps
+pub fn measure_delays(from: &[u64], to: &[u64]) -> Option<DelayStatistics> {
+    let mut delays = Vec::new();
+    let mut next = 0;
+    for timestamp in from {
+        while next < to.len() && to[next] < *timestamp {
+            next += 1;
+        }
+        match to.get(next) {
+            Some(to) => delays.push(to - timestamp),
+            None => break,
+        }
+    }
+    let min = *delays.iter().min()?;
+    let max = *delays.iter().max()?;
+    let bucket = ((max - min) / DELAY_BUCKETS + 1).max(1);
+    let mut histogram = (0..DELAY_BUCKETS)
+        .map(|i| (min + i * bucket..min + (i + 1) * bucket, 0))
+        .take_while(|(range, _)| range.start <= max)
+        .collect::<Vec<(Range<u64>, usize)>>();
+    for delay in &delays {
+        histogram[((delay - min) / bucket) as usize].1 += 1;
+    }
+    Some(DelayStatistics {
+        count: delays.len(),
+        min,
+        max,
+        mean: delays.iter().sum::<u64>() / delays.len() as u64,
+        histogram,
+    })
+}
+
+/// Timestamps of every rising edge of a single bit inside of the range, where
+/// the bit goes from low to high
+pub fn get_rising_edges(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    index: Option<usize>,
+    range: &Range<u64>,
+) -> Vec<u64> {
+    let mut edges = Vec::new();
+    let mut last = Logic::Unknown;
+    // Start from the value held just before the range, so that an edge right
+    // at the start of it still counts
+    let start = range.start.saturating_sub(1);
+    for (timestamp, bitvector) in waveform.get_vector_changes(idcode, start) {
+        if timestamp >= range.end {
+            break;
+        }
+        let bit = bitvector.get_bit(index.unwrap_or(0));
+        if bit == Logic::One && last == Logic::Zero && timestamp >= range.start {
+            edges.push(timestamp);
+        }
+        last = bit;
+    }
+    edges
+}
+
+/// Spread of the periods between consecutive rising edges of a signal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodStatistics {
+    pub count: usize,
+    /// The shortest period along with the edge it starts at
+    pub min: (u64, u64),
+    /// The longest period along with the edge it starts at
+    pub max: (u64, u64),
+    pub mean: u64,
+}
+
+/// Measures the periods between consecutive rising edges, which are sorted
+/// timestamps, keeping the first of any equally short or long periods
+pub fn measure_periods(edges: &[u64]) -> Option<PeriodStatistics> {
+    let periods = edges
+        .windows(2)
+        .map(|w| (w[1] - w[0], w[0]))
+        .collect::<Vec<(u64, u64)>>();
+    let min = *periods.iter().min_by_key(|(period, _)| *period)?;
+    let max = *periods.iter().rev().max_by_key(|(period, _)| *period)?;
+    Some(PeriodStatistics {
+        count: periods.len(),
+        min,
+        max,
+        mean: periods.iter().map(|(period, _)| period).sum::<u64>() / periods.len() as u64,
+    })
+}
+
+/// Most bits put back together into one vector, as many as fit in the values
+/// a virtual vector is built from
+pub const BIT_FAMILY_LIMIT: usize = 64;
+
+/// Splits the name of a single bit that synthesis blasted out of a vector,
+/// like `data_3_` or `data[3]`, into the name of the vector and the bit
+pub fn split_bit_name(name: &str) -> Option<(&str, usize)> {
+    let (base, bit) = match name.strip_suffix('_') {
+        Some(name) => name.rsplit_once('_')?,
+        None => name.strip_suffix(']')?.rsplit_once('[')?,
+    };
+    if base.is_empty() || bit.is_empty() || !bit.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((base, bit.parse().ok()?))
+}
+
+/// Groups the names of single-bit signals in one scope into the vectors they
+/// were blasted from, giving the name of each vector and the positions of its
+/// bits in the names from the lowest bit up. Only families of at least two
+/// bits without any gaps or repeats between them are kept.
+pub fn find_bit_families<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Vec<(String, Vec<usize>)> {
+    let mut families: Vec<(String, Vec<(usize, usize)>)> = Vec::new();
+    for (position, name) in names.into_iter().enumerate() {
+        let Some((base, bit)) = split_bit_name(name) else {
+            continue;
+        };
+        match families.iter_mut().find(|(name, _)| name == base) {
+            Some((_, bits)) => bits.push((bit, position)),
+            None => families.push((String::from(base), vec![(bit, position)])),
+        }
+    }
+    families
+        .into_iter()
+        .filter_map(|(name, mut bits)| {
+            bits.sort();
+            let contiguous = bits.windows(2).all(|w| w[1].0 == w[0].0 + 1);
+            (contiguous && (2..=BIT_FAMILY_LIMIT).contains(&bits.len()))
+                .then(|| (name, bits.into_iter().map(|(_, p)| p).collect()))
+        })
+        .collect()
+}
+
+/// Formats the frequency of a period given in timestamps of the timescale
+pub fn render_frequency(period: u64, timescale: i32) -> String {
+    let hertz = 10f64.powi(timescale) / period.max(1) as f64;
+    let (value, unit) = match hertz {
+        h if h >= 1e9 => (h / 1e9, "GHz"),
+        h if h >= 1e6 => (h / 1e6, "MHz"),
+        h if h >= 1e3 => (h / 1e3, "kHz"),
+        h => (h, "Hz"),
+    };
+    format!("{value:.3}{unit}")
+}
+
+#[test]
+fn detect_clock_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    waveform.initialize_vector(1, 1);
+    waveform.initialize_vector(2, 4);
+    for timestamp in 0..40u64 {
+        waveform.insert_timestamp(timestamp * 5).unwrap();
+        // Gated for a few cycles in the middle
+        if !(20..24).contains(&timestamp) {
+            let bit = if timestamp % 2 == 0 {
+                BitVector::new_zero_bit()
+            } else {
+                BitVector::new_one_bit()
+            };
+            waveform.update_vector(0, bit).unwrap();
+        }
+        if timestamp % 7 == 0 {
+            let bit = if timestamp % 3 == 0 {
+                BitVector::new_zero_bit()
+            } else {
+                BitVector::new_one_bit()
+            };
+            waveform.update_vector(1, bit).unwrap();
+        }
+        waveform
+            .update_vector(2, BitVector::from_bits_two_state(4, timestamp as u8 % 2))
+            .unwrap();
+    }
+    assert_eq!(detect_clock(&waveform, 0), Some(ClockInfo { period: 10 }));
+    assert_eq!(detect_clock(&waveform, 1), None);
+    assert_eq!(detect_clock(&waveform, 2), None);
+    assert_eq!(detect_clock(&waveform, 3), None);
+    assert_eq!(render_frequency(10, 9), "100.000MHz");
+}
+
+#[test]
+fn find_glitches_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 2);
+    for (timestamp, value) in [
+        (0, 0b00u8),
+        (10, 0b01),
+        (12, 0b00),
+        (20, 0b00),
+        (30, 0b10),
+        (31, 0b11),
+        (50, 0b01),
+    ] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform
+            .update_vector(0, BitVector::from_bits_two_state(2, value))
+            .unwrap();
+    }
+    assert_eq!(find_glitches(&waveform, 0, None, 5), vec![(10, 2), (30, 1)]);
+    assert_eq!(find_glitches(&waveform, 0, Some(0), 5), vec![(10, 2)]);
+    assert_eq!(find_glitches(&waveform, 0, Some(1), 25), vec![(30, 20)]);
+    assert_eq!(find_glitches(&waveform, 1, None, 5), vec![]);
+}
+
+#[test]
+fn get_value_history_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 2);
+    for (timestamp, value) in [(0, 0b00u8), (10, 0b01), (20, 0b01), (30, 0b11), (40, 0b10)] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform
+            .update_vector(0, BitVector::from_bits_two_state(2, value))
+            .unwrap();
+    }
+    let timestamps = |index, range| {
+        get_value_history(&waveform, 0, index, &range)
+            .into_iter()
+            .map(|(timestamp, _)| timestamp)
+            .collect::<Vec<u64>>()
+    };
+    // Writing the same value again at 20 is not a change
+    assert_eq!(timestamps(None, 5..41), vec![5, 10, 30, 40]);
+    assert_eq!(timestamps(None, 10..30), vec![10]);
+    assert_eq!(timestamps(Some(1), 0..50), vec![0, 30]);
+    let history = get_value_history(&waveform, 0, Some(0), &(25..35));
+    assert!(history[0].1 == BitVector::from(Logic::One));
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn find_unknown_start_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 2);
+    waveform.initialize_vector(1, 1);
+    let values = [
+        (0, BitVector::from_bits_four_state(2, 0b00u8, 0b11)),
+        (10, BitVector::from_bits_two_state(2, 0b01u8)),
+        (20, BitVector::from_bits_four_state(2, 0b00u8, 0b10)),
+        (30, BitVector::from_bits_four_state(2, 0b00u8, 0b11)),
+        (40, BitVector::from_bits_two_state(2, 0b11u8)),
+    ];
+    for (timestamp, value) in values {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform.update_vector(0, value).unwrap();
+    }
+    assert_eq!(find_unknown_start(&waveform, 0, None, 5), Some(0));
+    assert_eq!(find_unknown_start(&waveform, 0, None, 15), None);
+    assert_eq!(find_unknown_start(&waveform, 0, None, 35), Some(20));
+    assert_eq!(find_unknown_start(&waveform, 0, Some(0), 35), Some(30));
+    assert_eq!(find_unknown_start(&waveform, 0, None, 45), None);
+    assert!(is_unknown_at(&waveform, 0, Some(1), 25));
+    assert!(!is_unknown_at(&waveform, 0, Some(0), 25));
+    // Floating bits count too, which only x is checked for above
+    waveform.insert_timestamp(50).unwrap();
+    waveform
+        .update_vector(1, BitVector::from_bits_four_state(1, 1u8, 1))
+        .unwrap();
+    assert!(is_unknown_or_floating_at(&waveform, 1, None, 55));
+    assert!(!is_unknown_at(&waveform, 1, None, 55));
+    assert!(is_unknown_or_floating_at(&waveform, 0, None, 35));
+    assert!(!is_unknown_or_floating_at(&waveform, 0, None, 45));
+}
+
+#[test]
+fn measure_delays_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    for (timestamp, bit) in [
+        (0, Logic::Zero),
+        (10, Logic::One),
+        (20, Logic::One),
+        (30, Logic::Zero),
+    ] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform.update_vector(0, BitVector::from(bit)).unwrap();
+    }
+    assert_eq!(get_transitions(&waveform, 0, None, &(0..40)), vec![10, 30]);
+    assert_eq!(get_transitions(&waveform, 0, None, &(15..40)), vec![30]);
+    assert_eq!(get_transitions(&waveform, 0, Some(0), &(0..30)), vec![10]);
+
+    let statistics = measure_delays(&[0, 10, 20, 30, 40], &[2, 13, 20, 50]).unwrap();
+    assert_eq!(statistics.count, 5);
+    assert_eq!(
+        (statistics.min, statistics.max, statistics.mean),
+        (0, 20, 7)
+    );
+    assert_eq!(statistics.histogram.len(), 7);
+    assert_eq!(statistics.histogram[0], (0..3, 2));
+    assert_eq!(statistics.histogram[1], (3..6, 1));
+    assert_eq!(
+        statistics.histogram.iter().map(|(_, c)| c).sum::<usize>(),
+        5
+    );
+    assert_eq!(measure_delays(&[10], &[5]), None);
+    assert_eq!(
+        measure_delays(&[10], &[10]).unwrap().histogram,
+        vec![(0..1, 1)]
+    );
+}
+
+#[test]
+fn measure_periods_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 2);
+    for (timestamp, value) in [(0, 0b00u8), (10, 0b10), (20, 0b01), (25, 0b00), (40, 0b11)] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform
+            .update_vector(0, BitVector::from_bits_two_state(2, value))
+            .unwrap();
+    }
+    assert_eq!(
+        get_rising_edges(&waveform, 0, Some(0), &(0..50)),
+        vec![20, 40]
+    );
+    assert_eq!(
+        get_rising_edges(&waveform, 0, Some(1), &(0..50)),
+        vec![10, 40]
+    );
+    assert_eq!(get_rising_edges(&waveform, 0, Some(0), &(20..40)), vec![20]);
+    assert_eq!(get_rising_edges(&waveform, 0, Some(0), &(21..50)), vec![40]);
+
+    let statistics = measure_periods(&[0, 10, 30, 40, 70]).unwrap();
+    assert_eq!(statistics.count, 4);
+    assert_eq!((statistics.min, statistics.max), ((10, 0), (30, 40)));
+    assert_eq!(statistics.mean, 17);
+    assert_eq!(measure_periods(&[10]), None);
+}
+
+#[test]
+fn find_transition_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    for (timestamp, bit) in [
+        (0, Logic::Zero),
+        (10, Logic::One),
+        (20, Logic::One),
+        (30, Logic::Zero),
+    ] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform.update_vector(0, BitVector::from(bit)).unwrap();
+    }
+    assert_eq!(find_transition(&waveform, 0, None, 0, true), Some(10));
+    assert_eq!(find_transition(&waveform, 0, None, 10, true), Some(30));
+    assert_eq!(find_transition(&waveform, 0, None, 30, true), None);
+    assert_eq!(find_transition(&waveform, 0, None, 30, false), Some(10));
+    assert_eq!(find_transition(&waveform, 0, None, 31, false), Some(30));
+    assert_eq!(find_transition(&waveform, 0, Some(0), 10, false), None);
+}
+
+#[test]
+fn find_surrounding_changes_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 2);
+    waveform.initialize_vector(1, 1);
+    for (timestamp, value) in [(0, 0b00u8), (10, 0b01), (20, 0b01), (30, 0b11), (40, 0b10)] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform
+            .update_vector(0, BitVector::from_bits_two_state(2, value))
+            .unwrap();
+    }
+    let changes = |index, timestamp| find_surrounding_changes(&waveform, 0, index, timestamp);
+    assert_eq!(changes(None, 0), Some((None, Some(10))));
+    // Values recorded again without changing are skipped both ways
+    assert_eq!(changes(None, 25), Some((Some(10), Some(30))));
+    assert_eq!(changes(None, 40), Some((Some(40), None)));
+    assert_eq!(changes(None, 100), Some((Some(40), None)));
+    assert_eq!(changes(Some(1), 35), Some((Some(30), None)));
+    assert_eq!(changes(Some(0), 35), Some((Some(10), Some(40))));
+    assert_eq!(changes(Some(1), 25), Some((None, Some(30))));
+    assert_eq!(find_surrounding_changes(&waveform, 1, None, 10), None);
+}
+
+#[test]
+fn find_bit_families_test() {
+    assert_eq!(split_bit_name("data_12_"), Some(("data", 12)));
+    assert_eq!(split_bit_name("data[3]"), Some(("data", 3)));
+    assert_eq!(split_bit_name("rd_en_"), None);
+    assert_eq!(split_bit_name("_3_"), None);
+    assert_eq!(split_bit_name("data"), None);
+    let names = [
+        "data_1_",
+        "clk",
+        "data_0_",
+        "addr[0]",
+        "data_2_",
+        "addr[1]",
+        "gap_0_",
+        "gap_2_",
+        "single_0_",
+    ];
+    assert_eq!(
+        find_bit_families(names),
+        vec![
+            (String::from("data"), vec![2, 0, 4]),
+            (String::from("addr"), vec![3, 5]),
+        ]
+    );
+}
@@ -0,0 +1,772 @@
+//! The panes of nalu put together into one screen, along with the overlays
+//! and everything else drawn on top of them
+
+use makai::utils::messages::Messages;
+use tui::{
+    layout::{Direction, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, BorderType, Borders, Clear, Gauge, Paragraph},
+    Frame,
+};
+use tui_tiling::{
+    component::{Component, ComponentBase, ComponentBaseWidget},
+    container::{list::ContainerList, search::ContainerSearch, Container, ContainerChild},
+    FocusResult, ResizeError,
+};
+
+use crate::{
+    keymap::{fit_key_hints, get_help_lines},
+    python::error::{get_source_tokens, SourceToken, TracebackLine},
+    state::filter_viewer::FilterViewerState,
+    state::form::{Form, FormField},
+    state::header::HeaderState,
+    state::inspector::InspectorState,
+    state::netlist_viewer::NetlistViewerState,
+    state::python_view::{PythonViewState, PYTHON_PANE},
+    state::signal_viewer::SignalViewerState,
+    state::waveform_viewer::WaveformViewerState,
+    state::{NaluOverlay, NaluState, PYTHON_OUTPUT_ROWS},
+    theme::Theme,
+    widgets::browser::get_selected_style,
+    widgets::layout::PaneLayout,
+    widgets::overlay::{get_overlay_rect, Overlay, OverlayScroll},
+    widgets::resize::ResizeHandles,
+    widgets::timescale::{get_cursor_color, render_delta, render_time},
+};
+
+/// Typing into the filter box takes every key, even the ones of global actions
+pub fn is_filter_focused(tui: &dyn Container) -> bool {
+    matches!(
+        tui.search_focused(),
+        FocusResult::Focus((component, _)) if component.get_name() == "filter"
+    )
+}
+
+/// Browser panes take every key while typing ahead, like the filter box
+pub fn is_typing_ahead(tui: &dyn Container) -> bool {
+    let FocusResult::Focus((component, _)) = tui.search_focused() else {
+        return false;
+    };
+    let widget = component.get_widget().as_any();
+    widget
+        .downcast_ref::<NetlistViewerState>()
+        .is_some_and(|netlist| netlist.is_typing_ahead())
+        || widget
+            .downcast_ref::<SignalViewerState>()
+            .is_some_and(|signals| signals.is_typing_ahead())
+}
+
+/// Builds the tiled panes of nalu, which all talk to each other (and to the
+/// [`NaluState`]) through the messages
+pub fn get_tui(messages: &Messages) -> Result<Box<dyn Container>, ResizeError> {
+    let netlist_main =
+        ContainerList::new("netlist_main".to_string(), Direction::Vertical, false, 0, 0)
+            .from_children(vec![
+                ContainerChild::from(Component::new(
+                    "netlist".to_string(),
+                    1,
+                    Box::new(NetlistViewerState::new(messages.clone())),
+                )),
+                ContainerChild::from(
+                    Component::new(
+                        "filter".to_string(),
+                        1,
+                        Box::new(FilterViewerState::new(messages.clone())),
+                    )
+                    .fixed_height(Some(3)),
+                ),
+            ])?;
+
+    let signal_main =
+        ContainerList::new("signal_main".to_string(), Direction::Vertical, false, 0, 0)
+            .from_children(vec![
+                ContainerChild::from(Component::new(
+                    "signal".to_string(),
+                    1,
+                    Box::new(SignalViewerState::new(messages.clone())),
+                )),
+                ContainerChild::from(
+                    Component::new(
+                        "inspect".to_string(),
+                        1,
+                        Box::new(InspectorState::new(messages.clone())),
+                    )
+                    .fixed_height(Some(5)),
+                ),
+            ])?;
+
+    let main = ContainerList::new("main".to_string(), Direction::Horizontal, true, 0, 0)
+        .from_children(vec![
+            ContainerChild::from(netlist_main),
+            ContainerChild::from(signal_main),
+            ContainerChild::from(Component::new(
+                "waveform".to_string(),
+                1,
+                Box::new(WaveformViewerState::new(messages.clone())),
+            )),
+        ])?;
+
+    let nalu = ContainerList::new("nalu".to_string(), Direction::Vertical, false, 0, 0)
+        .from_children(vec![
+            ContainerChild::from(
+                Component::new(
+                    "header".to_string(),
+                    0,
+                    Box::new(HeaderState::new(
+                        format!(
+                            "nalu v{} (Press h for help, p for palette, r to reload, q to quit)",
+                            option_env!("CARGO_PKG_VERSION").unwrap_or("0.0.0")
+                        ),
+                        messages.clone(),
+                    )),
+                )
+                .fixed_height(Some(1)),
+            ),
+            ContainerChild::from(main),
+        ])?;
+
+    Ok(Box::new(nalu))
+}
+
+/// Panes left out of the layout at first, which are opened from the palette
+pub fn get_closed_panes(messages: &Messages) -> Vec<ContainerChild> {
+    vec![ContainerChild::from(Component::new(
+        PYTHON_PANE.to_string(),
+        1,
+        Box::new(PythonViewState::new(messages.clone())),
+    ))]
+}
+
+/// Rows of the overlays listing many lines, like the help and the warnings
+const LONG_OVERLAY_ROWS: u16 = 18;
+
+fn render_overlay<B: tui::backend::Backend>(frame: &mut Frame<B>, overlay: Overlay<'_>) {
+    let rect = overlay.get_rect(frame.size());
+    frame.render_widget(overlay, rect);
+}
+
+fn render_overlay_layout<B: tui::backend::Backend>(frame: &mut Frame<B>, nalu_state: &NaluState) {
+    let index = nalu_state.get_overlay_index();
+    match &nalu_state.get_overlay() {
+        NaluOverlay::Loading => frame.render_widget(
+            Gauge::default()
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .style(Style::default().fg(Color::White))
+                        .border_type(BorderType::Rounded)
+                        .title("Loading"),
+                )
+                .gauge_style(Style::default().fg(Color::LightCyan))
+                .percent(nalu_state.get_percent() as u16),
+            get_overlay_rect(frame.size(), 3),
+        ),
+        NaluOverlay::HelpPrompt => render_overlay(
+            frame,
+            Overlay::new(
+                "Help (arrows and page keys to scroll, esc to close)",
+                get_help_lines().join("\n"),
+            )
+            .rows(LONG_OVERLAY_ROWS)
+            .scroll(OverlayScroll::Offset(index)),
+        ),
+        NaluOverlay::QuitPrompt => render_overlay(
+            frame,
+            Overlay::new("Quit?", "Press q to quit, esc to not...").rows(1),
+        ),
+        NaluOverlay::Palette => render_overlay(
+            frame,
+            Overlay::new(
+                "Palette",
+                vec![
+                    Spans::from(format!("> {}", nalu_state.get_palette())),
+                    Spans::from(nalu_state.get_palette_message()),
+                ],
+            ),
+        ),
+        NaluOverlay::QuickAdd => {
+            let (input, candidates) = nalu_state.get_quick_add();
+            let mut lines = vec![Spans::from(format!("> {input}"))];
+            lines.extend(candidates.iter().map(|c| Spans::from(c.as_str())));
+            render_overlay(
+                frame,
+                Overlay::new("Add Signal (tab to complete, enter to append)", lines),
+            )
+        }
+        NaluOverlay::Annotations => {
+            let mut text = Text::raw("");
+            if nalu_state.get_annotations().is_empty() {
+                text.extend(Text::raw(
+                    "No annotations (press n in the waveform to add one)",
+                ));
+            }
+            for (i, annotation) in nalu_state.get_annotations().iter().enumerate() {
+                text.extend(Text::styled(
+                    format!(
+                        "{}: {}",
+                        render_time(annotation.timestamp, 1, nalu_state.get_timescale()),
+                        annotation.text
+                    ),
+                    get_selected_style(i == index, true),
+                ));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Annotations (enter to jump, del to remove)", text)
+                    .scroll(OverlayScroll::Select(index)),
+            )
+        }
+        NaluOverlay::Cursors => {
+            let timescale = nalu_state.get_timescale();
+            let cursors = nalu_state.get_named_cursors();
+            let mut text = Text::raw("");
+            if cursors.is_empty() {
+                text.extend(Text::raw(
+                    "No named cursors (press k in the waveform to place one)",
+                ));
+            } else {
+                // Each row has the time from that cursor to the one of each column
+                let mut header = format!("{:4}{:>12}{:>12}", "", "at", "cursor");
+                for cursor in cursors {
+                    header.push_str(&format!("{:>12}", cursor.name));
+                }
+                text.extend(Text::raw(header));
+            }
+            for (i, cursor) in cursors.iter().enumerate() {
+                let style = get_selected_style(i == index, true);
+                let mut row = format!(
+                    "{:>12}{:>12}",
+                    render_time(cursor.timestamp, 1, timescale),
+                    render_delta(cursor.timestamp, nalu_state.get_cursor(), timescale)
+                );
+                for other in cursors {
+                    let delta = if other.name == cursor.name {
+                        String::from("-")
+                    } else {
+                        render_delta(cursor.timestamp, other.timestamp, timescale)
+                    };
+                    row.push_str(&format!("{delta:>12}"));
+                }
+                text.extend(Text::from(Spans::from(vec![
+                    Span::styled(
+                        format!("{:4}", cursor.name),
+                        style.fg(get_cursor_color(cursor.name)),
+                    ),
+                    Span::styled(row, style),
+                ])));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Cursors (enter to jump, del to remove)", text)
+                    .rows(cursors.len() as u16 + 1)
+                    // The selected cursor comes after the header
+                    .scroll(OverlayScroll::Select(index + 1)),
+            )
+        }
+        NaluOverlay::Views => {
+            let mut text = Text::raw("");
+            if nalu_state.get_views().is_empty() {
+                text.extend(Text::raw(
+                    "No views (run save-view <name> in the palette to add one)",
+                ));
+            }
+            for (i, view) in nalu_state.get_views().iter().enumerate() {
+                text.extend(Text::styled(
+                    format!("{} ({} signals)", view.name, view.get_signals().len()),
+                    get_selected_style(i == index, true),
+                ));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Views (enter to switch)", text).scroll(OverlayScroll::Select(index)),
+            )
+        }
+        NaluOverlay::Glitches => {
+            let mut text = Text::raw("");
+            if nalu_state.get_glitches().is_empty() {
+                text.extend(Text::raw("No glitches found"));
+            }
+            for (i, glitch) in nalu_state.get_glitches().iter().enumerate() {
+                text.extend(Text::styled(
+                    format!(
+                        "{}: {} ({} wide)",
+                        render_time(glitch.timestamp, 1, nalu_state.get_timescale()),
+                        glitch.name,
+                        render_time(glitch.width, 1, nalu_state.get_timescale())
+                    ),
+                    get_selected_style(i == index, true),
+                ));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Glitches (enter to jump)", text).scroll(OverlayScroll::Select(index)),
+            )
+        }
+        NaluOverlay::Recent => {
+            let mut text = Text::raw("");
+            if nalu_state.get_recent().is_empty() {
+                text.extend(Text::raw(
+                    "No recent files (run nalu <vcd file> to open one, q to quit)",
+                ));
+            }
+            for (i, recent) in nalu_state.get_recent().iter().enumerate() {
+                text.extend(Text::styled(
+                    format!("{} ({})", recent.path.display(), recent.get_summary()),
+                    get_selected_style(i == index, true),
+                ));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Recent Files (enter to open)", text)
+                    .scroll(OverlayScroll::Select(index)),
+            )
+        }
+        NaluOverlay::Recovery => {
+            let text = match nalu_state.get_recovery() {
+                Some(recovery) => format!(
+                    "The last session of {} did not exit cleanly, restore its {} signals? (y to restore, n to discard)",
+                    recovery.vcd_path.display(),
+                    recovery.view.get_signals().len()
+                ),
+                None => String::new(),
+            };
+            render_overlay(frame, Overlay::new("Recover Signals", text).rows(2).wrap())
+        }
+        NaluOverlay::UnknownTrace => {
+            let mut text = Text::raw("");
+            if let Some(trace) = nalu_state.get_unknown_trace() {
+                text.extend(Text::raw(format!(
+                    "{} became unknown at {}",
+                    trace.name,
+                    render_time(trace.timestamp, 1, nalu_state.get_timescale())
+                )));
+                if !trace.others.is_empty() {
+                    text.extend(Text::raw("Also unknown at that time:"));
+                }
+                for other in &trace.others {
+                    text.extend(Text::raw(format!("  {other}")));
+                }
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Unknown Trace (enter to jump)", text)
+                    .scroll(OverlayScroll::Offset(index)),
+            )
+        }
+        NaluOverlay::History => {
+            let Some(history) = nalu_state.get_history() else {
+                return;
+            };
+            let rows = history.get_rows(nalu_state.get_timescale());
+            // The time and value columns are padded to their longest entry
+            let width = |column: usize, header: &str| {
+                rows.iter()
+                    .map(|row| row[column].chars().count())
+                    .fold(header.len(), usize::max)
+            };
+            let (time_width, value_width) = (width(0, "time"), width(1, "value"));
+            let format_row = |time: &str, value: &str, delta: &str| {
+                format!("{time:<time_width$}  {value:<value_width$}  {delta}")
+            };
+            let mut text = Text::styled(
+                format_row("time", "value", "delta"),
+                Style::default().add_modifier(Modifier::BOLD),
+            );
+            for [time, value, delta] in &rows {
+                text.extend(Text::raw(format_row(time, value, delta)));
+            }
+            if history.truncated {
+                text.extend(Text::raw(format!(
+                    "Only the first {} values are listed",
+                    rows.len()
+                )));
+            }
+            let title = format!(
+                "History of {} from {} to {} (c to copy)",
+                history.name,
+                render_time(history.range.start, 1, nalu_state.get_timescale()),
+                render_time(history.range.end - 1, 1, nalu_state.get_timescale())
+            );
+            render_overlay(
+                frame,
+                Overlay::new(title, text)
+                    .rows(LONG_OVERLAY_ROWS)
+                    .scroll(OverlayScroll::Offset(index)),
+            )
+        }
+        NaluOverlay::UnknownSignals => {
+            let mut text = Text::raw("");
+            if nalu_state.get_unknown_signals().is_empty() {
+                text.extend(Text::raw("No signals are x or z at the cursor"));
+            }
+            for (i, signal) in nalu_state.get_unknown_signals().iter().enumerate() {
+                let name = match signal.index {
+                    Some(index) => format!("{} [{index}]", signal.name),
+                    None => signal.name.clone(),
+                };
+                let added = if signal.in_viewer {
+                    ""
+                } else {
+                    " (not in viewer)"
+                };
+                text.extend(Text::styled(
+                    format!("{name} = {}{added}", signal.value),
+                    get_selected_style(i == index, true),
+                ));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("X/Z Signals (enter to select)", text)
+                    .scroll(OverlayScroll::Select(index)),
+            )
+        }
+        NaluOverlay::Menu => {
+            let Some(menu) = nalu_state.get_menu() else {
+                return;
+            };
+            let mut text = Text::raw("");
+            for (i, action) in menu.actions.iter().enumerate() {
+                text.extend(Text::styled(
+                    action.get_label(),
+                    get_selected_style(i == index, true),
+                ));
+            }
+            render_overlay(
+                frame,
+                Overlay::new(format!("{} (enter to choose)", menu.title), text)
+                    .rows(menu.actions.len() as u16)
+                    .scroll(OverlayScroll::Select(index)),
+            )
+        }
+        NaluOverlay::Form => {
+            let Some(form) = nalu_state.get_form() else {
+                return;
+            };
+            render_overlay(
+                frame,
+                Overlay::new(
+                    format!("{} (tab for next field, esc to cancel)", form.title),
+                    get_form_text(form),
+                )
+                .rows(form.get_height() as u16 + 1),
+            )
+        }
+        NaluOverlay::Python => {
+            let Some(output) = nalu_state.get_python_output() else {
+                return;
+            };
+            let text = Text::from(
+                output
+                    .lines
+                    .iter()
+                    .map(|line| Spans::from(line.clone()))
+                    .collect::<Vec<_>>(),
+            );
+            render_overlay(
+                frame,
+                Overlay::new(format!("{} (esc to close)", output.function), text)
+                    .rows((output.lines.len() as u16).min(PYTHON_OUTPUT_ROWS))
+                    .scroll(OverlayScroll::Offset(index)),
+            )
+        }
+        NaluOverlay::PythonError => {
+            let Some(error) = nalu_state.get_python_error() else {
+                return;
+            };
+            let lines = error
+                .lines
+                .iter()
+                .map(get_traceback_spans)
+                .collect::<Vec<_>>();
+            render_overlay(
+                frame,
+                Overlay::new(
+                    format!(
+                        "Python error in {} (r to run again, esc to close)",
+                        error.context
+                    ),
+                    lines,
+                )
+                .rows((error.lines.len() as u16).min(PYTHON_OUTPUT_ROWS))
+                .scroll(OverlayScroll::Offset(index))
+                .style(Style::default().fg(Color::Gray)),
+            )
+        }
+        NaluOverlay::Delays => {
+            let timescale = nalu_state.get_timescale();
+            let mut text = Text::raw("");
+            if let Some(report) = nalu_state.get_delays() {
+                text.extend(Text::raw(format!(
+                    "{} -> {} from {} to {}",
+                    report.from,
+                    report.to,
+                    render_time(report.range.start, 1, timescale),
+                    render_time(report.range.end, 1, timescale)
+                )));
+                match &report.statistics {
+                    Some(statistics) => {
+                        text.extend(Text::raw(format!(
+                            "{} transitions, min {}, max {}, mean {}",
+                            statistics.count,
+                            render_time(statistics.min, 1, timescale),
+                            render_time(statistics.max, 1, timescale),
+                            render_time(statistics.mean, 1, timescale)
+                        )));
+                        let most = statistics.histogram.iter().map(|(_, c)| *c).max();
+                        for (range, count) in &statistics.histogram {
+                            let bar = count * 30 / most.unwrap_or(1).max(1);
+                            text.extend(Text::raw(format!(
+                                "{:>10} {} {}",
+                                render_time(range.start, 1, timescale),
+                                "█".repeat(bar),
+                                count
+                            )));
+                        }
+                    }
+                    None => text.extend(Text::raw(format!(
+                        "No transitions of {} followed by one of {}",
+                        report.from, report.to
+                    ))),
+                }
+            }
+            render_overlay(frame, Overlay::new("Delays", text).rows(10))
+        }
+        NaluOverlay::Warnings => {
+            let diagnostics = nalu_state.get_diagnostics();
+            let mut text = Text::raw("");
+            if diagnostics.is_empty() {
+                text.extend(Text::raw("No warnings from loading"));
+            }
+            for message in diagnostics.get_messages() {
+                text.extend(Text::raw(message.clone()));
+            }
+            let hidden = diagnostics.get_skipped() - diagnostics.get_messages().len();
+            if hidden > 0 {
+                text.extend(Text::raw(format!("...and {hidden} more")));
+            }
+            render_overlay(
+                frame,
+                Overlay::new("Warnings", text)
+                    .rows(LONG_OVERLAY_ROWS)
+                    .scroll(OverlayScroll::Offset(index)),
+            )
+        }
+        NaluOverlay::None => {}
+    }
+}
+
+/// Colors a line of a python traceback, with the source code highlighted
+/// Draws each field of a form on its own row except for the buttons, which
+/// share a row when they follow each other
+fn get_form_text(form: &Form) -> Text<'_> {
+    let mut rows: Vec<Vec<Span>> = Vec::new();
+    let mut last_button = false;
+    for (i, field) in form.fields.iter().enumerate() {
+        let style = get_selected_style(i == form.focus, true);
+        let is_button = matches!(field, FormField::Button(_));
+        if !(is_button && last_button) {
+            rows.push(Vec::new());
+        }
+        last_button = is_button;
+        let row = rows.last_mut().unwrap();
+        match field {
+            FormField::Text(label, value) => {
+                row.push(Span::raw(format!("{label}: ")));
+                row.push(Span::styled(format!("{value}_"), style));
+            }
+            FormField::Choice(label, options, selected) => {
+                row.push(Span::raw(format!("{label}:")));
+                for (j, option) in options.iter().enumerate() {
+                    let mark = if j == *selected { "(•)" } else { "( )" };
+                    let style = get_selected_style(i == form.focus && j == *selected, true);
+                    row.push(Span::raw(" "));
+                    row.push(Span::styled(format!("{mark} {option}"), style));
+                }
+            }
+            FormField::Button(label) => {
+                if !row.is_empty() {
+                    row.push(Span::raw(" "));
+                }
+                row.push(Span::styled(format!("[ {label} ]"), style));
+            }
+        }
+    }
+    let mut text = Text::from(rows.into_iter().map(Spans::from).collect::<Vec<Spans>>());
+    text.extend(Text::styled(
+        form.message.as_str(),
+        Style::default().fg(Color::LightRed),
+    ));
+    text
+}
+
+fn get_traceback_spans(line: &TracebackLine) -> Spans<'_> {
+    match line {
+        TracebackLine::Frame(file, number, function) => {
+            let mut spans = vec![Span::styled(
+                format!("{file}:{number}"),
+                Style::default().fg(Color::LightBlue),
+            )];
+            if !function.is_empty() {
+                spans.push(Span::raw(" in "));
+                spans.push(Span::styled(function, Style::default().fg(Color::Yellow)));
+            }
+            Spans::from(spans)
+        }
+        TracebackLine::Source(number, source) => {
+            let mut spans = vec![Span::styled(
+                format!("{number:>6} │ "),
+                Style::default().fg(Color::DarkGray),
+            )];
+            for (token, text) in get_source_tokens(source) {
+                let color = match token {
+                    SourceToken::Keyword => Color::LightMagenta,
+                    SourceToken::String => Color::LightGreen,
+                    SourceToken::Number => Color::LightCyan,
+                    SourceToken::Comment => Color::DarkGray,
+                    SourceToken::Other => Color::White,
+                };
+                spans.push(Span::styled(text, Style::default().fg(color)));
+            }
+            Spans::from(spans)
+        }
+        TracebackLine::Exception(name, message) => Spans::from(vec![
+            Span::styled(
+                name,
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                match message.is_empty() {
+                    true => String::new(),
+                    false => format!(": {message}"),
+                },
+                Style::default().fg(Color::White),
+            ),
+        ]),
+        TracebackLine::Text(text) => Spans::from(text.as_str()),
+    }
+}
+
+fn render_banner<B: tui::backend::Backend>(frame: &mut Frame<B>, nalu_state: &NaluState) {
+    let Some(banner) = nalu_state.get_banner() else {
+        return;
+    };
+    let frame_rect = frame.size();
+    let rect = Rect::new(0, 0, frame_rect.width, frame_rect.height.min(1));
+    frame.render_widget(Clear, rect);
+    frame.render_widget(
+        Paragraph::new(banner).style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+        rect,
+    );
+}
+
+/// The name of the focused pane, when there is one
+fn get_focused_name(tui: &dyn Container) -> Option<String> {
+    match tui.search_focused() {
+        FocusResult::Focus((component, _)) => Some(component.get_name().clone()),
+        _ => None,
+    }
+}
+
+fn render_key_hints<B: tui::backend::Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    focused: Option<&str>,
+) {
+    let hints = fit_key_hints(focused.unwrap_or_default(), area.width as usize);
+    let mut spans = Vec::new();
+    for (i, (key, action)) in hints.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::styled(key, Style::default().fg(Color::Yellow)));
+        spans.push(Span::styled(
+            format!(" {action}"),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+fn render_hud<B: tui::backend::Backend>(frame: &mut Frame<B>, nalu_state: &NaluState) {
+    if !nalu_state.is_hud_enabled() {
+        return;
+    }
+    let lines = nalu_state.get_frame_statistics().get_lines();
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0) as u16 + 2;
+    let height = lines.len() as u16 + 2;
+    let frame_rect = frame.size();
+    if frame_rect.width < width || frame_rect.height < height {
+        return;
+    }
+    let rect = Rect::new(frame_rect.width - width, 0, width, height);
+    frame.render_widget(Clear, rect);
+    frame.render_widget(
+        Paragraph::new(lines.into_iter().map(Spans::from).collect::<Vec<Spans>>())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(Color::White))
+                    .border_type(BorderType::Rounded)
+                    .title("HUD"),
+            )
+            .style(Style::default().fg(Color::LightCyan)),
+        rect,
+    );
+}
+
+/// Draws a whole frame of nalu, the panes followed by everything on top of them
+pub fn render_nalu<B: tui::backend::Backend>(
+    frame: &mut Frame<B>,
+    tui: &mut dyn Container,
+    resize_handles: &ResizeHandles,
+    nalu_state: &NaluState,
+    theme: Theme,
+) {
+    let focused = get_focused_name(tui);
+    // The hint bar takes the bottom line away from the panes
+    let hints = nalu_state.is_hints_enabled() && frame.size().height > 1;
+    let area = Rect {
+        height: frame.size().height - hints as u16,
+        ..frame.size()
+    };
+    tui.as_base_mut().invalidate();
+    if let Err(err) = tui.as_base_mut().resize(area.width, area.height) {
+        log::error!("Resizing Error! ({err:?})");
+        panic!("Resizing Error! ({err:?})");
+    }
+    frame.render_stateful_widget(ComponentBaseWidget::from(tui.as_base_mut()), area, &mut ());
+    // Without the mouse the borders cannot be dragged, so are left unmarked
+    if nalu_state.is_mouse_enabled() {
+        frame.render_widget(resize_handles.get_widget(tui), area);
+    }
+    if hints {
+        let hint_area = Rect {
+            y: area.bottom(),
+            height: 1,
+            ..area
+        };
+        render_key_hints(frame, hint_area, focused.as_deref());
+    }
+    render_banner(frame, nalu_state);
+    render_overlay_layout(frame, nalu_state);
+    render_hud(frame, nalu_state);
+    frame.render_widget(theme, frame.size());
+}
+
+/// Passes messages around the components until there are none left, returning
+/// how many passes that took
+pub fn handle_updates(
+    nalu_state: &mut NaluState,
+    tui: &mut dyn Container,
+    pane_layout: &mut PaneLayout,
+) -> usize {
+    let mut update_passes = 0;
+    while !nalu_state.get_messages().is_empty() {
+        tui.as_base_mut().handle_update();
+        nalu_state.handle_update();
+        pane_layout.handle_update(tui);
+        update_passes += 1;
+    }
+    update_passes
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use makai::utils::messages::Messages;
+use makai_vcd_reader::parser::VcdVariableWidth;
+use makai_waveform_db::bitvector::BitVectorRadix;
+use tui::{buffer::Buffer, layout::Rect};
+use tui_tiling::component::ComponentWidget;
+
+use crate::{
+    loader::{load, LoadOptions},
+    logging::FrameTimestamps,
+    state::signal_viewer::{SignalViewerEntry, SignalViewerRow},
+    state::waveform_viewer::{WaveformViewerMessage, WaveformViewerState},
+};
+
+/// Terminal widths (in columns) rendered at each zoom level
+const BENCH_WIDTHS: [u16; 3] = [80, 200, 400];
+
+/// Fractions of the dump shown, from all of it down to a narrow window
+const BENCH_ZOOMS: [f64; 3] = [1.0, 0.01, 0.0001];
+
+/// Rows of a typical terminal, which limits how many signals are drawn
+const BENCH_HEIGHT: u16 = 48;
+
+fn render_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Loads the dump and renders the waveform viewer off-screen into a plain
+/// buffer, with as many signals as fit into a terminal, at each width and
+/// zoom level, printing how long loading and each frame took
+pub fn bench_render(path: &Path, frames: usize) -> Result<(), String> {
+    let start = Instant::now();
+    let bytes = std::fs::read_to_string(path).map_err(|err| format!("{err}"))?;
+    let status = Arc::new(Mutex::new((0, 0)));
+    let loaded = load(bytes, status, &LoadOptions::default())
+        .join()
+        .map_err(|_| String::from("Loader panicked"))?
+        .map_err(|err| format!("{err:?}"))?;
+    let load_duration = start.elapsed();
+
+    let range = loaded.waveform.get_timestamp_range();
+    let timescale = loaded.header.get_timescale().unwrap_or(0);
+    let mut signals = loaded
+        .header
+        .get_idcodes_map()
+        .iter()
+        .map(|(idcode, width)| (*idcode, *width == VcdVariableWidth::Real))
+        .collect::<Vec<(usize, bool)>>();
+    signals.sort();
+    let mut rows = signals
+        .into_iter()
+        .map(|(idcode, is_real)| {
+            Some(SignalViewerRow::Signal(SignalViewerEntry {
+                name: idcode.to_string(),
+                idcode,
+                index: None,
+                radix: BitVectorRadix::Hexadecimal,
+                is_real,
+                offset: 0,
+                plot_row: None,
+                is_selected: false,
+                is_primary: false,
+            }))
+        })
+        .collect::<Vec<Option<SignalViewerRow>>>();
+    let hidden = rows.len().saturating_sub(BENCH_HEIGHT as usize);
+    rows.truncate(BENCH_HEIGHT as usize);
+    println!(
+        "load: {} ({} signals drawn, {} more not in view, timestamps {}..{})",
+        render_duration(load_duration),
+        rows.len(),
+        hidden,
+        range.start,
+        range.end
+    );
+
+    let messages = Messages::new();
+    let mut viewer = WaveformViewerState::new(messages.clone());
+    messages.push(WaveformViewerMessage::UpdateWaveform(
+        Arc::new(loaded.waveform),
+        Arc::new(loaded.header),
+        timescale,
+        None,
+    ));
+    messages.push(WaveformViewerMessage::UpdateSignals(rows, hidden));
+    viewer.handle_update();
+
+    println!(
+        "{:>6} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "width", "zoom", "first", "mean", "max", "minimap", "widgets", "draw"
+    );
+    for width in BENCH_WIDTHS {
+        for zoom in BENCH_ZOOMS {
+            // Zoom into the middle of the dump
+            let span = range.end.saturating_sub(range.start);
+            let window = ((span as f64 * zoom) as u64).max(1);
+            let start = range.start + (span - window.min(span)) / 2;
+            viewer.resize(width, BENCH_HEIGHT);
+            messages.push(WaveformViewerMessage::SetRange(start..start + window));
+            viewer.handle_update();
+            // Only the viewer itself is run, so drop what it sends to the rest
+            messages.get::<()>();
+            let area = Rect::new(0, 0, width, BENCH_HEIGHT);
+            let frames = (0..frames.max(1))
+                .map(|_| {
+                    let mut buf = Buffer::empty(area);
+                    let mut timestamps = FrameTimestamps::new();
+                    viewer.render_timed(area, &mut buf, &mut timestamps);
+                    timestamps
+                })
+                .collect::<Vec<FrameTimestamps>>();
+            let totals = frames.iter().map(|f| f.total()).collect::<Vec<Duration>>();
+            // The mean of each stage, which are in the same order every frame
+            let stages = (0..frames[0].get_sections().len()).map(|i| {
+                let total = frames
+                    .iter()
+                    .map(|f| f.get_sections()[i].1)
+                    .sum::<Duration>();
+                render_duration(total / frames.len() as u32)
+            });
+            println!(
+                "{:>6} {:>7}% {:>10} {:>10} {:>10} {}",
+                width,
+                zoom * 100.0,
+                render_duration(totals[0]),
+                render_duration(totals.iter().sum::<Duration>() / totals.len() as u32),
+                render_duration(*totals.iter().max().unwrap()),
+                stages
+                    .map(|stage| format!("{stage:>10}"))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            );
+        }
+    }
+    Ok(())
+}
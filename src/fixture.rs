@@ -0,0 +1,233 @@
+//! Small waveforms built in code for tests, which load instantly and always
+//! look the same, unlike the values of a simulation dump
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use makai_vcd_reader::parser::VcdHeader;
+use makai_waveform_db::{bitvector::BitVector, Waveform};
+
+use crate::loader::{load, LoadOptions};
+
+/// The scope every signal of a fixture is declared in
+pub const FIXTURE_SCOPE: &str = "top";
+
+/// A waveform along with the header declaring its signals
+pub struct Fixture {
+    pub header: VcdHeader,
+    pub waveform: Waveform,
+    /// The last timestamp of the waveform
+    pub end: u64,
+}
+
+impl Fixture {
+    /// The idcode of a signal by the name it was added with
+    pub fn get_idcode(&self, name: &str) -> usize {
+        self.header
+            .get_variable(&format!("{FIXTURE_SCOPE}.{name}"))
+            .unwrap_or_else(|| panic!("No signal named {name} in the fixture"))
+            .get_idcode()
+    }
+}
+
+/// Reads a value written like in a dump from the highest bit down, like `01xz`
+fn parse_value(value: &str) -> BitVector {
+    let (mut bits, mut mask) = (0u64, 0u64);
+    for (i, c) in value.chars().rev().enumerate() {
+        match c {
+            '1' => bits |= 1 << i,
+            'x' | 'X' => mask |= 1 << i,
+            'z' | 'Z' => {
+                bits |= 1 << i;
+                mask |= 1 << i;
+            }
+            _ => {}
+        }
+    }
+    BitVector::from_bits_four_state(value.len(), bits, mask)
+}
+
+/// The identifier code of the signal at the position in the declarations,
+/// counting through the 94 printable characters and then taking more of them
+/// like simulators do
+fn get_id(mut position: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push(char::from(b'!' + (position % 94) as u8));
+        position /= 94;
+        if position == 0 {
+            return id;
+        }
+        position -= 1;
+    }
+}
+
+/// Collects signals and their values, like
+/// `FixtureBuilder::default().clock("clk", 10, 100).build()`
+#[derive(Default)]
+pub struct FixtureBuilder {
+    // Name and width of every signal in the order they were added
+    signals: Vec<(String, usize)>,
+    // Values by the position of their signal at each timestamp
+    changes: BTreeMap<u64, Vec<(usize, BitVector)>>,
+    end: u64,
+}
+
+impl FixtureBuilder {
+    fn add_signal(&mut self, name: &str, width: usize) -> usize {
+        self.signals.push((String::from(name), width));
+        self.signals.len() - 1
+    }
+
+    fn add_change(&mut self, signal: usize, timestamp: u64, value: BitVector) {
+        self.end = self.end.max(timestamp);
+        self.changes
+            .entry(timestamp)
+            .or_default()
+            .push((signal, value));
+    }
+
+    /// A single bit starting low at zero and toggling every half period until
+    /// the end
+    pub fn clock(mut self, name: &str, period: u64, end: u64) -> Self {
+        let signal = self.add_signal(name, 1);
+        let half = (period / 2).max(1);
+        for (i, timestamp) in (0..end).step_by(half as usize).enumerate() {
+            let value = BitVector::from_bits_two_state(1, i as u64 % 2);
+            self.add_change(signal, timestamp, value);
+        }
+        self.end = self.end.max(end);
+        self
+    }
+
+    /// A vector starting at zero and counting up every step until the end,
+    /// wrapping around once it runs out of bits
+    pub fn counter(mut self, name: &str, width: usize, step: u64, end: u64) -> Self {
+        let signal = self.add_signal(name, width);
+        let mask = u64::MAX >> (64 - width.clamp(1, 64));
+        for (i, timestamp) in (0..end).step_by(step.max(1) as usize).enumerate() {
+            let value = BitVector::from_bits_two_state(width, i as u64 & mask);
+            self.add_change(signal, timestamp, value);
+        }
+        self.end = self.end.max(end);
+        self
+    }
+
+    /// A signal taking each value (written from the highest bit down with
+    /// `0`, `1`, `x` and `z`, like `01xz`) from its timestamp on, as wide as
+    /// the first value
+    pub fn pattern(mut self, name: &str, values: &[(u64, &str)]) -> Self {
+        let width = values.first().map_or(1, |(_, value)| value.len());
+        let signal = self.add_signal(name, width);
+        for (timestamp, value) in values {
+            self.add_change(signal, *timestamp, parse_value(value));
+        }
+        self
+    }
+
+    /// Puts the waveform together, with its last timestamp at the end of the
+    /// longest signal. The reader has no other way of building a header, so
+    /// only the declarations of the signals are written out and read back, the
+    /// values go into the waveform directly.
+    pub fn build(self) -> Fixture {
+        let mut declarations = format!("$timescale 1ns $end\n$scope module {FIXTURE_SCOPE} $end\n");
+        for (i, (name, width)) in self.signals.iter().enumerate() {
+            let id = get_id(i);
+            declarations.push_str(&format!("$var wire {width} {id} {name} $end\n"));
+        }
+        declarations.push_str("$upscope $end\n$enddefinitions $end\n");
+        let options = LoadOptions {
+            signals: Some(HashSet::new()),
+            ..LoadOptions::default()
+        };
+        let header = load(declarations, Arc::new(Mutex::new((0, 0))), &options)
+            .join()
+            .unwrap()
+            .unwrap()
+            .header;
+        let idcodes = self
+            .signals
+            .iter()
+            .map(|(name, _)| {
+                let full_name = format!("{FIXTURE_SCOPE}.{name}");
+                header.get_variable(&full_name).unwrap().get_idcode()
+            })
+            .collect::<Vec<usize>>();
+        let mut waveform = Waveform::new();
+        header.initialize_waveform(&mut waveform);
+        for (timestamp, changes) in self.changes {
+            waveform.insert_timestamp(timestamp).unwrap();
+            for (signal, value) in changes {
+                waveform.update_vector(idcodes[signal], value).unwrap();
+            }
+        }
+        if waveform.get_timestamp_range().end <= self.end {
+            waveform.insert_timestamp(self.end).unwrap();
+        }
+        Fixture {
+            header,
+            waveform,
+            end: self.end,
+        }
+    }
+}
+
+#[test]
+fn fixture_test() {
+    use crate::source::WaveformSource;
+    use makai_waveform_db::bitvector::Logic;
+
+    let value = parse_value("01xz");
+    assert_eq!(value.get_bit_width(), 4);
+    assert!(
+        (0..4).map(|i| value.get_bit(i)).collect::<Vec<Logic>>()
+            == vec![
+                Logic::HighImpedance,
+                Logic::Unknown,
+                Logic::One,
+                Logic::Zero
+            ]
+    );
+
+    let fixture = FixtureBuilder::default()
+        .clock("clk", 10, 40)
+        .counter("count", 2, 10, 40)
+        .pattern("data", &[(0, "0"), (12, "x")])
+        .build();
+    assert_eq!(fixture.end, 40);
+    assert_eq!(*fixture.header.get_timescale(), Some(9));
+    let changes = |name| {
+        fixture
+            .waveform
+            .get_vector_changes(fixture.get_idcode(name), 0)
+            .map(|(timestamp, _)| timestamp)
+            .collect::<Vec<u64>>()
+    };
+    assert_eq!(changes("clk"), vec![0, 5, 10, 15, 20, 25, 30, 35]);
+    assert_eq!(changes("count"), vec![0, 10, 20, 30]);
+    assert_eq!(changes("data"), vec![0, 12]);
+
+    // Ids take more than one character past the printable ones
+    assert_eq!(
+        (get_id(0), get_id(93), get_id(94)),
+        ("!".into(), "~".into(), "!!".into())
+    );
+    let fixture = (0..200)
+        .fold(FixtureBuilder::default(), |builder, i| {
+            builder.pattern(&format!("s{i}"), &[(i, "1")])
+        })
+        .build();
+    let idcodes = (0..200)
+        .map(|i| fixture.get_idcode(&format!("s{i}")))
+        .collect::<HashSet<usize>>();
+    assert_eq!(idcodes.len(), 200);
+    let changes = fixture
+        .waveform
+        .get_vector_changes(fixture.get_idcode("s150"), 0);
+    assert_eq!(
+        changes
+            .map(|(timestamp, _)| timestamp)
+            .collect::<Vec<u64>>(),
+        vec![150]
+    );
+}
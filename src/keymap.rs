@@ -0,0 +1,130 @@
+/// A shortcut and what it does, as shown in the hint bar
+pub type KeyHint = (&'static str, &'static str);
+
+/// Keys that work from any pane, shown after the keys of the focused pane
+const GLOBAL_HINTS: &[KeyHint] = &[("p", "palette"), ("h", "help"), ("q", "quit")];
+
+const NETLIST_HINTS: &[KeyHint] = &[
+    ("a", "append"),
+    ("i", "insert"),
+    ("e/E", "expand/collapse all"),
+    ("/", "find"),
+    ("c", "menu"),
+    ("b", "hide blocks"),
+    ("w", "widths"),
+    ("G", "grouping"),
+    ("f", "full names"),
+];
+
+const FILTER_HINTS: &[KeyHint] = &[
+    ("Tab", "match mode"),
+    ("Shift+Tab", "prune/highlight"),
+    ("Delete", "clear"),
+];
+
+const SIGNAL_HINTS: &[KeyHint] = &[
+    ("Delete", "remove"),
+    ("P", "pin"),
+    ("y/v", "yank/paste"),
+    ("g", "group"),
+    ("F2", "rename"),
+    ("o", "add by name"),
+    ("c", "menu"),
+    ("e/E", "expand/collapse all"),
+    ("/", "find"),
+    ("-", "spacer"),
+    ("_", "separator"),
+    ("f", "full names"),
+];
+
+const WAVEFORM_HINTS: &[KeyHint] = &[
+    ("-/=", "zoom"),
+    ("[/]", "scroll"),
+    ("z/Z", "zoom to/all"),
+    ("</>", "zoom back/forward"),
+    ("F", "follow end"),
+    (",/.", "prev/next change"),
+    ("m/M", "marker"),
+    ("t", "go to time"),
+    ("k/K", "named cursors"),
+    ("/ ;/:", "search annotations"),
+    ("n/N", "annotations"),
+    ("b", "braille"),
+    ("x", "compress idle"),
+    ("v", "python view"),
+];
+
+/// The most useful shortcuts of a pane by its name, most useful first
+pub fn get_key_hints(pane: &str) -> &'static [KeyHint] {
+    match pane {
+        "netlist" => NETLIST_HINTS,
+        "filter" => FILTER_HINTS,
+        "signal" => SIGNAL_HINTS,
+        "waveform" => WAVEFORM_HINTS,
+        _ => &[],
+    }
+}
+
+/// Every shortcut under the name of the pane it works in, for the help overlay
+pub fn get_help_lines() -> Vec<String> {
+    let mut lines = Vec::new();
+    for (title, hints) in [
+        ("Everywhere", GLOBAL_HINTS),
+        ("Netlist", NETLIST_HINTS),
+        ("Filter", FILTER_HINTS),
+        ("Signals", SIGNAL_HINTS),
+        ("Waveform", WAVEFORM_HINTS),
+    ] {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("{title}:"));
+        let width = hints.iter().map(|(key, _)| key.chars().count()).max();
+        for (key, action) in hints {
+            lines.push(format!(
+                "  {key:width$}  {action}",
+                width = width.unwrap_or(0)
+            ));
+        }
+    }
+    lines
+}
+
+/// Picks the hints of the pane followed by the global ones that fit into the
+/// width, leaving out whole hints instead of cutting one off
+pub fn fit_key_hints(pane: &str, width: usize) -> Vec<KeyHint> {
+    let mut hints = Vec::new();
+    let mut used = 0;
+    for hint in get_key_hints(pane).iter().chain(GLOBAL_HINTS) {
+        // Two spaces between hints and one between the key and the action
+        let hint_width = hint.0.chars().count() + hint.1.chars().count() + 1;
+        let hint_width = if hints.is_empty() {
+            hint_width
+        } else {
+            hint_width + 2
+        };
+        if used + hint_width > width {
+            continue;
+        }
+        used += hint_width;
+        hints.push(*hint);
+    }
+    hints
+}
+
+#[test]
+fn key_hints_test() {
+    assert_eq!(
+        fit_key_hints("unknown", 80),
+        vec![("p", "palette"), ("h", "help"), ("q", "quit")]
+    );
+    assert_eq!(
+        fit_key_hints("filter", 40),
+        vec![("Tab", "match mode"), ("Delete", "clear"), ("p", "palette")]
+    );
+    assert!(fit_key_hints("waveform", 0).is_empty());
+    let help = get_help_lines();
+    assert_eq!(help[0], "Everywhere:");
+    assert_eq!(help[1], "  p  palette");
+    assert!(help.contains(&String::from("  x      compress idle")));
+}
@@ -0,0 +1,45 @@
+//! nalu is a TUI waveform viewer, split into a library so other TUI projects
+//! can embed its widgets and a thin binary that runs the viewer.
+//!
+//! The widget layer draws independently of the rest of the viewer:
+//!
+//! - [`widgets::timescale`] keeps the visible range and cursors of a dump in a
+//!   [`TimescaleState`](widgets::timescale::TimescaleState) and draws the time
+//!   axis above the waveforms.
+//! - [`widgets::waveform`] draws one signal over that range from any
+//!   [`WaveformSource`](source::WaveformSource), which a
+//!   [`Waveform`](makai_waveform_db::Waveform) loaded from a dump already is.
+//! - [`widgets::browser`] draws a collapsible tree like the netlist.
+//!
+//! The panes of the viewer live in [`state`], with [`state::NaluState`] handling
+//! everything between them, and [`app`] puts them together into one screen.
+//!
+//! ```
+//! use makai_waveform_db::{bitvector::BitVectorRadix, Waveform};
+//! use nalu::widgets::{timescale::TimescaleState, waveform::WaveformWidget};
+//! use tui::{buffer::Buffer, layout::Rect, widgets::Widget};
+//!
+//! let waveform = Waveform::default();
+//! let mut timescale_state = TimescaleState::new();
+//! timescale_state.load_waveform(0..100, 100, 9);
+//! let area = Rect::new(0, 0, 40, 1);
+//! let mut buf = Buffer::empty(area);
+//! WaveformWidget::new(&timescale_state, &waveform, 0, None, BitVectorRadix::Hexadecimal, false)
+//!     .render(area, &mut buf);
+//! ```
+
+pub mod analysis;
+pub mod app;
+pub mod bench;
+#[cfg(test)]
+pub mod fixture;
+pub mod keymap;
+pub mod loader;
+pub mod logging;
+pub mod python;
+pub mod query;
+pub mod screenshot;
+pub mod source;
+pub mod state;
+pub mod theme;
+pub mod widgets;
@@ -0,0 +1,432 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use makai::utils::bytes::ByteStorage;
+use makai_vcd_reader::errors::TokenizerError;
+use makai_vcd_reader::lexer::Lexer;
+use makai_vcd_reader::parser::{
+    VcdEntry, VcdHeader, VcdReader, VcdVariableNetType, VcdVariableWidth,
+};
+use makai_vcd_reader::tokenizer::{token::Token, Tokenizer};
+use makai_vcd_reader::utils::*;
+use makai_waveform_db::{
+    bitvector::{BitVector, Logic},
+    errors::WaveformError,
+    Waveform,
+};
+
+pub mod registry;
+
+/// How many diagnostics are kept from a tolerant load, any further problems
+/// are only counted
+const DIAGNOSTICS_LIMIT: usize = 16;
+
+/// Problems that were skipped over while loading a waveform
+#[derive(Debug, Clone, Default)]
+pub struct LoadDiagnostics {
+    skipped: usize,
+    messages: Vec<String>,
+}
+
+impl LoadDiagnostics {
+    fn record(&mut self, line: usize, message: String) {
+        self.skipped += 1;
+        if self.messages.len() < DIAGNOSTICS_LIMIT {
+            self.messages.push(format!("Line {line}: {message}"));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skipped == 0
+    }
+
+    pub fn get_skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn get_messages(&self) -> &Vec<String> {
+        &self.messages
+    }
+}
+
+/// Everything produced by loading a VCD file
+pub struct LoadedVcd {
+    pub header: VcdHeader,
+    pub waveform: Waveform,
+    pub diagnostics: LoadDiagnostics,
+    /// The declared net type of every idcode, which the header does not keep
+    pub net_types: HashMap<usize, VcdVariableNetType>,
+}
+
+pub type LoadResult = VcdResult<LoadedVcd>;
+
+/// Scans the variable declarations of a VCD header for their net types,
+/// stopping at the end of the definitions or the first malformed token
+fn parse_net_types(bytes: &str) -> HashMap<usize, VcdVariableNetType> {
+    let mut lexer = Lexer::new(bytes);
+    let mut tokenizer = Tokenizer::new(bytes);
+    let mut bs = ByteStorage::new();
+    let mut net_types = HashMap::new();
+    while let Ok(Some(token)) = lexer.next_token() {
+        match tokenizer.next(Some(token), &mut bs) {
+            Ok(Some(Token::Var {
+                net_type,
+                token_idcode,
+                ..
+            })) => {
+                net_types.insert(token_idcode.get_id(), net_type);
+            }
+            Ok(Some(Token::EndDefinitions(_))) | Ok(None) | Err(_) => break,
+            Ok(Some(_)) => {}
+        }
+    }
+    net_types
+}
+
+fn is_real(net_type: &VcdVariableNetType) -> bool {
+    matches!(
+        net_type,
+        VcdVariableNetType::Real | VcdVariableNetType::Realtime
+    )
+}
+
+/// Real values cannot be read back out of the waveform database, so they are
+/// kept as the bits of the float in a 64-bit vector instead
+pub fn encode_real(value: f64) -> BitVector {
+    BitVector::from_bits_two_state(64, value.to_bits())
+}
+
+pub fn decode_real(bitvector: &BitVector) -> f64 {
+    // Values read back from the database may be stored as four-state
+    let bits = (0..bitvector.get_bit_width().min(64))
+        .filter(|i| bitvector.get_bit(*i) == Logic::One)
+        .fold(0u64, |bits, i| bits | (1 << i));
+    f64::from_bits(bits)
+}
+
+/// Loads a VCD file using the multi-threaded reader, failing on the first
+/// malformed entry
+pub fn load_strict(bytes: String, status: Arc<Mutex<(usize, usize)>>) -> JoinHandle<LoadResult> {
+    thread::spawn(move || {
+        let net_types = parse_net_types(&bytes);
+        let (header, waveform) = load_multi_threaded(bytes, 4, status).join().unwrap()?;
+        Ok(LoadedVcd {
+            header,
+            waveform,
+            diagnostics: LoadDiagnostics::default(),
+            net_types,
+        })
+    })
+}
+
+/// Options for loading a VCD file that require the single-threaded loader
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Skip over malformed value changes instead of failing
+    pub tolerant: bool,
+    /// Discard value changes before this timestamp, keeping only the values
+    /// signals held when the window starts
+    pub from: Option<u64>,
+    /// Stop loading once past this timestamp
+    pub to: Option<u64>,
+    /// Only keep value changes of these idcodes, skipping the body entirely
+    /// when empty so the header can be browsed before any signal is needed
+    pub signals: Option<HashSet<usize>>,
+}
+
+impl LoadOptions {
+    pub fn is_windowed(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+}
+
+/// Loads a VCD file with the multi-threaded reader unless the options need
+/// the single-threaded one
+pub fn load(
+    bytes: String,
+    status: Arc<Mutex<(usize, usize)>>,
+    options: &LoadOptions,
+) -> JoinHandle<LoadResult> {
+    // Only the single-threaded loader can store real signals as vectors
+    let has_reals = parse_net_types(&bytes).values().any(is_real);
+    if *options == LoadOptions::default() && !has_reals {
+        load_strict(bytes, status)
+    } else {
+        load_single_threaded(bytes, status, options.clone())
+    }
+}
+
+/// Loads a VCD file on a single thread. In tolerant mode malformed value
+/// changes in the body are skipped instead of failing so truncated dumps can
+/// be inspected, the header must still be well formed.
+fn load_single_threaded(
+    bytes: String,
+    status: Arc<Mutex<(usize, usize)>>,
+    options: LoadOptions,
+) -> JoinHandle<LoadResult> {
+    let file_size = bytes.len();
+    let status_clean = status.clone();
+
+    let loader_fn = move || -> LoadResult {
+        let net_types = parse_net_types(&bytes);
+        let mut lexer = Lexer::new(&bytes);
+        let mut tokenizer = Tokenizer::new(&bytes);
+        let mut parser = VcdReader::new();
+        let mut waveform = Waveform::new();
+        let mut diagnostics = LoadDiagnostics::default();
+        parser.parse_header(&mut |bs| tokenizer.next(lexer.next_token()?, bs))?;
+        parser.get_header().initialize_waveform(&mut waveform);
+        for (idcode, width) in parser.get_header().get_idcodes_map() {
+            if *width == VcdVariableWidth::Real {
+                waveform.initialize_vector(*idcode, 64);
+            }
+        }
+        if options.signals.as_ref().is_some_and(|s| s.is_empty()) {
+            return Ok(LoadedVcd {
+                header: parser.into_header(),
+                waveform,
+                diagnostics,
+                net_types,
+            });
+        }
+        let mut last_index = lexer.get_position().get_index();
+        *status.lock().unwrap() = (last_index, file_size);
+        // Latest value of each signal seen before the window starts
+        let mut pending: HashMap<usize, VcdEntry> = HashMap::new();
+        let mut in_window = options.from.is_none();
+        // The last timestamp skipped before the window, where the values are
+        // kept when the dump ends before the window starts
+        let mut last_skipped = None;
+        // Tokens on the same line as a lexer error are partial leftovers of
+        // the malformed entry and are dropped as well
+        let mut skip_line = None;
+        loop {
+            let entry = parser.parse_waveform(&mut |bs| loop {
+                match lexer.next_token() {
+                    Ok(Some(_)) if skip_line == Some(lexer.get_position().get_line()) => {}
+                    Ok(token) => return tokenizer.next(token, bs),
+                    Err(pos) if !options.tolerant => return Err(TokenizerError::from(pos)),
+                    Err(pos) if skip_line == Some(pos.get_line()) => {}
+                    Err(pos) => {
+                        diagnostics.record(pos.get_line(), String::from("Unrecognized entry"));
+                        skip_line = Some(pos.get_line());
+                    }
+                }
+            });
+            let line = lexer.get_position().get_line();
+            let entry = match entry {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(err) if !options.tolerant => return Err(VcdError::from(err)),
+                Err(err) => {
+                    diagnostics.record(line, format!("{err:?}"));
+                    continue;
+                }
+            };
+            let result = match entry {
+                VcdEntry::Vector(_, idcode) | VcdEntry::Real(_, idcode)
+                    if options
+                        .signals
+                        .as_ref()
+                        .is_some_and(|s| !s.contains(&idcode)) =>
+                {
+                    continue
+                }
+                VcdEntry::Timestamp(timestamp) if options.to.is_some_and(|to| timestamp > to) => {
+                    break
+                }
+                VcdEntry::Timestamp(timestamp) if !in_window => {
+                    let from = options.from.unwrap();
+                    if timestamp < from {
+                        last_skipped = Some(timestamp);
+                        continue;
+                    }
+                    in_window = true;
+                    insert_window_start(&mut waveform, from, timestamp, pending.drain())
+                }
+                VcdEntry::Vector(_, idcode) | VcdEntry::Real(_, idcode) if !in_window => {
+                    pending.insert(idcode, entry);
+                    continue;
+                }
+                VcdEntry::Timestamp(timestamp) => waveform.insert_timestamp(timestamp),
+                VcdEntry::Vector(bv, idcode) => waveform.update_vector(idcode, bv),
+                VcdEntry::Real(value, idcode) => waveform.update_vector(idcode, encode_real(value)),
+            };
+            match result {
+                Ok(()) => {}
+                Err(err) if !options.tolerant => return Err(VcdError::from(err)),
+                Err(err) => diagnostics.record(line, format!("{err:?}")),
+            }
+            let index = lexer.get_position().get_index();
+            if (index - last_index) * 200 / file_size > 0 {
+                last_index = index;
+                *status.lock().unwrap() = (last_index, file_size);
+            }
+        }
+        if let (false, Some(timestamp)) = (in_window, last_skipped) {
+            match insert_window_start(&mut waveform, timestamp, timestamp, pending.drain()) {
+                Ok(()) => {}
+                Err(err) if !options.tolerant => return Err(VcdError::from(err)),
+                Err(err) => diagnostics.record(lexer.get_position().get_line(), format!("{err:?}")),
+            }
+        }
+        for message in diagnostics.get_messages() {
+            log::warn!("Skipped: {message}");
+        }
+        Ok(LoadedVcd {
+            header: parser.into_header(),
+            waveform,
+            diagnostics,
+            net_types,
+        })
+    };
+
+    thread::spawn(move || {
+        let result = loader_fn();
+        *status_clean.lock().unwrap() = (file_size, file_size);
+        result
+    })
+}
+
+/// Starts the waveform at the beginning of the time window with the values
+/// signals held going into it
+fn insert_window_start(
+    waveform: &mut Waveform,
+    from: u64,
+    timestamp: u64,
+    pending: impl Iterator<Item = (usize, VcdEntry)>,
+) -> Result<(), WaveformError> {
+    waveform.insert_timestamp(from)?;
+    for (_, entry) in pending {
+        match entry {
+            VcdEntry::Vector(bv, idcode) => waveform.update_vector(idcode, bv)?,
+            VcdEntry::Real(value, idcode) => waveform.update_vector(idcode, encode_real(value))?,
+            VcdEntry::Timestamp(_) => {}
+        }
+    }
+    if timestamp > from {
+        waveform.insert_timestamp(timestamp)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn load_options_test() {
+    use makai_waveform_db::{bitvector::Logic, WaveformSearchMode, WaveformValueResult};
+
+    let bytes = String::from(
+        "$timescale 1ns $end\n\
+         $scope module top $end\n\
+         $var wire 1 ! clk $end\n\
+         $var wire 4 \" data $end\n\
+         $upscope $end\n\
+         $enddefinitions $end\n\
+         #0\n0!\nb0000 \"\n\
+         #5\n1!\nb01?1 \"\n\
+         #10\n0!\nb1111 \"\n\
+         #15\n1!\nb10",
+    );
+    let status = Arc::new(Mutex::new((0, 0)));
+    assert!(load_strict(bytes.clone(), status.clone())
+        .join()
+        .unwrap()
+        .is_err());
+    let options = LoadOptions {
+        tolerant: true,
+        ..LoadOptions::default()
+    };
+    let LoadedVcd {
+        header,
+        waveform,
+        diagnostics,
+        net_types,
+    } = load(bytes.clone(), status.clone(), &options)
+        .join()
+        .unwrap()
+        .unwrap();
+    assert_eq!(diagnostics.get_skipped(), 2);
+    assert_eq!(diagnostics.get_messages().len(), 2);
+    assert_eq!(waveform.get_timestamps(), &vec![0, 5, 10, 15]);
+    assert!(header.get_variable("top.data").is_some());
+    let data = header.get_variable("top.data").unwrap().get_idcode();
+    assert_eq!(net_types.get(&data), Some(&VcdVariableNetType::Wire));
+
+    let options = LoadOptions {
+        tolerant: true,
+        from: Some(7),
+        to: Some(10),
+        signals: None,
+    };
+    let waveform = load(bytes.clone(), status.clone(), &options)
+        .join()
+        .unwrap()
+        .unwrap()
+        .waveform;
+    assert_eq!(waveform.get_timestamps(), &vec![7, 10]);
+    let clk = header.get_variable("top.clk").unwrap().get_idcode();
+    let value = waveform
+        .search_value_bit_index(clk, 0, WaveformSearchMode::Before, None)
+        .unwrap();
+    assert!(matches!(value, WaveformValueResult::Vector(bv, 0) if bv.get_bit(0) == Logic::One));
+
+    // A window starting after the dump ends keeps the last values
+    let options = LoadOptions {
+        tolerant: true,
+        from: Some(100),
+        to: None,
+        signals: None,
+    };
+    let waveform = load(bytes.clone(), status.clone(), &options)
+        .join()
+        .unwrap()
+        .unwrap()
+        .waveform;
+    assert_eq!(waveform.get_timestamps(), &vec![15]);
+    let value = waveform
+        .search_value_bit_index(clk, 0, WaveformSearchMode::Before, None)
+        .unwrap();
+    assert!(matches!(value, WaveformValueResult::Vector(bv, 0) if bv.get_bit(0) == Logic::One));
+
+    let options = LoadOptions {
+        tolerant: true,
+        signals: Some(HashSet::from([clk])),
+        ..LoadOptions::default()
+    };
+    let waveform = load(bytes, status, &options)
+        .join()
+        .unwrap()
+        .unwrap()
+        .waveform;
+    assert!(waveform
+        .search_value_bit_index(clk, 0, WaveformSearchMode::Before, None)
+        .is_some());
+    assert!(!crate::source::WaveformSource::has_history(&waveform, data));
+}
+
+#[test]
+fn load_real_test() {
+    use makai_waveform_db::{WaveformSearchMode, WaveformValueResult};
+
+    let bytes = String::from(
+        "$timescale 1ns $end\n\
+         $scope module top $end\n\
+         $var real 64 ! volts $end\n\
+         $upscope $end\n\
+         $enddefinitions $end\n\
+         #0\nr1.5 !\n\
+         #5\nr0.25 !\n",
+    );
+    let status = Arc::new(Mutex::new((0, 0)));
+    let LoadedVcd {
+        header, waveform, ..
+    } = load(bytes, status, &LoadOptions::default())
+        .join()
+        .unwrap()
+        .unwrap();
+    let volts = header.get_variable("top.volts").unwrap().get_idcode();
+    let value = waveform
+        .search_value_bit_index(volts, 1, WaveformSearchMode::Before, None)
+        .unwrap();
+    assert!(matches!(value, WaveformValueResult::Vector(bv, 1) if decode_real(&bv) == 0.25));
+}
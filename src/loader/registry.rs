@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::loader::{load, LoadOptions, LoadResult};
+
+/// A file format that can be loaded into a header and waveform
+pub trait Loader: Send + Sync {
+    /// The name of the format, used when logging what a file was loaded as
+    fn get_name(&self) -> &str;
+
+    /// Extensions (without the dot) of the files in this format
+    fn get_extensions(&self) -> &[&str];
+
+    /// If the contents of a file start like this format, which is checked
+    /// before any of the extensions so misnamed files still load
+    fn is_magic(&self, bytes: &[u8]) -> bool;
+
+    /// Loads the file on another thread. The status is the progress through
+    /// the file as (current, total), and the load is only picked up once they
+    /// are equal and not zero.
+    fn load(
+        &self,
+        bytes: Vec<u8>,
+        status: Arc<Mutex<(usize, usize)>>,
+        options: &LoadOptions,
+    ) -> JoinHandle<LoadResult>;
+}
+
+/// Loads VCD files with the loaders of this crate
+pub struct VcdLoader;
+
+impl Loader for VcdLoader {
+    fn get_name(&self) -> &str {
+        "VCD"
+    }
+
+    fn get_extensions(&self) -> &[&str] {
+        &["vcd"]
+    }
+
+    fn is_magic(&self, bytes: &[u8]) -> bool {
+        // Every section of a VCD header is a keyword starting with a dollar
+        bytes
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|b| *b == b'$')
+    }
+
+    fn load(
+        &self,
+        bytes: Vec<u8>,
+        status: Arc<Mutex<(usize, usize)>>,
+        options: &LoadOptions,
+    ) -> JoinHandle<LoadResult> {
+        match String::from_utf8(bytes) {
+            Ok(bytes) => load(bytes, status, options),
+            Err(err) => {
+                let err = std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+                *status.lock().unwrap() = (1, 1);
+                std::thread::spawn(move || Err(err.into()))
+            }
+        }
+    }
+}
+
+/// The loaders a file is matched against, first by the contents of the file
+/// and then by its extension
+#[derive(Clone)]
+pub struct LoaderRegistry {
+    loaders: Vec<Arc<dyn Loader>>,
+}
+
+impl Default for LoaderRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(VcdLoader);
+        registry
+    }
+}
+
+impl LoaderRegistry {
+    /// A registry without any loaders, not even for VCD files
+    pub fn new() -> Self {
+        Self {
+            loaders: Vec::new(),
+        }
+    }
+
+    /// Adds a loader, which is matched before the ones added earlier so it can
+    /// take over formats those already load
+    pub fn register(&mut self, loader: impl Loader + 'static) {
+        self.loaders.insert(0, Arc::new(loader));
+    }
+
+    /// Finds the loader for a file, or none when nothing recognizes it
+    pub fn find(&self, path: &Path, bytes: &[u8]) -> Option<Arc<dyn Loader>> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        self.loaders
+            .iter()
+            .find(|loader| loader.is_magic(bytes))
+            .or_else(|| {
+                self.loaders.iter().find(|loader| {
+                    loader
+                        .get_extensions()
+                        .iter()
+                        .any(|e| e.eq_ignore_ascii_case(extension))
+                })
+            })
+            .cloned()
+    }
+}
+
+#[test]
+fn loader_registry_test() {
+    use makai_vcd_reader::parser::VcdHeader;
+    use makai_waveform_db::Waveform;
+
+    use crate::loader::{LoadDiagnostics, LoadedVcd};
+
+    struct EmptyLoader;
+
+    impl Loader for EmptyLoader {
+        fn get_name(&self) -> &str {
+            "empty"
+        }
+
+        fn get_extensions(&self) -> &[&str] {
+            &["empty", "vcd"]
+        }
+
+        fn is_magic(&self, bytes: &[u8]) -> bool {
+            bytes.starts_with(b"EMPTY")
+        }
+
+        fn load(
+            &self,
+            _: Vec<u8>,
+            status: Arc<Mutex<(usize, usize)>>,
+            _: &LoadOptions,
+        ) -> JoinHandle<LoadResult> {
+            *status.lock().unwrap() = (1, 1);
+            std::thread::spawn(|| {
+                Ok(LoadedVcd {
+                    header: VcdHeader::new(),
+                    waveform: Waveform::new(),
+                    diagnostics: LoadDiagnostics::default(),
+                    net_types: Default::default(),
+                })
+            })
+        }
+    }
+
+    let name = |loader: Option<Arc<dyn Loader>>| loader.map(|l| String::from(l.get_name()));
+    let mut registry = LoaderRegistry::default();
+    let vcd = b"\n$timescale 1ns $end";
+    assert_eq!(
+        name(registry.find(Path::new("a.txt"), vcd)),
+        Some("VCD".into())
+    );
+    assert_eq!(
+        name(registry.find(Path::new("a.VCD"), b"")),
+        Some("VCD".into())
+    );
+    assert_eq!(name(registry.find(Path::new("a.empty"), b"EMPTY")), None);
+    registry.register(EmptyLoader);
+    assert_eq!(
+        name(registry.find(Path::new("a.x"), b"EMPTY")),
+        Some("empty".into())
+    );
+    assert_eq!(
+        name(registry.find(Path::new("a.x"), vcd)),
+        Some("VCD".into())
+    );
+    // Later loaders win when only the extension matches
+    assert_eq!(
+        name(registry.find(Path::new("a.vcd"), b"")),
+        Some("empty".into())
+    );
+    assert_eq!(
+        name(LoaderRegistry::new().find(Path::new("a.vcd"), vcd)),
+        None
+    );
+
+    let status = Arc::new(Mutex::new((0, 0)));
+    let loader = registry.find(Path::new("a.empty"), b"").unwrap();
+    let loaded = loader.load(Vec::new(), status.clone(), &LoadOptions::default());
+    assert!(loaded.join().unwrap().is_ok());
+    assert_eq!(*status.lock().unwrap(), (1, 1));
+    let loaded = VcdLoader.load(vec![b'$', 0xff], status, &LoadOptions::default());
+    assert!(loaded.join().unwrap().is_err());
+}
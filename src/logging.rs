@@ -1,5 +1,15 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
+/// Size the log can grow to before it is moved aside for a new one
+const LOG_SIZE_LIMIT: u64 = 4 << 20;
+
+/// How many of the logs moved aside are kept, as `nalu.log.1` and so on
+const LOG_ROTATIONS: usize = 2;
+
 #[derive(Debug)]
 pub struct FrameTimestamps {
     start: Instant,
@@ -31,6 +41,43 @@ impl FrameTimestamps {
     pub fn get_sections(&self) -> &Vec<(String, Duration)> {
         &self.sections
     }
+
+    /// Frames per second if every frame took as long as this one
+    pub fn get_fps(&self) -> f64 {
+        let total = self.total().as_secs_f64();
+        if total > 0.0 {
+            1.0 / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Per-frame statistics shown by the performance HUD
+#[derive(Debug, Default)]
+pub struct FrameStatistics {
+    pub timestamps: FrameTimestamps,
+    // Input events waiting to be handled at the start of the frame
+    pub input_backlog: usize,
+    // Passes through all components needed to drain the message queue
+    pub update_passes: usize,
+}
+
+impl FrameStatistics {
+    pub fn get_lines(&self) -> Vec<String> {
+        let mut lines = self
+            .timestamps
+            .get_sections()
+            .iter()
+            .map(|(name, duration)| {
+                format!("{:<8}{:>8.2}ms", name, duration.as_secs_f64() * 1000.0)
+            })
+            .collect::<Vec<String>>();
+        lines.push(format!("{:<8}{:>10.1}", "fps", self.timestamps.get_fps()));
+        lines.push(format!("{:<8}{:>10}", "backlog", self.input_backlog));
+        lines.push(format!("{:<8}{:>10}", "passes", self.update_passes));
+        lines
+    }
 }
 
 impl Default for FrameTimestamps {
@@ -38,3 +85,118 @@ impl Default for FrameTimestamps {
         Self::new()
     }
 }
+
+/// The log file in the cache directory, following the XDG cache directory if
+/// it is set and otherwise falling back to the temporary directory
+pub fn get_default_log_path() -> PathBuf {
+    let cache_dir = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")),
+    };
+    cache_dir
+        .unwrap_or_else(std::env::temp_dir)
+        .join("nalu")
+        .join("nalu.log")
+}
+
+fn get_rotated_path(path: &Path, rotation: usize) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(format!(".{rotation}"));
+    PathBuf::from(path)
+}
+
+/// A file appended to until it grows past a size, when it is moved aside
+/// (along with the older ones) and started over, only ever between lines
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    limit: u64,
+    rotations: usize,
+    line_start: bool,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, limit: u64, rotations: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: PathBuf::from(path),
+            size: file.metadata()?.len(),
+            file,
+            limit,
+            rotations,
+            line_start: true,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.rotations == 0 {
+            self.file.set_len(0)?;
+        } else {
+            for rotation in (1..self.rotations).rev() {
+                let from = get_rotated_path(&self.path, rotation);
+                if from.exists() {
+                    std::fs::rename(from, get_rotated_path(&self.path, rotation + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, get_rotated_path(&self.path, 1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.line_start && self.size >= self.limit {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        self.line_start = buf[..written].ends_with(b"\n");
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Logs to a rotating file, creating the directory it is in first
+pub fn setup_logging(path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = RotatingFile::open(path, LOG_SIZE_LIMIT, LOG_ROTATIONS)?;
+    simple_logging::log_to(file, log::LevelFilter::Info);
+    Ok(())
+}
+
+#[test]
+fn rotating_file_test() {
+    let dir = std::env::temp_dir().join(format!("nalu_rotating_file_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("test.log");
+    let mut file = RotatingFile::open(&path, 20, 2).unwrap();
+    for i in 0..7 {
+        // Lines can be written in pieces, which are never split up
+        write!(file, "line ").unwrap();
+        writeln!(file, "{i}").unwrap();
+    }
+    let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+    assert_eq!(read(&path), "line 6\n");
+    assert_eq!(
+        read(&get_rotated_path(&path, 1)),
+        "line 3\nline 4\nline 5\n"
+    );
+    assert_eq!(
+        read(&get_rotated_path(&path, 2)),
+        "line 0\nline 1\nline 2\n"
+    );
+    assert!(!get_rotated_path(&path, 3).exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
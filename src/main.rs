@@ -1,51 +1,144 @@
-pub mod logging;
-pub mod python;
-pub mod state;
-pub mod widgets;
-
+use std::collections::HashSet;
 use std::io::{stdout, Stdout, Write};
 use std::path::PathBuf;
 use std::thread;
 use std::time::{self, Duration, Instant};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossbeam::channel::{unbounded, Sender};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event as CrosstermEvent},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event as CrosstermEvent, MouseButton, MouseEventKind,
+    },
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     tty::IsTty,
     QueueableCommand, Result as CrosstermResult,
 };
-use makai::utils::messages::Messages;
-use tui::{
-    backend::CrosstermBackend,
-    layout::{Alignment, Direction, Rect},
-    style::{Color, Style},
-    widgets::{Block, BorderType, Borders, Gauge, Paragraph},
-    Frame, Terminal,
-};
-use tui_tiling::{
-    component::{simple::ComponentWidgetSimple, Component, ComponentBaseWidget},
-    container::{list::ContainerList, Container, ContainerChild},
-    ResizeError,
-};
+use tui::{backend::CrosstermBackend, Terminal};
 
-use crate::{
-    logging::FrameTimestamps,
-    state::netlist_viewer::NetlistViewerState,
-    state::signal_viewer::SignalViewerState,
-    state::waveform_viewer::WaveformViewerState,
-    state::{NaluOverlay, NaluState},
+use nalu::{
+    app::{
+        get_closed_panes, get_tui, handle_updates, is_filter_focused, is_typing_ahead, render_nalu,
+    },
+    bench,
+    loader::LoadOptions,
+    logging::{get_default_log_path, setup_logging, FrameStatistics, FrameTimestamps},
+    python::script::{set_timeout, DEFAULT_TIMEOUT},
+    query::{combine_hashes, list_signals, query, summarize},
+    state::recovery::{flush_recovery, remove_recovery},
+    state::share::SharedLocation,
+    state::view::parse_radix,
+    state::{is_refresh_key, NaluState},
+    theme::{is_color_disabled, Theme},
+    widgets::layout::{focus_at, PaneLayout},
+    widgets::resize::ResizeHandles,
+    widgets::timescale::DEFAULT_ACCELERATION,
 };
 
+/// Time each frame takes at least, sleeping for whatever is left
+const FRAME_TARGET: Duration = Duration::from_millis(20);
+/// The same while the terminal is not focused, as nobody is typing
+const BACKGROUND_FRAME_TARGET: Duration = Duration::from_millis(200);
+/// How often the screen (and the python view along with it) is drawn while the
+/// terminal is not focused, so loading and following still show up
+const BACKGROUND_REDRAW: Duration = Duration::from_secs(1);
+
+#[derive(Subcommand)]
+enum NaluCommand {
+    /// Print the full name and bit width of every signal in a dump, one per
+    /// line separated by a tab
+    List {
+        /// VCD file to read
+        vcd_file: String,
+        #[clap(long)]
+        /// Only list the signals matching this filter, written like in the
+        /// netlist filter in glob mode (like '**.core.*state*')
+        filter: Option<String>,
+    },
+    /// Print the values of signals at some times without opening the viewer,
+    /// one line per time with the values separated by tabs
+    Query {
+        /// VCD file to read
+        vcd_file: String,
+        #[clap(long = "signal", required = true)]
+        /// Full name of a signal, repeated for more signals
+        signals: Vec<String>,
+        #[clap(long = "at", required = true)]
+        /// Time like 1.5us or a number of timestamps, repeated for more times
+        times: Vec<String>,
+        #[clap(long, default_value = "hex")]
+        /// Radix of the values, one of bin, oct, dec or hex
+        radix: String,
+    },
+    /// Print the transition count, last value and a hash of the changes of
+    /// each signal separated by tabs, followed by a hash of all of them, for
+    /// comparing two runs without opening either
+    Summarize {
+        /// VCD file to read
+        vcd_file: String,
+        #[clap(long = "signal")]
+        /// Full name of a signal to summarize, repeated for more signals, or
+        /// every signal when not given
+        signals: Vec<String>,
+        #[clap(long, default_value = "hex")]
+        /// Radix of the last values, one of bin, oct, dec or hex
+        radix: String,
+    },
+}
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct NaluArgs {
-    /// VCD file that will be loaded
-    vcd_file: String,
+    #[clap(subcommand)]
+    command: Option<NaluCommand>,
+    /// VCD file that will be loaded, picked from the recent files if not given
+    vcd_file: Option<String>,
     #[clap(long)]
     /// Optional python program that can be run
     python: Option<String>,
+    #[clap(long, default_value_t = DEFAULT_TIMEOUT.as_secs())]
+    /// Seconds a call into the python program can run before it is
+    /// interrupted with a TimeoutError, where 0 lets it run forever
+    python_timeout: u64,
+    #[clap(long)]
+    /// Discard value changes before this timestamp
+    from: Option<u64>,
+    #[clap(long)]
+    /// Stop loading value changes after this timestamp
+    to: Option<u64>,
+    #[clap(long)]
+    /// Only parse the header up front, loading signal values when they are added
+    lazy: bool,
+    #[clap(long)]
+    /// Use attributes like bold and reverse video instead of colors (the
+    /// default when NO_COLOR is set or the terminal is dumb)
+    no_color: bool,
+    #[clap(long)]
+    /// Only draw ASCII characters, for fonts or terminals missing the symbols
+    ascii: bool,
+    #[clap(long, default_value_t = DEFAULT_ACCELERATION)]
+    /// How much faster zooming and scrolling gets for every repeat while the
+    /// key is held down, where 1 turns it off
+    zoom_acceleration: f64,
+    #[clap(long)]
+    /// Go to a location copied with `share` in the palette, opening its file
+    /// when no other file is given
+    goto: Option<String>,
+    #[clap(long)]
+    /// Leave out the line of shortcuts for the focused pane at the bottom
+    no_hints: bool,
+    #[clap(long)]
+    /// Leave mouse reporting off, for terminals and multiplexers that break
+    /// with it (the default when NALU_NO_MOUSE is set or TERM is screen)
+    no_mouse: bool,
+    #[clap(long)]
+    /// Where to write the log, which is moved to <file>.1 once it grows too
+    /// large (nalu/nalu.log in the cache directory by default)
+    log_file: Option<String>,
+    #[clap(long, hide = true)]
+    /// Render this many frames of the dump off-screen and print the timings
+    bench_render: Option<usize>,
 }
 
 fn spawn_input_listener(tx: Sender<CrosstermEvent>) {
@@ -56,148 +149,39 @@ fn spawn_input_listener(tx: Sender<CrosstermEvent>) {
     });
 }
 
-fn get_tui(messages: &Messages) -> Result<Box<dyn Container>, ResizeError> {
-    let netlist_main =
-        ContainerList::new("netlist_main".to_string(), Direction::Vertical, false, 0, 0)
-            .from_children(vec![
-                ContainerChild::from(Component::new(
-                    "netlist".to_string(),
-                    1,
-                    Box::new(NetlistViewerState::new(messages.clone())),
-                )),
-                ContainerChild::from(
-                    Component::new(
-                        "filter".to_string(),
-                        1,
-                        Box::new(ComponentWidgetSimple::new().text("TODO: Filter".to_string())),
-                    )
-                    .fixed_height(Some(3)),
-                ),
-            ])?;
-
-    let main = ContainerList::new("main".to_string(), Direction::Horizontal, true, 0, 0)
-        .from_children(vec![
-            ContainerChild::from(netlist_main),
-            ContainerChild::from(Component::new(
-                "signal".to_string(),
-                1,
-                Box::new(SignalViewerState::new(messages.clone())),
-            )),
-            ContainerChild::from(Component::new(
-                "waveform".to_string(),
-                1,
-                Box::new(WaveformViewerState::new(messages.clone())),
-            )),
-        ])?;
-
-    let nalu = ContainerList::new("nalu".to_string(), Direction::Vertical, false, 0, 0)
-        .from_children(vec![
-            ContainerChild::from(
-                Component::new(
-                    "header".to_string(),
-                    0,
-                    Box::new(
-                        ComponentWidgetSimple::new()
-                            .text(format!(
-                            "nalu v{} (Press h for help, p for palette, r to reload, q to quit)",
-                            option_env!("CARGO_PKG_VERSION").unwrap_or("0.0.0")
-                        ))
-                            .style(Style::default().fg(Color::LightCyan))
-                            .alignment(Alignment::Left),
-                    ),
-                )
-                .fixed_height(Some(1)),
-            ),
-            ContainerChild::from(main),
-        ])?;
-
-    Ok(Box::new(nalu))
+/// Returns if the mouse should be left alone by default, as plain `screen`
+/// is what GNU screen and old tmux configs set, which mangle mouse reports
+fn is_mouse_disabled() -> bool {
+    let no_mouse = std::env::var_os("NALU_NO_MOUSE").is_some_and(|v| !v.is_empty());
+    let term = std::env::var("TERM").is_ok_and(|term| term == "dumb" || term == "screen");
+    no_mouse || term
 }
 
-fn get_overlay_rect(frame_rect: Rect, overlay_height: u16) -> Rect {
-    let (y, height) = if frame_rect.height <= overlay_height {
-        (0, frame_rect.height)
-    } else {
-        ((frame_rect.height - overlay_height) / 2, overlay_height)
-    };
-    let (x, width) = if frame_rect.width <= 4 {
-        (0, frame_rect.width)
-    } else {
-        (1, frame_rect.width - 2)
-    };
-    Rect::new(x, y, width, height)
-}
-
-fn render_overlay_layout(
-    frame: &mut Frame<CrosstermBackend<std::io::Stdout>>,
-    nalu_state: &NaluState,
-) {
-    match &nalu_state.get_overlay() {
-        NaluOverlay::Loading => frame.render_widget(
-            Gauge::default()
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .style(Style::default().fg(Color::White))
-                        .border_type(BorderType::Rounded)
-                        .title("Loading"),
-                )
-                .gauge_style(Style::default().fg(Color::LightCyan))
-                .percent(nalu_state.get_percent() as u16),
-            get_overlay_rect(frame.size(), 3),
-        ),
-        NaluOverlay::HelpPrompt => frame.render_widget(
-            Paragraph::new("<Insert Help Messages>")
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .style(Style::default().fg(Color::White))
-                        .border_type(BorderType::Rounded)
-                        .title("Help"),
-                )
-                .style(Style::default().fg(Color::LightCyan)),
-            get_overlay_rect(frame.size(), 10),
-        ),
-        NaluOverlay::QuitPrompt => frame.render_widget(
-            Paragraph::new("Press q to quit, esc to not...")
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .style(Style::default().fg(Color::White))
-                        .border_type(BorderType::Rounded)
-                        .title("Quit?"),
-                )
-                .style(Style::default().fg(Color::LightCyan)),
-            get_overlay_rect(frame.size(), 3),
-        ),
-        NaluOverlay::Palette => frame.render_widget(
-            Paragraph::new(nalu_state.get_palette())
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .style(Style::default().fg(Color::White))
-                        .border_type(BorderType::Rounded)
-                        .title("Palette"),
-                )
-                .style(Style::default().fg(Color::LightCyan)),
-            get_overlay_rect(frame.size(), 10),
-        ),
-        NaluOverlay::None => {}
-    }
-}
-
-fn setup_terminal() -> CrosstermResult<Terminal<CrosstermBackend<Stdout>>> {
+/// Sets up the terminal, returning if the mouse is reported along with it
+fn setup_terminal(mouse: bool) -> CrosstermResult<(Terminal<CrosstermBackend<Stdout>>, bool)> {
     enable_raw_mode().unwrap();
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    terminal.backend_mut().queue(EnableMouseCapture)?;
+    // Going on without the mouse beats not starting at all
+    let mouse = mouse
+        && match terminal.backend_mut().queue(EnableMouseCapture) {
+            Ok(_) => true,
+            Err(err) => {
+                log::warn!("Cannot capture the mouse, going on without it ({err})");
+                false
+            }
+        };
+    terminal.backend_mut().queue(EnableFocusChange)?;
     terminal.backend_mut().queue(EnterAlternateScreen)?;
     terminal.backend_mut().flush()?;
     terminal.clear()?;
-    Ok(terminal)
+    Ok((terminal, mouse))
 }
 
 fn cleanup_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> CrosstermResult<()> {
-    terminal.backend_mut().queue(DisableMouseCapture)?;
+    // Fails the same way when capturing the mouse did, which is no reason to
+    // leave the terminal a mess
+    let _ = terminal.backend_mut().queue(DisableMouseCapture);
+    terminal.backend_mut().queue(DisableFocusChange)?;
     terminal.backend_mut().queue(LeaveAlternateScreen)?;
     terminal.backend_mut().flush()?;
     disable_raw_mode()?;
@@ -209,44 +193,109 @@ fn cleanup_terminal_force() -> CrosstermResult<()> {
     cleanup_terminal(&mut Terminal::new(CrosstermBackend::new(stdout()))?)
 }
 
-fn nalu_main(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> CrosstermResult<String> {
+/// Hands the terminal back to the shell and stops like ctrl+z would without
+/// raw mode (which turns it into a key press instead), setting the terminal up
+/// again and redrawing everything once continued with `fg`
+#[cfg(unix)]
+fn suspend(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mouse: bool) -> CrosstermResult<()> {
+    cleanup_terminal(terminal)?;
+    // Only returns after the shell continues nalu
+    unsafe { libc::raise(libc::SIGTSTP) };
+    enable_raw_mode()?;
+    if mouse {
+        terminal.backend_mut().queue(EnableMouseCapture)?;
+    }
+    terminal.backend_mut().queue(EnableFocusChange)?;
+    terminal.backend_mut().queue(EnterAlternateScreen)?;
+    terminal.backend_mut().flush()?;
+    terminal.clear()?;
+    Ok(())
+}
+
+fn nalu_main(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    mouse: bool,
+) -> CrosstermResult<String> {
     let args = NaluArgs::parse();
+    let theme = Theme {
+        monochrome: args.no_color || is_color_disabled(),
+        ascii: args.ascii,
+    };
 
+    // Checked to parse before setting up the terminal
+    let location = args
+        .goto
+        .map(|location| location.parse::<SharedLocation>().unwrap());
+    let vcd_file = args
+        .vcd_file
+        .or_else(|| location.as_ref().and_then(|location| location.file.clone()));
+    set_timeout(Duration::from_secs(args.python_timeout));
     let mut nalu_state = NaluState::new(
-        PathBuf::from(args.vcd_file.clone()),
+        vcd_file.map(PathBuf::from),
         args.python.map(PathBuf::from),
+        LoadOptions {
+            from: args.from,
+            to: args.to,
+            signals: args.lazy.then(HashSet::new),
+            ..LoadOptions::default()
+        },
     );
+    nalu_state.set_hints_enabled(!args.no_hints);
+    nalu_state.set_mouse_enabled(mouse);
+    nalu_state.set_zoom_acceleration(args.zoom_acceleration);
+    if let Some(location) = location {
+        nalu_state.set_location(location);
+    }
     let mut tui = get_tui(nalu_state.get_messages()).unwrap();
+    let mut resize_handles = ResizeHandles::new();
+    let mut pane_layout = PaneLayout::new(
+        nalu_state.get_messages().clone(),
+        get_closed_panes(nalu_state.get_messages()),
+    );
     nalu_state.handle_load();
 
     // Setup event listeners
     let (tx_input, rx_input) = unbounded();
     spawn_input_listener(tx_input);
 
+    // Terminals that do not report focus changes are always treated as focused
+    let mut focused = true;
+    let mut draw_time: Option<Instant> = None;
     loop {
         let mut frame_duration = FrameTimestamps::new();
         let frame_start = Instant::now();
 
-        terminal.draw(|frame| {
-            tui.as_base_mut().invalidate();
-            if let Err(err) = tui
-                .as_base_mut()
-                .resize(frame.size().width, frame.size().height)
-            {
-                log::error!("Resizing Error! ({err:?})");
-                panic!("Resizing Error! ({err:?})");
-            }
-            frame.render_stateful_widget(
-                ComponentBaseWidget::from(tui.as_base_mut()),
-                frame.size(),
-                &mut (),
-            );
-            render_overlay_layout(frame, &nalu_state);
-        })?;
+        // The size is asked for again in case the terminal never reported a
+        // resize, and clearing throws away what is thought to be on screen
+        if nalu_state.take_refresh() {
+            terminal.autoresize()?;
+            terminal.clear()?;
+            draw_time = None;
+        }
+        if focused || draw_time.is_none_or(|time| time.elapsed() >= BACKGROUND_REDRAW) {
+            terminal
+                .draw(|frame| render_nalu(frame, &mut *tui, &resize_handles, &nalu_state, theme))?;
+            draw_time = Some(Instant::now());
+        }
         frame_duration.timestamp(String::from("draw"));
 
+        let input_backlog = rx_input.len();
         while !rx_input.is_empty() {
             match rx_input.recv().unwrap() {
+                #[cfg(unix)]
+                CrosstermEvent::Key(event::KeyEvent {
+                    code: event::KeyCode::Char('z'),
+                    modifiers,
+                    ..
+                }) if modifiers.contains(event::KeyModifiers::CONTROL) => suspend(terminal, mouse)?,
+                // Typing in the filter box should not trigger any global keys
+                // other than redrawing the screen
+                CrosstermEvent::Key(key)
+                    if !is_refresh_key(&key)
+                        && (is_filter_focused(&*tui) || is_typing_ahead(&*tui)) =>
+                {
+                    tui.as_base_mut().handle_key(key);
+                }
                 CrosstermEvent::Key(key) => {
                     if let Some(key) = nalu_state.handle_key(key) {
                         tui.as_base_mut().handle_key(key);
@@ -256,30 +305,44 @@ fn nalu_main(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> CrosstermResu
                     if let Some((x, y, kind)) =
                         nalu_state.handle_mouse(event.column, event.row, event.kind)
                     {
+                        if kind == MouseEventKind::Down(MouseButton::Right) {
+                            focus_at(&mut *tui, x, y);
+                        }
+                        resize_handles.handle_mouse(&mut *tui, x, y, kind);
                         tui.as_base_mut().handle_mouse(x, y, Some(kind));
                     }
                 }
-                CrosstermEvent::Resize(_, _)
-                | CrosstermEvent::FocusGained
-                | CrosstermEvent::FocusLost
-                | CrosstermEvent::Paste(_) => {}
+                CrosstermEvent::FocusGained => focused = true,
+                CrosstermEvent::FocusLost => focused = false,
+                CrosstermEvent::Resize(_, _) | CrosstermEvent::Paste(_) => {}
             }
         }
+        // Anything typed or clicked in the background shows up on the next frame
+        if input_backlog > 0 {
+            draw_time = None;
+        }
         frame_duration.timestamp(String::from("input"));
 
         // Handle any updates
         nalu_state.handle_vcd();
-        while !nalu_state.get_messages().is_empty() {
-            tui.as_base_mut().handle_update();
-        }
+        nalu_state.handle_autosave();
+        nalu_state.handle_follow();
+        let update_passes = handle_updates(&mut nalu_state, &mut *tui, &mut pane_layout);
         if let Some(msg) = nalu_state.get_done() {
+            // Only a clean exit gives up on the signals kept for recovery
+            if msg.is_empty() {
+                remove_recovery();
+            }
             cleanup_terminal(terminal)?;
             return Ok(msg);
         }
         frame_duration.timestamp(String::from("updates"));
 
         // Sleep for unused frame time
-        let frame_target = Duration::from_millis(20);
+        let frame_target = match focused {
+            true => FRAME_TARGET,
+            false => BACKGROUND_FRAME_TARGET,
+        };
         let frame_elapsed = frame_start.elapsed();
         if frame_elapsed < frame_target {
             thread::sleep(frame_target - frame_start.elapsed());
@@ -291,6 +354,11 @@ fn nalu_main(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> CrosstermResu
             frame_duration.get_sections(),
             frame_duration.total()
         );
+        nalu_state.set_frame_statistics(FrameStatistics {
+            timestamps: frame_duration,
+            input_backlog,
+            update_passes,
+        });
     }
 }
 
@@ -300,19 +368,104 @@ use backtrace::Backtrace;
 use std::cell::RefCell;
 
 thread_local! {
-    static BACKTRACE: RefCell<Option<Backtrace>> = RefCell::new(None);
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
 }
 
 fn main() -> CrosstermResult<()> {
     // Parse args once to exit before setting up TUI if necessary
-    let _ = NaluArgs::parse();
+    let args = NaluArgs::parse();
+
+    if let Some(frames) = args.bench_render {
+        let Some(vcd_file) = args.vcd_file else {
+            println!("Error: Benchmarking needs a VCD file!");
+            return Ok(());
+        };
+        if let Err(err) = bench::bench_render(&PathBuf::from(vcd_file), frames) {
+            println!("Error: {err}");
+        }
+        return Ok(());
+    }
+
+    if let Some(NaluCommand::List { vcd_file, filter }) = &args.command {
+        match list_signals(&PathBuf::from(vcd_file), filter.as_deref()) {
+            Ok(signals) => signals
+                .iter()
+                .for_each(|(name, width)| println!("{name}\t{width}")),
+            Err(err) => {
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(NaluCommand::Summarize {
+        vcd_file,
+        signals,
+        radix,
+    }) = &args.command
+    {
+        let Some(radix) = parse_radix(radix) else {
+            eprintln!("Error: Radix has to be one of bin, oct, dec or hex");
+            std::process::exit(1);
+        };
+        match summarize(&PathBuf::from(vcd_file), signals, radix) {
+            Ok(summaries) => {
+                for summary in &summaries {
+                    println!(
+                        "{}\t{}\t{}\t{:016x}",
+                        summary.name, summary.transitions, summary.last, summary.hash
+                    );
+                }
+                println!("total\t{:016x}", combine_hashes(&summaries));
+            }
+            Err(err) => {
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(NaluCommand::Query {
+        vcd_file,
+        signals,
+        times,
+        radix,
+    }) = &args.command
+    {
+        // Errors go to stderr with a failing exit code for scripts to check
+        let Some(radix) = parse_radix(radix) else {
+            eprintln!("Error: Radix has to be one of bin, oct, dec or hex");
+            std::process::exit(1);
+        };
+        match query(&PathBuf::from(vcd_file), signals, times, radix) {
+            Ok(rows) => rows.iter().for_each(|row| println!("{}", row.join("\t"))),
+            Err(err) => {
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(Err(err)) = args.goto.as_ref().map(|l| l.parse::<SharedLocation>()) {
+        println!("Error: {err}");
+        return Ok(());
+    }
 
     if !stdout().is_tty() {
         println!("Error: Cannot open viewer when not TTY!");
         return Ok(());
     }
 
-    simple_logging::log_to_file(".nalu.log", log::LevelFilter::Info)?;
+    let log_path = args
+        .log_file
+        .map(PathBuf::from)
+        .unwrap_or_else(get_default_log_path);
+    if let Err(err) = setup_logging(&log_path) {
+        eprintln!("Warning: Cannot write the log to {log_path:?} ({err})");
+    }
     log::info!("Starting Nalu...");
 
     std::panic::set_hook(Box::new(|_| {
@@ -321,9 +474,14 @@ fn main() -> CrosstermResult<()> {
     }));
 
     // Catch any panics and try to cleanup the terminal first
-    match std::panic::catch_unwind(|| nalu_main(&mut setup_terminal().unwrap()).unwrap()) {
+    let mouse = !args.no_mouse && !is_mouse_disabled();
+    match std::panic::catch_unwind(|| {
+        let (mut terminal, mouse) = setup_terminal(mouse).unwrap();
+        nalu_main(&mut terminal, mouse).unwrap()
+    }) {
         Ok(msg) => println!("{}", msg),
         Err(e) => {
+            flush_recovery();
             cleanup_terminal_force()?;
             let backtrace = BACKTRACE.with(|b| b.borrow_mut().take()).unwrap();
             println!("Error:\n{:?}\n{:?}", e, backtrace);
@@ -1,4 +1,7 @@
 pub mod bitvector;
 pub mod buffer;
+pub mod color;
+pub mod error;
+pub mod script;
 pub mod vcd_header;
 pub mod waveform;
@@ -24,18 +24,23 @@ impl BitVectorPy {
     }
 }
 
+/// The value of the bits with unknown and high impedance bits read as 0 and 1
+pub fn to_biguint(bitvector: &BitVector) -> BigUint {
+    let mut value = BigUint::default();
+    for index in 0..bitvector.get_bit_width() {
+        match bitvector.get_bit(index) {
+            Logic::Zero | Logic::Unknown => value.set_bit(index as u64, false),
+            Logic::One | Logic::HighImpedance => value.set_bit(index as u64, true),
+        }
+    }
+    value
+}
+
 #[pymethods]
 impl BitVectorPy {
     #[pyo3(name = "get_value")]
     fn get_value_py(self_: PyRef<'_, Self>) -> PyResult<BigUint> {
-        let mut value = BigUint::default();
-        for index in 0..self_.bitvector.get_bit_width() {
-            match self_.bitvector.get_bit(index) {
-                Logic::Zero | Logic::Unknown => value.set_bit(index as u64, false),
-                Logic::One | Logic::HighImpedance => value.set_bit(index as u64, true),
-            }
-        }
-        Ok(value)
+        Ok(to_biguint(&self_.bitvector))
     }
 
     #[pyo3(name = "get_mask")]
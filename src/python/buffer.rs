@@ -13,7 +13,7 @@ impl BufferPy {
         Self {
             width,
             height,
-            buffer: vec![' '].repeat((width * height) as usize),
+            buffer: [' '].repeat((width * height) as usize),
         }
     }
 
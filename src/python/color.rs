@@ -0,0 +1,121 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use makai_waveform_db::{bitvector::BitVectorRadix, WaveformValueResult};
+use pyo3::prelude::*;
+use tui::style::Color;
+
+use crate::{
+    loader::decode_real,
+    python::bitvector::to_biguint,
+    python::script::{is_timeout, with_timeout},
+};
+
+/// Most values remembered per rule before starting over, for signals like
+/// counters that rarely repeat a value
+const COLOR_CACHE_LIMIT: usize = 4096;
+
+/// Parses the color names returned by coloring rules, either one of the
+/// terminal colors like "red" or "light_blue" or a hex color like "#ff8000"
+pub fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    let color = match name.to_lowercase().replace(['_', ' '], "").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// A python function picking the color of a signal from its value, which is
+/// passed as an int (with unknown bits as 0) or as a float for reals
+pub struct ColorRule {
+    rule: PyObject,
+    // Colors are looked up for every segment drawn, so only call into python
+    // for values that have not been seen yet
+    cache: RefCell<HashMap<String, Option<Color>>>,
+    // Set once the rule ran for too long, after which it is no longer called
+    timed_out: Cell<bool>,
+}
+
+impl ColorRule {
+    pub fn new(rule: PyObject) -> Self {
+        Self {
+            rule,
+            cache: RefCell::new(HashMap::new()),
+            timed_out: Cell::new(false),
+        }
+    }
+
+    pub fn get_color(&self, value: &WaveformValueResult, is_real: bool) -> Option<Color> {
+        let key = match value {
+            WaveformValueResult::Vector(bv, _) if is_real => format!("{}", decode_real(bv)),
+            WaveformValueResult::Vector(bv, _) => bv.to_string_radix(BitVectorRadix::Binary),
+            WaveformValueResult::Real(value, _) => format!("{value}"),
+        };
+        if let Some(color) = self.cache.borrow().get(&key) {
+            return *color;
+        }
+        if self.timed_out.get() {
+            return None;
+        }
+        let result = with_timeout(|py| {
+            let value = match value {
+                WaveformValueResult::Vector(bv, _) if is_real => decode_real(bv).into_py(py),
+                WaveformValueResult::Vector(bv, _) => to_biguint(bv).into_py(py),
+                WaveformValueResult::Real(value, _) => value.into_py(py),
+            };
+            self.rule.call1(py, (value,))?.extract::<Option<String>>(py)
+        });
+        let color = match result {
+            Ok(Some(name)) => {
+                let color = parse_color(&name);
+                if color.is_none() {
+                    log::error!("Python Color Rule Error: Unknown color {name}");
+                }
+                color
+            }
+            Ok(None) => None,
+            Err(err) => {
+                log::error!("Python Color Rule Error: {err:?}");
+                self.timed_out.set(is_timeout(&err));
+                None
+            }
+        };
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= COLOR_CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(key, color);
+        color
+    }
+}
+
+#[test]
+fn parse_color_test() {
+    assert_eq!(parse_color("red"), Some(Color::Red));
+    assert_eq!(parse_color("Light_Blue"), Some(Color::LightBlue));
+    assert_eq!(parse_color("#ff8000"), Some(Color::Rgb(255, 128, 0)));
+    assert_eq!(parse_color("#ff80"), None);
+    assert_eq!(parse_color("mauve"), None);
+}
@@ -0,0 +1,219 @@
+use pyo3::exceptions::PySyntaxError;
+use pyo3::prelude::*;
+
+use crate::python::script::format_error;
+
+/// Words of python source highlighted as keywords
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "break", "class", "continue", "def", "del", "elif", "else", "except",
+    "False", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "None",
+    "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while", "with", "yield",
+];
+
+/// A line of a python traceback, split up so each part can be highlighted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TracebackLine {
+    /// A frame of the call stack, as the file, line number and function
+    Frame(String, usize, String),
+    /// The source code a frame stopped at, along with its line number
+    Source(usize, String),
+    /// The type of the exception raised and its message
+    Exception(String, String),
+    Text(String),
+}
+
+/// What to run again once the script has been fixed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PythonRerun {
+    /// A function run from the palette with its arguments
+    Function(String, Vec<String>),
+    /// Everything loaded from the script, like views and annotations
+    Script,
+}
+
+/// A python exception raised while running part of the script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonError {
+    /// What was being run, like `annotations()`
+    pub context: String,
+    pub lines: Vec<TracebackLine>,
+    pub rerun: PythonRerun,
+}
+
+impl PythonError {
+    pub fn new(context: &str, err: &PyErr, rerun: PythonRerun) -> Self {
+        let mut lines = parse_traceback(&format_error(err));
+        // Syntax errors are raised before anything runs so there are no frames
+        // to show, point at the line it was found on instead
+        let syntax = Python::with_gil(|py| {
+            if !err.is_instance_of::<PySyntaxError>(py) {
+                return None;
+            }
+            let value = err.value(py);
+            let file = value.getattr("filename").ok()?.extract::<String>().ok()?;
+            let line = value.getattr("lineno").ok()?.extract::<usize>().ok()?;
+            let source = value.getattr("text").ok()?.extract::<String>().ok()?;
+            Some((file, line, source))
+        });
+        if let Some((file, line, source)) = syntax {
+            let position = lines
+                .iter()
+                .position(|l| matches!(l, TracebackLine::Exception(_, _)))
+                .unwrap_or(lines.len());
+            lines.insert(position, TracebackLine::Frame(file, line, String::new()));
+            lines.insert(
+                position + 1,
+                TracebackLine::Source(line, source.trim_end().to_string()),
+            );
+        }
+        Self {
+            context: String::from(context),
+            lines,
+            rerun,
+        }
+    }
+}
+
+/// Parses a frame line like `  File "gecko.py", line 12, in main`
+fn parse_frame(line: &str) -> Option<TracebackLine> {
+    let (file, rest) = line.strip_prefix("  File \"")?.split_once("\", line ")?;
+    let (number, function) = match rest.split_once(", in ") {
+        Some((number, function)) => (number, function),
+        None => (rest, ""),
+    };
+    Some(TracebackLine::Frame(
+        String::from(file),
+        number.parse().ok()?,
+        String::from(function),
+    ))
+}
+
+/// Parses the last line of a traceback like `KeyError: 'clk'`, where the name
+/// of the exception can include the module it is from
+fn parse_exception(line: &str) -> Option<TracebackLine> {
+    let (name, message) = line.split_once(": ").unwrap_or((line, ""));
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    is_name.then(|| TracebackLine::Exception(String::from(name), String::from(message)))
+}
+
+/// Splits up a traceback as python prints it, leaving out the lines of `^`
+/// that newer versions of python use to point at a part of the source
+pub fn parse_traceback(text: &str) -> Vec<TracebackLine> {
+    let mut lines = Vec::new();
+    let mut source_line = None;
+    for line in text.lines() {
+        if let Some(frame) = parse_frame(line) {
+            if let TracebackLine::Frame(_, number, _) = &frame {
+                source_line = Some(*number);
+            }
+            lines.push(frame);
+            continue;
+        }
+        if line.trim().chars().all(|c| c == '^' || c == '~') && !line.trim().is_empty() {
+            continue;
+        }
+        match source_line.take() {
+            Some(number) if line.starts_with("    ") => {
+                lines.push(TracebackLine::Source(number, line[4..].to_string()));
+            }
+            _ if line.starts_with(char::is_whitespace) => {
+                lines.push(TracebackLine::Text(String::from(line)))
+            }
+            _ => lines.push(
+                parse_exception(line).unwrap_or_else(|| TracebackLine::Text(String::from(line))),
+            ),
+        }
+    }
+    lines
+}
+
+/// Kinds of tokens in a line of python source that are colored differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceToken {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Other,
+}
+
+/// Splits a line of python source into roughly the tokens an editor would
+/// color, without trying to handle every corner of the language
+pub fn get_source_tokens(source: &str) -> Vec<(SourceToken, String)> {
+    let chars = source.chars().collect::<Vec<char>>();
+    let mut tokens: Vec<(SourceToken, String)> = Vec::new();
+    let mut push = |kind: SourceToken, text: String| match tokens.last_mut() {
+        Some((last, last_text)) if *last == kind && kind == SourceToken::Other => {
+            last_text.push_str(&text)
+        }
+        _ => tokens.push((kind, text)),
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c == '#' {
+            push(SourceToken::Comment, chars[i..].iter().collect());
+            break;
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            push(SourceToken::String, chars[start..i].iter().collect());
+        } else if c.is_alphanumeric() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word = chars[start..i].iter().collect::<String>();
+            let kind = if PYTHON_KEYWORDS.contains(&word.as_str()) {
+                SourceToken::Keyword
+            } else if c.is_ascii_digit() {
+                SourceToken::Number
+            } else {
+                SourceToken::Other
+            };
+            push(kind, word);
+        } else {
+            i += 1;
+            push(SourceToken::Other, c.to_string());
+        }
+    }
+    tokens
+}
+
+#[test]
+fn parse_traceback_test() {
+    let traceback = "Traceback (most recent call last):\n  \
+        File \"/sim/gecko.py\", line 12, in main\n    \
+        return report(vcd_header)\n           \
+        ^^^^^^^^^^^^^^^^^^\n\
+        KeyError: 'TOP.clk'";
+    assert_eq!(
+        parse_traceback(traceback),
+        vec![
+            TracebackLine::Text(String::from("Traceback (most recent call last):")),
+            TracebackLine::Frame(String::from("/sim/gecko.py"), 12, String::from("main")),
+            TracebackLine::Source(12, String::from("return report(vcd_header)")),
+            TracebackLine::Exception(String::from("KeyError"), String::from("'TOP.clk'")),
+        ]
+    );
+    assert_eq!(
+        get_source_tokens("if x == \"a\": return 10 # done"),
+        vec![
+            (SourceToken::Keyword, String::from("if")),
+            (SourceToken::Other, String::from(" x == ")),
+            (SourceToken::String, String::from("\"a\"")),
+            (SourceToken::Other, String::from(": ")),
+            (SourceToken::Keyword, String::from("return")),
+            (SourceToken::Other, String::from(" ")),
+            (SourceToken::Number, String::from("10")),
+            (SourceToken::Other, String::from(" ")),
+            (SourceToken::Comment, String::from("# done")),
+        ]
+    );
+}
@@ -0,0 +1,517 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use pyo3::exceptions::{PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+
+use crate::python::{buffer::BufferPy, vcd_header::VcdHeaderPy, waveform::WaveformPy};
+use crate::state::view::{get_radix_name, parse_radix, SignalView, ViewEntry};
+
+/// Colors the values of a signal by calling the rule with each value drawn,
+/// which returns a color name or None to keep the default color
+#[pyfunction]
+#[pyo3(name = "color_when")]
+fn color_when_py(py: Python<'_>, name: String, rule: PyObject) -> PyResult<()> {
+    py.import("nalu")?
+        .getattr("_color_rules")?
+        .call_method1("append", ((name, rule),))?;
+    Ok(())
+}
+
+/// The named cursors placed in the waveform viewer, kept up to date for
+/// scripts reading them with `nalu.cursors()`
+static CURSORS: Mutex<Vec<(char, u64)>> = Mutex::new(Vec::new());
+
+pub fn set_cursors(cursors: Vec<(char, u64)>) {
+    *CURSORS.lock().unwrap() = cursors;
+}
+
+/// Returns the named cursors as a dictionary of their names to timestamps
+#[pyfunction]
+#[pyo3(name = "cursors")]
+fn cursors_py() -> HashMap<String, u64> {
+    CURSORS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, timestamp)| (name.to_string(), *timestamp))
+        .collect()
+}
+
+/// Where a script asked to move the waveform viewer, applied once it returns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PythonNavigation {
+    Goto(u64),
+    Zoom(Range<u64>),
+}
+
+/// The navigation asked for with `nalu.goto()` and `nalu.zoom()` that has not
+/// been applied yet
+static NAVIGATION: Mutex<Vec<PythonNavigation>> = Mutex::new(Vec::new());
+
+/// Takes the navigation asked for since the last call, in the order asked
+pub fn take_navigation() -> Vec<PythonNavigation> {
+    std::mem::take(&mut *NAVIGATION.lock().unwrap())
+}
+
+/// Moves the cursor of the waveform viewer to the timestamp
+#[pyfunction]
+#[pyo3(name = "goto")]
+fn goto_py(timestamp: u64) {
+    NAVIGATION
+        .lock()
+        .unwrap()
+        .push(PythonNavigation::Goto(timestamp));
+}
+
+/// Shows the timestamps from start up to end in the waveform viewer
+#[pyfunction]
+#[pyo3(name = "zoom")]
+fn zoom_py(start: u64, end: u64) -> PyResult<()> {
+    if start >= end {
+        return Err(PyValueError::new_err(format!(
+            "Empty range: {start}..{end}"
+        )));
+    }
+    NAVIGATION
+        .lock()
+        .unwrap()
+        .push(PythonNavigation::Zoom(start..end));
+    Ok(())
+}
+
+/// How long a call into the script runs before it is interrupted by default
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The timeout in milliseconds, where zero lets the script run forever
+static TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_TIMEOUT.as_millis() as u64);
+
+pub fn set_timeout(timeout: Duration) {
+    TIMEOUT_MS.store(timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+thread_local! {
+    // If the watched call in progress timed out, so calls made from inside it
+    // are only watched again once it has
+    static WATCHED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Holds the GIL to run python code like [`Python::with_gil`], but raises a
+/// `TimeoutError` inside the script when it runs longer than the timeout, so
+/// an endless loop in a script shows up as an error instead of freezing nalu.
+/// The watchdog thread waits for the GIL, which the interpreter hands over
+/// between lines of python, so code stuck inside a single native call is only
+/// interrupted once that call returns. Calls made from inside a watched call
+/// share its watchdog, so a batch of them (like the coloring rules of a frame)
+/// only starts one.
+pub fn with_timeout<T>(f: impl FnOnce(Python<'_>) -> PyResult<T>) -> PyResult<T> {
+    let timeout = Duration::from_millis(TIMEOUT_MS.load(Ordering::Relaxed));
+    with_timeout_of(timeout, f)
+}
+
+fn with_timeout_of<T>(timeout: Duration, f: impl FnOnce(Python<'_>) -> PyResult<T>) -> PyResult<T> {
+    Python::with_gil(|py| {
+        let outer = WATCHED.with(|watched| watched.borrow().clone());
+        let is_watched = outer
+            .as_ref()
+            .is_some_and(|timed_out| !timed_out.load(Ordering::SeqCst));
+        if timeout.is_zero() || is_watched {
+            return f(py);
+        }
+        let thread = py
+            .import("threading")?
+            .getattr("get_ident")?
+            .call0()?
+            .extract::<u64>()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let (done, wait) = mpsc::channel::<()>();
+        {
+            let (running, timed_out) = (running.clone(), timed_out.clone());
+            thread::spawn(move || {
+                if wait.recv_timeout(timeout) != Err(mpsc::RecvTimeoutError::Timeout) {
+                    return;
+                }
+                Python::with_gil(|_| {
+                    // Holding the GIL, the call either still runs or is done
+                    if running.load(Ordering::SeqCst) {
+                        timed_out.store(true, Ordering::SeqCst);
+                        unsafe {
+                            pyo3::ffi::PyThreadState_SetAsyncExc(
+                                thread as _,
+                                pyo3::ffi::PyExc_TimeoutError,
+                            );
+                        }
+                    }
+                });
+            });
+        }
+        WATCHED.with(|watched| *watched.borrow_mut() = Some(timed_out.clone()));
+        let result = f(py);
+        WATCHED.with(|watched| *watched.borrow_mut() = outer);
+        running.store(false, Ordering::SeqCst);
+        drop(done);
+        if !timed_out.load(Ordering::SeqCst) {
+            return result;
+        }
+        // An interrupt raised after the last line ran would otherwise go off
+        // in whatever python runs next
+        unsafe { pyo3::ffi::PyThreadState_SetAsyncExc(thread as _, std::ptr::null_mut()) };
+        match result {
+            Err(err) if err.is_instance_of::<PyTimeoutError>(py) => {
+                let message = format!("Script ran for longer than {}s", timeout.as_secs_f64());
+                err.value(py).setattr("args", (message,))?;
+                Err(err)
+            }
+            result => result,
+        }
+    })
+}
+
+/// Checks if the script raised the error for running too long, so whatever
+/// calls it often can stop doing so
+pub fn is_timeout(err: &PyErr) -> bool {
+    Python::with_gil(|py| err.is_instance_of::<PyTimeoutError>(py))
+}
+
+/// Registers the nalu module and loads the python file as a module
+pub fn load_script<'py>(py: Python<'py>, path: &Path) -> PyResult<&'py PyModule> {
+    // Whatever a previous run asked for before it raised is left behind
+    NAVIGATION.lock().unwrap().clear();
+    let nalu = PyModule::new(py, "nalu")?;
+    nalu.add_class::<crate::python::waveform::WaveformSearchModePy>()?;
+    nalu.add("_color_rules", PyList::empty(py))?;
+    nalu.add_function(wrap_pyfunction!(color_when_py, nalu)?)?;
+    nalu.add_function(wrap_pyfunction!(cursors_py, nalu)?)?;
+    nalu.add_function(wrap_pyfunction!(goto_py, nalu)?)?;
+    nalu.add_function(wrap_pyfunction!(zoom_py, nalu)?)?;
+    py.import("sys")?
+        .getattr("modules")?
+        .set_item("nalu", nalu)?;
+
+    // Files next to the script can be imported, like groups saved on their own
+    if let Some(dir) = path.parent().and_then(|dir| dir.to_str()) {
+        let sys_path = py.import("sys")?.getattr("path")?;
+        if !sys_path.contains(dir)? {
+            sys_path.call_method1("insert", (0, dir))?;
+        }
+    }
+
+    let python_bytes = std::fs::read(path)?;
+    let python_file = String::from_utf8_lossy(&python_bytes);
+    // Naming the file lets tracebacks point at the lines of the script
+    PyModule::from_code(py, &python_file, &path.to_string_lossy(), "")
+}
+
+/// Runs the optional `annotations(vcd_header)` function of the script, which
+/// returns a list of (timestamp, text) tuples
+pub fn run_annotations(path: &Path, vcd_header: VcdHeaderPy) -> PyResult<Vec<(u64, String)>> {
+    with_timeout(|py| {
+        let module = load_script(py, path)?;
+        if !module.hasattr("annotations")? {
+            return Ok(Vec::new());
+        }
+        module
+            .getattr("annotations")?
+            .call1((vcd_header,))?
+            .extract::<Vec<(u64, String)>>()
+    })
+}
+
+/// Runs the script once for a hook called often enough that the script
+/// should not run again every time, keeping the module only if it defines it
+pub fn load_hook(path: &Path, name: &str) -> PyResult<Option<Py<PyModule>>> {
+    with_timeout(|py| {
+        let module = load_script(py, path)?;
+        Ok(module.hasattr(name)?.then(|| module.into()))
+    })
+}
+
+/// Calls an optional hook of the script, returning what it returned or
+/// nothing when the script does not define it
+fn run_hook<'py>(
+    py: Python<'py>,
+    path: &Path,
+    name: &str,
+    args: impl IntoPy<Py<PyTuple>>,
+) -> PyResult<Option<&'py PyAny>> {
+    let module = load_script(py, path)?;
+    if !module.hasattr(name)? {
+        return Ok(None);
+    }
+    Ok(Some(module.getattr(name)?.call1(args)?))
+}
+
+/// Runs the optional `on_load(vcd_header)` hook of the script once a dump has
+/// loaded, which can return a list of full names of signals to add
+pub fn run_load_hook(path: &Path, vcd_header: VcdHeaderPy) -> PyResult<Vec<String>> {
+    with_timeout(|py| match run_hook(py, path, "on_load", (vcd_header,))? {
+        Some(result) if !result.is_none() => result.extract::<Vec<String>>(),
+        _ => Ok(Vec::new()),
+    })
+}
+
+/// The signal list of a view like it is written in the script, signals as
+/// (full name, radix) tuples with the index of single bits after the radix and
+/// groups as dictionaries
+fn get_view_entries_py<'py>(py: Python<'py>, entries: &[ViewEntry]) -> PyResult<&'py PyList> {
+    let list = PyList::empty(py);
+    for entry in entries {
+        match entry {
+            ViewEntry::Signal(name, radix, None) => {
+                list.append((name, get_radix_name(*radix)))?;
+            }
+            ViewEntry::Signal(name, radix, Some(index)) => {
+                list.append((name, get_radix_name(*radix), index))?;
+            }
+            ViewEntry::Group(name, entries) => {
+                let group = PyDict::new(py);
+                group.set_item("group", name)?;
+                group.set_item("signals", get_view_entries_py(py, entries)?)?;
+                list.append(group)?;
+            }
+        }
+    }
+    Ok(list)
+}
+
+/// Runs the optional `on_save(name, signals)` hook of the script after a view
+/// is saved, with its signals and groups like in a view
+pub fn run_save_hook(path: &Path, view: &SignalView) -> PyResult<()> {
+    with_timeout(|py| {
+        let signals = get_view_entries_py(py, &view.signals)?;
+        run_hook(py, path, "on_save", (view.name.clone(), signals))?;
+        Ok(())
+    })
+}
+
+/// Runs the `on_cursor(timestamp)` hook of a script loaded with
+/// [`load_hook`] after the cursor moves, without running the script again
+pub fn run_cursor_hook(module: &Py<PyModule>, cursor: u64) -> PyResult<()> {
+    with_timeout(|py| {
+        module.as_ref(py).getattr("on_cursor")?.call1((cursor,))?;
+        Ok(())
+    })
+}
+
+/// Formats an error like python prints it, with the traceback when there is one
+pub fn format_error(err: &PyErr) -> String {
+    Python::with_gil(|py| {
+        let traceback = err.traceback(py).and_then(|tb| tb.format().ok());
+        format!("{}{err}", traceback.unwrap_or_default())
+    })
+}
+
+/// Calls a function of the script like `main()` with any extra arguments as
+/// strings, returning the lines of the string or buffer it returns
+pub fn run_function(
+    path: &Path,
+    name: &str,
+    args: &[String],
+    buffer: BufferPy,
+    waveform: WaveformPy,
+    vcd_header: VcdHeaderPy,
+    cursor: u64,
+) -> PyResult<Vec<String>> {
+    with_timeout(|py| {
+        let module = load_script(py, path)?;
+        if !module.hasattr(name)? {
+            return Err(PyValueError::new_err(format!("No function named {name}")));
+        }
+        let mut call_args = vec![
+            buffer.into_py(py),
+            waveform.into_py(py),
+            vcd_header.into_py(py),
+            cursor.into_py(py),
+        ];
+        call_args.extend(args.iter().map(|arg| arg.into_py(py)));
+        let result = module.getattr(name)?.call1(PyTuple::new(py, call_args))?;
+        if result.is_none() {
+            return Ok(Vec::new());
+        }
+        if let Ok(buffer) = result.extract::<BufferPy>() {
+            let lines = (0..buffer.get_height()).map(|y| {
+                (0..buffer.get_width())
+                    .map(|x| buffer.get_cell(x, y))
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            });
+            return Ok(lines.collect());
+        }
+        Ok(result.str()?.to_str()?.lines().map(String::from).collect())
+    })
+}
+
+/// Collects the coloring rules registered by the script with
+/// `nalu.color_when(name, rule)`, as (full name, rule) tuples
+pub fn run_color_rules(path: &Path) -> PyResult<Vec<(String, PyObject)>> {
+    with_timeout(|py| {
+        load_script(py, path)?;
+        py.import("nalu")?
+            .getattr("_color_rules")?
+            .extract::<Vec<(String, PyObject)>>()
+    })
+}
+
+/// Reads the signal list of a view, where a single bit has its index after the
+/// radix and a group is a dictionary with the "group" name and its own
+/// "signals"
+fn parse_view_entries(signals: &PyAny) -> PyResult<Vec<ViewEntry>> {
+    let mut entries = Vec::new();
+    for item in signals.iter()? {
+        let item = item?;
+        if let Ok(group) = item.downcast::<PyDict>() {
+            let (Some(name), Some(signals)) = (group.get_item("group"), group.get_item("signals"))
+            else {
+                return Err(PyValueError::new_err(
+                    "Groups need a \"group\" name and \"signals\"",
+                ));
+            };
+            entries.push(ViewEntry::Group(
+                name.extract()?,
+                parse_view_entries(signals)?,
+            ));
+            continue;
+        }
+        let (signal, radix, index) = match item.extract::<(String, String, usize)>() {
+            Ok((signal, radix, index)) => (signal, radix, Some(index)),
+            Err(_) => {
+                let (signal, radix) = item.extract::<(String, String)>()?;
+                (signal, radix, None)
+            }
+        };
+        let Some(radix) = parse_radix(&radix) else {
+            return Err(PyValueError::new_err(format!("Unknown radix: {radix}")));
+        };
+        entries.push(ViewEntry::Signal(signal, radix, index));
+    }
+    Ok(entries)
+}
+
+/// Runs every `view_<name>()` function of the script, each returning a
+/// dictionary with the view "name", the "signals" as (full name, radix) tuples
+/// or groups of them and an optional zoom "range"
+pub fn run_views(path: &Path) -> PyResult<Vec<SignalView>> {
+    with_timeout(|py| {
+        let module = load_script(py, path)?;
+        let mut views = Vec::new();
+        for function in module.dir().extract::<Vec<String>>()? {
+            if !function.starts_with("view_") {
+                continue;
+            }
+            let view = module.getattr(function.as_str())?.call0()?;
+            let name = match view.get_item("name") {
+                Ok(name) => name.extract::<String>()?,
+                Err(_) => String::from(&function["view_".len()..]),
+            };
+            let signals = parse_view_entries(view.get_item("signals")?)?;
+            let offsets = match view.get_item("offsets") {
+                Ok(offsets) => offsets.extract::<HashMap<String, i64>>()?,
+                Err(_) => HashMap::new(),
+            };
+            let range = match view.get_item("range") {
+                Ok(range) => {
+                    let (start, end) = range.extract::<(u64, u64)>()?;
+                    Some(start..end)
+                }
+                Err(_) => None,
+            };
+            let mut view = SignalView {
+                name,
+                signals,
+                offsets: Vec::new(),
+                range,
+            };
+            // Keep the offsets in the order of the signals so saving the view
+            // again does not shuffle them around
+            let mut offsets = offsets.into_iter().collect::<Vec<(String, i64)>>();
+            let signals = view.get_signals();
+            offsets.sort_by_key(|(name, _)| signals.iter().position(|signal| signal == name));
+            view.offsets = offsets;
+            views.push(view);
+        }
+        Ok(views)
+    })
+}
+
+#[test]
+fn timeout_test() {
+    let run =
+        |code: &str| with_timeout_of(Duration::from_millis(100), |py| py.run(code, None, None));
+    let err = run("while True: pass").unwrap_err();
+    assert!(is_timeout(&err));
+    assert!(err.to_string().contains("longer than 0.1s"));
+    // Python keeps working afterwards, without the interrupt going off again
+    assert!(run("x = sum(range(1000))").is_ok());
+    assert!(run("raise ValueError()").is_err_and(|err| !is_timeout(&err)));
+    // Calls inside a watched call share its watchdog until it went off, after
+    // which they are watched on their own
+    let result = with_timeout_of(Duration::from_millis(100), |_| {
+        assert!(run("while True: pass").is_err_and(|err| is_timeout(&err)));
+        run("while True: pass")
+    });
+    assert!(result.is_err_and(|err| is_timeout(&err)));
+}
+
+#[test]
+fn hooks_test() {
+    use crate::fixture::FixtureBuilder;
+    use makai_waveform_db::bitvector::BitVectorRadix;
+
+    let dir = std::env::temp_dir().join(format!("nalu_hooks_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path, saved) = (dir.join("hooks.py"), dir.join("saved.txt"));
+    let script = r#"
+cursors = []
+
+def on_load(vcd_header):
+    return [name for name in ["top.clk", "top.missing"] if vcd_header.get_variable(name)]
+
+def on_save(name, signals):
+    with open(SAVED, "w") as f:
+        f.write(repr((name, signals)))
+
+def on_cursor(timestamp):
+    cursors.append(timestamp)
+"#;
+    let script = script.replace("SAVED", &format!("{:?}", saved.to_str().unwrap()));
+    std::fs::write(&path, script).unwrap();
+
+    let fixture = FixtureBuilder::default().clock("clk", 10, 100).build();
+    let header = VcdHeaderPy::new(std::sync::Arc::new(fixture.header));
+    assert_eq!(run_load_hook(&path, header).unwrap(), vec!["top.clk"]);
+
+    let view = SignalView {
+        name: String::from("clocks"),
+        signals: vec![ViewEntry::Signal(
+            String::from("top.clk"),
+            BitVectorRadix::Binary,
+            None,
+        )],
+        offsets: Vec::new(),
+        range: None,
+    };
+    run_save_hook(&path, &view).unwrap();
+    let saved = std::fs::read_to_string(&saved).unwrap();
+    assert_eq!(saved, "('clocks', [('top.clk', 'bin')])");
+
+    // The module is loaded once and keeps its globals between cursor moves
+    let module = load_hook(&path, "on_cursor").unwrap().unwrap();
+    run_cursor_hook(&module, 10).unwrap();
+    run_cursor_hook(&module, 20).unwrap();
+    let cursors = Python::with_gil(|py| {
+        let cursors = module.as_ref(py).getattr("cursors")?;
+        cursors.extract::<Vec<u64>>()
+    });
+    assert_eq!(cursors.unwrap(), vec![10, 20]);
+    assert!(load_hook(&path, "on_missing").unwrap().is_none());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -5,6 +5,7 @@ use pyo3::prelude::*;
 use makai_waveform_db::{Waveform, WaveformSearchMode, WaveformValueResult};
 
 use crate::python::bitvector::BitVectorPy;
+use crate::source::WaveformSource;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[pyclass(name = "WaveformSearchMode")]
@@ -119,6 +120,9 @@ impl WaveformPy {
         timestamp_index: usize,
         bit_index: Option<usize>,
     ) -> PyResult<Option<WaveformValueResultPy>> {
+        if !self_.waveform.has_history(idcode) {
+            return Ok(None);
+        }
         if let Some(value) = self_.waveform.search_value_bit_index(
             idcode,
             timestamp_index,
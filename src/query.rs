@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use makai_vcd_reader::parser::{VcdScope, VcdVariable, VcdVariableWidth};
+use makai_waveform_db::bitvector::BitVectorRadix;
+
+use crate::{
+    loader::{decode_real, load, LoadOptions, LoadedVcd},
+    source::WaveformSource,
+    state::filter::{FilterMode, NetlistFilter},
+    state::inspector::render_value,
+    state::netlist_viewer::get_filter_attributes,
+    state::palette::{parse_signed_time, SignalOffset},
+    widgets::timescale::get_timestamps,
+};
+
+/// Loads only the header of a dump, along with the net types
+fn load_header(bytes: &str) -> Result<LoadedVcd, String> {
+    load_with(
+        bytes,
+        LoadOptions {
+            signals: Some(HashSet::new()),
+            ..LoadOptions::default()
+        },
+    )
+}
+
+fn collect_variables<'a>(
+    scope: &'a VcdScope,
+    path: &mut Vec<String>,
+    variables: &mut Vec<(Vec<String>, &'a VcdVariable)>,
+) {
+    path.push(scope.get_name().clone());
+    for variable in scope.get_variables() {
+        let mut full_name = path.clone();
+        full_name.push(variable.get_name().clone());
+        variables.push((full_name, variable));
+    }
+    for scope in scope.get_scopes() {
+        collect_variables(scope, path, variables);
+    }
+    path.pop();
+}
+
+/// Lists the full name and bit width of every variable in the dump, sorted by
+/// name, or only of those matching a filter like the one of the netlist in
+/// glob mode (like `**.core.*state*` or `width>1 & type:reg`)
+pub fn list_signals(path: &Path, filter: Option<&str>) -> Result<Vec<(String, usize)>, String> {
+    let bytes = std::fs::read_to_string(path).map_err(|err| format!("{path:?}: {err}"))?;
+    let loaded = load_header(&bytes)?;
+    let filter = filter.and_then(|filter| NetlistFilter::new(filter, FilterMode::Glob));
+    let mut variables = Vec::new();
+    for scope in loaded.header.get_scopes() {
+        collect_variables(scope, &mut Vec::new(), &mut variables);
+    }
+    let mut signals = variables
+        .into_iter()
+        .filter(|(full_name, variable)| {
+            filter.as_ref().is_none_or(|filter| {
+                let attributes = get_filter_attributes(variable, &loaded.net_types);
+                filter.score_variable(full_name, &attributes).is_some()
+            })
+        })
+        .map(|(full_name, variable)| (full_name.join("."), variable.get_bit_width()))
+        .collect::<Vec<(String, usize)>>();
+    signals.sort();
+    signals.dedup();
+    Ok(signals)
+}
+
+fn load_with(bytes: &str, options: LoadOptions) -> Result<LoadedVcd, String> {
+    let status = Arc::new(Mutex::new((0, 0)));
+    load(String::from(bytes), status, &options)
+        .join()
+        .map_err(|_| String::from("Loader panicked"))?
+        .map_err(|err| format!("{err:?}"))
+}
+
+/// Looks up the values of the signals with these full names at each of the
+/// times (like "1.5us", or a plain number of timestamps), returning a row of
+/// values for every time with "-" where a signal has no value yet. Only the
+/// header is read up front, then only the signals asked for up to the last
+/// of the times.
+pub fn query(
+    path: &Path,
+    signals: &[String],
+    times: &[String],
+    radix: BitVectorRadix,
+) -> Result<Vec<Vec<String>>, String> {
+    let bytes = std::fs::read_to_string(path).map_err(|err| format!("{path:?}: {err}"))?;
+    let header = load_header(&bytes)?.header;
+    let timescale = header.get_timescale().unwrap_or(0);
+    let timestamps = times
+        .iter()
+        .map(|time| {
+            match parse_signed_time(time)? {
+                SignalOffset::Timestamps(timestamps) => u64::try_from(timestamps).ok(),
+                SignalOffset::Time(value, exponent) => u64::try_from(value)
+                    .ok()
+                    .map(|value| get_timestamps(value, exponent, timescale)),
+            }
+            .ok_or_else(|| format!("Time is before the start of the dump: {time}"))
+        })
+        .collect::<Result<Vec<u64>, String>>()?;
+    let variables = signals
+        .iter()
+        .map(|name| {
+            header
+                .get_variable(name)
+                .cloned()
+                .ok_or_else(|| format!("No signal named {name}"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let loaded = load_with(
+        &bytes,
+        LoadOptions {
+            to: timestamps.iter().max().copied(),
+            signals: Some(variables.iter().map(|v| v.get_idcode()).collect()),
+            ..LoadOptions::default()
+        },
+    )?;
+    Ok(timestamps
+        .into_iter()
+        .map(|timestamp| {
+            variables
+                .iter()
+                .map(|variable| {
+                    render_value(
+                        &loaded.waveform,
+                        variable.get_idcode(),
+                        None,
+                        radix,
+                        *variable.get_width() == VcdVariableWidth::Real,
+                        timestamp,
+                    )
+                    .unwrap_or(String::from("-"))
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Hashes bytes with 64-bit FNV-1a, continuing from a previous hash, which
+/// unlike the hasher of the standard library stays the same between builds
+fn hash_bytes(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+const HASH_START: u64 = 0xcbf29ce484222325;
+
+/// What a signal did over the whole dump, for comparing two runs cheaply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalSummary {
+    pub name: String,
+    pub transitions: usize,
+    /// The value the dump ends with, "-" if it never had one
+    pub last: String,
+    /// Hash of every change of the value along with its timestamp
+    pub hash: u64,
+}
+
+/// Summarizes the signals with these full names, or every signal in the dump
+/// when none are given, sorted by name
+pub fn summarize(
+    path: &Path,
+    signals: &[String],
+    radix: BitVectorRadix,
+) -> Result<Vec<SignalSummary>, String> {
+    let bytes = std::fs::read_to_string(path).map_err(|err| format!("{path:?}: {err}"))?;
+    let variables = match signals {
+        [] => list_signals(path, None)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect(),
+        signals => signals.to_vec(),
+    };
+    let header = load_header(&bytes)?.header;
+    let variables = variables
+        .into_iter()
+        .map(|name| match header.get_variable(&name) {
+            Some(variable) => Ok((name, variable.clone())),
+            None => Err(format!("No signal named {name}")),
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let options = match signals {
+        [] => LoadOptions::default(),
+        _ => LoadOptions {
+            signals: Some(variables.iter().map(|(_, v)| v.get_idcode()).collect()),
+            ..LoadOptions::default()
+        },
+    };
+    let waveform = load_with(&bytes, options)?.waveform;
+    let mut summaries = variables
+        .into_iter()
+        .map(|(name, variable)| {
+            let is_real = *variable.get_width() == VcdVariableWidth::Real;
+            let mut summary = SignalSummary {
+                name,
+                transitions: 0,
+                last: String::from("-"),
+                hash: HASH_START,
+            };
+            let mut last = None;
+            for (timestamp, bitvector) in waveform.get_vector_changes(variable.get_idcode(), 0) {
+                if last.as_ref() == Some(&bitvector) {
+                    continue;
+                }
+                summary.transitions += last.is_some() as usize;
+                summary.last = match is_real {
+                    true => format!("{}", decode_real(&bitvector)),
+                    false => bitvector.to_string_radix(radix),
+                };
+                summary.hash = hash_bytes(summary.hash, &timestamp.to_le_bytes());
+                summary.hash = hash_bytes(summary.hash, summary.last.as_bytes());
+                last = Some(bitvector);
+            }
+            summary
+        })
+        .collect::<Vec<SignalSummary>>();
+    summaries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(summaries)
+}
+
+/// Hashes the names and hashes of all of the signals together, which only
+/// matches between two runs if every signal does
+pub fn combine_hashes(summaries: &[SignalSummary]) -> u64 {
+    summaries.iter().fold(HASH_START, |hash, summary| {
+        let hash = hash_bytes(hash, summary.name.as_bytes());
+        hash_bytes(hash, &summary.hash.to_le_bytes())
+    })
+}
+
+#[test]
+fn hash_bytes_test() {
+    assert_eq!(hash_bytes(HASH_START, b""), 0xcbf29ce484222325);
+    assert_eq!(hash_bytes(HASH_START, b"a"), 0xaf63dc4c8601ec8c);
+    assert_eq!(
+        hash_bytes(hash_bytes(HASH_START, b"a"), b"b"),
+        hash_bytes(HASH_START, b"ab")
+    );
+}
+
+#[test]
+fn summarize_test() {
+    let path = Path::new("res/gecko.vcd");
+    let signals = [String::from("TOP.rst"), String::from("TOP.clk")];
+    let summaries = summarize(path, &signals, BitVectorRadix::Binary).unwrap();
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].name, "TOP.clk");
+    assert!(summaries[0].transitions > 0);
+    assert_ne!(summaries[0].hash, summaries[1].hash);
+    // Loading every signal gives the same summary for the ones asked for
+    let all = summarize(path, &[], BitVectorRadix::Binary).unwrap();
+    assert!(all.contains(&summaries[0]));
+    assert_eq!(
+        combine_hashes(&summaries),
+        combine_hashes(&summarize(path, &signals, BitVectorRadix::Binary).unwrap())
+    );
+    assert!(summarize(path, &[String::from("TOP.missing")], BitVectorRadix::Binary).is_err());
+}
+
+#[test]
+fn list_signals_test() {
+    let path = Path::new("res/gecko.vcd");
+    let signals = list_signals(path, None).unwrap();
+    assert!(signals.contains(&(String::from("TOP.clk"), 1)));
+    assert!(signals.contains(&(String::from("TOP.tty_out_data"), 8)));
+    let signals = list_signals(path, Some("TOP.tty_*")).unwrap();
+    assert!(signals.iter().all(|(name, _)| name.starts_with("TOP.tty_")));
+    assert!(signals.contains(&(String::from("TOP.tty_out_valid"), 1)));
+    let signals = list_signals(path, Some("TOP.* & width>1")).unwrap();
+    assert!(!signals.contains(&(String::from("TOP.clk"), 1)));
+}
+
+#[test]
+fn query_test() {
+    let path = Path::new("res/gecko.vcd");
+    let signals = [String::from("TOP.clk")];
+    assert_eq!(
+        query(path, &signals, &[String::from("5")], BitVectorRadix::Binary),
+        Ok(vec![vec![String::from("b1")]])
+    );
+    assert!(query(
+        path,
+        &[String::from("TOP.missing")],
+        &[String::from("0")],
+        BitVectorRadix::Binary
+    )
+    .is_err());
+    assert!(query(
+        path,
+        &signals,
+        &[String::from("-5ns")],
+        BitVectorRadix::Binary
+    )
+    .is_err());
+}
@@ -0,0 +1,125 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use makai_waveform_db::bitvector::BitVectorRadix;
+use tui::{backend::TestBackend, buffer::Buffer, Terminal};
+use tui_tiling::container::Container;
+
+use crate::{
+    app::{get_closed_panes, get_tui, handle_updates, render_nalu},
+    loader::LoadOptions,
+    state::view::{SignalView, ViewEntry},
+    state::NaluState,
+    theme::Theme,
+    widgets::layout::PaneLayout,
+    widgets::resize::ResizeHandles,
+};
+
+/// Set to write the screens of golden file tests instead of comparing them
+pub const UPDATE_GOLDENS_VAR: &str = "NALU_UPDATE_GOLDENS";
+
+/// Each row of a rendered screen with the trailing spaces left off
+fn get_screen_rows(buf: &Buffer) -> Vec<String> {
+    (0..buf.area.height)
+        .map(|y| {
+            let row = (0..buf.area.width)
+                .map(|x| buf.get(x, y).symbol.as_str())
+                .collect::<String>();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Renders the whole screen of nalu off-screen with a dump loaded, the signals
+/// (by full name) added to the signal viewer and zoomed to a range, or the
+/// whole dump without one. The screen comes back as one line per row without
+/// any colors, which only depends on the arguments so it can be compared
+/// against a known good screen.
+pub fn render_screenshot(
+    vcd_path: &Path,
+    signals: &[(String, BitVectorRadix)],
+    range: Option<Range<u64>>,
+    width: u16,
+    height: u16,
+) -> Result<String, String> {
+    let mut nalu_state =
+        NaluState::new(Some(PathBuf::from(vcd_path)), None, LoadOptions::default());
+    nalu_state.set_persistent(false);
+    let mut tui = get_tui(nalu_state.get_messages()).map_err(|err| format!("{err:?}"))?;
+    let resize_handles = ResizeHandles::new();
+    let mut pane_layout = PaneLayout::new(
+        nalu_state.get_messages().clone(),
+        get_closed_panes(nalu_state.get_messages()),
+    );
+    nalu_state.handle_load();
+    while nalu_state.is_loading() {
+        nalu_state.handle_vcd();
+        thread::sleep(Duration::from_millis(1));
+    }
+    if let Some(err) = nalu_state.get_done() {
+        return Err(err);
+    }
+
+    let mut terminal =
+        Terminal::new(TestBackend::new(width, height)).map_err(|err| format!("{err:?}"))?;
+    let mut draw = |nalu_state: &mut NaluState, tui: &mut dyn Container| {
+        terminal
+            .draw(|frame| render_nalu(frame, tui, &resize_handles, nalu_state, Theme::default()))
+            .map(|frame| get_screen_rows(frame.buffer))
+            .map_err(|err| format!("{err:?}"))
+    };
+    // The panes only learn their sizes when drawn, which the view depends on
+    draw(&mut nalu_state, &mut *tui)?;
+    handle_updates(&mut nalu_state, &mut *tui, &mut pane_layout);
+    nalu_state.load_view(
+        SignalView {
+            name: vcd_path.display().to_string(),
+            signals: signals
+                .iter()
+                .map(|(name, radix)| ViewEntry::Signal(name.clone(), *radix, None))
+                .collect(),
+            offsets: Vec::new(),
+            range,
+        },
+        false,
+    );
+    handle_updates(&mut nalu_state, &mut *tui, &mut pane_layout);
+    // Drawing can send more updates, like the height of the waveform viewer
+    draw(&mut nalu_state, &mut *tui)?;
+    handle_updates(&mut nalu_state, &mut *tui, &mut pane_layout);
+    Ok(draw(&mut nalu_state, &mut *tui)?.join("\n"))
+}
+
+/// Compares a screen against the one saved in a golden file, panicking with
+/// both of them when they differ. With `NALU_UPDATE_GOLDENS` set the golden
+/// file is written instead.
+pub fn assert_golden(golden_path: &Path, screen: &str) {
+    if std::env::var_os(UPDATE_GOLDENS_VAR).is_some() {
+        std::fs::write(golden_path, format!("{screen}\n")).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(golden_path).unwrap_or_else(|err| {
+        panic!("Cannot read {golden_path:?} ({err}), run with {UPDATE_GOLDENS_VAR}=1 to create it")
+    });
+    assert!(
+        golden.trim_end_matches('\n') == screen,
+        "Screen does not match {golden_path:?}\n\nExpected:\n{golden}\nFound:\n{screen}\n"
+    );
+}
+
+#[test]
+fn screenshot_golden_test() {
+    let signals = [
+        ("TOP.clk", BitVectorRadix::Binary),
+        ("TOP.rst", BitVectorRadix::Binary),
+        ("TOP.tty_out_valid", BitVectorRadix::Binary),
+        ("TOP.tty_out_data", BitVectorRadix::Hexadecimal),
+    ]
+    .map(|(name, radix)| (String::from(name), radix));
+    let screen = render_screenshot(Path::new("res/gecko.vcd"), &signals, Some(0..200), 120, 24);
+    assert_golden(Path::new("res/golden/gecko.txt"), &screen.unwrap());
+    let screen = render_screenshot(Path::new("res/gecko.vcd"), &signals[2..], None, 80, 16);
+    assert_golden(Path::new("res/golden/gecko_all.txt"), &screen.unwrap());
+}
@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use makai_waveform_db::{
+    bitvector::{BitVector, Logic},
+    Waveform, WaveformSearchMode, WaveformSignalResult, WaveformValueResult,
+};
+
+/// Each recorded value of a vector signal along with its timestamp, in order
+pub type VectorChanges<'a> = Box<dyn Iterator<Item = (u64, BitVector)> + 'a>;
+
+/// Where the values drawn by the widgets and searched by the analysis come
+/// from, so another backend (like a different file format, a server or signals
+/// derived from others) can be drawn without touching any of the rendering.
+/// The methods shared with [`Waveform`] work the same way as its own.
+pub trait WaveformSource {
+    /// The first and last timestamps of the dump
+    fn get_timestamp_range(&self) -> Range<u64>;
+
+    /// Finds the index of a timestamp, or of the nearest one in the direction
+    /// of the search mode
+    fn search_timestamp(&self, timestamp: u64, search_mode: WaveformSearchMode) -> Option<usize>;
+
+    /// The timestamp at an index, or nothing past the last one
+    fn get_timestamp(&self, timestamp_index: usize) -> Option<u64>;
+
+    /// Finds the value of a signal (or one bit of it) from the change nearest
+    /// to a timestamp index in the direction of the search mode
+    fn search_value_bit_index(
+        &self,
+        idcode: usize,
+        timestamp_index: usize,
+        search_mode: WaveformSearchMode,
+        bit_index: Option<usize>,
+    ) -> Option<WaveformValueResult>;
+
+    /// Returns if a signal has any recorded values, which must be checked
+    /// before searching as empty histories cannot be searched
+    fn has_history(&self, idcode: usize) -> bool;
+
+    /// Bits in a vector signal, nothing for real signals or missing ones
+    fn get_vector_width(&self, idcode: usize) -> Option<usize>;
+
+    /// The values of a vector signal from the one it holds at the timestamp
+    /// on (or from its first value when it has none yet), which can repeat
+    /// the same value
+    fn get_vector_changes(&self, idcode: usize, from: u64) -> VectorChanges<'_>;
+}
+
+impl WaveformSource for Waveform {
+    fn get_timestamp_range(&self) -> Range<u64> {
+        Waveform::get_timestamp_range(self)
+    }
+
+    fn search_timestamp(&self, timestamp: u64, search_mode: WaveformSearchMode) -> Option<usize> {
+        // The waveform database underflows searching without any timestamps
+        if self.get_timestamps().is_empty() {
+            return None;
+        }
+        Waveform::search_timestamp(self, timestamp, search_mode)
+    }
+
+    fn get_timestamp(&self, timestamp_index: usize) -> Option<u64> {
+        self.get_timestamps().get(timestamp_index).copied()
+    }
+
+    fn search_value_bit_index(
+        &self,
+        idcode: usize,
+        timestamp_index: usize,
+        search_mode: WaveformSearchMode,
+        bit_index: Option<usize>,
+    ) -> Option<WaveformValueResult> {
+        Waveform::search_value_bit_index(self, idcode, timestamp_index, search_mode, bit_index)
+    }
+
+    fn has_history(&self, idcode: usize) -> bool {
+        match self.get_signal(idcode) {
+            Some(WaveformSignalResult::Vector(signal)) => {
+                signal.get_history().get_block_count() > 0
+            }
+            Some(WaveformSignalResult::Real(signal)) => signal.get_history().get_block_count() > 0,
+            None => false,
+        }
+    }
+
+    fn get_vector_width(&self, idcode: usize) -> Option<usize> {
+        self.get_vector_signal(idcode)
+            .map(|signal| signal.get_width())
+    }
+
+    fn get_vector_changes(&self, idcode: usize, from: u64) -> VectorChanges<'_> {
+        let Some(signal) = self.get_vector_signal(idcode) else {
+            return Box::new(std::iter::empty());
+        };
+        if signal.get_history().get_block_count() == 0 {
+            return Box::new(std::iter::empty());
+        }
+        let timestamps = self.get_timestamps();
+        let mut history = signal.get_history().into_iter();
+        // Seeking leaves the history where it was when there is nothing before
+        let first = Waveform::search_timestamp(self, from, WaveformSearchMode::Before)
+            .and_then(|timestamp_index| history.seek(timestamp_index));
+        Box::new(first.into_iter().chain(history).map(move |index| {
+            (
+                timestamps[index.get_timestamp_index()],
+                signal.get_bitvector(index.get_value_index()),
+            )
+        }))
+    }
+}
+
+/// The viewer shares one loaded dump between its panes
+impl<T: WaveformSource + ?Sized> WaveformSource for Arc<T> {
+    fn get_timestamp_range(&self) -> Range<u64> {
+        (**self).get_timestamp_range()
+    }
+
+    fn search_timestamp(&self, timestamp: u64, search_mode: WaveformSearchMode) -> Option<usize> {
+        (**self).search_timestamp(timestamp, search_mode)
+    }
+
+    fn get_timestamp(&self, timestamp_index: usize) -> Option<u64> {
+        (**self).get_timestamp(timestamp_index)
+    }
+
+    fn search_value_bit_index(
+        &self,
+        idcode: usize,
+        timestamp_index: usize,
+        search_mode: WaveformSearchMode,
+        bit_index: Option<usize>,
+    ) -> Option<WaveformValueResult> {
+        (**self).search_value_bit_index(idcode, timestamp_index, search_mode, bit_index)
+    }
+
+    fn has_history(&self, idcode: usize) -> bool {
+        (**self).has_history(idcode)
+    }
+
+    fn get_vector_width(&self, idcode: usize) -> Option<usize> {
+        (**self).get_vector_width(idcode)
+    }
+
+    fn get_vector_changes(&self, idcode: usize, from: u64) -> VectorChanges<'_> {
+        (**self).get_vector_changes(idcode, from)
+    }
+}
+
+/// A vector put together from single-bit signals of the dump, like a bus that
+/// synthesis blasted into one net per bit, drawn under an idcode of its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VirtualVector {
+    pub idcode: usize,
+    /// Idcodes of the single-bit signals from bit 0 up
+    pub bits: Vec<usize>,
+}
+
+/// Packs the bits from bit 0 up into one vector, at most 64 of them
+fn pack_bits(bits: &[Logic]) -> BitVector {
+    let (mut value, mut mask) = (0u64, 0u64);
+    for (i, bit) in bits.iter().enumerate() {
+        match bit {
+            Logic::Zero => {}
+            Logic::One => value |= 1 << i,
+            Logic::Unknown => mask |= 1 << i,
+            Logic::HighImpedance => {
+                value |= 1 << i;
+                mask |= 1 << i;
+            }
+        }
+    }
+    BitVector::from_bits_four_state(bits.len(), value, mask)
+}
+
+/// A loaded dump along with the virtual vectors derived from its signals,
+/// which is otherwise the same as the dump itself
+#[derive(Default)]
+pub struct VirtualWaveform {
+    waveform: Arc<Waveform>,
+    vectors: HashMap<usize, Vec<usize>>,
+}
+
+impl VirtualWaveform {
+    pub fn new(waveform: Arc<Waveform>) -> Self {
+        Self {
+            waveform,
+            vectors: HashMap::new(),
+        }
+    }
+
+    /// Swaps in a rescan of the same dump, keeping the virtual vectors
+    pub fn replace(&mut self, waveform: Arc<Waveform>) {
+        self.waveform = waveform;
+    }
+
+    pub fn add_vector(&mut self, vector: VirtualVector) {
+        self.vectors.insert(vector.idcode, vector.bits);
+    }
+
+    /// The value of a single bit at a timestamp index, unknown before it has one
+    fn get_bit(&self, idcode: usize, timestamp_index: usize) -> Logic {
+        if !self.waveform.has_history(idcode) {
+            return Logic::Unknown;
+        }
+        match self.waveform.search_value_bit_index(
+            idcode,
+            timestamp_index,
+            WaveformSearchMode::Before,
+            None,
+        ) {
+            Some(WaveformValueResult::Vector(bv, _)) => bv.get_bit(0),
+            _ => Logic::Unknown,
+        }
+    }
+}
+
+impl WaveformSource for VirtualWaveform {
+    fn get_timestamp_range(&self) -> Range<u64> {
+        self.waveform.get_timestamp_range()
+    }
+
+    fn search_timestamp(&self, timestamp: u64, search_mode: WaveformSearchMode) -> Option<usize> {
+        WaveformSource::search_timestamp(&self.waveform, timestamp, search_mode)
+    }
+
+    fn get_timestamp(&self, timestamp_index: usize) -> Option<u64> {
+        WaveformSource::get_timestamp(&self.waveform, timestamp_index)
+    }
+
+    /// The value of a virtual vector changes whenever one of its bits does,
+    /// so the nearest change is the nearest of any of them
+    fn search_value_bit_index(
+        &self,
+        idcode: usize,
+        timestamp_index: usize,
+        search_mode: WaveformSearchMode,
+        bit_index: Option<usize>,
+    ) -> Option<WaveformValueResult> {
+        let Some(bits) = self.vectors.get(&idcode) else {
+            return self.waveform.search_value_bit_index(
+                idcode,
+                timestamp_index,
+                search_mode,
+                bit_index,
+            );
+        };
+        if let Some(bit_index) = bit_index {
+            let bit = *bits.get(bit_index)?;
+            return self
+                .waveform
+                .search_value_bit_index(bit, timestamp_index, search_mode, None);
+        }
+        let before = matches!(search_mode, WaveformSearchMode::Before);
+        let changes = bits
+            .iter()
+            .filter(|bit| self.waveform.has_history(**bit))
+            .filter_map(|bit| {
+                let search_mode = match before {
+                    true => WaveformSearchMode::Before,
+                    false => WaveformSearchMode::After,
+                };
+                self.waveform
+                    .search_value_bit_index(*bit, timestamp_index, search_mode, None)
+            })
+            .map(|result| result.get_timestamp_index());
+        let change = match before {
+            true => changes.max()?,
+            false => changes.min()?,
+        };
+        let value = bits
+            .iter()
+            .map(|bit| self.get_bit(*bit, change))
+            .collect::<Vec<Logic>>();
+        Some(WaveformValueResult::Vector(pack_bits(&value), change))
+    }
+
+    fn has_history(&self, idcode: usize) -> bool {
+        match self.vectors.get(&idcode) {
+            Some(bits) => bits.iter().any(|bit| self.waveform.has_history(*bit)),
+            None => self.waveform.has_history(idcode),
+        }
+    }
+
+    fn get_vector_width(&self, idcode: usize) -> Option<usize> {
+        match self.vectors.get(&idcode) {
+            Some(bits) => Some(bits.len()),
+            None => WaveformSource::get_vector_width(&self.waveform, idcode),
+        }
+    }
+
+    /// Merges the changes of the bits of a virtual vector, giving its value
+    /// after each timestamp any of them changed at
+    fn get_vector_changes(&self, idcode: usize, from: u64) -> VectorChanges<'_> {
+        let Some(bits) = self.vectors.get(&idcode) else {
+            return self.waveform.get_vector_changes(idcode, from);
+        };
+        let changes = bits
+            .iter()
+            .map(|bit| {
+                self.waveform
+                    .get_vector_changes(*bit, from)
+                    .map(|(timestamp, bv)| (timestamp, bv.get_bit(0)))
+                    .collect::<Vec<(u64, Logic)>>()
+            })
+            .collect::<Vec<Vec<(u64, Logic)>>>();
+        let mut timestamps = changes
+            .iter()
+            .flatten()
+            .map(|(timestamp, _)| *timestamp)
+            .collect::<Vec<u64>>();
+        timestamps.sort();
+        timestamps.dedup();
+        let mut next = vec![0; bits.len()];
+        let mut value = vec![Logic::Unknown; bits.len()];
+        Box::new(timestamps.into_iter().map(move |timestamp| {
+            for (i, changes) in changes.iter().enumerate() {
+                while let Some((change, bit)) = changes.get(next[i]) {
+                    if *change > timestamp {
+                        break;
+                    }
+                    value[i] = *bit;
+                    next[i] += 1;
+                }
+            }
+            (timestamp, pack_bits(&value))
+        }))
+    }
+}
+
+#[test]
+fn vector_changes_test() {
+    use makai_waveform_db::bitvector::Logic;
+
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    waveform.initialize_real(1);
+    for (timestamp, bit) in [
+        (5, Logic::Zero),
+        (10, Logic::One),
+        (15, Logic::One),
+        (20, Logic::Zero),
+    ] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform.update_vector(0, BitVector::from(bit)).unwrap();
+    }
+    let changes = |from| {
+        waveform
+            .get_vector_changes(0, from)
+            .map(|(timestamp, _)| timestamp)
+            .collect::<Vec<u64>>()
+    };
+    assert_eq!(changes(0), vec![5, 10, 15, 20]);
+    assert_eq!(changes(12), vec![10, 15, 20]);
+    assert_eq!(changes(20), vec![20]);
+    assert_eq!(changes(100), vec![20]);
+    assert!(waveform.has_history(0));
+    assert!(!waveform.has_history(1));
+    assert_eq!(waveform.get_vector_width(0), Some(1));
+    assert_eq!(waveform.get_vector_width(1), None);
+    assert_eq!(waveform.get_vector_changes(1, 0).count(), 0);
+}
+
+#[test]
+fn virtual_vector_test() {
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    waveform.initialize_vector(1, 1);
+    for (timestamp, bits) in [
+        (0, [Logic::Zero, Logic::Zero]),
+        (5, [Logic::One, Logic::Zero]),
+        (10, [Logic::One, Logic::Unknown]),
+        (15, [Logic::Zero, Logic::One]),
+    ] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        for (idcode, bit) in bits.into_iter().enumerate() {
+            waveform
+                .update_vector(idcode, BitVector::from(bit))
+                .unwrap();
+        }
+    }
+    let mut waveform = VirtualWaveform::new(Arc::new(waveform));
+    waveform.add_vector(VirtualVector {
+        idcode: 2,
+        bits: vec![0, 1],
+    });
+    assert!(waveform.has_history(2));
+    assert_eq!(waveform.get_vector_width(2), Some(2));
+    assert_eq!(waveform.get_vector_width(0), Some(1));
+    let changes = waveform
+        .get_vector_changes(2, 0)
+        .collect::<Vec<(u64, BitVector)>>();
+    assert_eq!(
+        changes,
+        vec![
+            (0, BitVector::from_bits_two_state(2, 0b00u8)),
+            (5, BitVector::from_bits_two_state(2, 0b01u8)),
+            (10, BitVector::from_bits_four_state(2, 0b01u8, 0b10)),
+            (15, BitVector::from_bits_two_state(2, 0b10u8)),
+        ]
+    );
+    let value = waveform.search_value_bit_index(2, 3, WaveformSearchMode::Before, None);
+    assert!(matches!(value, Some(WaveformValueResult::Vector(bv, 3))
+        if bv == BitVector::from_bits_two_state(2, 0b10u8)));
+    let value = waveform.search_value_bit_index(2, 1, WaveformSearchMode::Before, Some(0));
+    assert!(matches!(value, Some(WaveformValueResult::Vector(bv, 1))
+        if bv.get_bit(0) == Logic::One));
+}
@@ -1,55 +1,397 @@
+pub mod completion;
 pub mod filter;
+pub mod filter_viewer;
+pub mod form;
+pub mod header;
+pub mod inspector;
+pub mod menu;
 pub mod netlist_viewer;
+pub mod palette;
+pub mod python_view;
+pub mod recent;
+pub mod recovery;
+pub mod share;
 pub mod signal_viewer;
+pub mod view;
 pub mod waveform_viewer;
 
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
 
-use crossterm::event::{KeyCode, KeyEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
 
 use makai::utils::messages::Messages;
-use makai_vcd_reader::parser::VcdHeader;
-use makai_vcd_reader::utils::*;
-use makai_waveform_db::Waveform;
+use makai_vcd_reader::parser::{VcdHeader, VcdVariable, VcdVariableNetType};
+use makai_waveform_db::{bitvector::BitVectorRadix, Waveform};
+use pyo3::{types::PyModule, Py};
 
+use crate::analysis::{
+    detect_clocks, find_glitches, find_unknown_start, get_transitions, is_unknown_at,
+    is_unknown_or_floating_at, measure_delays, ClockInfo, DelayStatistics,
+};
+use crate::keymap::get_help_lines;
+use crate::loader::registry::LoaderRegistry;
+use crate::loader::{LoadDiagnostics, LoadOptions, LoadResult};
+use crate::logging::FrameStatistics;
+use crate::python::{
+    buffer::BufferPy,
+    error::{PythonError, PythonRerun},
+    script::{load_hook, run_cursor_hook, run_function, run_load_hook, run_save_hook, run_views},
+    vcd_header::VcdHeaderPy,
+    waveform::WaveformPy,
+};
+use crate::source::VirtualVector;
+use crate::state::completion::{complete_path, get_variable_names};
+use crate::state::form::{Form, FormKind, FormOutcome};
+use crate::state::header::HeaderMessage;
+use crate::state::inspector::{render_value, InspectorMessage};
+use crate::state::menu::{copy_to_clipboard, ContextMenu, MenuTarget};
 use crate::state::netlist_viewer::NetlistViewerMessage;
+use crate::state::palette::{
+    parse_command, parse_signed_time, BrowserPane, CursorPlacement, GlitchWidth, PaletteCommand,
+    SignalOffset, ZoomWidth,
+};
+use crate::state::python_view::{push_navigation, PythonViewMessage, PYTHON_PANE};
+use crate::state::recent::{add_recent, load_recent, save_recent, RecentFile};
+use crate::state::recovery::{
+    flush_recovery, load_recovery, remove_recovery, set_pending_recovery, Recovery,
+};
+use crate::state::share::SharedLocation;
+use crate::state::signal_viewer::{get_view_nodes, SignalViewerMessage, ViewNode};
+use crate::state::view::{save_view_file, SignalView, ViewEntry};
 use crate::state::waveform_viewer::WaveformViewerMessage;
+use crate::widgets::layout::LayoutMessage;
+use crate::widgets::overlay::scroll_index;
+use crate::widgets::timescale::{
+    get_timestamps, render_delta, render_time, NamedCursor, TimescaleAnnotation,
+};
+
+/// Most glitches listed at once, so a noisy signal does not flood the list
+const GLITCH_LIMIT: usize = 1000;
+
+/// How often the signals in the viewer are collected to recover after a crash,
+/// and how often they are written out when they changed
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(1);
+const AUTOSAVE_WRITE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the dump is checked for changes while following its end
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A pulse that was shorter than the width searched for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glitch {
+    pub name: String,
+    pub timestamp: u64,
+    pub width: u64,
+}
+
+/// Where the unknown value of a signal at the cursor began
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTrace {
+    pub name: String,
+    pub timestamp: u64,
+    /// Other signals that were also unknown at that time
+    pub others: Vec<String>,
+}
+
+/// A signal with unknown or floating bits at the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSignal {
+    pub name: String,
+    pub index: Option<usize>,
+    /// The value at the cursor in binary, showing which bits are x or z
+    pub value: String,
+    /// If it is in the signal viewer already, or is added to it when chosen
+    pub in_viewer: bool,
+}
+
+/// Delays from the transitions of one signal to the next of another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayReport {
+    pub from: String,
+    pub to: String,
+    pub range: Range<u64>,
+    pub statistics: Option<DelayStatistics>,
+}
+
+/// Every value a signal held from the cursor to the marker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueHistory {
+    pub name: String,
+    pub range: Range<u64>,
+    /// The value held at the start of the range and then each change
+    pub values: Vec<(u64, String)>,
+    /// If there were more changes than fit in the table
+    pub truncated: bool,
+}
+
+impl ValueHistory {
+    /// The time, value and time since the change before of each row
+    pub fn get_rows(&self, timescale: i32) -> Vec<[String; 3]> {
+        let mut previous = None;
+        self.values
+            .iter()
+            .map(|(timestamp, value)| {
+                let delta = match previous.replace(*timestamp) {
+                    Some(previous) => render_delta(previous, *timestamp, timescale),
+                    None => String::from("-"),
+                };
+                [render_time(*timestamp, 1, timescale), value.clone(), delta]
+            })
+            .collect()
+    }
+
+    /// The table as lines of tab separated columns under a header, which
+    /// pastes into a spreadsheet
+    pub fn to_tsv(&self, timescale: i32) -> String {
+        let mut tsv = String::from("time\tvalue\tdelta\n");
+        for row in self.get_rows(timescale) {
+            tsv.push_str(&row.join("\t"));
+            tsv.push('\n');
+        }
+        tsv
+    }
+}
+
+/// Rows of the buffer passed to functions run from the palette, which is as
+/// wide as the overlay their output is shown in
+pub const PYTHON_OUTPUT_ROWS: u16 = 20;
+
+/// What a python function run from the palette returned, or the error raised
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonOutput {
+    pub function: String,
+    pub lines: Vec<String>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NaluOverlay {
     Loading,
     Palette,
+    QuickAdd,
+    Annotations,
+    Cursors,
+    Views,
+    Glitches,
+    UnknownTrace,
+    UnknownSignals,
+    Delays,
+    History,
+    Menu,
+    Form,
+    Python,
+    PythonError,
+    Recent,
+    Recovery,
+    Warnings,
     HelpPrompt,
     QuitPrompt,
     None,
 }
 
+pub(crate) enum NaluMessage {
+    OpenPalette(String),
+    OpenQuickAdd,
+    PaletteError(String),
+    OpenAnnotations,
+    UpdateAnnotations(Vec<TimescaleAnnotation>),
+    OpenCursors,
+    UpdateCursors(Vec<NamedCursor>),
+    LoadSignal(usize),
+    SaveView(SignalView),
+    Autosave(SignalView),
+    PythonError(PythonError),
+    FindGlitches(Vec<(String, usize, Option<usize>)>, u64),
+    TraceUnknown(
+        Option<(String, usize, Option<usize>)>,
+        Vec<(String, usize, Option<usize>)>,
+    ),
+    // Full names of the signals in the viewer to check for x and z values,
+    // along with every other signal in the dump when set
+    ListUnknownSignals(Vec<(String, usize, Option<usize>)>, bool),
+    SetCursor(u64),
+    MeasureDelays(Vec<(String, usize, Option<usize>)>, Range<u64>),
+    ShowHistory(ValueHistory),
+    OpenVcd(PathBuf, Vec<ViewEntry>),
+    OpenMenu(ContextMenu),
+    OpenForm(Form),
+    // Starts or stops reloading the dump when it changes
+    SetFollow(bool),
+    // Appends a vector put together from these bits under this full name
+    AddVirtualVector(Vec<String>, Vec<(Vec<String>, VcdVariable)>),
+    // Copies where the viewer is looking, missing the file until it gets here
+    Share(SharedLocation),
+    // A group was saved as a view to a python file of its own
+    GroupSaved(SignalView),
+}
+
+/// Ctrl+l clears the screen and draws everything again, like in a shell, for
+/// terminals that miss resizes or get written over
+pub fn is_refresh_key(event: &KeyEvent) -> bool {
+    event.code == KeyCode::Char('l') && event.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// The size and modification time of a file, which change as it is written
+fn get_file_stamp(path: &Path) -> (u64, Option<SystemTime>) {
+    match std::fs::metadata(path) {
+        Ok(metadata) => (metadata.len(), metadata.modified().ok()),
+        Err(_) => (0, None),
+    }
+}
+
+/// The variables of the signals of a view with their radixes in its groups,
+/// and the offsets of the ones shifted in time by idcode
+type ViewVariables = (Vec<ViewNode>, Vec<(usize, i64)>);
+
 pub struct NaluState {
-    vcd_path: PathBuf,
+    vcd_path: Option<PathBuf>,
     python_path: Option<PathBuf>,
-    vcd_handle: Option<JoinHandle<VcdResult<(VcdHeader, Waveform)>>>,
+    vcd_handle: Option<JoinHandle<LoadResult>>,
+    load_options: LoadOptions,
+    loaders: LoaderRegistry,
+    // If the current load only rescans the body for more signals
+    vcd_rescan: bool,
+    vcd_rescan_pending: bool,
+    vcd_diagnostics: LoadDiagnostics,
+    // Signals (by full name) to find in a newly opened file once it loads
+    vcd_view: Option<SignalView>,
+    // Where to go once the file being opened has loaded
+    vcd_location: Option<SharedLocation>,
+    // The file open before the last one, which undoing the open goes back to
+    undo_path: Option<PathBuf>,
+    // If the signals from before the last open are restored once loaded
+    undo_pending: bool,
     overlay: NaluOverlay,
     progress: Arc<Mutex<(usize, usize)>>,
     vcd_header: Arc<VcdHeader>,
+    waveform: Arc<Waveform>,
+    clocks: Arc<HashMap<usize, ClockInfo>>,
+    // Idcodes of virtual vectors are handed out after the ones of the dump
+    next_virtual_idcode: usize,
     filter_input: String,
     palette_input: String,
+    palette_message: String,
+    // Full names of the variables completed against and the input so far
+    quick_add_names: Vec<String>,
+    quick_add_input: String,
+    quick_add_candidates: Vec<String>,
+    annotations: Vec<TimescaleAnnotation>,
+    named_cursors: Vec<NamedCursor>,
+    views: Vec<SignalView>,
+    glitches: Vec<Glitch>,
+    unknown_trace: Option<UnknownTrace>,
+    unknown_signals: Vec<UnknownSignal>,
+    delays: Option<DelayReport>,
+    history: Option<ValueHistory>,
+    menu: Option<ContextMenu>,
+    form: Option<Form>,
+    python_output: Option<PythonOutput>,
+    python_error: Option<PythonError>,
+    recent: Vec<RecentFile>,
+    cursor: u64,
+    overlay_index: usize,
+    hud_enabled: bool,
+    hints_enabled: bool,
+    // The script loaded once when it has an on_cursor() hook, so every move
+    // only calls the hook instead of running the whole script again
+    cursor_hook: Option<Py<PyModule>>,
+    // If on_load() still has to run for the file being loaded, which reloading
+    // the same file (like when following it) does not do again
+    load_hook_pending: bool,
+    // If the netlist and signals are drawn with guide lines and icons
+    tree_enabled: bool,
+    // If the whole screen is cleared and drawn again on the next frame
+    refresh: bool,
+    // If the terminal reports the mouse, which decides if borders show handles
+    mouse_enabled: bool,
+    // If the recent files list and the recovery file are kept up to date
+    persistent: bool,
+    // Signals left behind by a session that did not exit cleanly
+    recovery: Option<Recovery>,
+    // The signals last kept for recovery, to only write them when they change
+    autosaved: Option<Recovery>,
+    autosave_time: Instant,
+    autosave_write_time: Instant,
+    // The size and modification time of the dump last loaded while following
+    follow_stamp: Option<(u64, Option<SystemTime>)>,
+    follow_time: Instant,
+    frame_statistics: FrameStatistics,
     done: Option<String>,
     messages: Messages,
 }
 
 impl NaluState {
-    pub fn new(vcd_path: PathBuf, python_path: Option<PathBuf>) -> Self {
+    /// Starts out with the recent files list when there is no file to load
+    pub fn new(
+        vcd_path: Option<PathBuf>,
+        python_path: Option<PathBuf>,
+        load_options: LoadOptions,
+    ) -> Self {
+        let recovery = load_recovery();
+        let overlay = match (&vcd_path, &recovery) {
+            (Some(_), _) => NaluOverlay::Loading,
+            (None, Some(_)) => NaluOverlay::Recovery,
+            (None, None) => NaluOverlay::Recent,
+        };
         Self {
             vcd_path,
             python_path,
             vcd_handle: None,
-            overlay: NaluOverlay::Loading,
+            load_options,
+            loaders: LoaderRegistry::default(),
+            vcd_rescan: false,
+            vcd_rescan_pending: false,
+            vcd_diagnostics: LoadDiagnostics::default(),
+            vcd_view: None,
+            vcd_location: None,
+            undo_path: None,
+            undo_pending: false,
+            overlay,
             progress: Arc::new(Mutex::new((0, 0))),
             vcd_header: Arc::new(VcdHeader::new()),
+            waveform: Arc::new(Waveform::default()),
+            clocks: Arc::new(HashMap::new()),
+            next_virtual_idcode: 0,
             filter_input: String::new(),
             palette_input: String::new(),
+            palette_message: String::new(),
+            quick_add_names: Vec::new(),
+            quick_add_input: String::new(),
+            quick_add_candidates: Vec::new(),
+            annotations: Vec::new(),
+            named_cursors: Vec::new(),
+            views: Vec::new(),
+            glitches: Vec::new(),
+            unknown_trace: None,
+            unknown_signals: Vec::new(),
+            delays: None,
+            history: None,
+            menu: None,
+            form: None,
+            python_output: None,
+            python_error: None,
+            recent: load_recent(),
+            cursor: 0,
+            overlay_index: 0,
+            hud_enabled: false,
+            hints_enabled: true,
+            cursor_hook: None,
+            load_hook_pending: true,
+            tree_enabled: false,
+            refresh: false,
+            mouse_enabled: true,
+            persistent: true,
+            recovery,
+            autosaved: None,
+            autosave_time: Instant::now(),
+            autosave_write_time: Instant::now(),
+            follow_stamp: None,
+            follow_time: Instant::now(),
+            frame_statistics: FrameStatistics::default(),
             done: None,
             messages: Messages::new(),
         }
@@ -68,17 +410,192 @@ impl NaluState {
     }
 
     pub fn handle_key(&mut self, event: KeyEvent) -> Option<KeyEvent> {
+        if event.code == KeyCode::F(12) {
+            self.hud_enabled = !self.hud_enabled;
+            return None;
+        }
+        if is_refresh_key(&event) {
+            self.refresh = true;
+            return None;
+        }
         match self.overlay {
             NaluOverlay::Loading if event.code == KeyCode::Char('q') => {
                 self.done = Some(String::new());
             }
-            NaluOverlay::Palette if event.code == KeyCode::Esc => {
+            NaluOverlay::Palette => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => self.handle_palette(),
+                KeyCode::Backspace => {
+                    self.palette_input.pop();
+                }
+                KeyCode::Char(c) => self.palette_input.push(c),
+                _ => {}
+            },
+            NaluOverlay::QuickAdd => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => self.quick_add(),
+                KeyCode::Tab => {
+                    let (input, candidates) =
+                        complete_path(&self.quick_add_names, &self.quick_add_input);
+                    self.quick_add_input = input;
+                    self.quick_add_candidates = candidates;
+                }
+                KeyCode::Backspace => {
+                    self.quick_add_input.pop();
+                }
+                KeyCode::Char(c) => self.quick_add_input.push(c),
+                _ => {}
+            },
+            NaluOverlay::Annotations => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => {
+                    if let Some(annotation) = self.annotations.get(self.overlay_index) {
+                        self.messages
+                            .push(WaveformViewerMessage::GotoTimestamp(annotation.timestamp));
+                        self.overlay = NaluOverlay::None;
+                    }
+                }
+                KeyCode::Delete if self.overlay_index < self.annotations.len() => {
+                    self.messages
+                        .push(WaveformViewerMessage::RemoveAnnotation(self.overlay_index));
+                }
+                code => self.scroll_overlay(code, self.annotations.len()),
+            },
+            NaluOverlay::Cursors => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => {
+                    if let Some(cursor) = self.named_cursors.get(self.overlay_index) {
+                        self.messages
+                            .push(WaveformViewerMessage::GotoTimestamp(cursor.timestamp));
+                        self.overlay = NaluOverlay::None;
+                    }
+                }
+                KeyCode::Delete => {
+                    if let Some(cursor) = self.named_cursors.get(self.overlay_index) {
+                        self.messages
+                            .push(WaveformViewerMessage::RemoveNamedCursor(cursor.name));
+                    }
+                }
+                code => self.scroll_overlay(code, self.named_cursors.len()),
+            },
+            NaluOverlay::Views => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => {
+                    if let Some(view) = self.views.get(self.overlay_index) {
+                        self.load_view(view.clone(), true);
+                        self.overlay = NaluOverlay::None;
+                    }
+                }
+                code => self.scroll_overlay(code, self.views.len()),
+            },
+            NaluOverlay::Glitches => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => {
+                    if let Some(glitch) = self.glitches.get(self.overlay_index) {
+                        self.messages
+                            .push(WaveformViewerMessage::GotoTimestamp(glitch.timestamp));
+                        self.overlay = NaluOverlay::None;
+                    }
+                }
+                code => self.scroll_overlay(code, self.glitches.len()),
+            },
+            NaluOverlay::UnknownTrace => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => {
+                    if let Some(trace) = &self.unknown_trace {
+                        self.messages
+                            .push(WaveformViewerMessage::GotoTimestamp(trace.timestamp));
+                    }
+                    self.overlay = NaluOverlay::None;
+                }
+                code => {
+                    let len = self
+                        .unknown_trace
+                        .as_ref()
+                        .map_or(0, |t| t.others.len() + 2);
+                    self.scroll_overlay(code, len)
+                }
+            },
+            NaluOverlay::UnknownSignals => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => {
+                    self.select_unknown_signal(true);
+                    self.overlay = NaluOverlay::None;
+                }
+                code => {
+                    self.scroll_overlay(code, self.unknown_signals.len());
+                    self.select_unknown_signal(false);
+                }
+            },
+            NaluOverlay::Menu => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Enter => self.choose_menu_action(),
+                code => {
+                    let len = self.menu.as_ref().map_or(0, |menu| menu.actions.len());
+                    self.scroll_overlay(code, len)
+                }
+            },
+            NaluOverlay::Form => match self.form.as_mut().and_then(|form| form.handle_key(event)) {
+                Some(FormOutcome::Submit(button)) => self.submit_form(&button),
+                Some(FormOutcome::Cancel) => self.overlay = NaluOverlay::None,
+                None => {}
+            },
+            NaluOverlay::Python => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                code => {
+                    let lines = self.python_output.as_ref().map_or(0, |o| o.lines.len());
+                    self.scroll_overlay(code, lines)
+                }
+            },
+            NaluOverlay::PythonError => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Char('r') => self.rerun_python(),
+                code => {
+                    let lines = self.python_error.as_ref().map_or(0, |e| e.lines.len());
+                    self.scroll_overlay(code, lines)
+                }
+            },
+            NaluOverlay::Delays if event.code == KeyCode::Esc => {
                 self.overlay = NaluOverlay::None;
             }
+            NaluOverlay::History => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                KeyCode::Char('c') => {
+                    if let Some(history) = &self.history {
+                        copy_to_clipboard(&history.to_tsv(self.get_timescale()));
+                    }
+                    self.overlay = NaluOverlay::None;
+                }
+                code => {
+                    let rows = self.history.as_ref().map_or(0, |h| h.values.len() + 1);
+                    self.scroll_overlay(code, rows)
+                }
+            },
+            NaluOverlay::Recent => match event.code {
+                // There is nothing to go back to before the first file is opened
+                KeyCode::Esc if self.vcd_path.is_some() => self.overlay = NaluOverlay::None,
+                KeyCode::Char('q') if self.vcd_path.is_none() => self.done = Some(String::new()),
+                KeyCode::Enter => self.open_recent(),
+                code => self.scroll_overlay(code, self.recent.len()),
+            },
+            NaluOverlay::Recovery => match event.code {
+                KeyCode::Char('y') | KeyCode::Enter => self.restore_recovery(),
+                KeyCode::Char('n') | KeyCode::Esc => self.discard_recovery(),
+                _ => {}
+            },
+            NaluOverlay::Warnings => match event.code {
+                KeyCode::Esc => self.overlay = NaluOverlay::None,
+                code => {
+                    let diagnostics = &self.vcd_diagnostics;
+                    let hidden = diagnostics.get_skipped() > diagnostics.get_messages().len();
+                    let lines = diagnostics.get_messages().len().max(1) + hidden as usize;
+                    self.scroll_overlay(code, lines)
+                }
+            },
             NaluOverlay::HelpPrompt => match event.code {
                 KeyCode::Char('q') => self.done = Some(String::new()),
                 KeyCode::Esc => self.overlay = NaluOverlay::None,
-                _ => {}
+                code => self.scroll_overlay(code, get_help_lines().len()),
             },
             NaluOverlay::QuitPrompt => match event.code {
                 KeyCode::Char('q') => self.done = Some(String::new()),
@@ -87,8 +604,16 @@ impl NaluState {
             },
             NaluOverlay::None => match event.code {
                 KeyCode::Char('q') => self.done = Some(String::new()),
-                KeyCode::Char('h') => self.overlay = NaluOverlay::HelpPrompt,
-                KeyCode::Char('p') => self.overlay = NaluOverlay::Palette,
+                KeyCode::Char('h') => {
+                    self.overlay_index = 0;
+                    self.overlay = NaluOverlay::HelpPrompt;
+                }
+                KeyCode::Char('p') => self.open_palette(String::new()),
+                KeyCode::Char('R') => {
+                    self.recent = load_recent();
+                    self.overlay_index = 0;
+                    self.overlay = NaluOverlay::Recent;
+                }
                 KeyCode::Char('r') => {
                     self.overlay = NaluOverlay::Loading;
                     self.handle_load();
@@ -100,10 +625,1099 @@ impl NaluState {
         None
     }
 
+    /// Moves through the rows of the overlay with the arrow, page, home and end
+    /// keys
+    fn scroll_overlay(&mut self, code: KeyCode, rows: usize) {
+        if let Some(index) = scroll_index(code, self.overlay_index, rows.saturating_sub(1)) {
+            self.overlay_index = index;
+        }
+    }
+
+    fn open_palette(&mut self, input: String) {
+        self.palette_input = input;
+        self.palette_message = String::new();
+        self.overlay = NaluOverlay::Palette;
+    }
+
+    fn open_quick_add(&mut self) {
+        self.quick_add_names = get_variable_names(&self.vcd_header);
+        self.quick_add_input = String::new();
+        self.quick_add_candidates = Vec::new();
+        self.overlay = NaluOverlay::QuickAdd;
+    }
+
+    /// Appends the variable typed into the quick add input to the signal viewer
+    fn quick_add(&mut self) {
+        let full_name = self.quick_add_input.trim();
+        let Some(variable) = self.vcd_header.get_variable(full_name) else {
+            self.quick_add_candidates = vec![format!("No signal named {full_name}")];
+            return;
+        };
+        self.append_variable(full_name, variable);
+        self.overlay = NaluOverlay::None;
+    }
+
+    /// Appends a variable of the dump to the signal viewer by its full name
+    fn append_variable(&self, full_name: &str, variable: &VcdVariable) {
+        let mut path = full_name
+            .split('.')
+            .map(String::from)
+            .collect::<Vec<String>>();
+        path.pop();
+        path.push(variable.to_string());
+        self.messages
+            .push(SignalViewerMessage::NetlistAppend(path, variable.clone()));
+    }
+
+    /// Appends the signals listed one full name per line in a file, skipping
+    /// blank lines and ones starting with '#', and reports any not in the dump
+    fn import_signals(&mut self, path: &str, radix: BitVectorRadix) {
+        let list = match std::fs::read_to_string(path) {
+            Ok(list) => list,
+            Err(err) => {
+                self.messages.push(NaluMessage::PaletteError(format!(
+                    "Could not read {path}: {err}"
+                )));
+                return;
+            }
+        };
+        let mut signals = Vec::new();
+        let mut missing = Vec::new();
+        for full_name in list.lines().map(str::trim) {
+            if full_name.is_empty() || full_name.starts_with('#') {
+                continue;
+            }
+            let Some(variable) = self.vcd_header.get_variable(full_name) else {
+                log::warn!("Imported signal not found: {full_name}");
+                missing.push(full_name);
+                continue;
+            };
+            let mut path = full_name
+                .split('.')
+                .map(String::from)
+                .collect::<Vec<String>>();
+            path.pop();
+            path.push(variable.to_string());
+            signals.push((path, variable.clone(), radix));
+        }
+        if !missing.is_empty() {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "{} of {} signals not found: {}",
+                missing.len(),
+                missing.len() + signals.len(),
+                missing.join(", ")
+            )));
+        }
+        self.messages
+            .push(SignalViewerMessage::AppendSignals(signals));
+    }
+
+    /// Copies the location with the file filled in, and shows it in the palette
+    /// too for terminals that do not support copying
+    fn share_location(&mut self, location: SharedLocation) {
+        let location = SharedLocation {
+            file: self.vcd_path.as_ref().map(|path| {
+                std::fs::canonicalize(path)
+                    .unwrap_or_else(|_| path.clone())
+                    .display()
+                    .to_string()
+            }),
+            ..location
+        };
+        copy_to_clipboard(&location.to_string());
+        self.palette_message = format!("Copied {location}");
+        self.overlay = NaluOverlay::Palette;
+    }
+
+    /// Zooms to the range, moves the cursor and selects the signal of a shared
+    /// location, opening its file first when it is a different one
+    fn goto_location(&mut self, location: SharedLocation) {
+        let file = location.file.as_ref().map(PathBuf::from);
+        let is_open = |file: &PathBuf| {
+            self.vcd_path.as_ref().is_some_and(|path| {
+                path == file || std::fs::canonicalize(path).ok() == std::fs::canonicalize(file).ok()
+            })
+        };
+        match file {
+            Some(file) if !is_open(&file) => {
+                if !file.is_file() {
+                    self.palette_message = format!("Not a file: {}", file.display());
+                    self.overlay = NaluOverlay::Palette;
+                    return;
+                }
+                self.vcd_location = Some(location);
+                self.messages.push(SignalViewerMessage::OpenVcd(file, true));
+            }
+            _ => {
+                self.messages
+                    .push(WaveformViewerMessage::SetRange(location.range));
+                self.messages
+                    .push(WaveformViewerMessage::GotoTimestamp(location.cursor));
+                if let Some(signal) = location.signal {
+                    self.messages
+                        .push(SignalViewerMessage::SelectSignal(signal));
+                }
+            }
+        }
+    }
+
+    /// Goes to the location once the file given on the command line has loaded
+    pub fn set_location(&mut self, location: SharedLocation) {
+        self.vcd_location = Some(location);
+    }
+
+    fn open_annotations(&mut self) {
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::Annotations;
+    }
+
+    fn open_cursors(&mut self) {
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::Cursors;
+    }
+
+    fn open_menu(&mut self, menu: ContextMenu) {
+        if menu.actions.is_empty() {
+            return;
+        }
+        self.menu = Some(menu);
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::Menu;
+    }
+
+    /// Sends the chosen action back to the pane the menu was opened from
+    fn choose_menu_action(&mut self) {
+        let Some(menu) = &self.menu else {
+            return;
+        };
+        let Some(action) = menu.actions.get(self.overlay_index) else {
+            return;
+        };
+        match menu.target {
+            MenuTarget::Netlist => self
+                .messages
+                .push(NetlistViewerMessage::MenuAction(*action)),
+            MenuTarget::Signal => self.messages.push(SignalViewerMessage::MenuAction(*action)),
+        }
+        self.overlay = NaluOverlay::None;
+    }
+
+    /// Sends the values of the form where it says, or leaves it open with a
+    /// message when they do not make sense
+    fn submit_form(&mut self, button: &str) {
+        let Some(form) = &self.form else {
+            return;
+        };
+        if button == "Cancel" {
+            self.overlay = NaluOverlay::None;
+            return;
+        }
+        let result = match form.kind {
+            FormKind::GroupName => match form.get_text("Name").unwrap_or("").trim() {
+                "" => Err(String::from("Groups need a name")),
+                name => {
+                    let name = String::from(name);
+                    self.messages.push(SignalViewerMessage::GroupSelected(name));
+                    Ok(())
+                }
+            },
+            FormKind::Rename => match form.get_text("Name").unwrap_or("").trim() {
+                "" => Err(String::from("Names cannot be empty")),
+                name => {
+                    let name = String::from(name);
+                    self.messages
+                        .push(SignalViewerMessage::RenameSelected(name));
+                    Ok(())
+                }
+            },
+            FormKind::GotoTime => {
+                // Times are from the start of the dump or from the cursor
+                let start = match form.get_choice("From") {
+                    Some(1) => self.cursor,
+                    _ => 0,
+                };
+                parse_signed_time(form.get_text("Time").unwrap_or("").trim())
+                    .and_then(|offset| {
+                        start
+                            .checked_add_signed(self.get_offset_timestamps(offset))
+                            .ok_or_else(|| String::from("Time is before the start of the dump"))
+                    })
+                    .and_then(|timestamp| match self.waveform.get_timestamp_range().end {
+                        end if timestamp > end => {
+                            Err(String::from("Time is after the end of the dump"))
+                        }
+                        _ => Ok(timestamp),
+                    })
+                    .map(|timestamp| {
+                        self.messages
+                            .push(WaveformViewerMessage::GotoTimestamp(timestamp));
+                    })
+            }
+        };
+        match (result, &mut self.form) {
+            (Err(err), Some(form)) => form.message = err,
+            _ => self.overlay = NaluOverlay::None,
+        }
+    }
+
+    fn handle_palette(&mut self) {
+        let command = match parse_command(&self.palette_input) {
+            Ok(command) => command,
+            Err(err) => {
+                self.palette_message = err;
+                return;
+            }
+        };
+        self.overlay = NaluOverlay::None;
+        match command {
+            PaletteCommand::Annotate(text) => self
+                .messages
+                .push(WaveformViewerMessage::AddAnnotation(text)),
+            PaletteCommand::Annotations => self.open_annotations(),
+            PaletteCommand::Search(text) => self
+                .messages
+                .push(WaveformViewerMessage::SearchAnnotations(text)),
+            PaletteCommand::Close => self.messages.push(LayoutMessage::Close),
+            PaletteCommand::PythonView => self
+                .messages
+                .push(LayoutMessage::Toggle(String::from(PYTHON_PANE))),
+            PaletteCommand::Cursor(name, placement) => self.messages.push(match placement {
+                CursorPlacement::AtCursor => WaveformViewerMessage::SetNamedCursor(name, None),
+                CursorPlacement::At(timestamp) => {
+                    WaveformViewerMessage::SetNamedCursor(name, Some(timestamp))
+                }
+                CursorPlacement::Remove => WaveformViewerMessage::RemoveNamedCursor(name),
+            }),
+            PaletteCommand::Cursors => self.open_cursors(),
+            PaletteCommand::Dedup => self.messages.push(SignalViewerMessage::RemoveDuplicates),
+            PaletteCommand::Delays => self.messages.push(SignalViewerMessage::MeasureDelays),
+            PaletteCommand::ExpandAll(BrowserPane::Netlist, expanded) => self
+                .messages
+                .push(NetlistViewerMessage::ExpandAll(expanded)),
+            PaletteCommand::ExpandAll(BrowserPane::Signals, expanded) => {
+                self.messages.push(SignalViewerMessage::ExpandAll(expanded))
+            }
+            PaletteCommand::Glitches(width, all) => {
+                let width = match width {
+                    GlitchWidth::Duration(width) => width,
+                    GlitchWidth::Clock(name) => match self.get_clock_period(&name) {
+                        Some(period) => period,
+                        None => {
+                            self.palette_message = format!("Not a clock: {name}");
+                            self.overlay = NaluOverlay::Palette;
+                            return;
+                        }
+                    },
+                };
+                self.messages
+                    .push(SignalViewerMessage::FindGlitches(width, all));
+            }
+            PaletteCommand::Hud => self.hud_enabled = !self.hud_enabled,
+            PaletteCommand::Import(path, radix) => self.import_signals(&path, radix),
+            PaletteCommand::LoadConfig(path, append) => {
+                self.load_config(PathBuf::from(path), append)
+            }
+            PaletteCommand::Export(path) => self.messages.push(SignalViewerMessage::Export(path)),
+            PaletteCommand::Hints => self.hints_enabled = !self.hints_enabled,
+            PaletteCommand::History => self.messages.push(WaveformViewerMessage::ShowHistory),
+            PaletteCommand::Open(path, keep) => {
+                let path = PathBuf::from(path);
+                if path.is_file() {
+                    self.messages.push(SignalViewerMessage::OpenVcd(path, keep));
+                } else {
+                    self.palette_message = format!("Not a file: {}", path.display());
+                    self.overlay = NaluOverlay::Palette;
+                }
+            }
+            PaletteCommand::Goto(location) => self.goto_location(location),
+            PaletteCommand::Share => self.messages.push(SignalViewerMessage::Share),
+            PaletteCommand::Period(cursors) => self
+                .messages
+                .push(SignalViewerMessage::MeasurePeriod(cursors)),
+            PaletteCommand::Python(function, args) => self.run_python(function, args),
+            PaletteCommand::SaveView(name) => {
+                self.messages.push(SignalViewerMessage::SaveView(name))
+            }
+            PaletteCommand::SaveGroup(group, path) => self
+                .messages
+                .push(SignalViewerMessage::SaveGroup(group, path)),
+            PaletteCommand::Separator(label) => self
+                .messages
+                .push(SignalViewerMessage::InsertSeparator(Some(label))),
+            PaletteCommand::Split(direction, pane) => {
+                self.messages.push(LayoutMessage::Split(direction, pane))
+            }
+            PaletteCommand::Resize(direction, cells) => {
+                self.messages.push(LayoutMessage::Resize(direction, cells))
+            }
+            PaletteCommand::EqualSizes => self.messages.push(LayoutMessage::EqualSizes),
+            PaletteCommand::TraceUnknown(all) => {
+                self.messages.push(SignalViewerMessage::TraceUnknown(all))
+            }
+            PaletteCommand::UnknownSignals(all) => {
+                self.messages.push(SignalViewerMessage::ListUnknown(all))
+            }
+            PaletteCommand::Tree => {
+                self.tree_enabled = !self.tree_enabled;
+                self.messages
+                    .push(NetlistViewerMessage::SetTree(self.tree_enabled));
+                self.messages
+                    .push(SignalViewerMessage::SetTree(self.tree_enabled));
+            }
+            PaletteCommand::UndoLoad => self.undo_load(),
+            PaletteCommand::View(name) => match self.views.iter().find(|v| v.name == name) {
+                Some(view) => self.load_view(view.clone(), true),
+                None => {
+                    self.palette_message = format!("Unknown view: {name}");
+                    self.overlay = NaluOverlay::Palette;
+                }
+            },
+            PaletteCommand::Views => {
+                self.overlay_index = 0;
+                self.overlay = NaluOverlay::Views;
+            }
+            PaletteCommand::Warnings => {
+                self.overlay_index = 0;
+                self.overlay = NaluOverlay::Warnings;
+            }
+            PaletteCommand::Zoom(width) => self.zoom(width),
+            PaletteCommand::Offset(offset) => self.offset_signals(offset),
+            PaletteCommand::Radix(radix) => {
+                self.messages.push(SignalViewerMessage::SetRadix(radix))
+            }
+            PaletteCommand::Height(height) => {
+                self.messages.push(SignalViewerMessage::SetHeight(height))
+            }
+            PaletteCommand::Sort(sort) => self.messages.push(NetlistViewerMessage::SetSort(sort)),
+            PaletteCommand::Window(from, to) => {
+                self.load_options.from = from;
+                self.load_options.to = to;
+                self.overlay = NaluOverlay::Loading;
+                self.handle_load();
+            }
+        }
+    }
+
+    pub fn handle_update(&mut self) {
+        for message in self.messages.get::<NaluMessage>() {
+            match message {
+                NaluMessage::OpenPalette(input) => self.open_palette(input),
+                NaluMessage::OpenQuickAdd => self.open_quick_add(),
+                NaluMessage::PaletteError(message) => {
+                    self.palette_message = message;
+                    self.overlay = NaluOverlay::Palette;
+                }
+                NaluMessage::OpenAnnotations => self.open_annotations(),
+                NaluMessage::OpenCursors => self.open_cursors(),
+                NaluMessage::UpdateCursors(cursors) => {
+                    self.named_cursors = cursors;
+                    self.overlay_index = self
+                        .overlay_index
+                        .min(self.named_cursors.len().saturating_sub(1));
+                }
+                NaluMessage::UpdateAnnotations(annotations) => {
+                    self.annotations = annotations;
+                    self.overlay_index = self
+                        .overlay_index
+                        .min(self.annotations.len().saturating_sub(1));
+                }
+                NaluMessage::LoadSignal(idcode) => self.load_signal(idcode),
+                NaluMessage::SaveView(view) => self.save_view(view),
+                NaluMessage::Autosave(view) => self.autosave(view),
+                NaluMessage::SetFollow(follow) => self.set_follow(follow),
+                NaluMessage::PythonError(error) => self.show_python_error(error),
+                NaluMessage::FindGlitches(signals, width) => self.find_glitches(signals, width),
+                NaluMessage::TraceUnknown(signal, others) => self.trace_unknown(signal, others),
+                NaluMessage::ListUnknownSignals(signals, all) => {
+                    self.list_unknown_signals(signals, all)
+                }
+                NaluMessage::SetCursor(cursor) => self.set_cursor(cursor),
+                NaluMessage::GroupSaved(view) => self.run_on_save(&view),
+                NaluMessage::MeasureDelays(signals, range) => self.measure_delays(signals, range),
+                NaluMessage::ShowHistory(history) => {
+                    self.history = Some(history);
+                    self.overlay_index = 0;
+                    self.overlay = NaluOverlay::History;
+                }
+                NaluMessage::OpenVcd(path, signals) => self.open_vcd(path, signals),
+                NaluMessage::OpenMenu(menu) => self.open_menu(menu),
+                NaluMessage::OpenForm(form) => {
+                    self.form = Some(form);
+                    self.overlay = NaluOverlay::Form;
+                }
+                NaluMessage::AddVirtualVector(full_name, bits) => {
+                    self.add_virtual_vector(full_name, bits)
+                }
+                NaluMessage::Share(location) => self.share_location(location),
+            }
+        }
+    }
+
+    /// Gives the vector put together from the bits an idcode of its own for
+    /// the waveform viewer to draw it under
+    fn add_virtual_vector(
+        &mut self,
+        full_name: Vec<String>,
+        bits: Vec<(Vec<String>, VcdVariable)>,
+    ) {
+        let vector = VirtualVector {
+            idcode: self.next_virtual_idcode,
+            bits: bits
+                .iter()
+                .map(|(_, variable)| variable.get_idcode())
+                .collect(),
+        };
+        self.next_virtual_idcode += 1;
+        for idcode in &vector.bits {
+            self.load_signal(*idcode);
+        }
+        self.messages.push(SignalViewerMessage::AppendVirtualVector(
+            full_name,
+            vector.idcode,
+            bits,
+        ));
+        self.messages
+            .push(WaveformViewerMessage::AddVirtualVector(vector));
+    }
+
+    fn run_python(&mut self, function: String, args: Vec<String>) {
+        let Some(python_path) = &self.python_path else {
+            self.palette_message = String::from("No python script loaded, see --python");
+            self.overlay = NaluOverlay::Palette;
+            return;
+        };
+        let width = crossterm::terminal::size().map_or(80, |(w, _)| w.saturating_sub(4));
+        let lines = match run_function(
+            python_path,
+            &function,
+            &args,
+            BufferPy::new(width, PYTHON_OUTPUT_ROWS),
+            WaveformPy::new(self.waveform.clone()),
+            VcdHeaderPy::new(self.vcd_header.clone()),
+            self.cursor,
+        ) {
+            Ok(lines) if lines.is_empty() => vec![String::from("(no result)")],
+            Ok(lines) => lines,
+            Err(err) => {
+                log::error!("Python Function Error: {err:?}");
+                let rerun = PythonRerun::Function(function.clone(), args);
+                self.show_python_error(PythonError::new(&format!("{function}()"), &err, rerun));
+                return;
+            }
+        };
+        push_navigation(&self.messages, self.cursor);
+        self.python_output = Some(PythonOutput { function, lines });
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::Python;
+    }
+
+    fn zoom(&mut self, width: ZoomWidth) {
+        let width = match width {
+            ZoomWidth::All => {
+                self.messages.push(WaveformViewerMessage::ZoomAll);
+                return;
+            }
+            ZoomWidth::Timestamps(width) => width,
+            ZoomWidth::Time(value, exponent) => self.get_timestamps(value, exponent),
+            ZoomWidth::Cycles(count, clock) => {
+                let period = match &clock {
+                    Some(name) => self.get_clock_period(name),
+                    None => self.clocks.values().map(|clock| clock.period).min(),
+                };
+                match (period, clock) {
+                    (Some(period), _) => count.saturating_mul(period),
+                    (None, Some(name)) => {
+                        self.palette_message = format!("Not a clock: {name}");
+                        self.overlay = NaluOverlay::Palette;
+                        return;
+                    }
+                    (None, None) => {
+                        self.palette_message = String::from("No clocks found in the dump");
+                        self.overlay = NaluOverlay::Palette;
+                        return;
+                    }
+                }
+            }
+        };
+        if width == 0 {
+            self.palette_message = String::from("Zoom window is shorter than one timestamp");
+            self.overlay = NaluOverlay::Palette;
+            return;
+        }
+        self.messages.push(WaveformViewerMessage::ZoomAround(width));
+    }
+
+    /// Converts a time of the value times ten to the power in seconds into
+    /// timestamps of the dump, rounding down
+    fn get_timestamps(&self, value: u64, exponent: i32) -> u64 {
+        get_timestamps(value, exponent, self.get_timescale())
+    }
+
+    fn offset_signals(&mut self, offset: SignalOffset) {
+        let offset = self.get_offset_timestamps(offset);
+        self.messages.push(SignalViewerMessage::SetOffset(offset));
+    }
+
+    fn get_offset_timestamps(&self, offset: SignalOffset) -> i64 {
+        match offset {
+            SignalOffset::Timestamps(offset) => offset,
+            SignalOffset::Time(value, exponent) => {
+                let timestamps = self.get_timestamps(value.unsigned_abs(), exponent);
+                let timestamps = i64::try_from(timestamps).unwrap_or(i64::MAX);
+                if value < 0 {
+                    -timestamps
+                } else {
+                    timestamps
+                }
+            }
+        }
+    }
+
+    fn get_clock_period(&self, full_name: &str) -> Option<u64> {
+        let variable = self.vcd_header.get_variable(full_name)?;
+        Some(self.clocks.get(&variable.get_idcode())?.period)
+    }
+
+    fn find_glitches(&mut self, signals: Vec<(String, usize, Option<usize>)>, width: u64) {
+        let mut glitches = Vec::new();
+        for (name, idcode, index) in signals {
+            for (timestamp, width) in find_glitches(&self.waveform, idcode, index, width) {
+                glitches.push(Glitch {
+                    name: name.clone(),
+                    timestamp,
+                    width,
+                });
+            }
+        }
+        glitches.sort_by_key(|g| g.timestamp);
+        glitches.truncate(GLITCH_LIMIT);
+        self.glitches = glitches;
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::Glitches;
+    }
+
+    fn trace_unknown(
+        &mut self,
+        signal: Option<(String, usize, Option<usize>)>,
+        others: Vec<(String, usize, Option<usize>)>,
+    ) {
+        let Some((name, idcode, index)) = signal else {
+            self.palette_message = String::from("No signal selected");
+            self.overlay = NaluOverlay::Palette;
+            return;
+        };
+        let Some(timestamp) = find_unknown_start(&self.waveform, idcode, index, self.cursor) else {
+            self.palette_message = format!("{name} is not unknown at the cursor");
+            self.overlay = NaluOverlay::Palette;
+            return;
+        };
+        let others = others
+            .into_iter()
+            .filter(|(_, other_idcode, other_index)| {
+                (*other_idcode, *other_index) != (idcode, index)
+                    && is_unknown_at(&self.waveform, *other_idcode, *other_index, timestamp)
+            })
+            .map(|(name, _, _)| name)
+            .collect();
+        self.unknown_trace = Some(UnknownTrace {
+            name,
+            timestamp,
+            others,
+        });
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::UnknownTrace;
+    }
+
+    /// Lists the signals with x or z bits at the cursor, first the ones in the
+    /// viewer in their order there and then the rest of the dump by name
+    fn list_unknown_signals(&mut self, signals: Vec<(String, usize, Option<usize>)>, all: bool) {
+        let in_viewer = signals
+            .iter()
+            .map(|(name, _, _)| name.clone())
+            .collect::<HashSet<String>>();
+        let mut candidates = signals
+            .into_iter()
+            .map(|signal| (signal, true))
+            .collect::<Vec<_>>();
+        if all {
+            for name in get_variable_names(&self.vcd_header) {
+                if let Some(variable) = self.vcd_header.get_variable(&name) {
+                    if !in_viewer.contains(&name) {
+                        candidates.push(((name, variable.get_idcode(), None), false));
+                    }
+                }
+            }
+        }
+        self.unknown_signals = candidates
+            .into_iter()
+            .filter(|((_, idcode, index), _)| {
+                is_unknown_or_floating_at(&self.waveform, *idcode, *index, self.cursor)
+            })
+            .map(|((name, idcode, index), in_viewer)| UnknownSignal {
+                value: render_value(
+                    &self.waveform,
+                    idcode,
+                    index,
+                    BitVectorRadix::Binary,
+                    false,
+                    self.cursor,
+                )
+                .unwrap_or_default(),
+                name,
+                index,
+                in_viewer,
+            })
+            .collect();
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::UnknownSignals;
+        self.select_unknown_signal(false);
+    }
+
+    /// Selects the signal picked in the list of unknown signals in the signal
+    /// viewer, adding it to the end of the viewer first when asked to
+    fn select_unknown_signal(&mut self, add: bool) {
+        let Some(signal) = self.unknown_signals.get(self.overlay_index) else {
+            return;
+        };
+        if !signal.in_viewer {
+            let variable = self.vcd_header.get_variable(&signal.name);
+            let (true, Some(variable)) = (add, variable) else {
+                return;
+            };
+            self.append_variable(&signal.name, variable);
+        }
+        self.messages
+            .push(SignalViewerMessage::SelectSignal(signal.name.clone()));
+        if let Some(signal) = self.unknown_signals.get_mut(self.overlay_index) {
+            signal.in_viewer = true;
+        }
+    }
+
+    fn measure_delays(&mut self, signals: Vec<(String, usize, Option<usize>)>, range: Range<u64>) {
+        let [(from, from_idcode, from_index), (to, to_idcode, to_index)] = &signals[..] else {
+            self.palette_message = String::from("Select two signals to measure delays between");
+            self.overlay = NaluOverlay::Palette;
+            return;
+        };
+        let statistics = measure_delays(
+            &get_transitions(&self.waveform, *from_idcode, *from_index, &range),
+            &get_transitions(&self.waveform, *to_idcode, *to_index, &range),
+        );
+        self.delays = Some(DelayReport {
+            from: from.clone(),
+            to: to.clone(),
+            range,
+            statistics,
+        });
+        self.overlay = NaluOverlay::Delays;
+    }
+
+    fn load_views(&mut self) {
+        let Some(python_path) = &self.python_path else {
+            return;
+        };
+        match run_views(python_path) {
+            Ok(views) => self.views = views,
+            Err(err) => {
+                log::error!("Python Views Error: {err:?}");
+                self.show_python_error(PythonError::new("views", &err, PythonRerun::Script));
+            }
+        }
+    }
+
+    fn load_cursor_hook(&mut self) {
+        self.cursor_hook = self
+            .python_path
+            .as_ref()
+            .and_then(|path| load_hook(path, "on_cursor").ok().flatten());
+    }
+
+    /// Runs the on_load() hook of the script once a dump has been opened,
+    /// adding the signals it returns to the end of the viewer
+    fn run_on_load(&mut self) {
+        self.load_cursor_hook();
+        if !std::mem::take(&mut self.load_hook_pending) {
+            return;
+        }
+        let Some(python_path) = &self.python_path else {
+            return;
+        };
+        match run_load_hook(python_path, VcdHeaderPy::new(self.vcd_header.clone())) {
+            Ok(names) => {
+                for name in names {
+                    match self.vcd_header.get_variable(&name) {
+                        Some(variable) => self.append_variable(&name, variable),
+                        None => log::warn!("on_load() signal not found: {name}"),
+                    }
+                }
+                push_navigation(&self.messages, self.cursor);
+            }
+            Err(err) => {
+                log::error!("Python Hook Error: {err:?}");
+                self.show_python_error(PythonError::new("on_load()", &err, PythonRerun::Script));
+            }
+        }
+    }
+
+    fn run_on_save(&mut self, view: &SignalView) {
+        let Some(python_path) = &self.python_path else {
+            return;
+        };
+        match run_save_hook(python_path, view) {
+            Ok(()) => push_navigation(&self.messages, self.cursor),
+            Err(err) => {
+                log::error!("Python Hook Error: {err:?}");
+                self.show_python_error(PythonError::new("on_save()", &err, PythonRerun::Script));
+            }
+        }
+    }
+
+    /// Keeps track of the cursor for the palette, calling the on_cursor() hook
+    /// of the script when it moved, until the hook raises an error
+    fn set_cursor(&mut self, cursor: u64) {
+        let moved = cursor != self.cursor;
+        self.cursor = cursor;
+        let Some(module) = self.cursor_hook.as_ref().filter(|_| moved) else {
+            return;
+        };
+        match run_cursor_hook(module, cursor) {
+            Ok(()) => push_navigation(&self.messages, cursor),
+            Err(err) => {
+                log::error!("Python Hook Error: {err:?}");
+                self.cursor_hook = None;
+                self.show_python_error(PythonError::new("on_cursor()", &err, PythonRerun::Script));
+            }
+        }
+    }
+
+    fn show_python_error(&mut self, error: PythonError) {
+        self.python_error = Some(error);
+        self.overlay_index = 0;
+        self.overlay = NaluOverlay::PythonError;
+    }
+
+    /// Runs whatever raised the error again, which shows the error again if
+    /// it has not been fixed yet
+    fn rerun_python(&mut self) {
+        let Some(error) = self.python_error.take() else {
+            return;
+        };
+        self.overlay = NaluOverlay::None;
+        match error.rerun {
+            PythonRerun::Function(function, args) => self.run_python(function, args),
+            PythonRerun::Script => {
+                self.load_views();
+                self.load_cursor_hook();
+                self.messages.push(WaveformViewerMessage::ReloadPython);
+                self.messages.push(PythonViewMessage::ReloadPython);
+            }
+        }
+    }
+
+    /// Runs the views of another python file and keeps them for this session
+    /// next to the ones of the script, then either switches to the first one
+    /// or appends each of them as a group remembering the file it came from
+    fn load_config(&mut self, path: PathBuf, append: bool) {
+        let views = match run_views(&path) {
+            Ok(views) => views,
+            Err(err) => {
+                log::error!("Python Config Error: {err:?}");
+                self.messages.push(NaluMessage::PaletteError(format!(
+                    "Could not load views from {}: {err}",
+                    path.display()
+                )));
+                return;
+            }
+        };
+        let Some(first) = views.first().cloned() else {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "No view functions in {}",
+                path.display()
+            )));
+            return;
+        };
+        for view in &views {
+            match self.views.iter_mut().find(|v| v.name == view.name) {
+                Some(existing) => *existing = view.clone(),
+                None => self.views.push(view.clone()),
+            }
+        }
+        if !append {
+            self.load_view(first, true);
+            return;
+        }
+        for view in views {
+            let (signals, offsets) = self.get_view_variables(&view);
+            self.messages.push(SignalViewerMessage::AppendView(
+                view.name,
+                signals,
+                offsets,
+                path.clone(),
+            ));
+        }
+    }
+
+    /// The variables of the signals of a view along with the idcodes of the
+    /// ones shifted in time, skipping any that are not in the current dump
+    fn get_view_variables(&self, view: &SignalView) -> ViewVariables {
+        let signals = get_view_nodes(&self.vcd_header, &view.name, &view.signals);
+        let offsets = view
+            .offsets
+            .iter()
+            .filter_map(|(full_name, offset)| {
+                Some((
+                    self.vcd_header.get_variable(full_name)?.get_idcode(),
+                    *offset,
+                ))
+            })
+            .collect();
+        (signals, offsets)
+    }
+
+    /// Switches the signal viewer to the signals of a view, skipping any that
+    /// are not in the current dump, and zooms to its range
+    pub fn load_view(&mut self, view: SignalView, undo: bool) {
+        let (signals, offsets) = self.get_view_variables(&view);
+        if undo {
+            // The signals to undo back to are now from this file
+            self.undo_path = None;
+        }
+        self.messages
+            .push(SignalViewerMessage::LoadView(signals, offsets, undo));
+        if let Some(range) = view.range {
+            self.messages.push(WaveformViewerMessage::SetRange(range));
+        }
+    }
+
+    /// Keeps the view for this session and saves it to the python script, where
+    /// it replaces any view with the same name, but not a different view saved
+    /// as the same function
+    fn save_view(&mut self, view: SignalView) {
+        let Some(python_path) = &self.python_path else {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "No python script to save view {} to, it is only kept until nalu exits",
+                view.name
+            )));
+            self.keep_view(view);
+            return;
+        };
+        if let Err(err) = save_view_file(python_path, &view) {
+            log::error!("Saving View Error: {err:?}");
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "Could not save view {}: {err}",
+                view.name
+            )));
+            return;
+        }
+        self.run_on_save(&view);
+        self.keep_view(view);
+    }
+
+    fn keep_view(&mut self, view: SignalView) {
+        match self.views.iter_mut().find(|v| v.name == view.name) {
+            Some(existing) => *existing = view,
+            None => self.views.push(view),
+        }
+    }
+
+    /// Opens the selected recent file, dropping it from the list instead if it
+    /// has since been removed
+    fn open_recent(&mut self) {
+        let Some(recent) = self.recent.get(self.overlay_index) else {
+            return;
+        };
+        if !recent.path.exists() {
+            log::warn!("Recent file no longer exists: {:?}", recent.path);
+            self.recent.remove(self.overlay_index);
+            self.overlay_index = self.overlay_index.min(self.recent.len().saturating_sub(1));
+            save_recent(&self.recent);
+            return;
+        }
+        self.messages
+            .push(SignalViewerMessage::OpenVcd(recent.path.clone(), false));
+    }
+
+    /// Switches to a different file, which starts over with only the given
+    /// signals in the viewer once it has loaded
+    fn open_vcd(&mut self, path: PathBuf, signals: Vec<ViewEntry>) {
+        let view = SignalView {
+            name: path.display().to_string(),
+            signals,
+            offsets: Vec::new(),
+            range: None,
+        };
+        self.open_vcd_view(path, view);
+    }
+
+    fn open_vcd_view(&mut self, path: PathBuf, view: SignalView) {
+        self.undo_path = self.vcd_path.clone();
+        self.vcd_view = Some(view);
+        self.switch_vcd(path);
+    }
+
+    /// Brings back the signals of the session that did not exit cleanly,
+    /// opening its file first if a different one is open
+    fn restore_recovery(&mut self) {
+        let Some(recovery) = self.recovery.take() else {
+            return;
+        };
+        let vcd_path = self
+            .vcd_path
+            .as_ref()
+            .map(|path| std::fs::canonicalize(path).unwrap_or(path.clone()));
+        if vcd_path.as_ref() == Some(&recovery.vcd_path) {
+            self.overlay = NaluOverlay::None;
+            self.load_view(recovery.view, true);
+        } else if recovery.vcd_path.exists() {
+            self.open_vcd_view(recovery.vcd_path, recovery.view);
+        } else {
+            log::warn!("Recovered file no longer exists: {:?}", recovery.vcd_path);
+            self.discard_recovery();
+        }
+    }
+
+    fn discard_recovery(&mut self) {
+        self.recovery = None;
+        remove_recovery();
+        self.overlay = match self.vcd_path {
+            Some(_) => NaluOverlay::None,
+            None => NaluOverlay::Recent,
+        };
+    }
+
+    /// Collects the signals in the viewer every so often to keep for recovery,
+    /// writing them out less often, which waits until the user has decided
+    /// what to do with the signals left from before
+    pub fn handle_autosave(&mut self) {
+        if !self.persistent
+            || self.recovery.is_some()
+            || self.vcd_path.is_none()
+            || self.vcd_handle.is_some()
+        {
+            return;
+        }
+        if self.autosave_time.elapsed() >= AUTOSAVE_INTERVAL {
+            self.autosave_time = Instant::now();
+            self.messages.push(SignalViewerMessage::Autosave);
+        }
+        if self.autosave_write_time.elapsed() >= AUTOSAVE_WRITE_INTERVAL {
+            self.autosave_write_time = Instant::now();
+            flush_recovery();
+        }
+    }
+
+    fn set_follow(&mut self, follow: bool) {
+        self.follow_stamp = match &self.vcd_path {
+            Some(vcd_path) if follow => Some(get_file_stamp(vcd_path)),
+            _ => None,
+        };
+    }
+
+    /// Reloads the dump when it changed on disk while following its end, like
+    /// when a simulation is still writing it, waiting for any open overlay to
+    /// be closed first
+    pub fn handle_follow(&mut self) {
+        let (Some(stamp), Some(vcd_path)) = (&self.follow_stamp, &self.vcd_path) else {
+            return;
+        };
+        if self.vcd_handle.is_some()
+            || !matches!(self.overlay, NaluOverlay::None)
+            || self.follow_time.elapsed() < FOLLOW_INTERVAL
+        {
+            return;
+        }
+        self.follow_time = Instant::now();
+        let new_stamp = get_file_stamp(vcd_path);
+        if new_stamp != *stamp {
+            self.follow_stamp = Some(new_stamp);
+            self.handle_load();
+        }
+    }
+
+    fn autosave(&mut self, view: SignalView) {
+        let Some(vcd_path) = &self.vcd_path else {
+            return;
+        };
+        let recovery = Recovery {
+            vcd_path: std::fs::canonicalize(vcd_path).unwrap_or(vcd_path.clone()),
+            view,
+        };
+        if self.autosaved.as_ref() != Some(&recovery) {
+            set_pending_recovery(recovery.clone());
+            self.autosaved = Some(recovery);
+        }
+    }
+
+    /// Restores the signals from before the last view or file was loaded,
+    /// reopening the previous file first if it was an open
+    fn undo_load(&mut self) {
+        match self.undo_path.take() {
+            Some(path) => {
+                self.undo_path = self.vcd_path.clone();
+                self.undo_pending = true;
+                self.switch_vcd(path);
+            }
+            None => self.messages.push(SignalViewerMessage::UndoLoad),
+        }
+    }
+
+    fn switch_vcd(&mut self, path: PathBuf) {
+        self.vcd_path = Some(path);
+        self.load_hook_pending = true;
+        if let Some(signals) = &mut self.load_options.signals {
+            signals.clear();
+        }
+        self.overlay = NaluOverlay::Loading;
+        self.handle_load();
+    }
+
+    /// Moves the loaded file to the front of the recent files list
+    fn update_recent(&mut self) {
+        let Some(vcd_path) = &self.vcd_path else {
+            return;
+        };
+        let path = std::fs::canonicalize(vcd_path).unwrap_or(vcd_path.clone());
+        let file = RecentFile {
+            path,
+            signals: self.vcd_header.get_idcodes_map().len(),
+            end: self.waveform.get_timestamp_range().end,
+            timescale: self.get_timescale(),
+        };
+        self.recent = load_recent();
+        add_recent(&mut self.recent, file);
+        save_recent(&self.recent);
+    }
+
     pub fn handle_load(&mut self) {
-        log::info!("Loading {:?}...", self.vcd_path);
+        self.vcd_rescan = false;
+        self.vcd_rescan_pending = false;
+        self.start_load(false);
+    }
+
+    /// Rescans the body of a lazily loaded VCD file when a signal without any
+    /// loaded values is added to the viewer
+    fn load_signal(&mut self, idcode: usize) {
+        let Some(signals) = &mut self.load_options.signals else {
+            return;
+        };
+        if !signals.insert(idcode) {
+            return;
+        }
+        if self.vcd_handle.is_some() {
+            self.vcd_rescan_pending = true;
+        } else {
+            self.vcd_rescan = true;
+            self.start_load(self.load_options.tolerant);
+        }
+    }
+
+    fn start_load(&mut self, tolerant: bool) {
+        let Some(vcd_path) = &self.vcd_path else {
+            return;
+        };
+        log::info!("Loading {:?}...", vcd_path);
         *self.progress.lock().unwrap() = (0, 0);
-        let bytes = match std::fs::read_to_string(&self.vcd_path) {
+        let bytes = match std::fs::read(vcd_path) {
             Ok(bytes) => bytes,
             Err(err) => {
                 log::error!("VCD Loading Error: {:?}", err);
@@ -111,8 +1725,14 @@ impl NaluState {
                 return;
             }
         };
-        let handle = load_multi_threaded(bytes, 4, self.progress.clone());
-        self.vcd_handle = Some(handle);
+        let Some(loader) = self.loaders.find(vcd_path, &bytes) else {
+            log::error!("No loader for {:?}", vcd_path);
+            self.done = Some(format!("No loader for {:?}", vcd_path));
+            return;
+        };
+        log::info!("Loading as {}", loader.get_name());
+        self.load_options.tolerant = tolerant;
+        self.vcd_handle = Some(loader.load(bytes, self.progress.clone(), &self.load_options));
     }
 
     pub fn handle_vcd(&mut self) {
@@ -129,44 +1749,147 @@ impl NaluState {
         // Replace existing handle with none and extract values
         let mut vcd_handle_swap = None;
         std::mem::swap(&mut vcd_handle_swap, &mut self.vcd_handle);
-        let (vcd_header, waveform) = match vcd_handle_swap.unwrap().join().unwrap() {
-            Ok((vcd_header, waveform)) => (vcd_header, waveform),
+        let loaded = match vcd_handle_swap.unwrap().join().unwrap() {
+            Ok(loaded) => loaded,
+            Err(err) if !self.load_options.tolerant => {
+                // Retry skipping over the malformed entries to show what we can
+                log::warn!("VCD Loading Error: {:?}, retrying in tolerant mode", err);
+                self.start_load(true);
+                return;
+            }
             Err(err) => {
                 log::error!("VCD Loading Error: {:?}", err);
                 self.done = Some(format!("VCD Loading Error: {:?}", err));
                 return;
             }
         };
+        if !loaded.diagnostics.is_empty() {
+            log::warn!(
+                "Partially loaded, skipped {} malformed entries",
+                loaded.diagnostics.get_skipped()
+            );
+        }
+        self.vcd_diagnostics = loaded.diagnostics;
+        if self.vcd_rescan {
+            let waveform = Arc::new(loaded.waveform);
+            self.update_waveform(&waveform);
+            self.messages
+                .push(WaveformViewerMessage::ReplaceWaveform(waveform));
+            if self.vcd_rescan_pending {
+                self.vcd_rescan_pending = false;
+                self.start_load(self.load_options.tolerant);
+            }
+            return;
+        }
         self.overlay = NaluOverlay::None;
-        self.vcd_header = Arc::new(vcd_header);
+        self.vcd_header = Arc::new(loaded.header);
+        self.next_virtual_idcode = self
+            .vcd_header
+            .get_idcodes_map()
+            .keys()
+            .map(|idcode| idcode + 1)
+            .max()
+            .unwrap_or(0);
+        self.load_views();
         let timescale = match self.vcd_header.get_timescale() {
             Some(timescale) => *timescale,
             None => 0,
         };
+        let events = loaded
+            .net_types
+            .iter()
+            .filter(|(_, net_type)| matches!(net_type, VcdVariableNetType::Event))
+            .map(|(idcode, _)| *idcode)
+            .collect::<HashSet<usize>>();
+        self.messages
+            .push(WaveformViewerMessage::SetEvents(Arc::new(events)));
         self.messages.push(NetlistViewerMessage::UpdateScopes(
             self.vcd_header.get_scopes().clone(),
+            Arc::new(loaded.net_types),
+        ));
+        let waveform = Arc::new(loaded.waveform);
+        self.update_waveform(&waveform);
+        if self.persistent {
+            self.update_recent();
+        }
+        self.messages.push(HeaderMessage::SetFile(
+            self.vcd_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().to_string()),
+        ));
+        self.messages.push(PythonViewMessage::UpdateScript(
+            self.vcd_header.clone(),
+            self.python_path.clone(),
         ));
         self.messages.push(WaveformViewerMessage::UpdateWaveform(
-            Arc::new(waveform),
+            waveform,
             self.vcd_header.clone(),
             timescale,
             self.python_path.clone(),
         ));
+        if let Some(view) = self.vcd_view.take() {
+            self.load_view(view, false);
+        } else if self.recovery.is_some() {
+            self.overlay = NaluOverlay::Recovery;
+        }
+        if std::mem::take(&mut self.undo_pending) {
+            self.messages.push(SignalViewerMessage::UndoLoad);
+        }
+        if let Some(location) = self.vcd_location.take() {
+            // The file is loaded now, so only the rest is left to do
+            self.goto_location(SharedLocation {
+                file: None,
+                ..location
+            });
+        }
+        self.run_on_load();
+    }
+
+    /// Keeps the waveform for analysis and looks for clocks among the loaded
+    /// signals, which have to be found again whenever a lazy load adds more
+    fn update_waveform(&mut self, waveform: &Arc<Waveform>) {
+        let clocks = Arc::new(detect_clocks(&self.vcd_header, waveform));
+        self.waveform = waveform.clone();
+        self.clocks = clocks.clone();
+        self.messages
+            .push(NetlistViewerMessage::SetClocks(clocks.clone()));
+        self.messages.push(InspectorMessage::SetClocks(clocks));
+        self.messages.push(SignalViewerMessage::UpdateWaveform(
+            waveform.clone(),
+            self.get_timescale(),
+        ));
+        self.messages.push(InspectorMessage::UpdateWaveform(
+            waveform.clone(),
+            self.get_timescale(),
+        ));
+        self.messages
+            .push(PythonViewMessage::UpdateWaveform(waveform.clone()));
     }
 
     pub fn get_overlay(&self) -> &NaluOverlay {
         &self.overlay
     }
 
-    pub fn get_percent(&self) -> usize {
-        let (current, total) = *self.progress.lock().unwrap();
-        if total == 0 {
-            0
+    /// Returns a message to show in place of the header line
+    pub fn get_banner(&self) -> Option<String> {
+        if self.vcd_rescan && self.vcd_handle.is_some() {
+            Some(format!("Loading signals... {}%", self.get_percent()))
+        } else if !self.vcd_diagnostics.is_empty() {
+            Some(format!(
+                "Warning: partial load, skipped {} malformed entries (run 'warnings' in the palette for details)",
+                self.vcd_diagnostics.get_skipped()
+            ))
         } else {
-            current * 100 / total
+            None
         }
     }
 
+    pub fn get_percent(&self) -> usize {
+        let (current, total) = *self.progress.lock().unwrap();
+        (current * 100).checked_div(total).unwrap_or(0)
+    }
+
     pub fn get_filter(&self) -> String {
         self.filter_input.clone()
     }
@@ -175,6 +1898,148 @@ impl NaluState {
         self.palette_input.clone()
     }
 
+    pub fn get_quick_add(&self) -> (&str, &Vec<String>) {
+        (&self.quick_add_input, &self.quick_add_candidates)
+    }
+
+    pub fn get_palette_message(&self) -> String {
+        self.palette_message.clone()
+    }
+
+    pub fn get_annotations(&self) -> &Vec<TimescaleAnnotation> {
+        &self.annotations
+    }
+
+    pub fn get_named_cursors(&self) -> &Vec<NamedCursor> {
+        &self.named_cursors
+    }
+
+    pub fn get_cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    pub fn get_views(&self) -> &Vec<SignalView> {
+        &self.views
+    }
+
+    pub fn get_glitches(&self) -> &Vec<Glitch> {
+        &self.glitches
+    }
+
+    pub fn get_unknown_trace(&self) -> Option<&UnknownTrace> {
+        self.unknown_trace.as_ref()
+    }
+
+    pub fn get_unknown_signals(&self) -> &Vec<UnknownSignal> {
+        &self.unknown_signals
+    }
+
+    pub fn get_delays(&self) -> Option<&DelayReport> {
+        self.delays.as_ref()
+    }
+
+    pub fn get_history(&self) -> Option<&ValueHistory> {
+        self.history.as_ref()
+    }
+
+    pub fn get_menu(&self) -> Option<&ContextMenu> {
+        self.menu.as_ref()
+    }
+
+    pub fn get_form(&self) -> Option<&Form> {
+        self.form.as_ref()
+    }
+
+    pub fn get_python_output(&self) -> Option<&PythonOutput> {
+        self.python_output.as_ref()
+    }
+
+    pub fn get_python_error(&self) -> Option<&PythonError> {
+        self.python_error.as_ref()
+    }
+
+    pub fn get_recent(&self) -> &Vec<RecentFile> {
+        &self.recent
+    }
+
+    pub fn get_overlay_index(&self) -> usize {
+        self.overlay_index
+    }
+
+    pub fn get_timescale(&self) -> i32 {
+        self.vcd_header.get_timescale().unwrap_or(0)
+    }
+
+    pub fn get_diagnostics(&self) -> &LoadDiagnostics {
+        &self.vcd_diagnostics
+    }
+
+    pub fn is_hud_enabled(&self) -> bool {
+        self.hud_enabled
+    }
+
+    /// If ctrl+l asked for the screen to be drawn again since the last call
+    pub fn take_refresh(&mut self) -> bool {
+        std::mem::take(&mut self.refresh)
+    }
+
+    pub fn is_hints_enabled(&self) -> bool {
+        self.hints_enabled
+    }
+
+    pub fn set_hints_enabled(&mut self, hints_enabled: bool) {
+        self.hints_enabled = hints_enabled;
+    }
+
+    pub fn is_mouse_enabled(&self) -> bool {
+        self.mouse_enabled
+    }
+
+    pub fn set_mouse_enabled(&mut self, mouse_enabled: bool) {
+        self.mouse_enabled = mouse_enabled;
+    }
+
+    /// Sets how quickly holding down a zoom or scroll key speeds it up
+    pub fn set_zoom_acceleration(&mut self, acceleration: f64) {
+        self.messages
+            .push(WaveformViewerMessage::SetAcceleration(acceleration));
+    }
+
+    /// Leaves the recent files list and the recovery file alone when disabled,
+    /// for sessions that are not run by a user
+    pub fn set_persistent(&mut self, persistent: bool) {
+        self.persistent = persistent;
+        if !persistent {
+            self.recovery = None;
+            if self.overlay == NaluOverlay::Recovery {
+                self.overlay = NaluOverlay::Recent;
+            }
+        }
+    }
+
+    /// The loaders files are matched against, where more formats can be
+    /// registered before the first load
+    pub fn get_loaders_mut(&mut self) -> &mut LoaderRegistry {
+        &mut self.loaders
+    }
+
+    pub fn get_recovery(&self) -> Option<&Recovery> {
+        self.recovery.as_ref()
+    }
+
+    /// If a file is still being loaded (or reloaded after failing)
+    pub fn is_loading(&self) -> bool {
+        self.vcd_handle.is_some()
+    }
+
+    pub fn get_frame_statistics(&self) -> &FrameStatistics {
+        &self.frame_statistics
+    }
+
+    pub fn set_frame_statistics(&mut self, frame_statistics: FrameStatistics) {
+        self.frame_statistics = frame_statistics;
+    }
+
     pub fn get_done(&self) -> Option<String> {
         self.done.clone()
     }
@@ -0,0 +1,96 @@
+use makai_vcd_reader::parser::{VcdHeader, VcdScope};
+
+fn collect_variable_names(scope: &VcdScope, prefix: &str, names: &mut Vec<String>) {
+    let prefix = format!("{prefix}{}.", scope.get_name());
+    for variable in scope.get_variables() {
+        names.push(format!("{prefix}{}", variable.get_name()));
+    }
+    for scope in scope.get_scopes() {
+        collect_variable_names(scope, &prefix, names);
+    }
+}
+
+/// Full names of every variable in the dump, sorted and without duplicates
+pub fn get_variable_names(header: &VcdHeader) -> Vec<String> {
+    let mut names = Vec::new();
+    for scope in header.get_scopes() {
+        collect_variable_names(scope, "", &mut names);
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Completes a path as far as all of the (sorted) names starting with it
+/// agree, one level of hierarchy at a time like completing paths in a shell.
+/// Returns the completed path and the possible next levels when there is more
+/// than one.
+pub fn complete_path(names: &[String], input: &str) -> (String, Vec<String>) {
+    let mut levels = names
+        .iter()
+        .filter(|name| name.starts_with(input))
+        .map(|name| match name[input.len()..].find('.') {
+            // Scopes keep the dot so completing again goes inside of them
+            Some(dot) => &name[..input.len() + dot + 1],
+            None => name.as_str(),
+        })
+        .collect::<Vec<&str>>();
+    levels.dedup();
+    let Some(first) = levels.first() else {
+        return (String::from(input), Vec::new());
+    };
+    let mut common = first.len();
+    for level in &levels[1..] {
+        common = first
+            .char_indices()
+            .zip(level.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(common);
+    }
+    let candidates = match levels.len() {
+        1 => Vec::new(),
+        _ => levels.iter().map(|level| String::from(*level)).collect(),
+    };
+    (String::from(&first[..common]), candidates)
+}
+
+#[test]
+fn complete_path_test() {
+    let names = [
+        "TOP.clk",
+        "TOP.core.pc",
+        "TOP.core.pc_next",
+        "TOP.core.valid",
+        "TOP.cpu_reset",
+    ]
+    .map(String::from);
+    assert_eq!(complete_path(&names, "T"), (String::from("TOP."), vec![]));
+    assert_eq!(
+        complete_path(&names, "TOP.c"),
+        (
+            String::from("TOP.c"),
+            ["TOP.clk", "TOP.core.", "TOP.cpu_reset"]
+                .map(String::from)
+                .to_vec()
+        )
+    );
+    assert_eq!(
+        complete_path(&names, "TOP.co"),
+        (String::from("TOP.core."), vec![])
+    );
+    assert_eq!(
+        complete_path(&names, "TOP.core.p"),
+        (
+            String::from("TOP.core.pc"),
+            ["TOP.core.pc", "TOP.core.pc_next"]
+                .map(String::from)
+                .to_vec()
+        )
+    );
+    assert_eq!(
+        complete_path(&names, "TOP.x"),
+        (String::from("TOP.x"), vec![])
+    );
+}
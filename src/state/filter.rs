@@ -40,3 +40,369 @@ pub fn construct_filter(filter: String) -> Vec<BrowserFilterSection> {
     }
     filters
 }
+
+fn matches_section(section: &BrowserFilterSection, name: &str) -> bool {
+    match section {
+        BrowserFilterSection::Wildcard | BrowserFilterSection::WildcardDouble => true,
+        BrowserFilterSection::WildcardBefore(s) => name.ends_with(s.as_str()),
+        BrowserFilterSection::WildcardAfter(s) => name.starts_with(s.as_str()),
+        BrowserFilterSection::WildcardBoth(s) => name.contains(s.as_str()),
+        BrowserFilterSection::Match(s) => name == s,
+    }
+}
+
+fn matches_glob(sections: &[BrowserFilterSection], path: &[String]) -> bool {
+    match (sections.first(), path.first()) {
+        (None, None) => true,
+        (Some(BrowserFilterSection::WildcardDouble), _) => {
+            // Match any number of hierarchy levels, including none
+            matches_glob(&sections[1..], path)
+                || (!path.is_empty() && matches_glob(sections, &path[1..]))
+        }
+        (Some(section), Some(name)) => {
+            matches_section(section, name) && matches_glob(&sections[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Scores how well the query matches as a subsequence of the text, favoring
+/// consecutive characters and the starts of names
+fn score_fuzzy(query: &str, text: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut text = text.chars().map(|c| c.to_ascii_lowercase());
+    // The character before the current one and whether it was matched
+    let mut previous = None;
+    let mut matched = false;
+    for q in query.chars().map(|c| c.to_ascii_lowercase()) {
+        let mut gap = 0;
+        loop {
+            let c = text.next()?;
+            if c == q {
+                score += 1;
+                if matched && gap == 0 {
+                    score += 5;
+                }
+                if matches!(previous, None | Some('.') | Some('_')) {
+                    score += 10;
+                }
+                score -= gap.min(5);
+                previous = Some(c);
+                matched = true;
+                break;
+            }
+            previous = Some(c);
+            matched = false;
+            gap += 1;
+        }
+    }
+    Some(score)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    Substring,
+    CaseInsensitive,
+    Glob,
+    Fuzzy,
+}
+
+impl FilterMode {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Substring => Self::CaseInsensitive,
+            Self::CaseInsensitive => Self::Glob,
+            Self::Glob => Self::Fuzzy,
+            Self::Fuzzy => Self::Substring,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Substring => "substring",
+            Self::CaseInsensitive => "case-insensitive",
+            Self::Glob => "glob",
+            Self::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+/// How the netlist shows the result of a filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterDisplay {
+    /// Only the matching variables and the scopes leading to them
+    Prune,
+    /// The whole tree with the matches highlighted and their scopes expanded
+    Highlight,
+}
+
+impl FilterDisplay {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Prune => Self::Highlight,
+            Self::Highlight => Self::Prune,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Prune => "only matches",
+            Self::Highlight => "highlight",
+        }
+    }
+}
+
+/// How a bit width or scope depth is compared in a filter like `width>=8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn compare(&self, value: usize, against: usize) -> bool {
+        match self {
+            Self::Less => value < against,
+            Self::LessEqual => value <= against,
+            Self::Equal => value == against,
+            Self::GreaterEqual => value >= against,
+            Self::Greater => value > against,
+        }
+    }
+}
+
+fn parse_comparison(text: &str) -> Option<(Comparison, usize)> {
+    let (comparison, value) = [
+        (">=", Comparison::GreaterEqual),
+        ("<=", Comparison::LessEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+        (":", Comparison::Equal),
+    ]
+    .into_iter()
+    .find_map(|(op, comparison)| Some((comparison, text.strip_prefix(op)?)))?;
+    Some((comparison, value.trim().parse().ok()?))
+}
+
+/// Everything about a variable other than its name that a filter can check
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FilterAttributes<'a> {
+    pub width: usize,
+    /// Like "reg" or "wire", empty when not known
+    pub net_type: &'a str,
+}
+
+/// One of the terms a filter expression is made of
+enum FilterTerm {
+    Name(FilterMode, String, Vec<BrowserFilterSection>),
+    Width(Comparison, usize),
+    /// Scopes above the variable, where a top level variable has none
+    Depth(Comparison, usize),
+    /// Any of these net types
+    Type(Vec<String>),
+}
+
+impl FilterTerm {
+    /// Parses a predicate like `width>1` or `type:reg,wire`, and otherwise a
+    /// name in the mode given by its prefix (s:, i:, g: or f:) or the selected
+    /// mode
+    fn new(term: &str, mode: FilterMode) -> Self {
+        if let Some((comparison, width)) = term.strip_prefix("width").and_then(parse_comparison) {
+            return Self::Width(comparison, width);
+        }
+        if let Some((comparison, depth)) = term.strip_prefix("depth").and_then(parse_comparison) {
+            return Self::Depth(comparison, depth);
+        }
+        match term.strip_prefix("type:") {
+            Some(types) if !types.is_empty() => {
+                return Self::Type(types.split(',').map(String::from).collect())
+            }
+            _ => {}
+        }
+        let (mode, query) = match term.split_once(':') {
+            Some(("s", query)) => (FilterMode::Substring, query),
+            Some(("i", query)) => (FilterMode::CaseInsensitive, query),
+            Some(("g", query)) => (FilterMode::Glob, query),
+            Some(("f", query)) => (FilterMode::Fuzzy, query),
+            _ => (mode, term),
+        };
+        Self::Name(
+            mode,
+            String::from(query),
+            construct_filter(String::from(query)),
+        )
+    }
+
+    fn score_name(
+        mode: FilterMode,
+        query: &str,
+        sections: &[BrowserFilterSection],
+        path: &[String],
+    ) -> Option<i64> {
+        let full_name = path.join(".");
+        match mode {
+            FilterMode::Substring => full_name.contains(query).then_some(0),
+            FilterMode::CaseInsensitive => full_name
+                .to_lowercase()
+                .contains(&query.to_lowercase())
+                .then_some(0),
+            // A pattern without any hierarchy only has to match the name
+            FilterMode::Glob if sections.len() == 1 => path
+                .last()
+                .is_some_and(|name| matches_section(&sections[0], name))
+                .then_some(0),
+            FilterMode::Glob => matches_glob(sections, path).then_some(0),
+            FilterMode::Fuzzy => score_fuzzy(query, &full_name),
+        }
+    }
+}
+
+/// A filter over the full hierarchical names of variables, which can also be
+/// an expression of several terms joined by `&` that all have to match (or
+/// not match when starting with `!`), like `width>1 & type:reg & g:**.state`
+pub struct NetlistFilter {
+    mode: FilterMode,
+    terms: Vec<(bool, FilterTerm)>,
+}
+
+impl NetlistFilter {
+    /// Creates a filter from the filter input, where a prefix (s:, i:, g: or
+    /// f:) overrides the selected mode. An empty query filters nothing.
+    pub fn new(input: &str, mode: FilterMode) -> Option<Self> {
+        let terms = input
+            .split('&')
+            .map(str::trim)
+            .filter(|term| !term.is_empty() && *term != "!")
+            .map(|term| match term.strip_prefix('!') {
+                Some(term) => (true, FilterTerm::new(term.trim_start(), mode)),
+                None => (false, FilterTerm::new(term, mode)),
+            })
+            .collect::<Vec<(bool, FilterTerm)>>();
+        if terms.is_empty() {
+            return None;
+        }
+        // Ordering the matches best first only makes sense for fuzzy names
+        let fuzzy = terms
+            .iter()
+            .any(|term| matches!(term, (false, FilterTerm::Name(FilterMode::Fuzzy, _, _))));
+        let mode = match terms.iter().find_map(|term| match term {
+            (false, FilterTerm::Name(mode, _, _)) => Some(*mode),
+            _ => None,
+        }) {
+            _ if fuzzy => FilterMode::Fuzzy,
+            Some(mode) => mode,
+            None => mode,
+        };
+        Some(Self { mode, terms })
+    }
+
+    pub fn get_mode(&self) -> FilterMode {
+        self.mode
+    }
+
+    /// Returns a score for the path if the names in the filter match, higher
+    /// is a better match, without checking any of the other predicates
+    pub fn score(&self, path: &[String]) -> Option<i64> {
+        let mut score = 0;
+        for (negated, term) in &self.terms {
+            let FilterTerm::Name(mode, query, sections) = term else {
+                continue;
+            };
+            match (
+                FilterTerm::score_name(*mode, query, sections, path),
+                negated,
+            ) {
+                (Some(term_score), false) => score += term_score,
+                (None, true) => {}
+                _ => return None,
+            }
+        }
+        Some(score)
+    }
+
+    /// Returns a score like [`Self::score`] if the whole filter matches the
+    /// variable, including its width, net type and depth
+    pub fn score_variable(&self, path: &[String], attributes: &FilterAttributes) -> Option<i64> {
+        let depth = path.len().saturating_sub(1);
+        let matches = self.terms.iter().all(|(negated, term)| {
+            let matches = match term {
+                FilterTerm::Name(_, _, _) => return true,
+                FilterTerm::Width(comparison, width) => {
+                    comparison.compare(attributes.width, *width)
+                }
+                FilterTerm::Depth(comparison, against) => comparison.compare(depth, *against),
+                FilterTerm::Type(types) => types.iter().any(|t| t == attributes.net_type),
+            };
+            matches != *negated
+        });
+        if matches {
+            self.score(path)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn netlist_filter_test() {
+    let path = |s: &str| s.split('.').map(String::from).collect::<Vec<String>>();
+    let filter = |input: &str| NetlistFilter::new(input, FilterMode::Substring).unwrap();
+
+    assert!(NetlistFilter::new("", FilterMode::Fuzzy).is_none());
+    assert!(filter("core.data")
+        .score(&path("TOP.core.data_valid"))
+        .is_some());
+    assert!(filter("Core").score(&path("TOP.core.data")).is_none());
+    assert!(filter("i:Core").score(&path("TOP.core.data")).is_some());
+
+    assert!(filter("g:*_valid")
+        .score(&path("TOP.core.data_valid"))
+        .is_some());
+    assert!(filter("g:TOP.*.data*")
+        .score(&path("TOP.core.data_valid"))
+        .is_some());
+    assert!(filter("g:TOP.*.data*")
+        .score(&path("TOP.a.core.data"))
+        .is_none());
+    assert!(filter("g:TOP.**.data")
+        .score(&path("TOP.a.core.data"))
+        .is_some());
+
+    let fuzzy = filter("f:cdv");
+    assert_eq!(fuzzy.get_mode(), FilterMode::Fuzzy);
+    assert!(fuzzy.score(&path("TOP.core.x")).is_none());
+    let tight = fuzzy.score(&path("TOP.core.data_valid")).unwrap();
+    let loose = fuzzy.score(&path("TOP.cache.drive_level")).unwrap();
+    assert!(tight >= loose);
+
+    let reg = FilterAttributes {
+        width: 4,
+        net_type: "reg",
+    };
+    let wire = FilterAttributes {
+        width: 1,
+        net_type: "wire",
+    };
+    let state = path("TOP.core.fsm.state");
+    let expression = filter("width>1 & type:reg & g:**.core.*.state");
+    assert!(expression.score_variable(&state, &reg).is_some());
+    assert!(expression.score_variable(&state, &wire).is_none());
+    assert!(expression
+        .score_variable(&path("TOP.core.state"), &reg)
+        .is_none());
+    assert!(filter("!type:wire & depth<=2")
+        .score_variable(&path("TOP.core.data"), &reg)
+        .is_some());
+    assert!(filter("depth=3 & state")
+        .score_variable(&state, &wire)
+        .is_some());
+    assert!(filter("width:4 & !state")
+        .score_variable(&state, &reg)
+        .is_none());
+    assert!(filter("state & ").score(&state).is_some());
+    assert!(NetlistFilter::new(" & ", FilterMode::Glob).is_none());
+}
@@ -0,0 +1,93 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseEventKind};
+use makai::utils::messages::Messages;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Paragraph, Widget},
+};
+use tui_tiling::component::ComponentWidget;
+
+use crate::state::{
+    filter::{FilterDisplay, FilterMode},
+    netlist_viewer::NetlistViewerMessage,
+};
+
+pub struct FilterViewerState {
+    input: String,
+    mode: FilterMode,
+    display: FilterDisplay,
+    messages: Messages,
+}
+
+impl FilterViewerState {
+    pub fn new(messages: Messages) -> Self {
+        Self {
+            input: String::new(),
+            mode: FilterMode::Substring,
+            display: FilterDisplay::Prune,
+            messages,
+        }
+    }
+
+    fn update_filter(&mut self) {
+        self.messages.push(NetlistViewerMessage::UpdateFilter(
+            self.input.clone(),
+            self.mode,
+            self.display,
+        ));
+    }
+}
+
+impl ComponentWidget for FilterViewerState {
+    fn handle_mouse(&mut self, _x: u16, _y: u16, _kind: MouseEventKind) -> bool {
+        false
+    }
+
+    fn handle_key(&mut self, e: KeyEvent) -> bool {
+        match e.code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Delete => self.input.clear(),
+            KeyCode::Tab => self.mode = self.mode.next(),
+            KeyCode::BackTab => self.display = self.display.next(),
+            _ => return false,
+        }
+        self.update_filter();
+        true
+    }
+
+    fn handle_update(&mut self) -> bool {
+        false
+    }
+
+    fn resize(&mut self, _width: u16, _height: u16) {}
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(Spans::from(vec![
+            Span::styled(
+                match self.display {
+                    FilterDisplay::Prune => format!("[{}] ", self.mode.get_name()),
+                    display => format!("[{}, {}] ", self.mode.get_name(), display.get_name()),
+                },
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(
+                format!("> {}", self.input),
+                Style::default().fg(Color::LightCyan),
+            ),
+        ]))
+        .render(area, buf);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
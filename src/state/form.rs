@@ -0,0 +1,197 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// One field of a form, moved between with tab and the arrow keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormField {
+    /// A line of text typed in after its label
+    Text(String, String),
+    /// One of a few options after its label, picked with left and right
+    Choice(String, Vec<String>, usize),
+    /// Submits the form, with consecutive buttons drawn in one row
+    Button(String),
+}
+
+/// What a form is for, which decides where its values go once submitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormKind {
+    GroupName,
+    Rename,
+    GotoTime,
+}
+
+/// How a key ended a form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormOutcome {
+    /// The label of the button pressed, or of the first button when Enter was
+    /// pressed in another field
+    Submit(String),
+    Cancel,
+}
+
+/// A prompt with a few fields, where Enter submits and Esc cancels from
+/// anywhere in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Form {
+    pub title: String,
+    pub kind: FormKind,
+    pub fields: Vec<FormField>,
+    pub focus: usize,
+    /// Why the form could not be submitted, drawn below the fields
+    pub message: String,
+}
+
+impl Form {
+    pub fn new(title: &str, kind: FormKind, fields: Vec<FormField>) -> Self {
+        Self {
+            title: String::from(title),
+            kind,
+            fields,
+            focus: 0,
+            message: String::new(),
+        }
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let len = self.fields.len().max(1);
+        self.focus = match forward {
+            true => (self.focus + 1) % len,
+            false => (self.focus + len - 1) % len,
+        };
+    }
+
+    fn get_default_button(&self) -> Option<String> {
+        self.fields.iter().find_map(|field| match field {
+            FormField::Button(label) => Some(label.clone()),
+            _ => None,
+        })
+    }
+
+    /// Handles a key while the form is open, returning how it ended when the
+    /// key submitted or cancelled it. When a button is focused the first
+    /// letter of any button presses it, like answering `[y/n]`.
+    pub fn handle_key(&mut self, event: KeyEvent) -> Option<FormOutcome> {
+        let shift = event.modifiers.contains(KeyModifiers::SHIFT);
+        match (event.code, self.fields.get_mut(self.focus)) {
+            (KeyCode::Esc, _) => return Some(FormOutcome::Cancel),
+            (KeyCode::Tab, _) if shift => self.move_focus(false),
+            (KeyCode::Tab | KeyCode::Down, _) => self.move_focus(true),
+            (KeyCode::BackTab | KeyCode::Up, _) => self.move_focus(false),
+            (KeyCode::Enter, Some(FormField::Button(label))) => {
+                return Some(FormOutcome::Submit(label.clone()));
+            }
+            (KeyCode::Enter, _) => return self.get_default_button().map(FormOutcome::Submit),
+            (KeyCode::Backspace, Some(FormField::Text(_, value))) => {
+                value.pop();
+            }
+            (KeyCode::Char(c), Some(FormField::Text(_, value))) => value.push(c),
+            (KeyCode::Left, Some(FormField::Choice(_, options, selected))) => {
+                *selected = (*selected + options.len() - 1) % options.len().max(1);
+            }
+            (KeyCode::Right, Some(FormField::Choice(_, options, selected))) => {
+                *selected = (*selected + 1) % options.len().max(1);
+            }
+            (code @ (KeyCode::Left | KeyCode::Right), Some(FormField::Button(_))) => {
+                self.move_focus(code == KeyCode::Right)
+            }
+            (KeyCode::Char(c), Some(FormField::Button(_))) => {
+                return self
+                    .fields
+                    .iter()
+                    .find_map(|field| match field {
+                        FormField::Button(label) if label.starts_with(c.to_ascii_uppercase()) => {
+                            Some(label.clone())
+                        }
+                        FormField::Button(label) if label.starts_with(c) => Some(label.clone()),
+                        _ => None,
+                    })
+                    .map(FormOutcome::Submit);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// The text typed into the field with this label
+    pub fn get_text(&self, label: &str) -> Option<&str> {
+        self.fields.iter().find_map(|field| match field {
+            FormField::Text(l, value) if l == label => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The index of the option picked in the field with this label
+    pub fn get_choice(&self, label: &str) -> Option<usize> {
+        self.fields.iter().find_map(|field| match field {
+            FormField::Choice(l, _, selected) if l == label => Some(*selected),
+            _ => None,
+        })
+    }
+
+    /// Rows needed to draw the fields, with consecutive buttons sharing one
+    pub fn get_height(&self) -> usize {
+        let mut rows = 0;
+        let mut last_button = false;
+        for field in &self.fields {
+            let is_button = matches!(field, FormField::Button(_));
+            if !(is_button && last_button) {
+                rows += 1;
+            }
+            last_button = is_button;
+        }
+        rows
+    }
+}
+
+#[test]
+fn form_test() {
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    let mut form = Form::new(
+        "Overwrite",
+        FormKind::GroupName,
+        vec![
+            FormField::Text(String::from("Name"), String::from("ab")),
+            FormField::Choice(
+                String::from("Mode"),
+                vec![String::from("a"), String::from("b")],
+                0,
+            ),
+            FormField::Button(String::from("Yes")),
+            FormField::Button(String::from("No")),
+            FormField::Button(String::from("force")),
+        ],
+    );
+    assert_eq!(form.get_height(), 3);
+    assert_eq!(form.handle_key(key(KeyCode::Backspace)), None);
+    assert_eq!(form.handle_key(key(KeyCode::Char('n'))), None);
+    assert_eq!(form.get_text("Name"), Some("an"));
+    form.handle_key(key(KeyCode::Tab));
+    form.handle_key(key(KeyCode::Left));
+    assert_eq!(form.get_choice("Mode"), Some(1));
+    form.handle_key(key(KeyCode::Right));
+    assert_eq!(form.get_choice("Mode"), Some(0));
+    // Enter outside of a button presses the first one
+    assert_eq!(
+        form.handle_key(key(KeyCode::Enter)),
+        Some(FormOutcome::Submit(String::from("Yes")))
+    );
+    form.handle_key(key(KeyCode::Tab));
+    form.handle_key(key(KeyCode::Right));
+    assert_eq!(
+        form.handle_key(key(KeyCode::Enter)),
+        Some(FormOutcome::Submit(String::from("No")))
+    );
+    assert_eq!(
+        form.handle_key(key(KeyCode::Char('f'))),
+        Some(FormOutcome::Submit(String::from("force")))
+    );
+    // Tabbing past the end wraps around to the first field
+    form.handle_key(key(KeyCode::Tab));
+    form.handle_key(key(KeyCode::Tab));
+    assert_eq!(form.focus, 0);
+    form.handle_key(key(KeyCode::BackTab));
+    assert_eq!(form.focus, 4);
+    assert_eq!(
+        form.handle_key(key(KeyCode::Esc)),
+        Some(FormOutcome::Cancel)
+    );
+}
@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyEvent, MouseEventKind};
+use makai::utils::messages::Messages;
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    widgets::{Paragraph, Widget},
+};
+use tui_tiling::component::ComponentWidget;
+
+use crate::widgets::timescale::render_time;
+
+pub(crate) enum HeaderMessage {
+    SetFile(Option<String>),
+    // The visible range and the range of the whole dump, in its timescale
+    SetRange(Range<u64>, Range<u64>, i32),
+    // If the end of the dump is followed, and if so whether it is paused
+    UpdateFollow(Option<bool>),
+    // How many signals in the viewer are there more than once
+    SetDuplicates(usize),
+}
+
+/// Renders the visible range as part of the whole dump, with the times rounded
+/// to a few digits more than the width of each range, like
+/// "12.5us..13.1us of 2.0ms (30.0%)"
+pub fn render_zoom(visible: &Range<u64>, full: &Range<u64>, timescale: i32) -> String {
+    let width = visible.end.saturating_sub(visible.start);
+    let full_width = full.end.saturating_sub(full.start);
+    let resolution = (width / 100).max(1);
+    let full_resolution = (full_width / 100).max(1);
+    let full_text = if full.start == 0 {
+        render_time(full.end, full_resolution, timescale)
+    } else {
+        format!(
+            "{}..{}",
+            render_time(full.start, full_resolution, timescale),
+            render_time(full.end, full_resolution, timescale)
+        )
+    };
+    let percent = width as f64 * 100.0 / full_width.max(1) as f64;
+    let percent = if percent >= 1.0 {
+        format!("{percent:.1}%")
+    } else if percent >= 0.01 {
+        format!("{percent:.2}%")
+    } else {
+        String::from("<0.01%")
+    };
+    format!(
+        "{}..{} of {} ({})",
+        render_time(visible.start, resolution, timescale),
+        render_time(visible.end, resolution, timescale),
+        full_text,
+        percent
+    )
+}
+
+/// The line at the top of the screen with the key hints, followed by the open
+/// file and what part of it is in view
+pub struct HeaderState {
+    title: String,
+    file: Option<String>,
+    range: Option<(Range<u64>, Range<u64>, i32)>,
+    follow: Option<bool>,
+    duplicates: usize,
+    messages: Messages,
+}
+
+impl HeaderState {
+    pub fn new(title: String, messages: Messages) -> Self {
+        Self {
+            title,
+            file: None,
+            range: None,
+            follow: None,
+            duplicates: 0,
+            messages,
+        }
+    }
+
+    fn get_status(&self) -> String {
+        let zoom = match &self.range {
+            Some((visible, full, timescale)) if full.start < full.end => {
+                Some(render_zoom(visible, full, *timescale))
+            }
+            _ => None,
+        };
+        let status = match (&self.file, zoom) {
+            (Some(file), Some(zoom)) => format!("{file}  {zoom}"),
+            (Some(file), None) => file.clone(),
+            (None, Some(zoom)) => zoom,
+            (None, None) => String::new(),
+        };
+        let status = match self.follow {
+            Some(false) => format!("following  {status}"),
+            Some(true) => format!("paused  {status}"),
+            None => status,
+        };
+        match self.duplicates {
+            0 => status,
+            1 => format!("1 duplicate signal ('dedup')  {status}"),
+            n => format!("{n} duplicate signals ('dedup')  {status}"),
+        }
+    }
+}
+
+impl ComponentWidget for HeaderState {
+    fn handle_mouse(&mut self, _x: u16, _y: u16, _kind: MouseEventKind) -> bool {
+        false
+    }
+
+    fn handle_key(&mut self, _e: KeyEvent) -> bool {
+        false
+    }
+
+    fn handle_update(&mut self) -> bool {
+        let mut updated = false;
+        for message in self.messages.get::<HeaderMessage>() {
+            match message {
+                HeaderMessage::SetFile(file) => self.file = file,
+                HeaderMessage::SetRange(visible, full, timescale) => {
+                    self.range = Some((visible, full, timescale))
+                }
+                HeaderMessage::UpdateFollow(follow) => self.follow = follow,
+                HeaderMessage::SetDuplicates(duplicates) => self.duplicates = duplicates,
+            }
+            updated = true;
+        }
+        updated
+    }
+
+    fn resize(&mut self, _width: u16, _height: u16) {}
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        // The status wins over the key hints when there is not room for both
+        let status = self.get_status();
+        let status_width = (status.chars().count() as u16).min(area.width);
+        let title_area = Rect {
+            width: area.width.saturating_sub(status_width + 1),
+            ..area
+        };
+        Paragraph::new(self.title.as_str())
+            .style(Style::default().fg(Color::LightCyan))
+            .render(title_area, buf);
+        Paragraph::new(status)
+            .style(Style::default().fg(Color::LightCyan))
+            .alignment(Alignment::Right)
+            .render(area, buf);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[test]
+fn render_zoom_test() {
+    assert_eq!(
+        render_zoom(&(12500..13100), &(0..2000000), 9),
+        "12.5us..13.1us of 2.0ms (0.03%)"
+    );
+    assert_eq!(
+        render_zoom(&(0..1000), &(0..1000), 9),
+        "0ns..1.0us of 1.0us (100.0%)"
+    );
+    assert_eq!(
+        render_zoom(&(250..500), &(100..1100), 9),
+        "250.0ns..500.0ns of 100ns..1.1us (25.0%)"
+    );
+}
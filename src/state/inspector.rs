@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crossterm::event::{KeyEvent, MouseEventKind};
+use makai::utils::messages::Messages;
+use makai_waveform_db::{
+    bitvector::BitVectorRadix, Waveform, WaveformSearchMode, WaveformValueResult,
+};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::Text,
+    widgets::{Paragraph, Widget},
+};
+use tui_tiling::component::ComponentWidget;
+
+use crate::{
+    analysis::{find_surrounding_changes, render_frequency, ClockInfo, PeriodStatistics},
+    loader::decode_real,
+    source::WaveformSource,
+    widgets::timescale::render_time,
+};
+
+/// The signal selected in the signal viewer
+#[derive(Debug, Clone)]
+pub struct InspectorSignal {
+    pub name: String,
+    pub idcode: usize,
+    pub index: Option<usize>,
+    pub radix: BitVectorRadix,
+    pub is_real: bool,
+}
+
+/// Renders the value of a signal (or one bit of it) at the timestamp, if it
+/// has one by then
+pub fn render_value<W: WaveformSource + ?Sized>(
+    waveform: &W,
+    idcode: usize,
+    index: Option<usize>,
+    radix: BitVectorRadix,
+    is_real: bool,
+    timestamp: u64,
+) -> Option<String> {
+    let value = waveform
+        .search_timestamp(timestamp, WaveformSearchMode::Before)
+        .and_then(|timestamp_index| {
+            waveform.search_value_bit_index(
+                idcode,
+                timestamp_index,
+                WaveformSearchMode::Before,
+                index,
+            )
+        })?;
+    Some(match value {
+        WaveformValueResult::Vector(bv, _) if is_real => format!("{}", decode_real(&bv)),
+        WaveformValueResult::Vector(bv, _) => bv.to_string_radix(radix),
+        WaveformValueResult::Real(value, _) => format!("{value}"),
+    })
+}
+
+pub(crate) enum InspectorMessage {
+    UpdateWaveform(Arc<Waveform>, i32),
+    SetClocks(Arc<HashMap<usize, ClockInfo>>),
+    SetCursor(u64),
+    Select(Option<InspectorSignal>),
+    // Periods measured of a signal (or one bit of it) over the range in view
+    SetPeriods(usize, Option<usize>, PeriodStatistics),
+}
+
+/// Shows details about the selected signal at the cursor
+pub struct InspectorState {
+    waveform: Arc<Waveform>,
+    timescale: i32,
+    clocks: Arc<HashMap<usize, ClockInfo>>,
+    cursor: u64,
+    selected: Option<InspectorSignal>,
+    // The last periods measured, shown while their signal is selected
+    periods: Option<(usize, Option<usize>, PeriodStatistics)>,
+    messages: Messages,
+}
+
+impl InspectorState {
+    pub fn new(messages: Messages) -> Self {
+        Self {
+            waveform: Arc::new(Waveform::default()),
+            timescale: 0,
+            clocks: Arc::new(HashMap::new()),
+            cursor: 0,
+            selected: None,
+            periods: None,
+            messages,
+        }
+    }
+
+    fn get_value(&self, signal: &InspectorSignal) -> String {
+        if !self.waveform.has_history(signal.idcode) {
+            return String::from("(not loaded)");
+        }
+        render_value(
+            &self.waveform,
+            signal.idcode,
+            signal.index,
+            signal.radix,
+            signal.is_real,
+            self.cursor,
+        )
+        .unwrap_or(String::from("(no value)"))
+    }
+
+    /// How long the value at the cursor has held and how long until it changes
+    fn get_changes(&self, signal: &InspectorSignal) -> Option<String> {
+        let (last, next) =
+            find_surrounding_changes(&*self.waveform, signal.idcode, signal.index, self.cursor)?;
+        let last = match last.map(|last| self.cursor - last) {
+            None => String::from("Unchanged since start"),
+            Some(0) => String::from("Changed at cursor"),
+            Some(ago) => format!("Changed {} ago", render_time(ago, 1, self.timescale)),
+        };
+        Some(match next {
+            Some(next) => format!(
+                "{last}, next in {}",
+                render_time(next - self.cursor, 1, self.timescale)
+            ),
+            None => format!("{last}, no change after"),
+        })
+    }
+
+    fn get_text(&self) -> Text<'_> {
+        let Some(signal) = &self.selected else {
+            return Text::raw("No signal selected");
+        };
+        let mut text = Text::raw(format!("{} = {}", signal.name, self.get_value(signal)));
+        if let Some(changes) = self.get_changes(signal) {
+            text.extend(Text::raw(changes));
+        }
+        if let Some(clock) = self.clocks.get(&signal.idcode) {
+            text.extend(Text::raw(format!(
+                "Clock: {} period ({})",
+                render_time(clock.period, 1, self.timescale),
+                render_frequency(clock.period, self.timescale)
+            )));
+        }
+        match &self.periods {
+            Some((idcode, index, periods))
+                if *idcode == signal.idcode && *index == signal.index =>
+            {
+                let render = |period| render_time(period, 1, self.timescale);
+                text.extend(Text::raw(format!(
+                    "Periods in view: {} from {} to {}, mean {} ({})",
+                    periods.count,
+                    render(periods.min.0),
+                    render(periods.max.0),
+                    render(periods.mean),
+                    render_frequency(periods.mean, self.timescale)
+                )));
+            }
+            _ => {}
+        }
+        text
+    }
+}
+
+impl ComponentWidget for InspectorState {
+    fn handle_mouse(&mut self, _x: u16, _y: u16, _kind: MouseEventKind) -> bool {
+        false
+    }
+
+    fn handle_key(&mut self, _e: KeyEvent) -> bool {
+        false
+    }
+
+    fn handle_update(&mut self) -> bool {
+        let mut updated = false;
+        for message in self.messages.get::<InspectorMessage>() {
+            match message {
+                InspectorMessage::UpdateWaveform(waveform, timescale) => {
+                    self.waveform = waveform;
+                    self.timescale = timescale;
+                    self.periods = None;
+                }
+                InspectorMessage::SetClocks(clocks) => self.clocks = clocks,
+                InspectorMessage::SetCursor(cursor) => self.cursor = cursor,
+                InspectorMessage::Select(selected) => self.selected = selected,
+                InspectorMessage::SetPeriods(idcode, index, periods) => {
+                    self.periods = Some((idcode, index, periods))
+                }
+            }
+            updated = true;
+        }
+        updated
+    }
+
+    fn resize(&mut self, _width: u16, _height: u16) {}
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.get_text())
+            .style(Style::default().fg(Color::LightCyan))
+            .render(area, buf);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
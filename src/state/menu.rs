@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use makai_waveform_db::bitvector::BitVectorRadix;
+
+/// Actions offered in the context menus of the netlist and signal viewer,
+/// applied to the entry selected when the menu was opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Append,
+    Insert,
+    Expand,
+    ExpandAll,
+    CollapseAll,
+    Radix(BitVectorRadix),
+    Pin,
+    Delete,
+    CopyPath,
+    Rename,
+    // Puts the bits synthesis blasted out of a vector back together
+    CombineBits,
+}
+
+impl MenuAction {
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            Self::Append => "Append to signals",
+            Self::Insert => "Insert after selected signal",
+            Self::Expand => "Expand/collapse",
+            Self::ExpandAll => "Expand everything below",
+            Self::CollapseAll => "Collapse everything below",
+            Self::Radix(BitVectorRadix::Binary) => "Show as binary",
+            Self::Radix(BitVectorRadix::Octal) => "Show as octal",
+            Self::Radix(BitVectorRadix::Decimal) => "Show as decimal",
+            Self::Radix(BitVectorRadix::Hexadecimal) => "Show as hexadecimal",
+            Self::Pin => "Pin/unpin",
+            Self::Delete => "Remove",
+            Self::CopyPath => "Copy full name",
+            Self::Rename => "Rename",
+            Self::CombineBits => "Combine bits into a vector",
+        }
+    }
+}
+
+/// The pane a context menu was opened from, which the action is sent back to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuTarget {
+    Netlist,
+    Signal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMenu {
+    pub title: String,
+    pub target: MenuTarget,
+    pub actions: Vec<MenuAction>,
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - i * 8)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Copies the text to the clipboard through the terminal with an OSC 52
+/// sequence, which also works over ssh but is ignored by some terminals
+pub fn copy_to_clipboard(text: &str) {
+    let mut stdout = std::io::stdout();
+    let result = write!(stdout, "\x1b]52;c;{}\x07", encode_base64(text.as_bytes()))
+        .and_then(|_| stdout.flush());
+    if let Err(err) = result {
+        log::error!("Clipboard Error: {err:?}");
+    }
+}
+
+#[test]
+fn base64_test() {
+    assert_eq!(encode_base64(b""), "");
+    assert_eq!(encode_base64(b"top.clk"), "dG9wLmNsaw==");
+    assert_eq!(encode_base64(b"top.data"), "dG9wLmRhdGE=");
+    assert_eq!(encode_base64(b"top"), "dG9w");
+}
@@ -1,8 +1,13 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use lazy_static::*;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
 use makai::utils::messages::Messages;
-use makai_vcd_reader::parser::{VcdScope, VcdVariable};
+use makai_vcd_reader::parser::{
+    VcdScope, VcdScopeType, VcdVariable, VcdVariableNetType, VcdVariableWidth,
+};
 use tui::widgets::Widget;
 use tui::{
     buffer::Buffer,
@@ -12,29 +17,46 @@ use tui::{
 use tui_tiling::component::ComponentWidget;
 
 use crate::{
-    state::filter::{construct_filter, BrowserFilterSection},
-    state::signal_viewer::SignalViewerMessage,
-    widgets::browser::{Browser, BrowserNode, BrowserState},
+    analysis::{find_bit_families, ClockInfo},
+    state::filter::{FilterAttributes, FilterDisplay, FilterMode, NetlistFilter},
+    state::menu::{copy_to_clipboard, ContextMenu, MenuAction, MenuTarget},
+    state::signal_viewer::{get_variable_icon, SignalViewerMessage, GROUP_ICON},
+    state::NaluMessage,
+    widgets::browser::{Browser, BrowserNode, BrowserNodePath, BrowserState, TypeAhead},
 };
 
 #[derive(Clone)]
 pub enum NetlistNode {
-    Scope(String),
+    Scope(String, VcdScopeType),
     Variable(VcdVariable),
 }
 
 impl std::fmt::Display for NetlistNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Scope(name) => write!(f, "{}", name),
+            Self::Scope(name, _) => write!(f, "{}", name),
             Self::Variable(variable) => write!(f, "{}", variable),
         }
     }
 }
 
+/// The order of the children of each scope in the netlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NetlistSort {
+    /// Alphanumerically with scopes before variables
+    #[default]
+    Name,
+    /// In the order they were declared in the dump
+    Declaration,
+    /// Alphanumerically with the widest variables first
+    Width,
+    /// Alphanumerically with variables before scopes
+    VariablesFirst,
+}
+
 impl Default for NetlistNode {
     fn default() -> Self {
-        Self::Scope(String::new())
+        Self::Scope(String::new(), VcdScopeType::Module)
     }
 }
 
@@ -57,48 +79,383 @@ fn search_nodes<'a>(
     &DEFAULT_NODE
 }
 
+fn get_scope_type_name(scope_type: &VcdScopeType) -> &'static str {
+    match scope_type {
+        VcdScopeType::Module => "module",
+        VcdScopeType::Task => "task",
+        VcdScopeType::Function => "function",
+        VcdScopeType::Begin => "begin",
+        VcdScopeType::Fork => "fork",
+        VcdScopeType::Struct => "struct",
+        VcdScopeType::Union => "union",
+        VcdScopeType::Interface => "interface",
+    }
+}
+
+fn get_net_type_name(net_type: &VcdVariableNetType) -> &'static str {
+    match net_type {
+        VcdVariableNetType::Event => "event",
+        VcdVariableNetType::Integer => "integer",
+        VcdVariableNetType::Parameter => "parameter",
+        VcdVariableNetType::Real => "real",
+        VcdVariableNetType::Realtime => "realtime",
+        VcdVariableNetType::Reg => "reg",
+        VcdVariableNetType::Supply0 => "supply0",
+        VcdVariableNetType::Supply1 => "supply1",
+        VcdVariableNetType::Time => "time",
+        VcdVariableNetType::Tri => "tri",
+        VcdVariableNetType::Triand => "triand",
+        VcdVariableNetType::Trior => "trior",
+        VcdVariableNetType::Trireg => "trireg",
+        VcdVariableNetType::Tri0 => "tri0",
+        VcdVariableNetType::Tri1 => "tri1",
+        VcdVariableNetType::Wand => "wand",
+        VcdVariableNetType::Wire => "wire",
+        VcdVariableNetType::Wor => "wor",
+    }
+}
+
+pub(crate) fn get_filter_attributes<'a>(
+    variable: &VcdVariable,
+    net_types: &'a HashMap<usize, VcdVariableNetType>,
+) -> FilterAttributes<'a> {
+    FilterAttributes {
+        width: variable.get_bit_width(),
+        net_type: net_types
+            .get(&variable.get_idcode())
+            .map(get_net_type_name)
+            .unwrap_or_default(),
+    }
+}
+
+/// Marks every scope that is not a module with its type, and every variable
+/// that looks like a clock
+fn get_node_suffix(
+    node: &NetlistNode,
+    clocks: &HashMap<usize, ClockInfo>,
+    net_types: &HashMap<usize, VcdVariableNetType>,
+) -> String {
+    match node {
+        NetlistNode::Scope(_, VcdScopeType::Module) => String::new(),
+        NetlistNode::Scope(_, scope_type) => format!("({})", get_scope_type_name(scope_type)),
+        NetlistNode::Variable(variable)
+            if matches!(
+                net_types.get(&variable.get_idcode()),
+                Some(VcdVariableNetType::Event)
+            ) =>
+        {
+            String::from("(event)")
+        }
+        NetlistNode::Variable(variable) if clocks.contains_key(&variable.get_idcode()) => {
+            String::from("⏰")
+        }
+        NetlistNode::Variable(_) => String::new(),
+    }
+}
+
+// Functions, tasks and generate/fork blocks are only kept when hiding blocks if
+// there is a module somewhere inside of them
+fn is_scope_visible(scope: &VcdScope, hide_blocks: bool) -> bool {
+    let is_block = matches!(
+        scope.get_type(),
+        VcdScopeType::Task | VcdScopeType::Function | VcdScopeType::Begin | VcdScopeType::Fork
+    );
+    !hide_blocks || !is_block || scope.get_scopes().iter().any(contains_module)
+}
+
+fn contains_module(scope: &VcdScope) -> bool {
+    *scope.get_type() == VcdScopeType::Module || scope.get_scopes().iter().any(contains_module)
+}
+
+fn sort_new_nodes(nodes: &mut [BrowserNode<NetlistNode>], sort: NetlistSort) {
+    match sort {
+        NetlistSort::Declaration => {}
+        NetlistSort::Width => nodes.sort_by(|a, b| {
+            let width = |node: &BrowserNode<NetlistNode>| match node.get_entry() {
+                Some(NetlistNode::Variable(variable)) => variable.get_bit_width(),
+                _ => 0,
+            };
+            width(b)
+                .cmp(&width(a))
+                .then_with(|| alphanumeric_sort::compare_str(a.to_string(), b.to_string()))
+        }),
+        NetlistSort::Name | NetlistSort::VariablesFirst => {
+            nodes.sort_by(|a, b| alphanumeric_sort::compare_str(a.to_string(), b.to_string()))
+        }
+    }
+}
+
+// Finds the scope with the full name in the header
+fn find_scope<'a>(scopes: &'a [VcdScope], full_name: &[String]) -> Option<&'a VcdScope> {
+    let (name, rest) = full_name.split_first()?;
+    let scope = scopes.iter().find(|s| s.get_name() == name)?;
+    match rest {
+        [] => Some(scope),
+        rest => find_scope(scope.get_scopes(), rest),
+    }
+}
+
+// Scopes that were never expanded only get their children once they are, see
+// generate_children, which keeps loading headers with hundreds of thousands of
+// variables quick
 fn generate_new_node(
     old_node: &BrowserNode<NetlistNode>,
     new_scope: &VcdScope,
+    hide_blocks: bool,
+    sort: NetlistSort,
 ) -> BrowserNode<NetlistNode> {
+    let entry = NetlistNode::Scope(new_scope.get_name().clone(), new_scope.get_type().clone());
+    if !old_node.is_expanded() && old_node.get_children().is_empty() {
+        let is_empty = new_scope.get_variables().is_empty()
+            && !new_scope
+                .get_scopes()
+                .iter()
+                .any(|s| is_scope_visible(s, hide_blocks));
+        return match is_empty {
+            true => BrowserNode::new(Some(entry)),
+            false => BrowserNode::deferred(Some(entry)),
+        };
+    }
+    let children = generate_children(old_node, new_scope, hide_blocks, sort);
+    BrowserNode::from_expanded(Some(entry), old_node.is_expanded(), children)
+}
+
+fn generate_children(
+    old_node: &BrowserNode<NetlistNode>,
+    new_scope: &VcdScope,
+    hide_blocks: bool,
+    sort: NetlistSort,
+) -> Vec<BrowserNode<NetlistNode>> {
     // Search through the old node's children for matches to the new scope children
     let mut new_scopes = new_scope
         .get_scopes()
         .iter()
+        .filter(|s| is_scope_visible(s, hide_blocks))
         .enumerate()
-        .map(|(i, s)| generate_new_node(search_nodes(old_node.get_children(), s.get_name(), i), s))
+        .map(|(i, s)| {
+            generate_new_node(
+                search_nodes(old_node.get_children(), s.get_name(), i),
+                s,
+                hide_blocks,
+                sort,
+            )
+        })
         .collect::<Vec<BrowserNode<NetlistNode>>>();
 
     // Sort the new child scope nodes
-    new_scopes.sort_by(|a, b| alphanumeric_sort::compare_str(&a.to_string(), &b.to_string()));
+    sort_new_nodes(&mut new_scopes, sort);
     // Create a copy of the variables and sort them separately
     let mut new_variables = new_scope
         .get_variables()
         .iter()
         .map(|v| BrowserNode::new(Some(NetlistNode::Variable(v.clone()))))
         .collect::<Vec<BrowserNode<NetlistNode>>>();
-    new_variables.sort_by(|a, b| alphanumeric_sort::compare_str(&a.to_string(), &b.to_string()));
-    // Create new node with proper expansion and the new scopes followed by new variables
-    let entry = NetlistNode::Scope(new_scope.get_name().clone());
-    new_scopes.append(&mut new_variables);
-    BrowserNode::from_expanded(Some(entry), old_node.is_expanded(), new_scopes)
+    sort_new_nodes(&mut new_variables, sort);
+    // Put the new scopes next to the new variables
+    match sort {
+        NetlistSort::VariablesFirst => {
+            new_variables.append(&mut new_scopes);
+            new_variables
+        }
+        _ => {
+            new_scopes.append(&mut new_variables);
+            new_scopes
+        }
+    }
+}
+
+// Expands (or collapses) the scopes and every scope inside of them, creating
+// the children of the ones that were never expanded from the scopes they are in
+fn set_scopes_expanded(
+    nodes: &mut [BrowserNode<NetlistNode>],
+    scopes: &[VcdScope],
+    expanded: bool,
+    hide_blocks: bool,
+    sort: NetlistSort,
+) {
+    for node in nodes {
+        let Some(NetlistNode::Scope(name, _)) = node.get_entry() else {
+            continue;
+        };
+        let Some(scope) = scopes.iter().find(|s| s.get_name() == name) else {
+            continue;
+        };
+        if expanded && node.is_deferred() {
+            node.set_children(generate_children(&DEFAULT_NODE, scope, hide_blocks, sort));
+        }
+        node.set_expanded(expanded);
+        set_scopes_expanded(
+            node.get_children_mut(),
+            scope.get_scopes(),
+            expanded,
+            hide_blocks,
+            sort,
+        );
+    }
 }
 
 fn generate_new_nodes(
     old_nodes: &BrowserNode<NetlistNode>,
     new_scopes: &[VcdScope],
+    hide_blocks: bool,
+    sort: NetlistSort,
 ) -> BrowserNode<NetlistNode> {
     // Search through the old node's children for matches to the new scope children
     let mut new_scopes = new_scopes
         .iter()
+        .filter(|s| is_scope_visible(s, hide_blocks))
         .enumerate()
-        .map(|(i, s)| generate_new_node(search_nodes(old_nodes.get_children(), s.get_name(), i), s))
+        .map(|(i, s)| {
+            generate_new_node(
+                search_nodes(old_nodes.get_children(), s.get_name(), i),
+                s,
+                hide_blocks,
+                sort,
+            )
+        })
         .collect::<Vec<BrowserNode<NetlistNode>>>();
     // Sort the new child scope nodes
-    new_scopes.sort_by(|a, b| alphanumeric_sort::compare_str(&a.to_string(), &b.to_string()));
+    sort_new_nodes(&mut new_scopes, sort);
     BrowserNode::from_expanded(None, true, new_scopes)
 }
 
+// Builds a fully expanded copy of the scope keeping only the variables that
+// match the filter, along with the best score of anything inside it
+fn generate_filtered_node(
+    scope: &VcdScope,
+    path: &mut Vec<String>,
+    hide_blocks: bool,
+    filter: &NetlistFilter,
+    net_types: &HashMap<usize, VcdVariableNetType>,
+) -> Option<(BrowserNode<NetlistNode>, i64)> {
+    path.push(scope.get_name().clone());
+    let mut scopes = scope
+        .get_scopes()
+        .iter()
+        .filter(|s| is_scope_visible(s, hide_blocks))
+        .filter_map(|s| generate_filtered_node(s, path, hide_blocks, filter, net_types))
+        .collect::<Vec<(BrowserNode<NetlistNode>, i64)>>();
+    let mut variables = scope
+        .get_variables()
+        .iter()
+        .filter_map(|v| {
+            path.push(v.get_name().clone());
+            let score = filter.score_variable(path, &get_filter_attributes(v, net_types));
+            path.pop();
+            Some((
+                BrowserNode::new(Some(NetlistNode::Variable(v.clone()))),
+                score?,
+            ))
+        })
+        .collect::<Vec<(BrowserNode<NetlistNode>, i64)>>();
+    path.pop();
+
+    sort_filtered_nodes(&mut scopes, filter.get_mode());
+    sort_filtered_nodes(&mut variables, filter.get_mode());
+    let score = scopes
+        .iter()
+        .chain(variables.iter())
+        .map(|(_, s)| *s)
+        .max()?;
+    let entry = NetlistNode::Scope(scope.get_name().clone(), scope.get_type().clone());
+    let children = scopes
+        .into_iter()
+        .chain(variables)
+        .map(|(n, _)| n)
+        .collect();
+    Some((
+        BrowserNode::from_expanded(Some(entry), true, children),
+        score,
+    ))
+}
+
+// Fuzzy matches are ordered best first, everything else stays alphanumeric
+fn sort_filtered_nodes(nodes: &mut [(BrowserNode<NetlistNode>, i64)], mode: FilterMode) {
+    nodes.sort_by(|(a, a_score), (b, b_score)| match mode {
+        FilterMode::Fuzzy => b_score
+            .cmp(a_score)
+            .then_with(|| alphanumeric_sort::compare_str(a.to_string(), b.to_string())),
+        _ => alphanumeric_sort::compare_str(a.to_string(), b.to_string()),
+    });
+}
+
+fn generate_filtered_nodes(
+    scopes: &[VcdScope],
+    hide_blocks: bool,
+    filter: &NetlistFilter,
+    net_types: &HashMap<usize, VcdVariableNetType>,
+) -> BrowserNode<NetlistNode> {
+    let mut scopes = scopes
+        .iter()
+        .filter(|s| is_scope_visible(s, hide_blocks))
+        .filter_map(|s| generate_filtered_node(s, &mut Vec::new(), hide_blocks, filter, net_types))
+        .collect::<Vec<(BrowserNode<NetlistNode>, i64)>>();
+    sort_filtered_nodes(&mut scopes, filter.get_mode());
+    BrowserNode::from_expanded(None, true, scopes.into_iter().map(|(n, _)| n).collect())
+}
+
+// Expands every scope on the way to a variable matching the filter, collecting
+// the idcodes of the matches, and returns if there were any below the node.
+// Scopes without matches that were never expanded are left without children.
+fn highlight_matches(
+    node: &mut BrowserNode<NetlistNode>,
+    scopes: &[VcdScope],
+    path: &mut Vec<String>,
+    (hide_blocks, sort): (bool, NetlistSort),
+    filter: &NetlistFilter,
+    net_types: &HashMap<usize, VcdVariableNetType>,
+    matches: &mut HashSet<usize>,
+) -> bool {
+    let mut found = false;
+    for child in node.get_children_mut() {
+        let (name, variable) = match child.get_entry() {
+            Some(NetlistNode::Variable(variable)) => {
+                (variable.get_name().clone(), Some(variable.clone()))
+            }
+            Some(NetlistNode::Scope(name, _)) => (name.clone(), None),
+            None => continue,
+        };
+        path.push(name);
+        match variable {
+            Some(variable) => {
+                let attributes = get_filter_attributes(&variable, net_types);
+                if filter.score_variable(path, &attributes).is_some() {
+                    matches.insert(variable.get_idcode());
+                    found = true;
+                }
+            }
+            None => {
+                let name = path.last().cloned().unwrap_or_default();
+                let Some(scope) = scopes.iter().find(|s| *s.get_name() == name) else {
+                    path.pop();
+                    continue;
+                };
+                let deferred = child.is_deferred();
+                if deferred {
+                    child.set_children(generate_children(&DEFAULT_NODE, scope, hide_blocks, sort));
+                }
+                let options = (hide_blocks, sort);
+                if highlight_matches(
+                    child,
+                    scope.get_scopes(),
+                    path,
+                    options,
+                    filter,
+                    net_types,
+                    matches,
+                ) {
+                    child.set_expanded(true);
+                    found = true;
+                } else if deferred {
+                    *child = BrowserNode::deferred(child.get_entry().clone());
+                }
+            }
+        }
+        path.pop();
+    }
+    found
+}
+
 #[derive(Clone)]
 enum NetlistViewerAction {
     Append,
@@ -107,13 +464,61 @@ enum NetlistViewerAction {
 }
 
 pub(crate) enum NetlistViewerMessage {
-    UpdateScopes(Vec<VcdScope>),
+    UpdateScopes(Vec<VcdScope>, Arc<HashMap<usize, VcdVariableNetType>>),
+    UpdateFilter(String, FilterMode, FilterDisplay),
+    SetClocks(Arc<HashMap<usize, ClockInfo>>),
+    MenuAction(MenuAction),
+    // Expands or collapses the whole tree
+    ExpandAll(bool),
+    SetSort(NetlistSort),
+    // Draws guide lines and icons in the tree
+    SetTree(bool),
+}
+
+/// The deepest scope containing all of the variables, as the names of the
+/// scopes leading to it
+fn get_common_scope(variables: &[(Vec<String>, VcdVariable)]) -> Vec<String> {
+    let mut scopes = variables
+        .iter()
+        .map(|(full_name, _)| &full_name[..full_name.len().saturating_sub(1)]);
+    let Some(first) = scopes.next() else {
+        return Vec::new();
+    };
+    let common = scopes.fold(first.len(), |common, scope| {
+        first
+            .iter()
+            .zip(scope)
+            .take(common)
+            .take_while(|(a, b)| a == b)
+            .count()
+    });
+    first[..common].to_vec()
 }
 
+/// The full name of a vector blasted into single bits, along with the full
+/// name and variable of each of its bits
+type BitFamily = (Vec<String>, Vec<(Vec<String>, VcdVariable)>);
+
 pub struct NetlistViewerState {
     state: BrowserState,
     node: BrowserNode<NetlistNode>,
-    filters: Vec<BrowserFilterSection>,
+    scopes: Vec<VcdScope>,
+    net_types: Arc<HashMap<usize, VcdVariableNetType>>,
+    clocks: Arc<HashMap<usize, ClockInfo>>,
+    // Show the width and net type of each variable
+    details_enabled: bool,
+    // Hide scopes that are functions, tasks or generate/fork blocks
+    hide_blocks: bool,
+    sort: NetlistSort,
+    // Append several variables at once into a group named after their scope
+    auto_group: bool,
+    filter: Option<NetlistFilter>,
+    filter_display: FilterDisplay,
+    // Idcodes of the variables matching the filter when highlighting them
+    highlighted: HashSet<usize>,
+    // The tree from before filtering, kept to restore what was expanded
+    unfiltered_node: Option<BrowserNode<NetlistNode>>,
+    type_ahead: TypeAhead,
     border_width: u16,
     messages: Messages,
 }
@@ -123,20 +528,83 @@ impl NetlistViewerState {
         Self {
             state: BrowserState::new(true, true, false),
             node: BrowserNode::from_expanded(None, true, Vec::new()),
-            filters: Vec::new(),
+            scopes: Vec::new(),
+            net_types: Arc::new(HashMap::new()),
+            clocks: Arc::new(HashMap::new()),
+            details_enabled: false,
+            hide_blocks: false,
+            sort: NetlistSort::default(),
+            auto_group: false,
+            filter: None,
+            filter_display: FilterDisplay::Prune,
+            highlighted: HashSet::new(),
+            unfiltered_node: None,
+            type_ahead: TypeAhead::default(),
             border_width: 1,
             messages,
         }
     }
 
-    pub fn update_filter(&mut self, filter: String) {
-        self.filters = construct_filter(filter);
+    pub fn update_filter(&mut self, input: String, mode: FilterMode, display: FilterDisplay) {
+        self.filter = NetlistFilter::new(&input, mode);
+        self.filter_display = display;
+        self.regenerate_nodes();
     }
 
-    fn update_scopes(&mut self, new_scopes: &[VcdScope]) {
-        // Set new scopes and clear the selected item
-        self.node = generate_new_nodes(&self.node, new_scopes);
-        self.state.select_relative(&self.node, 0, true);
+    fn update_scopes(
+        &mut self,
+        new_scopes: Vec<VcdScope>,
+        net_types: Arc<HashMap<usize, VcdVariableNetType>>,
+    ) {
+        self.scopes = new_scopes;
+        self.net_types = net_types;
+        self.regenerate_nodes();
+    }
+
+    fn regenerate_nodes(&mut self) {
+        // Set new scopes and select the same item again if it is still there
+        let selected = self
+            .node
+            .get_full_name(&self.state.get_primary_selected_path(&self.node));
+        self.highlighted.clear();
+        match &self.filter {
+            Some(filter) if self.filter_display == FilterDisplay::Prune => {
+                if self.unfiltered_node.is_none() {
+                    self.unfiltered_node = Some(std::mem::take(&mut self.node));
+                }
+                self.node = generate_filtered_nodes(
+                    &self.scopes,
+                    self.hide_blocks,
+                    filter,
+                    &self.net_types,
+                );
+            }
+            Some(filter) => {
+                // Start over from the tree from before filtering every time, so
+                // scopes only stay expanded while they lead to a match
+                let old_node = self
+                    .unfiltered_node
+                    .get_or_insert_with(|| std::mem::take(&mut self.node));
+                self.node = generate_new_nodes(old_node, &self.scopes, self.hide_blocks, self.sort);
+                highlight_matches(
+                    &mut self.node,
+                    &self.scopes,
+                    &mut Vec::new(),
+                    (self.hide_blocks, self.sort),
+                    filter,
+                    &self.net_types,
+                    &mut self.highlighted,
+                );
+            }
+            None => {
+                let old_node = self.unfiltered_node.take();
+                let old_node = old_node.unwrap_or_else(|| std::mem::take(&mut self.node));
+                self.node =
+                    generate_new_nodes(&old_node, &self.scopes, self.hide_blocks, self.sort);
+            }
+        }
+        let path = self.node.find_full_name(&selected);
+        self.state.select_visible_path(&self.node, &path);
     }
 
     pub fn set_size(&mut self, size: &Rect) {
@@ -147,8 +615,40 @@ impl NetlistViewerState {
         self.state.scroll_relative(&self.node, 0);
     }
 
+    /// Typing ahead takes every key, even the ones of global actions
+    pub fn is_typing_ahead(&self) -> bool {
+        self.type_ahead.is_active()
+    }
+
     pub fn get_browser(&self) -> Browser<'_, NetlistNode> {
-        Browser::new(&self.state, &self.node)
+        let browser = Browser::new(&self.state, &self.node)
+            .suffix(|node| get_node_suffix(node, &self.clocks, &self.net_types))
+            .highlight(|node| match node {
+                NetlistNode::Variable(variable) => {
+                    self.highlighted.contains(&variable.get_idcode())
+                }
+                NetlistNode::Scope(_, _) => false,
+            })
+            .icon(|node| match node {
+                NetlistNode::Scope(_, _) => String::from(GROUP_ICON),
+                NetlistNode::Variable(variable) => get_variable_icon(variable, None),
+            });
+        if !self.details_enabled {
+            return browser;
+        }
+        browser.column(|node| match node {
+            NetlistNode::Scope(_, _) => String::new(),
+            NetlistNode::Variable(variable) => {
+                let net_type = match self.net_types.get(&variable.get_idcode()) {
+                    Some(net_type) => get_net_type_name(net_type),
+                    None => "",
+                };
+                match variable.get_width() {
+                    VcdVariableWidth::Real => String::from(net_type),
+                    VcdVariableWidth::Vector { width } => format!("{width:>3} {net_type}"),
+                }
+            }
+        })
     }
 
     fn get_selected_variables(&self) -> Vec<(Vec<String>, VcdVariable)> {
@@ -168,6 +668,23 @@ impl NetlistViewerState {
 
     fn modify(&mut self, action: NetlistViewerAction) {
         let requests = match action {
+            NetlistViewerAction::Append if self.auto_group => {
+                let variables = self.get_selected_variables();
+                let scope = get_common_scope(&variables);
+                if variables.len() > 1 && !scope.is_empty() {
+                    vec![SignalViewerMessage::NetlistAppendGroup(
+                        scope.join("."),
+                        variables,
+                    )]
+                } else {
+                    variables
+                        .into_iter()
+                        .map(|(full_name, variable)| {
+                            SignalViewerMessage::NetlistAppend(full_name, variable)
+                        })
+                        .collect()
+                }
+            }
             NetlistViewerAction::Append => self
                 .get_selected_variables()
                 .iter()
@@ -184,6 +701,7 @@ impl NetlistViewerState {
                 .collect(),
             NetlistViewerAction::Expand => {
                 let path = self.state.get_primary_selected_path(&self.node);
+                self.create_children(&path);
                 if let Some(node) = self.node.get_node_mut(&path) {
                     node.set_expanded(!node.is_expanded());
                 }
@@ -192,6 +710,180 @@ impl NetlistViewerState {
         };
         self.messages.append(requests);
     }
+
+    /// Creates the children of a scope that was never expanded, from the scope
+    /// with the same full name in the header
+    fn create_children(&mut self, path: &BrowserNodePath) {
+        let full_name = self.node.get_full_name(path);
+        let Some(scope) = find_scope(&self.scopes, &full_name) else {
+            return;
+        };
+        if let Some(node) = self.node.get_node_mut(path).filter(|n| n.is_deferred()) {
+            node.set_children(generate_children(
+                &DEFAULT_NODE,
+                scope,
+                self.hide_blocks,
+                self.sort,
+            ));
+        }
+    }
+
+    /// Expands or collapses every scope under the selected one (or in the whole
+    /// tree), while keeping the selection on something still showing
+    fn set_expanded_below(&mut self, all: bool, expanded: bool) {
+        let path = self.state.get_primary_selected_path(&self.node);
+        let (hide_blocks, sort) = (self.hide_blocks, self.sort);
+        if all {
+            // The root has no entry and always stays expanded
+            let nodes = self.node.get_children_mut();
+            set_scopes_expanded(nodes, &self.scopes, expanded, hide_blocks, sort);
+        } else {
+            let mut parent = self.node.get_full_name(&path);
+            parent.pop();
+            let scopes = match parent.is_empty() {
+                true => Some(self.scopes.as_slice()),
+                false => find_scope(&self.scopes, &parent).map(|s| s.get_scopes().as_slice()),
+            };
+            if let (Some(node), Some(scopes)) = (self.node.get_node_mut(&path), scopes) {
+                set_scopes_expanded(
+                    std::slice::from_mut(node),
+                    scopes,
+                    expanded,
+                    hide_blocks,
+                    sort,
+                );
+            }
+        }
+        self.state.select_visible_path(&self.node, &path);
+    }
+
+    /// The vectors blasted into single bits directly inside the selected scope,
+    /// or the one the selected bit belongs to, as the full name of each vector
+    /// and the full names of its bits from bit 0 up
+    fn get_bit_families(&self) -> Vec<BitFamily> {
+        let path = self.state.get_primary_selected_path(&self.node);
+        let entry = self
+            .node
+            .get_node(&path)
+            .and_then(|n| n.get_entry().as_ref());
+        let mut scope = path.to_vec();
+        let selected = match entry {
+            Some(NetlistNode::Scope(_, _)) => None,
+            Some(NetlistNode::Variable(variable)) => {
+                scope.pop();
+                Some(variable.get_idcode())
+            }
+            None => return Vec::new(),
+        };
+        let Some(scope_node) = self.node.get_node(&BrowserNodePath::new(scope.clone())) else {
+            return Vec::new();
+        };
+        let bits = scope_node
+            .get_children()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, child)| match child.get_entry() {
+                Some(NetlistNode::Variable(variable)) if variable.get_bit_width() == 1 => {
+                    Some((i, variable))
+                }
+                _ => None,
+            })
+            .collect::<Vec<(usize, &VcdVariable)>>();
+        let scope_name = self
+            .node
+            .get_full_name(&BrowserNodePath::new(scope.clone()));
+        find_bit_families(
+            bits.iter()
+                .map(|(_, variable)| variable.get_name().as_str()),
+        )
+        .into_iter()
+        .map(|(name, positions)| {
+            let bits = positions
+                .into_iter()
+                .map(|position| {
+                    let (i, variable) = bits[position];
+                    let mut path = scope.clone();
+                    path.push(i);
+                    let full_name = self.node.get_full_name(&BrowserNodePath::new(path));
+                    (full_name, variable.clone())
+                })
+                .collect::<Vec<(Vec<String>, VcdVariable)>>();
+            let mut full_name = scope_name.clone();
+            full_name.push(format!("{name}[{}]", bits.len()));
+            (full_name, bits)
+        })
+        .filter(|(_, bits)| {
+            selected.is_none_or(|idcode| bits.iter().any(|(_, v)| v.get_idcode() == idcode))
+        })
+        .collect()
+    }
+
+    /// Offers the actions for the selected entry, where scopes can only be
+    /// expanded as appending them does nothing
+    fn open_menu(&mut self) {
+        let path = self.state.get_primary_selected_path(&self.node);
+        // Finding the bits to combine looks at the children of a scope
+        self.create_children(&path);
+        let Some(entry) = self
+            .node
+            .get_node(&path)
+            .and_then(|n| n.get_entry().as_ref())
+        else {
+            return;
+        };
+        let mut actions = match entry {
+            NetlistNode::Scope(_, _) => vec![
+                MenuAction::Expand,
+                MenuAction::ExpandAll,
+                MenuAction::CollapseAll,
+                MenuAction::CopyPath,
+            ],
+            NetlistNode::Variable(_) => {
+                vec![MenuAction::Append, MenuAction::Insert, MenuAction::CopyPath]
+            }
+        };
+        if !self.get_bit_families().is_empty() {
+            actions.push(MenuAction::CombineBits);
+        }
+        self.messages.push(NaluMessage::OpenMenu(ContextMenu {
+            title: entry.to_string(),
+            target: MenuTarget::Netlist,
+            actions,
+        }));
+    }
+
+    fn copy_path(&self) {
+        let path = self.state.get_primary_selected_path(&self.node);
+        let Some(node) = self.node.get_node(&path) else {
+            return;
+        };
+        let mut full_name = self.node.get_full_name(&path);
+        // Leave out the width shown after the name of vectors
+        if let (Some(NetlistNode::Variable(variable)), Some(name)) =
+            (node.get_entry(), full_name.last_mut())
+        {
+            *name = variable.get_name().clone();
+        }
+        copy_to_clipboard(&full_name.join("."));
+    }
+
+    fn handle_menu_action(&mut self, action: MenuAction) {
+        match action {
+            MenuAction::Append => self.modify(NetlistViewerAction::Append),
+            MenuAction::Insert => self.modify(NetlistViewerAction::Insert),
+            MenuAction::Expand => self.modify(NetlistViewerAction::Expand),
+            MenuAction::ExpandAll => self.set_expanded_below(false, true),
+            MenuAction::CollapseAll => self.set_expanded_below(false, false),
+            MenuAction::CopyPath => self.copy_path(),
+            MenuAction::CombineBits => {
+                for (full_name, bits) in self.get_bit_families() {
+                    self.messages
+                        .push(NaluMessage::AddVirtualVector(full_name, bits));
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl ComponentWidget for NetlistViewerState {
@@ -202,6 +894,10 @@ impl ComponentWidget for NetlistViewerState {
                     self.modify(NetlistViewerAction::Expand);
                 }
             }
+            MouseEventKind::Down(MouseButton::Right) => {
+                self.state.select_absolute(&self.node, y as isize, true);
+                self.open_menu();
+            }
             MouseEventKind::ScrollDown => self.state.select_relative(&self.node, 5, true),
             MouseEventKind::ScrollUp => self.state.select_relative(&self.node, -5, true),
             _ => return false,
@@ -210,6 +906,10 @@ impl ComponentWidget for NetlistViewerState {
     }
 
     fn handle_key(&mut self, e: KeyEvent) -> bool {
+        if let Some((text, skip_selected)) = self.type_ahead.handle_key(&e) {
+            self.state.select_matching(&self.node, &text, skip_selected);
+            return true;
+        }
         let shift = e.modifiers.contains(KeyModifiers::SHIFT);
         match e.code {
             KeyCode::Up => self.state.select_relative(&self.node, -1, !shift),
@@ -219,6 +919,15 @@ impl ComponentWidget for NetlistViewerState {
             KeyCode::Enter => self.modify(NetlistViewerAction::Expand),
             KeyCode::Char('a') => self.modify(NetlistViewerAction::Append),
             KeyCode::Char('i') => self.modify(NetlistViewerAction::Insert),
+            KeyCode::Char('c') | KeyCode::Menu => self.open_menu(),
+            KeyCode::Char('e') => self.set_expanded_below(false, true),
+            KeyCode::Char('E') => self.set_expanded_below(false, false),
+            KeyCode::Char('w') => self.details_enabled = !self.details_enabled,
+            KeyCode::Char('G') => self.auto_group = !self.auto_group,
+            KeyCode::Char('b') => {
+                self.hide_blocks = !self.hide_blocks;
+                self.regenerate_nodes();
+            }
             KeyCode::Char('f') => {
                 self.state
                     .set_indent_enabled(!self.state.is_full_name_enabled());
@@ -234,8 +943,33 @@ impl ComponentWidget for NetlistViewerState {
         let mut updated = false;
         for message in self.messages.get::<NetlistViewerMessage>() {
             match message {
-                NetlistViewerMessage::UpdateScopes(scopes) => {
-                    self.update_scopes(&scopes);
+                NetlistViewerMessage::UpdateScopes(scopes, net_types) => {
+                    self.update_scopes(scopes, net_types);
+                    updated = true;
+                }
+                NetlistViewerMessage::SetClocks(clocks) => {
+                    self.clocks = clocks;
+                    updated = true;
+                }
+                NetlistViewerMessage::UpdateFilter(input, mode, display) => {
+                    self.update_filter(input, mode, display);
+                    updated = true;
+                }
+                NetlistViewerMessage::MenuAction(action) => {
+                    self.handle_menu_action(action);
+                    updated = true;
+                }
+                NetlistViewerMessage::ExpandAll(expanded) => {
+                    self.set_expanded_below(true, expanded);
+                    updated = true;
+                }
+                NetlistViewerMessage::SetSort(sort) => {
+                    self.sort = sort;
+                    self.regenerate_nodes();
+                    updated = true;
+                }
+                NetlistViewerMessage::SetTree(tree) => {
+                    self.state.set_tree_enabled(tree);
                     updated = true;
                 }
             }
@@ -254,6 +988,7 @@ impl ComponentWidget for NetlistViewerState {
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) {
         self.get_browser()
+            .prompt(self.type_ahead.get_prompt())
             .style(Style::default().fg(Color::LightCyan))
             .render(area, buf);
     }
@@ -266,3 +1001,74 @@ impl ComponentWidget for NetlistViewerState {
         self
     }
 }
+
+#[test]
+fn lazy_children_test() {
+    use crate::loader::{load, LoadOptions};
+    use std::sync::Mutex;
+
+    let declarations = "$scope module top $end\n$var wire 1 ! clk $end\n\
+        $scope module core $end\n$var wire 8 \" pc $end\n\
+        $scope module alu $end\n$var wire 1 # carry $end\n$upscope $end\n$upscope $end\n\
+        $scope module mem $end\n$var wire 1 $ ready $end\n$upscope $end\n\
+        $upscope $end\n$enddefinitions $end\n";
+    let options = LoadOptions {
+        signals: Some(HashSet::new()),
+        ..LoadOptions::default()
+    };
+    let header = load(
+        String::from(declarations),
+        Arc::new(Mutex::new((0, 0))),
+        &options,
+    )
+    .join()
+    .unwrap()
+    .unwrap()
+    .header;
+    let mut state = NetlistViewerState::new(Messages::new());
+    state.update_scopes(header.get_scopes().clone(), Arc::new(HashMap::new()));
+    let node = |state: &NetlistViewerState, path: &[usize]| {
+        let node = state.node.get_node(&BrowserNodePath::new(path.to_vec()));
+        let node = node.unwrap();
+        (node.to_string(), node.is_deferred(), node.is_expanded())
+    };
+    assert_eq!(node(&state, &[0]), (String::from("top"), true, false));
+
+    // Expanding builds the children of the scope, but not of the ones in it
+    state.modify(NetlistViewerAction::Expand);
+    assert_eq!(node(&state, &[0]), (String::from("top"), false, true));
+    assert_eq!(node(&state, &[0, 0]), (String::from("core"), true, false));
+    assert_eq!(node(&state, &[0, 1]), (String::from("mem"), true, false));
+    state.modify(NetlistViewerAction::Expand);
+
+    // Highlighting builds the scopes leading to a match and defers the others
+    // again, while the scope built before keeps its children
+    state.update_filter(
+        String::from("carry"),
+        FilterMode::Substring,
+        FilterDisplay::Highlight,
+    );
+    assert_eq!(node(&state, &[0]), (String::from("top"), false, true));
+    assert_eq!(node(&state, &[0, 0]), (String::from("core"), false, true));
+    assert_eq!(node(&state, &[0, 0, 0]), (String::from("alu"), false, true));
+    assert_eq!(node(&state, &[0, 1]), (String::from("mem"), true, false));
+    let carry = header.get_variable("top.core.alu.carry").unwrap();
+    assert_eq!(state.highlighted, HashSet::from([carry.get_idcode()]));
+
+    // Clearing the filter goes back to the tree from before, collapsed
+    state.update_filter(
+        String::new(),
+        FilterMode::Substring,
+        FilterDisplay::Highlight,
+    );
+    assert_eq!(node(&state, &[0]), (String::from("top"), false, false));
+    assert_eq!(node(&state, &[0, 0]), (String::from("core"), true, false));
+    assert_eq!(node(&state, &[0, 1]), (String::from("mem"), true, false));
+
+    // Expanding everything builds the scopes still deferred along the way
+    state.set_expanded_below(true, true);
+    assert_eq!(node(&state, &[0, 0]), (String::from("core"), false, true));
+    assert_eq!(node(&state, &[0, 0, 0]), (String::from("alu"), false, true));
+    assert_eq!(node(&state, &[0, 1]), (String::from("mem"), false, true));
+    assert_eq!(state.node.get_render_len(), 8);
+}
@@ -0,0 +1,650 @@
+use makai_waveform_db::bitvector::BitVectorRadix;
+use tui::layout::Direction;
+
+use crate::state::{netlist_viewer::NetlistSort, share::SharedLocation, view::parse_radix};
+
+/// The tree of a browser pane expanded or collapsed by the palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserPane {
+    Netlist,
+    Signals,
+}
+
+/// Pulses shorter than this are reported as glitches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlitchWidth {
+    Duration(u64),
+    /// One period of the clock with this full name
+    Clock(String),
+}
+
+/// How much time a zoom preset shows around the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZoomWidth {
+    Timestamps(u64),
+    /// A time of the value times ten to the power in seconds, like 25e-7 for
+    /// 2.5us, which depends on the timescale of the dump
+    Time(u64, i32),
+    /// Periods of the clock with this full name, or of the fastest clock
+    Cycles(u64, Option<String>),
+    /// The whole dump
+    All,
+}
+
+/// How far the selected signals are drawn shifted in time, later when positive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalOffset {
+    Timestamps(i64),
+    /// Like [`ZoomWidth::Time`] with a sign
+    Time(i64, i32),
+}
+
+/// Where the palette places a named cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorPlacement {
+    AtCursor,
+    At(u64),
+    Remove,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteCommand {
+    Annotate(String),
+    Annotations,
+    Close,
+    Cursor(char, CursorPlacement),
+    Cursors,
+    // Removes the signals in the viewer more than once
+    Dedup,
+    Delays,
+    // Expands (or collapses) every entry in the tree of the pane
+    ExpandAll(BrowserPane, bool),
+    // Writes the signal list to this file
+    Export(String),
+    // Goes to a location copied with share
+    Goto(SharedLocation),
+    // Checks every signal in the viewer instead of just the selected one
+    Glitches(GlitchWidth, bool),
+    // Rows each selected signal takes up in the signal and waveform panes
+    Height(usize),
+    Hints,
+    // Lists every value of the selected signal from the cursor to the marker
+    History,
+    Hud,
+    // Appends every signal listed in the file with the radix
+    Import(String, BitVectorRadix),
+    // Runs the views of another python file, appending each as a group
+    // instead of switching to the first
+    LoadConfig(String, bool),
+    // Keeps the signals in the viewer that are also in the new file
+    Open(String, bool),
+    Offset(SignalOffset),
+    // Also places named cursors at the shortest and longest periods
+    Period(bool),
+    // Calls the function of the python script with the rest as arguments
+    Python(String, Vec<String>),
+    // Opens (or closes) the pane drawing the main() function of the script
+    PythonView,
+    Radix(BitVectorRadix),
+    SaveView(String),
+    // Saves the group with the name as a view in a python file of its own, or
+    // back to the file it was loaded from
+    SaveGroup(String, Option<String>),
+    // Copies the file, range, cursor and selected signal as a string
+    Share,
+    // Orders the children of every scope in the netlist
+    Sort(NetlistSort),
+    // Jumps to the next annotation with this text in it
+    Search(String),
+    Separator(String),
+    // Opens the closed pane with this name in the new half instead of a blank one
+    Split(Direction, Option<String>),
+    // Grows the focused pane by this many cells across, or shrinks it when
+    // negative, for resizing without dragging a border
+    Resize(Direction, i16),
+    // Resets the panes next to the focused one back to equal sizes
+    EqualSizes,
+    // Also lists the signals in the viewer that were unknown at the same time
+    TraceUnknown(bool),
+    // Draws guide lines and icons in the trees of the netlist and signals
+    Tree,
+    UndoLoad,
+    View(String),
+    Views,
+    Warnings,
+    Window(Option<u64>, Option<u64>),
+    // Lists the signals that are unknown or floating at the cursor, checking
+    // every signal in the dump instead of just the ones in the viewer
+    UnknownSignals(bool),
+    Zoom(ZoomWidth),
+}
+
+fn split_command(input: &str) -> (&str, &str) {
+    let input = input.trim();
+    match input.split_once(char::is_whitespace) {
+        Some((command, args)) => (command, args.trim()),
+        None => (input, ""),
+    }
+}
+
+/// Parses a window bound where '-' leaves that side of the window open
+fn parse_bound(bound: Option<&str>) -> Result<Option<u64>, String> {
+    match bound {
+        None | Some("-") => Ok(None),
+        Some(bound) => match bound.parse::<u64>() {
+            Ok(bound) => Ok(Some(bound)),
+            Err(_) => Err(format!("Invalid timestamp: {bound}")),
+        },
+    }
+}
+
+fn parse_window(args: &str) -> Result<PaletteCommand, String> {
+    let mut bounds = args.split_whitespace();
+    let from = parse_bound(bounds.next())?;
+    let to = parse_bound(bounds.next())?;
+    if bounds.next().is_some() {
+        return Err(String::from("Usage: window [from|-] [to|-]"));
+    }
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(String::from("Window starts after it ends"));
+        }
+    }
+    Ok(PaletteCommand::Window(from, to))
+}
+
+fn parse_open(args: &str) -> Result<PaletteCommand, String> {
+    match args.rsplit_once(char::is_whitespace) {
+        _ if args.is_empty() => Err(String::from("Usage: open <vcd file> [keep]")),
+        Some((path, "keep")) => Ok(PaletteCommand::Open(String::from(path.trim_end()), true)),
+        _ => Ok(PaletteCommand::Open(String::from(args), false)),
+    }
+}
+
+fn parse_load_config(args: &str) -> Result<PaletteCommand, String> {
+    match args.rsplit_once(char::is_whitespace) {
+        _ if args.is_empty() || args == "--append" => {
+            Err(String::from("Usage: load-config <python file> [--append]"))
+        }
+        Some((path, "--append")) => Ok(PaletteCommand::LoadConfig(
+            String::from(path.trim_end()),
+            true,
+        )),
+        _ => Ok(PaletteCommand::LoadConfig(String::from(args), false)),
+    }
+}
+
+/// The last word is only the file when it is a python file, since group names
+/// can have spaces in them
+fn parse_save_group(args: &str) -> Result<PaletteCommand, String> {
+    match args.rsplit_once(char::is_whitespace) {
+        _ if args.is_empty() => Err(String::from("Usage: save-group <group> [python file]")),
+        Some((group, path)) if path.ends_with(".py") => Ok(PaletteCommand::SaveGroup(
+            String::from(group.trim_end()),
+            Some(String::from(path)),
+        )),
+        _ => Ok(PaletteCommand::SaveGroup(String::from(args), None)),
+    }
+}
+
+fn parse_import(args: &str) -> Result<PaletteCommand, String> {
+    match args.rsplit_once(char::is_whitespace) {
+        _ if args.is_empty() => Err(String::from("Usage: import <list file> [bin|oct|dec|hex]")),
+        Some((path, radix)) if parse_radix(radix).is_some() => Ok(PaletteCommand::Import(
+            String::from(path.trim_end()),
+            parse_radix(radix).unwrap(),
+        )),
+        _ => Ok(PaletteCommand::Import(
+            String::from(args),
+            BitVectorRadix::Hexadecimal,
+        )),
+    }
+}
+
+fn parse_split(args: &str) -> Result<PaletteCommand, String> {
+    let usage = || String::from("Usage: split <h|v> [closed pane]");
+    let (direction, pane) = match args.split_once(char::is_whitespace) {
+        Some((direction, pane)) => (direction, Some(String::from(pane.trim()))),
+        None => (args, None),
+    };
+    let direction = match direction {
+        "h" | "horizontal" => Direction::Horizontal,
+        "v" | "vertical" => Direction::Vertical,
+        _ => return Err(usage()),
+    };
+    Ok(PaletteCommand::Split(direction, pane))
+}
+
+fn parse_resize(args: &str) -> Result<PaletteCommand, String> {
+    let usage = || String::from("Usage: resize <h|v> <cells, negative to shrink>");
+    let (direction, cells) = args.split_once(char::is_whitespace).ok_or_else(usage)?;
+    let direction = match direction {
+        "h" | "horizontal" => Direction::Horizontal,
+        "v" | "vertical" => Direction::Vertical,
+        _ => return Err(usage()),
+    };
+    match cells.trim().trim_start_matches('+').parse::<i16>() {
+        Ok(cells) if cells != 0 => Ok(PaletteCommand::Resize(direction, cells)),
+        _ => Err(usage()),
+    }
+}
+
+fn parse_expand(args: &str, expanded: bool) -> Result<PaletteCommand, String> {
+    let pane = match args {
+        "netlist" | "n" => BrowserPane::Netlist,
+        "signals" | "s" => BrowserPane::Signals,
+        _ if expanded => return Err(String::from("Usage: expand <netlist|signals>")),
+        _ => return Err(String::from("Usage: collapse <netlist|signals>")),
+    };
+    Ok(PaletteCommand::ExpandAll(pane, expanded))
+}
+
+fn parse_cursor(args: &str) -> Result<PaletteCommand, String> {
+    let usage = || String::from("Usage: cursor <A-Z> [timestamp|-]");
+    let (name, placement) = match args.split_whitespace().collect::<Vec<&str>>()[..] {
+        [name] => (name, CursorPlacement::AtCursor),
+        [name, "-"] => (name, CursorPlacement::Remove),
+        [name, timestamp] => match timestamp.parse::<u64>() {
+            Ok(timestamp) => (name, CursorPlacement::At(timestamp)),
+            Err(_) => return Err(format!("Invalid timestamp: {timestamp}")),
+        },
+        _ => return Err(usage()),
+    };
+    match name.chars().collect::<Vec<char>>()[..] {
+        [name] if name.is_ascii_alphabetic() => {
+            Ok(PaletteCommand::Cursor(name.to_ascii_uppercase(), placement))
+        }
+        _ => Err(usage()),
+    }
+}
+
+fn parse_glitches(args: &str) -> Result<PaletteCommand, String> {
+    let usage = || String::from("Usage: glitches <width|clock> [all]");
+    let (width, all) = match args.split_whitespace().collect::<Vec<&str>>()[..] {
+        [width] => (width, false),
+        [width, "all"] => (width, true),
+        _ => return Err(usage()),
+    };
+    let width = match width.parse::<u64>() {
+        Ok(0) => return Err(String::from("Glitch width has to be more than zero")),
+        Ok(width) => GlitchWidth::Duration(width),
+        Err(_) => GlitchWidth::Clock(String::from(width)),
+    };
+    Ok(PaletteCommand::Glitches(width, all))
+}
+
+/// Parses a time with a unit like "100ns" or "2.5us" into its digits and the
+/// power of ten in seconds they are in
+fn parse_time(time: &str) -> Option<(u64, i32)> {
+    let split = time.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = time.split_at(split);
+    let exponent = match unit {
+        "fs" => -15,
+        "ps" => -12,
+        "ns" => -9,
+        "us" => -6,
+        "ms" => -3,
+        "s" => 0,
+        _ => return None,
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let value = format!("{whole}{fraction}").parse::<u64>().ok()?;
+    Some((value, exponent - fraction.len() as i32))
+}
+
+fn parse_zoom(args: &str) -> Result<PaletteCommand, String> {
+    let usage = || String::from("Usage: zoom <time|all> or zoom <count> cycles [clock]");
+    let width = match args.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["all"] => ZoomWidth::All,
+        [count, "cycles" | "cycle", ref clock @ ..] if clock.len() <= 1 => {
+            match count.parse::<u64>() {
+                Ok(0) | Err(_) => return Err(format!("Invalid cycle count: {count}")),
+                Ok(count) => ZoomWidth::Cycles(count, clock.first().map(|c| String::from(*c))),
+            }
+        }
+        [width] => match (width.parse::<u64>(), parse_time(width)) {
+            (Ok(0), _) | (_, Some((0, _))) => {
+                return Err(String::from("Zoom width has to be more than zero"))
+            }
+            (Ok(width), _) => ZoomWidth::Timestamps(width),
+            (_, Some((value, exponent))) => ZoomWidth::Time(value, exponent),
+            (Err(_), None) => return Err(format!("Invalid time: {width}")),
+        },
+        _ => return Err(usage()),
+    };
+    Ok(PaletteCommand::Zoom(width))
+}
+
+fn parse_offset(args: &str) -> Result<PaletteCommand, String> {
+    if args.is_empty() || args.contains(char::is_whitespace) {
+        return Err(String::from("Usage: offset <time>"));
+    }
+    Ok(PaletteCommand::Offset(parse_signed_time(args)?))
+}
+
+/// Parses a time (or a plain number of timestamps) with an optional sign
+pub fn parse_signed_time(args: &str) -> Result<SignalOffset, String> {
+    let (sign, time) = match args.split_at(args.starts_with(['+', '-']) as usize) {
+        ("-", time) => (-1, time),
+        (_, time) => (1, time),
+    };
+    let (value, exponent) = match (time.parse::<u64>(), parse_time(time)) {
+        (Ok(value), _) => (value, None),
+        (_, Some((value, exponent))) => (value, Some(exponent)),
+        (Err(_), None) => return Err(format!("Invalid time: {args}")),
+    };
+    let Ok(value) = i64::try_from(value) else {
+        return Err(format!("Invalid time: {args}"));
+    };
+    Ok(match exponent {
+        Some(exponent) => SignalOffset::Time(sign * value, exponent),
+        None => SignalOffset::Timestamps(sign * value),
+    })
+}
+
+pub fn parse_command(input: &str) -> Result<PaletteCommand, String> {
+    match split_command(input) {
+        ("annotate", "") => Err(String::from("Usage: annotate <text>")),
+        ("annotate", text) => Ok(PaletteCommand::Annotate(String::from(text))),
+        ("annotations", _) => Ok(PaletteCommand::Annotations),
+        ("close", _) => Ok(PaletteCommand::Close),
+        ("collapse", args) => parse_expand(args, false),
+        ("cursor", args) => parse_cursor(args),
+        ("cursors", _) => Ok(PaletteCommand::Cursors),
+        ("dedup", _) => Ok(PaletteCommand::Dedup),
+        ("delays", _) => Ok(PaletteCommand::Delays),
+        ("equal-sizes", _) => Ok(PaletteCommand::EqualSizes),
+        ("expand", args) => parse_expand(args, true),
+        ("export", "") => Err(String::from("Usage: export <file>")),
+        ("export", path) => Ok(PaletteCommand::Export(String::from(path))),
+        ("glitches", args) => parse_glitches(args),
+        ("goto", "") => Err(String::from("Usage: goto <location from share>")),
+        ("goto", location) => location.parse().map(PaletteCommand::Goto),
+        ("height", height) => match height.parse::<usize>() {
+            Ok(height @ 1..=4) => Ok(PaletteCommand::Height(height)),
+            _ => Err(String::from("Usage: height <1-4>")),
+        },
+        ("hints", _) => Ok(PaletteCommand::Hints),
+        ("history", _) => Ok(PaletteCommand::History),
+        ("hud", _) => Ok(PaletteCommand::Hud),
+        ("import", args) => parse_import(args),
+        ("load-config", args) => parse_load_config(args),
+        ("offset", args) => parse_offset(args),
+        ("open", args) => parse_open(args),
+        ("period", "") => Ok(PaletteCommand::Period(false)),
+        ("period", "cursors") => Ok(PaletteCommand::Period(true)),
+        ("period", _) => Err(String::from("Usage: period [cursors]")),
+        ("py", "") => Err(String::from("Usage: py <function> [args]")),
+        ("py", args) => {
+            let mut args = args.split_whitespace().map(String::from);
+            let function = args.next().unwrap_or_default();
+            Ok(PaletteCommand::Python(function, args.collect()))
+        }
+        ("python-view", _) => Ok(PaletteCommand::PythonView),
+        ("radix", radix) => match parse_radix(radix) {
+            Some(radix) => Ok(PaletteCommand::Radix(radix)),
+            None => Err(String::from("Usage: radix <bin|oct|dec|hex>")),
+        },
+        ("resize", args) => parse_resize(args),
+        ("save-group", args) => parse_save_group(args),
+        ("save-view", "") => Err(String::from("Usage: save-view <name>")),
+        ("save-view", name) => Ok(PaletteCommand::SaveView(String::from(name))),
+        ("search", "") => Err(String::from("Usage: search <text>")),
+        ("search", text) => Ok(PaletteCommand::Search(String::from(text))),
+        ("separator", "") => Err(String::from("Usage: separator <label>")),
+        ("separator", label) => Ok(PaletteCommand::Separator(String::from(label))),
+        ("share", _) => Ok(PaletteCommand::Share),
+        ("sort", "name") => Ok(PaletteCommand::Sort(NetlistSort::Name)),
+        ("sort", "declared") => Ok(PaletteCommand::Sort(NetlistSort::Declaration)),
+        ("sort", "width") => Ok(PaletteCommand::Sort(NetlistSort::Width)),
+        ("sort", "variables") => Ok(PaletteCommand::Sort(NetlistSort::VariablesFirst)),
+        ("sort", _) => Err(String::from("Usage: sort <name|declared|width|variables>")),
+        ("split", args) => parse_split(args),
+        ("trace-x", "") => Ok(PaletteCommand::TraceUnknown(false)),
+        ("trace-x", "all") => Ok(PaletteCommand::TraceUnknown(true)),
+        ("trace-x", _) => Err(String::from("Usage: trace-x [all]")),
+        ("tree", _) => Ok(PaletteCommand::Tree),
+        ("undo-load", _) => Ok(PaletteCommand::UndoLoad),
+        ("view", "") => Err(String::from("Usage: view <name>")),
+        ("view", name) => Ok(PaletteCommand::View(String::from(name))),
+        ("views", _) => Ok(PaletteCommand::Views),
+        ("warnings", _) => Ok(PaletteCommand::Warnings),
+        ("window", args) => parse_window(args),
+        ("xz", "") => Ok(PaletteCommand::UnknownSignals(false)),
+        ("xz", "all") => Ok(PaletteCommand::UnknownSignals(true)),
+        ("xz", _) => Err(String::from("Usage: xz [all]")),
+        ("zoom", args) => parse_zoom(args),
+        ("", _) => Err(String::from("No command entered")),
+        (command, _) => Err(format!("Unknown command: {command}")),
+    }
+}
+
+#[test]
+fn palette_parse_test() {
+    assert_eq!(
+        parse_command("annotate  IRQ fired "),
+        Ok(PaletteCommand::Annotate(String::from("IRQ fired")))
+    );
+    assert_eq!(
+        parse_command("search  tx 0x55"),
+        Ok(PaletteCommand::Search(String::from("tx 0x55")))
+    );
+    assert!(parse_command("search").is_err());
+    assert_eq!(parse_command("dedup"), Ok(PaletteCommand::Dedup));
+    assert_eq!(
+        parse_command("annotations"),
+        Ok(PaletteCommand::Annotations)
+    );
+    assert_eq!(
+        parse_command("window 100 -"),
+        Ok(PaletteCommand::Window(Some(100), None))
+    );
+    assert_eq!(
+        parse_command("window"),
+        Ok(PaletteCommand::Window(None, None))
+    );
+    assert_eq!(
+        parse_command("save-view memory bus"),
+        Ok(PaletteCommand::SaveView(String::from("memory bus")))
+    );
+    assert_eq!(
+        parse_command("save-group axi bus axi.py"),
+        Ok(PaletteCommand::SaveGroup(
+            String::from("axi bus"),
+            Some(String::from("axi.py"))
+        ))
+    );
+    assert_eq!(
+        parse_command("save-group axi bus"),
+        Ok(PaletteCommand::SaveGroup(String::from("axi bus"), None))
+    );
+    assert!(parse_command("save-group").is_err());
+    assert_eq!(
+        parse_command("load-config extra.py --append"),
+        Ok(PaletteCommand::LoadConfig(String::from("extra.py"), true))
+    );
+    assert_eq!(
+        parse_command("load-config extra.py"),
+        Ok(PaletteCommand::LoadConfig(String::from("extra.py"), false))
+    );
+    assert!(parse_command("load-config --append").is_err());
+    assert_eq!(
+        parse_command("glitches 5 all"),
+        Ok(PaletteCommand::Glitches(GlitchWidth::Duration(5), true))
+    );
+    assert_eq!(
+        parse_command("glitches TOP.clk"),
+        Ok(PaletteCommand::Glitches(
+            GlitchWidth::Clock(String::from("TOP.clk")),
+            false
+        ))
+    );
+    assert!(parse_command("glitches").is_err());
+    assert!(parse_command("glitches 0").is_err());
+    assert_eq!(
+        parse_command("trace-x all"),
+        Ok(PaletteCommand::TraceUnknown(true))
+    );
+    assert!(parse_command("trace-x bogus").is_err());
+    assert_eq!(
+        parse_command("xz"),
+        Ok(PaletteCommand::UnknownSignals(false))
+    );
+    assert!(parse_command("xz bogus").is_err());
+    assert_eq!(
+        parse_command("period cursors"),
+        Ok(PaletteCommand::Period(true))
+    );
+    assert!(parse_command("period bogus").is_err());
+    assert_eq!(
+        parse_command("open sim/my dump.vcd keep"),
+        Ok(PaletteCommand::Open(String::from("sim/my dump.vcd"), true))
+    );
+    assert_eq!(
+        parse_command("open dump.vcd"),
+        Ok(PaletteCommand::Open(String::from("dump.vcd"), false))
+    );
+    assert!(parse_command("open").is_err());
+    assert_eq!(
+        parse_command("import signals.txt bin"),
+        Ok(PaletteCommand::Import(
+            String::from("signals.txt"),
+            BitVectorRadix::Binary
+        ))
+    );
+    assert_eq!(
+        parse_command("import my signals.txt"),
+        Ok(PaletteCommand::Import(
+            String::from("my signals.txt"),
+            BitVectorRadix::Hexadecimal
+        ))
+    );
+    assert!(parse_command("import").is_err());
+    assert_eq!(
+        parse_command("export signals.yaml"),
+        Ok(PaletteCommand::Export(String::from("signals.yaml")))
+    );
+    assert!(parse_command("export").is_err());
+    assert_eq!(
+        parse_command("goto nalu:dump.vcd?range=0-10&cursor=5"),
+        Ok(PaletteCommand::Goto(SharedLocation {
+            file: Some(String::from("dump.vcd")),
+            range: 0..10,
+            cursor: 5,
+            signal: None,
+        }))
+    );
+    assert!(parse_command("goto").is_err());
+    assert_eq!(
+        parse_command("split v waveform"),
+        Ok(PaletteCommand::Split(
+            Direction::Vertical,
+            Some(String::from("waveform"))
+        ))
+    );
+    assert_eq!(
+        parse_command("split h"),
+        Ok(PaletteCommand::Split(Direction::Horizontal, None))
+    );
+    assert!(parse_command("split").is_err());
+    assert_eq!(
+        parse_command("resize h +5"),
+        Ok(PaletteCommand::Resize(Direction::Horizontal, 5))
+    );
+    assert_eq!(
+        parse_command("resize v -3"),
+        Ok(PaletteCommand::Resize(Direction::Vertical, -3))
+    );
+    assert!(parse_command("resize h 0").is_err());
+    assert!(parse_command("resize 5").is_err());
+    assert_eq!(parse_command("equal-sizes"), Ok(PaletteCommand::EqualSizes));
+    assert_eq!(parse_command("python-view"), Ok(PaletteCommand::PythonView));
+    assert_eq!(
+        parse_command("collapse signals"),
+        Ok(PaletteCommand::ExpandAll(BrowserPane::Signals, false))
+    );
+    assert!(parse_command("expand").is_err());
+    assert_eq!(
+        parse_command("separator memory bus"),
+        Ok(PaletteCommand::Separator(String::from("memory bus")))
+    );
+    assert!(parse_command("separator").is_err());
+    assert_eq!(
+        parse_command("py stalls TOP.valid 100"),
+        Ok(PaletteCommand::Python(
+            String::from("stalls"),
+            vec![String::from("TOP.valid"), String::from("100")]
+        ))
+    );
+    assert!(parse_command("py").is_err());
+    assert_eq!(
+        parse_command("cursor b 120"),
+        Ok(PaletteCommand::Cursor('B', CursorPlacement::At(120)))
+    );
+    assert_eq!(
+        parse_command("cursor A -"),
+        Ok(PaletteCommand::Cursor('A', CursorPlacement::Remove))
+    );
+    assert!(parse_command("cursor AB").is_err());
+    assert_eq!(
+        parse_command("zoom 100ns"),
+        Ok(PaletteCommand::Zoom(ZoomWidth::Time(100, -9)))
+    );
+    assert_eq!(
+        parse_command("zoom 2.5us"),
+        Ok(PaletteCommand::Zoom(ZoomWidth::Time(25, -7)))
+    );
+    assert_eq!(
+        parse_command("zoom 500"),
+        Ok(PaletteCommand::Zoom(ZoomWidth::Timestamps(500)))
+    );
+    assert_eq!(
+        parse_command("zoom 10 cycles TOP.clk"),
+        Ok(PaletteCommand::Zoom(ZoomWidth::Cycles(
+            10,
+            Some(String::from("TOP.clk"))
+        )))
+    );
+    assert_eq!(
+        parse_command("zoom 4 cycles"),
+        Ok(PaletteCommand::Zoom(ZoomWidth::Cycles(4, None)))
+    );
+    assert_eq!(
+        parse_command("zoom all"),
+        Ok(PaletteCommand::Zoom(ZoomWidth::All))
+    );
+    assert!(parse_command("zoom 10 parsecs").is_err());
+    assert_eq!(
+        parse_command("offset -2.5ns"),
+        Ok(PaletteCommand::Offset(SignalOffset::Time(-25, -10)))
+    );
+    assert_eq!(
+        parse_command("offset +3"),
+        Ok(PaletteCommand::Offset(SignalOffset::Timestamps(3)))
+    );
+    assert!(parse_command("offset --3").is_err());
+    assert!(parse_command("offset").is_err());
+    assert_eq!(
+        parse_command("radix dec"),
+        Ok(PaletteCommand::Radix(BitVectorRadix::Decimal))
+    );
+    assert!(parse_command("radix").is_err());
+    assert_eq!(parse_command("height 3"), Ok(PaletteCommand::Height(3)));
+    assert!(parse_command("height 5").is_err());
+    assert!(parse_command("height").is_err());
+    assert_eq!(
+        parse_command("sort declared"),
+        Ok(PaletteCommand::Sort(NetlistSort::Declaration))
+    );
+    assert!(parse_command("sort").is_err());
+    assert!(parse_command("zoom 0ns").is_err());
+    assert!(parse_command("zoom").is_err());
+    assert!(parse_command("view").is_err());
+    assert!(parse_command("window 20 10").is_err());
+    assert!(parse_command("annotate").is_err());
+    assert!(parse_command("").is_err());
+    assert!(parse_command("bogus").is_err());
+}
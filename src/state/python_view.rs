@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crossterm::event::{KeyEvent, MouseEventKind};
+use makai::utils::messages::Messages;
+use makai_vcd_reader::parser::VcdHeader;
+use makai_waveform_db::Waveform;
+use pyo3::prelude::*;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::Spans,
+    widgets::{Paragraph, Widget},
+};
+use tui_tiling::component::ComponentWidget;
+
+use crate::{
+    python::{
+        buffer::BufferPy,
+        error::{PythonError, PythonRerun},
+        script::{
+            format_error, is_timeout, load_script, take_navigation, with_timeout, PythonNavigation,
+        },
+        vcd_header::VcdHeaderPy,
+        waveform::WaveformPy,
+    },
+    state::{waveform_viewer::WaveformViewerMessage, NaluMessage},
+};
+
+/// Name of the pane the python view is drawn in
+pub const PYTHON_PANE: &str = "python";
+
+pub(crate) enum PythonViewMessage {
+    UpdateWaveform(Arc<Waveform>),
+    UpdateScript(Arc<VcdHeader>, Option<PathBuf>),
+    SetCursor(u64),
+    ReloadPython,
+}
+
+/// Sends the navigation a script asked for to the waveform viewer, leaving out
+/// moves to where the cursor already is so a script asking on every render
+/// does not keep redrawing
+pub(crate) fn push_navigation(messages: &Messages, mut cursor: u64) {
+    for navigation in take_navigation() {
+        match navigation {
+            PythonNavigation::Goto(timestamp) if timestamp == cursor => {}
+            PythonNavigation::Goto(timestamp) => {
+                cursor = timestamp;
+                messages.push(WaveformViewerMessage::GotoTimestamp(timestamp));
+            }
+            PythonNavigation::Zoom(range) => messages.push(WaveformViewerMessage::SetRange(range)),
+        }
+    }
+}
+
+/// Draws whatever the main() function of the python script returns, in a pane
+/// of its own so it can be open next to the waveform viewer
+pub struct PythonViewState {
+    width: u16,
+    height: u16,
+    waveform: Arc<Waveform>,
+    vcd_header: Arc<VcdHeader>,
+    python_path: Option<PathBuf>,
+    cursor: u64,
+    // The last error raised by main(), which is only shown once until it changes
+    python_error: Option<String>,
+    // Set when main() ran for too long, which stops it being called on every
+    // frame until the script is run again
+    timed_out: bool,
+    messages: Messages,
+}
+
+impl PythonViewState {
+    pub fn new(messages: Messages) -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            waveform: Arc::new(Waveform::default()),
+            vcd_header: Arc::new(VcdHeader::default()),
+            python_path: None,
+            cursor: 0,
+            python_error: None,
+            timed_out: false,
+            messages,
+        }
+    }
+
+    fn get_python_widget(&mut self) -> Paragraph<'_> {
+        let Some(python_path) = self.python_path.clone() else {
+            return Paragraph::new("No python loaded!");
+        };
+        if self.timed_out {
+            return Paragraph::new(self.python_error.clone().unwrap_or_default());
+        }
+
+        let result: PyResult<BufferPy> = with_timeout(|py| {
+            let main: Py<PyAny> = load_script(py, &python_path)?.getattr("main")?.into();
+
+            let buffer = BufferPy::new(self.width, self.height);
+            let waveform = WaveformPy::new(self.waveform.clone());
+            let vcd_header = VcdHeaderPy::new(self.vcd_header.clone());
+            main.call1(py, (buffer, waveform, vcd_header, self.cursor))?
+                .extract::<BufferPy>(py)
+        });
+
+        match result {
+            Ok(buffer) => {
+                self.python_error = None;
+                let mut spans = Vec::new();
+                for y in 0..buffer.get_height() {
+                    let mut string = String::new();
+                    for x in 0..buffer.get_width() {
+                        string.push(buffer.get_cell(x, y));
+                    }
+                    spans.push(Spans::from(string.trim().to_string()));
+                }
+                Paragraph::new(spans)
+            }
+            Err(err) => {
+                self.timed_out = is_timeout(&err);
+                let text = format_error(&err);
+                if self.python_error.as_ref() != Some(&text) {
+                    log::error!("Python Main Error: {err:?}");
+                    self.messages
+                        .push(NaluMessage::PythonError(PythonError::new(
+                            "main()",
+                            &err,
+                            PythonRerun::Script,
+                        )));
+                    self.python_error = Some(text.clone());
+                }
+                Paragraph::new(text)
+            }
+        }
+    }
+}
+
+impl ComponentWidget for PythonViewState {
+    fn handle_mouse(&mut self, _x: u16, _y: u16, _kind: MouseEventKind) -> bool {
+        false
+    }
+
+    fn handle_key(&mut self, _e: KeyEvent) -> bool {
+        false
+    }
+
+    fn handle_update(&mut self) -> bool {
+        let mut updated = false;
+        for message in self.messages.get::<PythonViewMessage>() {
+            match message {
+                PythonViewMessage::UpdateWaveform(waveform) => self.waveform = waveform,
+                PythonViewMessage::UpdateScript(vcd_header, python_path) => {
+                    self.vcd_header = vcd_header;
+                    self.python_path = python_path;
+                    self.python_error = None;
+                    self.timed_out = false;
+                }
+                PythonViewMessage::SetCursor(cursor) => self.cursor = cursor,
+                PythonViewMessage::ReloadPython => {
+                    self.python_error = None;
+                    self.timed_out = false;
+                }
+            }
+            updated = true;
+        }
+        updated
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.get_python_widget()
+            .style(Style::default().fg(Color::LightCyan))
+            .render(area, buf);
+        push_navigation(&self.messages, self.cursor);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use crate::widgets::timescale::render_time;
+
+/// Most files kept in the recent files list
+const RECENT_LIMIT: usize = 20;
+
+/// A previously opened dump, along with a summary of it from that session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub signals: usize,
+    pub end: u64,
+    pub timescale: i32,
+}
+
+impl RecentFile {
+    pub fn get_summary(&self) -> String {
+        format!(
+            "{} signals, {}",
+            self.signals,
+            render_time(self.end, 1, self.timescale)
+        )
+    }
+}
+
+/// Directory for files that persist between sessions, following the XDG
+/// config directory if it is set
+pub fn get_config_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("nalu")),
+        _ => Some(PathBuf::from(std::env::var_os("HOME")?).join(".config/nalu")),
+    }
+}
+
+fn get_recent_path() -> Option<PathBuf> {
+    Some(get_config_dir()?.join("recent"))
+}
+
+/// Parses the recent files list, with one tab separated file per line and the
+/// most recent first, skipping any lines that do not make sense
+pub fn parse_recent(source: &str) -> Vec<RecentFile> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let path = PathBuf::from(fields.next().filter(|p| !p.is_empty())?);
+            let signals = fields.next()?.parse().ok()?;
+            let end = fields.next()?.parse().ok()?;
+            let timescale = fields.next()?.parse().ok()?;
+            Some(RecentFile {
+                path,
+                signals,
+                end,
+                timescale,
+            })
+        })
+        .collect()
+}
+
+pub fn render_recent(recent: &[RecentFile]) -> String {
+    recent
+        .iter()
+        .map(|r| {
+            format!(
+                "{}\t{}\t{}\t{}\n",
+                r.path.display(),
+                r.signals,
+                r.end,
+                r.timescale
+            )
+        })
+        .collect()
+}
+
+/// Moves the file to the front of the list, replacing the old summary of it
+pub fn add_recent(recent: &mut Vec<RecentFile>, file: RecentFile) {
+    recent.retain(|r| r.path != file.path);
+    recent.insert(0, file);
+    recent.truncate(RECENT_LIMIT);
+}
+
+pub fn load_recent() -> Vec<RecentFile> {
+    let Some(path) = get_recent_path() else {
+        return Vec::new();
+    };
+    match std::fs::read_to_string(path) {
+        Ok(source) => parse_recent(&source),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn save_recent(recent: &[RecentFile]) {
+    let Some(path) = get_recent_path() else {
+        log::warn!("No config directory to save recent files to");
+        return;
+    };
+    let result = std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+        .and_then(|_| std::fs::write(&path, render_recent(recent)));
+    if let Err(err) = result {
+        log::error!("Saving Recent Files Error: {err:?}");
+    }
+}
+
+#[test]
+fn recent_test() {
+    let file = |path: &str, signals| RecentFile {
+        path: PathBuf::from(path),
+        signals,
+        end: 1000,
+        timescale: -9,
+    };
+    let mut recent = Vec::new();
+    add_recent(&mut recent, file("/a.vcd", 1));
+    add_recent(&mut recent, file("/b.vcd", 2));
+    add_recent(&mut recent, file("/a.vcd", 3));
+    assert_eq!(recent, vec![file("/a.vcd", 3), file("/b.vcd", 2)]);
+    let source = render_recent(&recent);
+    assert_eq!(source, "/a.vcd\t3\t1000\t-9\n/b.vcd\t2\t1000\t-9\n");
+    assert_eq!(
+        parse_recent(&format!("{source}garbage\n\t1\t2\t3\n")),
+        recent
+    );
+}
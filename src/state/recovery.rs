@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::state::recent::get_config_dir;
+use crate::state::view::{get_radix_name, parse_radix, SignalView, ViewEntry};
+
+/// The signals in the viewer of a session, kept until it exits cleanly so they
+/// can be restored after a crash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    pub vcd_path: PathBuf,
+    pub view: SignalView,
+}
+
+/// The newest layout that has not been written yet, kept where the panic hook
+/// can still get to it after the state is gone
+static PENDING: Mutex<Option<Recovery>> = Mutex::new(None);
+
+fn get_recovery_path() -> Option<PathBuf> {
+    Some(get_config_dir()?.join("recovery"))
+}
+
+fn render_recovery_entries(entries: &[ViewEntry], offsets: &[(String, i64)], source: &mut String) {
+    for entry in entries {
+        match entry {
+            ViewEntry::Signal(signal, radix, index) => {
+                let offset = offsets
+                    .iter()
+                    .find(|(name, _)| name == signal)
+                    .map_or(0, |(_, offset)| *offset);
+                source.push_str(&format!(
+                    "signal\t{}\t{}\t{}",
+                    signal,
+                    get_radix_name(*radix),
+                    offset
+                ));
+                match index {
+                    Some(index) => source.push_str(&format!("\t{index}\n")),
+                    None => source.push('\n'),
+                }
+            }
+            ViewEntry::Group(name, entries) => {
+                source.push_str(&format!("group\t{name}\n"));
+                render_recovery_entries(entries, offsets, source);
+                source.push_str("end\n");
+            }
+        }
+    }
+}
+
+/// Renders the dump on the first line followed by a tab separated line for the
+/// range and each signal, with its radix, offset and the index of single bits,
+/// and the signals of a group between a line with its name and an `end` line
+pub fn render_recovery(recovery: &Recovery) -> String {
+    let mut source = format!("{}\n", recovery.vcd_path.display());
+    if let Some(range) = &recovery.view.range {
+        source.push_str(&format!("range\t{}\t{}\n", range.start, range.end));
+    }
+    render_recovery_entries(&recovery.view.signals, &recovery.view.offsets, &mut source);
+    source
+}
+
+/// Parses a recovery file, skipping any lines that do not make sense
+pub fn parse_recovery(source: &str) -> Option<Recovery> {
+    let mut lines = source.lines();
+    let vcd_path = PathBuf::from(lines.next().filter(|p| !p.is_empty())?);
+    let mut view = SignalView {
+        name: String::from("recovery"),
+        signals: Vec::new(),
+        offsets: Vec::new(),
+        range: None,
+    };
+    // The names and entries so far of the groups the line is inside of
+    let mut groups: Vec<(String, Vec<ViewEntry>)> = Vec::new();
+    for line in lines {
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        let entries = match groups.last_mut() {
+            Some((_, entries)) => entries,
+            None => &mut view.signals,
+        };
+        match fields.as_slice() {
+            ["range", start, end] => {
+                if let (Ok(start), Ok(end)) = (start.parse(), end.parse()) {
+                    view.range = Some(start..end);
+                }
+            }
+            ["signal", signal, radix, offset, index @ ..] if index.len() <= 1 => {
+                let (Some(radix), Ok(offset)) = (parse_radix(radix), offset.parse::<i64>()) else {
+                    continue;
+                };
+                let index = match index.first().map(|index| index.parse::<usize>()) {
+                    Some(Ok(index)) => Some(index),
+                    Some(Err(_)) => continue,
+                    None => None,
+                };
+                entries.push(ViewEntry::Signal(String::from(*signal), radix, index));
+                // Signals in the viewer more than once share their offset
+                if offset != 0 && view.offsets.iter().all(|(name, _)| name != signal) {
+                    view.offsets.push((String::from(*signal), offset));
+                }
+            }
+            ["group", name] => groups.push((String::from(*name), Vec::new())),
+            ["end"] => {
+                let Some((name, group)) = groups.pop() else {
+                    continue;
+                };
+                match groups.last_mut() {
+                    Some((_, entries)) => entries.push(ViewEntry::Group(name, group)),
+                    None => view.signals.push(ViewEntry::Group(name, group)),
+                }
+            }
+            _ => {}
+        }
+    }
+    // Groups cut off before their end still keep their signals
+    while let Some((name, group)) = groups.pop() {
+        match groups.last_mut() {
+            Some((_, entries)) => entries.push(ViewEntry::Group(name, group)),
+            None => view.signals.push(ViewEntry::Group(name, group)),
+        }
+    }
+    Some(Recovery { vcd_path, view })
+}
+
+pub fn load_recovery() -> Option<Recovery> {
+    let source = std::fs::read_to_string(get_recovery_path()?).ok()?;
+    parse_recovery(&source).filter(|recovery| !recovery.view.get_signals().is_empty())
+}
+
+/// Writes the recovery file, or removes it when there are no signals to keep
+pub fn save_recovery(recovery: &Recovery) {
+    let Some(path) = get_recovery_path() else {
+        log::warn!("No config directory to save the recovery file to");
+        return;
+    };
+    if recovery.view.get_signals().is_empty() {
+        remove_recovery();
+        return;
+    }
+    let result = std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+        .and_then(|_| std::fs::write(&path, render_recovery(recovery)));
+    if let Err(err) = result {
+        log::error!("Saving Recovery Error: {err:?}");
+    }
+}
+
+/// Removes the recovery file once a session exits cleanly or the user passes
+/// on restoring it
+pub fn remove_recovery() {
+    *PENDING.lock().unwrap() = None;
+    let Some(path) = get_recovery_path() else {
+        return;
+    };
+    if let Err(err) = std::fs::remove_file(path) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            log::error!("Removing Recovery Error: {err:?}");
+        }
+    }
+}
+
+/// Keeps the newest layout to write on the next save, or when nalu panics
+pub fn set_pending_recovery(recovery: Recovery) {
+    *PENDING.lock().unwrap() = Some(recovery);
+}
+
+/// Writes the layout kept since the last save, if there is one
+pub fn flush_recovery() {
+    // The lock is only poisoned if a panic happened while holding it
+    let pending = match PENDING.lock() {
+        Ok(mut pending) => pending.take(),
+        Err(poisoned) => poisoned.into_inner().take(),
+    };
+    if let Some(recovery) = pending {
+        save_recovery(&recovery);
+    }
+}
+
+#[test]
+fn recovery_test() {
+    use makai_waveform_db::bitvector::BitVectorRadix;
+
+    let recovery = Recovery {
+        vcd_path: PathBuf::from("/sim/top.vcd"),
+        view: SignalView {
+            name: String::from("recovery"),
+            signals: vec![
+                ViewEntry::Signal(String::from("TOP.clk"), BitVectorRadix::Binary, None),
+                ViewEntry::Group(
+                    String::from("core"),
+                    vec![
+                        ViewEntry::Signal(
+                            String::from("TOP.core.pc"),
+                            BitVectorRadix::Hexadecimal,
+                            None,
+                        ),
+                        ViewEntry::Signal(
+                            String::from("TOP.core.pc"),
+                            BitVectorRadix::Hexadecimal,
+                            Some(3),
+                        ),
+                    ],
+                ),
+            ],
+            offsets: vec![(String::from("TOP.core.pc"), -2)],
+            range: Some(10..20),
+        },
+    };
+    let source = render_recovery(&recovery);
+    assert_eq!(
+        source,
+        "/sim/top.vcd\nrange\t10\t20\nsignal\tTOP.clk\tbin\t0\n\
+         group\tcore\nsignal\tTOP.core.pc\thex\t-2\nsignal\tTOP.core.pc\thex\t-2\t3\nend\n"
+    );
+    assert_eq!(
+        parse_recovery(&format!("{source}signal\tTOP.x\tbad\t0\ngarbage\n")),
+        Some(recovery)
+    );
+    assert_eq!(parse_recovery(""), None);
+}
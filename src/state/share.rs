@@ -0,0 +1,141 @@
+use std::ops::Range;
+
+/// Where someone was looking in a dump, shared as a string like
+/// `nalu:/sim/dump.vcd?range=100-200&cursor=150&signal=TOP.core.pc`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedLocation {
+    pub file: Option<String>,
+    pub range: Range<u64>,
+    pub cursor: u64,
+    /// Full name of the selected signal
+    pub signal: Option<String>,
+}
+
+const SCHEME: &str = "nalu:";
+
+/// Escapes the characters that separate the parts of the string
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '%' | '?' | '&' | '=' | '#' => encoded.push_str(&format!("%{:02X}", c as u8)),
+            c if c.is_whitespace() && c.is_ascii() => {
+                encoded.push_str(&format!("%{:02X}", c as u8))
+            }
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+fn decode(text: &str) -> Result<String, String> {
+    let mut decoded = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let escaped = match hex {
+            [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(escaped) => decoded.push(escaped),
+            None => return Err(format!("Invalid escape in {text}")),
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| format!("Invalid escape in {text}"))
+}
+
+impl std::fmt::Display for SharedLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{SCHEME}{}?range={}-{}&cursor={}",
+            self.file.as_deref().map(encode).unwrap_or_default(),
+            self.range.start,
+            self.range.end,
+            self.cursor
+        )?;
+        if let Some(signal) = &self.signal {
+            write!(f, "&signal={}", encode(signal))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for SharedLocation {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let usage = || String::from("Expected nalu:<file>?range=<from>-<to>&cursor=<timestamp>");
+        let text = text.trim().strip_prefix(SCHEME).ok_or_else(usage)?;
+        let (file, query) = text.split_once('?').ok_or_else(usage)?;
+        let parse_timestamp = |timestamp: &str| {
+            timestamp
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid timestamp: {timestamp}"))
+        };
+        let mut range = None;
+        let mut cursor = None;
+        let mut signal = None;
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            match pair.split_once('=') {
+                Some(("range", value)) => {
+                    let (from, to) = value.split_once('-').ok_or_else(usage)?;
+                    let (from, to) = (parse_timestamp(from)?, parse_timestamp(to)?);
+                    if from >= to {
+                        return Err(format!("Empty range: {value}"));
+                    }
+                    range = Some(from..to);
+                }
+                Some(("cursor", value)) => cursor = Some(parse_timestamp(value)?),
+                Some(("signal", value)) => signal = Some(decode(value)?),
+                // Parts added by newer versions are left alone
+                _ => {}
+            }
+        }
+        Ok(Self {
+            file: (!file.is_empty()).then(|| decode(file)).transpose()?,
+            range: range.ok_or_else(usage)?,
+            cursor: cursor.ok_or_else(usage)?,
+            signal,
+        })
+    }
+}
+
+#[test]
+fn shared_location_test() {
+    let location = SharedLocation {
+        file: Some(String::from("/sim/my dump.vcd")),
+        range: 100..200,
+        cursor: 150,
+        signal: Some(String::from("TOP.core.pc")),
+    };
+    let text = location.to_string();
+    assert_eq!(
+        text,
+        "nalu:/sim/my%20dump.vcd?range=100-200&cursor=150&signal=TOP.core.pc"
+    );
+    assert_eq!(text.parse(), Ok(location));
+    let location = SharedLocation {
+        file: None,
+        range: 0..10,
+        cursor: 5,
+        signal: None,
+    };
+    assert_eq!(location.to_string().parse(), Ok(location));
+    assert!("nalu:dump.vcd?range=20-10&cursor=15"
+        .parse::<SharedLocation>()
+        .is_err());
+    assert!("nalu:dump.vcd?range=0-10"
+        .parse::<SharedLocation>()
+        .is_err());
+    assert!("dump.vcd?range=0-10&cursor=5"
+        .parse::<SharedLocation>()
+        .is_err());
+}
@@ -1,7 +1,11 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
 use makai::utils::messages::Messages;
-use makai_vcd_reader::parser::VcdVariable;
-use makai_waveform_db::bitvector::BitVectorRadix;
+use makai_vcd_reader::parser::{VcdHeader, VcdVariable, VcdVariableWidth};
+use makai_waveform_db::{bitvector::BitVectorRadix, Waveform};
 use tui::{
     buffer::Buffer,
     layout::Rect,
@@ -10,20 +14,51 @@ use tui::{
 };
 use tui_tiling::component::ComponentWidget;
 
-use crate::{state::waveform_viewer::WaveformViewerMessage, widgets::browser::*};
+use crate::{
+    state::form::{Form, FormField, FormKind},
+    state::header::HeaderMessage,
+    state::inspector::{render_value, InspectorMessage, InspectorSignal},
+    state::menu::{copy_to_clipboard, ContextMenu, MenuAction, MenuTarget},
+    state::view::{render_signal_list, save_view_file, ListEntry, SignalView, ViewEntry},
+    state::waveform_viewer::WaveformViewerMessage,
+    state::NaluMessage,
+    widgets::browser::*,
+    widgets::minimap::MINIMAP_HEIGHT,
+    widgets::timescale::render_delta,
+};
+
+/// Rows taken up by the analog plot of an expanded real signal
+const PLOT_ROWS: usize = 4;
 
-#[derive(Clone)]
+const RADIXES: [BitVectorRadix; 4] = [
+    BitVectorRadix::Binary,
+    BitVectorRadix::Octal,
+    BitVectorRadix::Decimal,
+    BitVectorRadix::Hexadecimal,
+];
+
+#[derive(Clone, Default)]
 pub enum SignalNode {
+    #[default]
     Spacer,
+    // A labeled line across the waveforms
+    Separator(String),
     Group(String),
     VectorSignal(Vec<String>, VcdVariable, BitVectorRadix, Option<usize>),
+    // A vector put together from single-bit signals, which are its children,
+    // as its full name, idcode and width
+    VirtualVector(Vec<String>, usize, usize, BitVectorRadix),
 }
 
 impl std::fmt::Display for SignalNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Spacer => write!(f, ""),
+            Self::Separator(label) => write!(f, "── {} ──", label),
             Self::Group(name) => write!(f, "{}", name),
+            Self::VirtualVector(path, _, _, _) => {
+                write!(f, "{}", path.last().map_or("", |name| name.as_str()))
+            }
             Self::VectorSignal(_, variable, _, index) => {
                 if let Some(index) = index {
                     write!(f, "{} [{}]", variable, index)
@@ -35,12 +70,6 @@ impl std::fmt::Display for SignalNode {
     }
 }
 
-impl Default for SignalNode {
-    fn default() -> Self {
-        Self::Spacer
-    }
-}
-
 // impl SignalNode {
 //     fn print_path(&self) -> String {
 //         let mut s = String::new();
@@ -57,27 +86,315 @@ impl Default for SignalNode {
 //     }
 // }
 
+/// Drawn before the names of groups and scopes when the tree has guides
+pub const GROUP_ICON: &str = "▣";
+
+/// The symbol drawn before the name of a signal when the tree has guides,
+/// telling single bits, vectors and real signals apart
+pub fn get_variable_icon(variable: &VcdVariable, index: Option<usize>) -> String {
+    String::from(match (variable.get_width(), index) {
+        (VcdVariableWidth::Real, _) => "∿",
+        (_, Some(_)) | (VcdVariableWidth::Vector { width: 1 }, None) => "•",
+        (VcdVariableWidth::Vector { .. }, None) => "≡",
+    })
+}
+
+/// Collects the name, idcode and bit index of every signal under a node,
+/// without descending into the bits of a vector
+fn collect_signals(
+    node: &BrowserNode<SignalNode>,
+    signals: &mut Vec<(String, usize, Option<usize>)>,
+) {
+    for child in node.get_children() {
+        match child.get_entry() {
+            Some(entry @ SignalNode::VectorSignal(_, variable, _, index)) => {
+                signals.push((entry.to_string(), variable.get_idcode(), *index))
+            }
+            _ => collect_signals(child, signals),
+        }
+    }
+}
+
+/// Collects the full name of every signal along with its idcode and bit index,
+/// looking inside of groups but not the bits of vectors
+fn collect_signal_names(
+    node: &BrowserNode<SignalNode>,
+    signals: &mut Vec<(String, usize, Option<usize>)>,
+) {
+    for child in node.get_children() {
+        match child.get_entry() {
+            Some(SignalNode::Group(_)) => collect_signal_names(child, signals),
+            Some(entry) => {
+                if let (Some(name), Some((idcode, index))) =
+                    (get_signal_full_name(entry), get_signal_key(entry))
+                {
+                    signals.push((name, idcode, index));
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// The idcode and bit index a signal is drawn from, where virtual vectors
+/// have their own idcodes
+fn get_signal_key(entry: &SignalNode) -> Option<(usize, Option<usize>)> {
+    match entry {
+        SignalNode::VectorSignal(_, variable, _, index) => Some((variable.get_idcode(), *index)),
+        SignalNode::VirtualVector(_, idcode, _, _) => Some((*idcode, None)),
+        _ => None,
+    }
+}
+
+/// Counts the signals under a node that are already shown further up, without
+/// descending into the bits of a vector
+fn count_duplicates(
+    node: &BrowserNode<SignalNode>,
+    seen: &mut HashSet<(usize, Option<usize>)>,
+) -> usize {
+    let mut duplicates = 0;
+    for child in node.get_children() {
+        match child.get_entry().as_ref().and_then(get_signal_key) {
+            Some(key) => duplicates += usize::from(!seen.insert(key)),
+            None => duplicates += count_duplicates(child, seen),
+        }
+    }
+    duplicates
+}
+
+/// Removes the signals under a node that are already shown further up, leaving
+/// the groups they were in behind
+fn remove_duplicates(
+    node: &mut BrowserNode<SignalNode>,
+    seen: &mut HashSet<(usize, Option<usize>)>,
+) {
+    node.get_children_mut().retain_mut(|child| {
+        match child.get_entry().as_ref().and_then(get_signal_key) {
+            Some(key) => seen.insert(key),
+            None => {
+                remove_duplicates(child, seen);
+                true
+            }
+        }
+    });
+}
+
+/// Collects the entries of the exported signal list, without descending into
+/// the bits of a vector
+fn collect_list_entries(node: &BrowserNode<SignalNode>) -> Vec<ListEntry> {
+    node.get_children()
+        .iter()
+        .filter_map(|child| match child.get_entry().as_ref()? {
+            SignalNode::VectorSignal(path, variable, radix, index) => {
+                let mut full_name = path[..path.len().saturating_sub(1)].to_vec();
+                full_name.push(variable.get_name().clone());
+                Some(ListEntry::Signal(full_name.join("."), *radix, *index))
+            }
+            SignalNode::VirtualVector(full_name, _, _, radix) => {
+                let bits = child
+                    .get_children()
+                    .iter()
+                    .filter_map(|bit| match bit.get_entry() {
+                        Some(SignalNode::VectorSignal(path, variable, _, _)) => {
+                            let mut full_name = path[..path.len().saturating_sub(1)].to_vec();
+                            full_name.push(variable.get_name().clone());
+                            Some(full_name.join("."))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                Some(ListEntry::Virtual(full_name.join("."), *radix, bits))
+            }
+            SignalNode::Group(name) => {
+                Some(ListEntry::Group(name.clone(), collect_list_entries(child)))
+            }
+            SignalNode::Separator(label) => Some(ListEntry::Separator(label.clone())),
+            SignalNode::Spacer => Some(ListEntry::Spacer),
+        })
+        .collect()
+}
+
+/// The full name of a signal, without the index of a bit
+fn get_signal_full_name(entry: &SignalNode) -> Option<String> {
+    match entry {
+        SignalNode::VectorSignal(path, variable, _, _) => {
+            let mut full_name = path[..path.len().saturating_sub(1)].to_vec();
+            full_name.push(variable.get_name().clone());
+            Some(full_name.join("."))
+        }
+        SignalNode::VirtualVector(full_name, _, _, _) => Some(full_name.join(".")),
+        _ => None,
+    }
+}
+
+/// The full name of a signal in the tooltips of the waveform viewer, with the
+/// index of a single bit
+fn get_entry_name(entry: &SignalNode) -> String {
+    let full_name = get_signal_full_name(entry).unwrap_or_default();
+    match entry {
+        SignalNode::VectorSignal(_, _, _, Some(index)) => format!("{full_name} [{index}]"),
+        _ => full_name,
+    }
+}
+
+/// Finds the path to the first signal with the full name, descending into
+/// groups but not into the bits of vectors
+fn find_signal_path(
+    node: &BrowserNode<SignalNode>,
+    full_name: &str,
+    path: &mut Vec<usize>,
+) -> bool {
+    for (i, child) in node.get_children().iter().enumerate() {
+        path.push(i);
+        let found = match child.get_entry() {
+            Some(SignalNode::Group(_)) => find_signal_path(child, full_name, path),
+            Some(entry) => get_signal_full_name(entry).as_deref() == Some(full_name),
+            None => false,
+        };
+        if found {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// The first group with the name, looking inside of other groups
+fn find_group<'a>(
+    node: &'a BrowserNode<SignalNode>,
+    name: &str,
+) -> Option<&'a BrowserNode<SignalNode>> {
+    node.get_children()
+        .iter()
+        .find_map(|child| match child.get_entry() {
+            Some(SignalNode::Group(group)) if group == name => Some(child),
+            Some(SignalNode::Group(_)) => find_group(child, name),
+            _ => None,
+        })
+}
+
+/// The signals and single bits of a view and the groups around them, leaving
+/// out everything else like separators and the bits inside of vectors
+fn collect_view_entries(node: &BrowserNode<SignalNode>) -> Vec<ViewEntry> {
+    node.get_children()
+        .iter()
+        .filter_map(|child| match child.get_entry().as_ref()? {
+            SignalNode::VectorSignal(path, variable, radix, index) => {
+                let mut full_name = path[..path.len().saturating_sub(1)].to_vec();
+                full_name.push(variable.get_name().clone());
+                Some(ViewEntry::Signal(full_name.join("."), *radix, *index))
+            }
+            SignalNode::Group(name) => {
+                Some(ViewEntry::Group(name.clone(), collect_view_entries(child)))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_view_signals(
+    node: &BrowserNode<SignalNode>,
+    signals: &mut Vec<(String, BitVectorRadix, usize)>,
+) {
+    for child in node.get_children() {
+        match child.get_entry() {
+            Some(SignalNode::VectorSignal(path, variable, radix, _)) => {
+                let mut full_name = path[..path.len().saturating_sub(1)].to_vec();
+                full_name.push(variable.get_name().clone());
+                signals.push((full_name.join("."), *radix, variable.get_idcode()));
+            }
+            Some(SignalNode::Group(_)) => collect_view_signals(child, signals),
+            _ => {}
+        }
+    }
+}
+
+/// Collects the signals anywhere inside of a group along with their offsets,
+/// which a collapsed group shows the activity of
+fn collect_group_signals(
+    node: &BrowserNode<SignalNode>,
+    offsets: &HashMap<usize, i64>,
+    signals: &mut Vec<(usize, Option<usize>, i64)>,
+) {
+    for child in node.get_children() {
+        match child.get_entry() {
+            Some(SignalNode::VectorSignal(_, variable, _, index)) => {
+                let idcode = variable.get_idcode();
+                let offset = offsets.get(&idcode).copied().unwrap_or(0);
+                signals.push((idcode, *index, offset));
+            }
+            Some(SignalNode::VirtualVector(_, idcode, _, _)) => signals.push((*idcode, None, 0)),
+            Some(SignalNode::Group(_)) => collect_group_signals(child, offsets, signals),
+            _ => {}
+        }
+    }
+}
+
+/// Renders how far a signal is shifted like "+2.0ns" or "-500ps"
+fn render_offset(offset: i64, timescale: i32) -> String {
+    match offset {
+        offset if offset < 0 => render_delta(offset.unsigned_abs(), 0, timescale),
+        offset => render_delta(0, offset as u64, timescale),
+    }
+}
+
+fn is_real(variable: &VcdVariable) -> bool {
+    *variable.get_width() == VcdVariableWidth::Real
+}
+
+/// Creates the node of a signal, where vectors can be expanded into their bits
+/// and reals into an analog plot taking up the spacer rows below them. The bits
+/// are only created once the vector is first expanded with [`create_bits`],
+/// since wide buses would otherwise carry thousands of nodes nobody looks at
 fn create_variable_node(
     path: Vec<String>,
     variable: VcdVariable,
     radix: BitVectorRadix,
 ) -> BrowserNode<SignalNode> {
+    let is_plot = is_real(&variable);
+    let is_vector = variable.get_bit_width() > 1 && !is_plot;
+    let entry = Some(SignalNode::VectorSignal(path, variable, radix, None));
+    if is_plot {
+        let rows = (1..PLOT_ROWS)
+            .map(|_| BrowserNode::new(Some(SignalNode::Spacer)))
+            .collect();
+        BrowserNode::from(entry, rows)
+    } else if is_vector {
+        BrowserNode::deferred(entry)
+    } else {
+        BrowserNode::new(entry)
+    }
+}
+
+/// Creates the bits of a vector the first time it is expanded, in the radix
+/// the vector has by then
+fn create_bits(node: &mut BrowserNode<SignalNode>) {
+    if !node.is_deferred() {
+        return;
+    }
+    let Some(SignalNode::VectorSignal(path, variable, radix, None)) = node.get_entry() else {
+        return;
+    };
+    let bits = (0..variable.get_bit_width())
+        .map(|i| SignalNode::VectorSignal(path.clone(), variable.clone(), *radix, Some(i)))
+        .map(|n| BrowserNode::new(Some(n)))
+        .collect();
+    node.set_children(bits);
+}
+
+/// Creates the node of a virtual vector with its bits below it
+fn create_virtual_node(
+    full_name: Vec<String>,
+    idcode: usize,
+    bits: Vec<(Vec<String>, VcdVariable)>,
+    radix: BitVectorRadix,
+) -> BrowserNode<SignalNode> {
+    let width = bits.len();
     BrowserNode::from(
-        Some(SignalNode::VectorSignal(
-            path.clone(),
-            variable.clone(),
-            radix,
-            None,
-        )),
-        if variable.get_bit_width() > 1 {
-            (0..variable.get_bit_width())
-                .into_iter()
-                .map(|i| SignalNode::VectorSignal(path.clone(), variable.clone(), radix, Some(i)))
-                .map(|n| BrowserNode::new(Some(n)))
-                .collect()
-        } else {
-            Vec::new()
-        },
+        Some(SignalNode::VirtualVector(full_name, idcode, width, radix)),
+        bits.into_iter()
+            .map(|(path, variable)| create_variable_node(path, variable, radix))
+            .collect(),
     )
 }
 
@@ -86,25 +403,171 @@ enum ListAction {
     Group,
     Delete,
     Expand,
+    Pin,
 }
 
 #[derive(Debug, Clone)]
 pub struct SignalViewerEntry {
+    /// Full name, with the index of a single bit
+    pub(crate) name: String,
     pub(crate) idcode: usize,
     pub(crate) index: Option<usize>,
     pub(crate) radix: BitVectorRadix,
+    pub(crate) is_real: bool,
+    /// Timestamps the signal is drawn later by, or earlier when negative
+    pub(crate) offset: i64,
+    /// Row out of the rows of the analog plot of a real signal, or out of
+    /// the rows of a signal taller than one row
+    pub(crate) plot_row: Option<(usize, usize)>,
     pub(crate) is_selected: bool,
+    pub(crate) is_primary: bool,
+}
+
+/// A row of the signal viewer for the waveform viewer to draw next to it
+#[derive(Debug, Clone)]
+pub enum SignalViewerRow {
+    Signal(SignalViewerEntry),
+    Separator(String),
+    // A collapsed group as the idcode, bit and offset of every signal inside
+    Group(Vec<(usize, Option<usize>, i64)>),
+}
+
+/// A signal of a view found in the dump with its path and radix, and the index
+/// when it is a single bit, or a group
+pub(crate) enum ViewNode {
+    Signal(Vec<String>, VcdVariable, BitVectorRadix, Option<usize>),
+    Group(String, Vec<ViewNode>),
+}
+
+/// The variables of the signals of a view, skipping any that are not in the
+/// dump but keeping its groups even when none of their signals are
+pub(crate) fn get_view_nodes(
+    header: &VcdHeader,
+    view: &str,
+    entries: &[ViewEntry],
+) -> Vec<ViewNode> {
+    let mut nodes = Vec::new();
+    for entry in entries {
+        match entry {
+            ViewEntry::Signal(full_name, radix, index) => {
+                let Some(variable) = header.get_variable(full_name) else {
+                    log::warn!("View {view:?} signal not found: {full_name}");
+                    continue;
+                };
+                if index.is_some_and(|index| index >= variable.get_bit_width()) {
+                    log::warn!("View {view:?} bit not found: {full_name} [{index:?}]");
+                    continue;
+                }
+                let mut path = full_name
+                    .split('.')
+                    .map(String::from)
+                    .collect::<Vec<String>>();
+                path.pop();
+                path.push(variable.to_string());
+                nodes.push(ViewNode::Signal(path, variable.clone(), *radix, *index));
+            }
+            ViewEntry::Group(group, entries) => nodes.push(ViewNode::Group(
+                group.clone(),
+                get_view_nodes(header, view, entries),
+            )),
+        }
+    }
+    nodes
 }
 
 pub(crate) enum SignalViewerMessage {
     NetlistAppend(Vec<String>, VcdVariable),
     NetlistInsert(Vec<String>, VcdVariable),
+    // Appends into the top level group with this name, creating it if needed
+    NetlistAppendGroup(String, Vec<(Vec<String>, VcdVariable)>),
+    // Writes the signal list out to this file
+    Export(String),
+    // Writes the group with the name out as a view to this python file, or to
+    // the one it was loaded from
+    SaveGroup(String, Option<String>),
+    // Appends the signals of a view from another python file as a group named
+    // after it, shifting the variables with the idcodes in time
+    AppendView(String, Vec<ViewNode>, Vec<(usize, i64)>, PathBuf),
+    // Appends each signal with its own radix
+    AppendSignals(Vec<(Vec<String>, VcdVariable, BitVectorRadix)>),
+    // Appends a virtual vector with this full name and idcode made of the bits
+    AppendVirtualVector(Vec<String>, usize, Vec<(Vec<String>, VcdVariable)>),
     WaveformKey(KeyEvent),
+    SaveView(String),
+    // Collects the selected signal for the location to share
+    Share,
+    // Selects the first signal with this full name
+    SelectSignal(String),
+    // Collects the signals to recover after a crash
+    Autosave,
+    // Keeps the signals being replaced to undo back to
+    LoadView(Vec<ViewNode>, Vec<(usize, i64)>, bool),
+    FindGlitches(u64, bool),
+    TraceUnknown(bool),
+    // Also checks the signals of the dump that are not in the viewer
+    ListUnknown(bool),
+    MeasureDelays,
+    // Also places named cursors at the shortest and longest periods
+    MeasurePeriod(bool),
+    OpenVcd(PathBuf, bool),
+    UpdateWaveform(Arc<Waveform>, i32),
+    SetCursor(u64),
+    SetMarker(Option<u64>),
+    UndoLoad,
+    SetWaveformHeight(isize),
+    MenuAction(MenuAction),
+    // Expands or collapses the whole tree
+    ExpandAll(bool),
+    // Inserts a separator with this label, or a blank spacer without one
+    InsertSeparator(Option<String>),
+    // Moves the selected entries into a new group with this name
+    GroupSelected(String),
+    // Renames the selected group or separator
+    RenameSelected(String),
+    // Removes every signal but the first from the list
+    RemoveDuplicates,
+    // Shifts the selected signals by this many timestamps, or back when zero
+    SetOffset(i64),
+    // Changes the radix of everything selected
+    SetRadix(BitVectorRadix),
+    // Changes how many rows the selected signals take up
+    SetHeight(usize),
+    // Draws guide lines and icons in the tree
+    SetTree(bool),
 }
 
+/// Signals, pinned signals and offsets kept to undo a load back to
+type SignalViewerUndo = (
+    Vec<BrowserNode<SignalNode>>,
+    Vec<SignalNode>,
+    HashMap<usize, i64>,
+);
+
 pub struct SignalViewerState {
     browser: BrowserState,
     node: BrowserNode<SignalNode>,
+    // Signals always shown at the top of the viewer regardless of scrolling
+    pinned: Vec<SignalNode>,
+    // Copies of the nodes yanked last, pasted as many times as needed
+    yanked: Vec<BrowserNode<SignalNode>>,
+    // Signals and pinned signals from before the last view or file was loaded
+    undo: Option<SignalViewerUndo>,
+    // Timestamps each variable is shifted by when drawn, by idcode
+    offsets: HashMap<usize, i64>,
+    // Python files the groups appended from other configs came from, by the
+    // name of the group, which they are saved back to
+    group_sources: HashMap<String, PathBuf>,
+    // Signals in the list more than once, warned about in the header
+    duplicates: usize,
+    height: isize,
+    // Inner height of the waveform viewer, as the rows have to line up with it
+    waveform_height: isize,
+    // For showing the values at the cursor and marker side by side
+    waveform: Arc<Waveform>,
+    timescale: i32,
+    cursor: u64,
+    marker: Option<u64>,
+    type_ahead: TypeAhead,
     messages: Messages,
 }
 
@@ -113,33 +576,583 @@ impl SignalViewerState {
         Self {
             browser: BrowserState::new(true, true, false),
             node: BrowserNode::from_expanded(None, true, Vec::new()),
+            pinned: Vec::new(),
+            yanked: Vec::new(),
+            undo: None,
+            offsets: HashMap::new(),
+            group_sources: HashMap::new(),
+            duplicates: 0,
+            height: 0,
+            waveform_height: isize::MAX,
+            waveform: Arc::new(Waveform::default()),
+            timescale: 0,
+            cursor: 0,
+            marker: None,
+            type_ahead: TypeAhead::default(),
             messages,
         }
     }
 
+    fn toggle_pin(&mut self) {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        let Some(node) = self.node.get_node(&path) else {
+            return;
+        };
+        let Some(SignalNode::VectorSignal(_, variable, _, index)) = node.get_entry() else {
+            return;
+        };
+        let position = self.pinned.iter().position(|p| match p {
+            SignalNode::VectorSignal(_, pinned_variable, _, pinned_index) => {
+                pinned_variable.get_idcode() == variable.get_idcode() && pinned_index == index
+            }
+            _ => false,
+        });
+        match position {
+            Some(position) => {
+                self.pinned.remove(position);
+            }
+            None => self.pinned.push(node.get_entry().clone().unwrap()),
+        }
+        self.update_height();
+    }
+
+    fn update_height(&mut self) {
+        let pinned_height = get_pinned_height(self.pinned.len()) as isize;
+        let height = self.height.min(self.waveform_height);
+        self.browser
+            .set_height((height - MINIMAP_HEIGHT as isize - pinned_height).max(0));
+        self.browser.scroll_relative(&self.node, 0);
+    }
+
     fn browser_request_append(
         &mut self,
         path: Vec<String>,
         variable: VcdVariable,
         radix: BitVectorRadix,
     ) {
+        self.messages
+            .push(NaluMessage::LoadSignal(variable.get_idcode()));
         self.node
             .get_children_mut()
             .push(create_variable_node(path, variable, radix));
         self.push_request();
     }
 
+    fn browser_request_append_group(
+        &mut self,
+        name: String,
+        signals: Vec<(Vec<String>, VcdVariable)>,
+        radix: BitVectorRadix,
+    ) {
+        let mut nodes = Vec::new();
+        for (path, variable) in signals {
+            self.messages
+                .push(NaluMessage::LoadSignal(variable.get_idcode()));
+            nodes.push(create_variable_node(path, variable, radix));
+        }
+        let children = self.node.get_children_mut();
+        let group = children.iter_mut().find(
+            |child| matches!(child.get_entry(), Some(SignalNode::Group(group)) if *group == name),
+        );
+        match group {
+            Some(group) => group.get_children_mut().extend(nodes),
+            None => children.push(BrowserNode::from_expanded(
+                Some(SignalNode::Group(name)),
+                true,
+                nodes,
+            )),
+        }
+        self.push_request();
+    }
+
+    fn append_virtual_vector(
+        &mut self,
+        full_name: Vec<String>,
+        idcode: usize,
+        bits: Vec<(Vec<String>, VcdVariable)>,
+    ) {
+        self.node.get_children_mut().push(create_virtual_node(
+            full_name,
+            idcode,
+            bits,
+            BitVectorRadix::Hexadecimal,
+        ));
+        self.push_request();
+    }
+
+    fn browser_request_insert(
+        &mut self,
+        path: Vec<String>,
+        variable: VcdVariable,
+        radix: BitVectorRadix,
+    ) {
+        self.messages
+            .push(NaluMessage::LoadSignal(variable.get_idcode()));
+        self.insert_node(create_variable_node(path, variable, radix));
+    }
+
+    fn insert_separator(&mut self, label: Option<String>) {
+        let entry = match label {
+            Some(label) => SignalNode::Separator(label),
+            None => SignalNode::Spacer,
+        };
+        self.insert_node(BrowserNode::new(Some(entry)));
+    }
+
+    fn insert_node(&mut self, node: BrowserNode<SignalNode>) {
+        // Insert after the selected node, but never inside of a vector
+        let mut selected = self.browser.get_primary_selected_path(&self.node).to_vec();
+        while selected.len() > 1 {
+            let parent = BrowserNodePath::new(selected[..selected.len() - 1].to_vec());
+            match self.node.get_node(&parent).unwrap().get_entry() {
+                Some(
+                    SignalNode::VectorSignal(_, _, _, _) | SignalNode::VirtualVector(_, _, _, _),
+                ) => {
+                    selected.pop();
+                }
+                _ => break,
+            }
+        }
+        let Some(index) = selected.pop() else {
+            self.node.get_children_mut().push(node);
+            self.push_request();
+            return;
+        };
+        let parent = if selected.is_empty() {
+            &mut self.node
+        } else {
+            self.node
+                .get_node_mut(&BrowserNodePath::new(selected.clone()))
+                .unwrap()
+        };
+        let index = (index + 1).min(parent.get_children().len());
+        parent.get_children_mut().insert(index, node);
+        // Select the new node so consecutive inserts keep their order
+        selected.push(index);
+        self.browser
+            .select_path(&self.node, &BrowserNodePath::new(selected));
+        self.push_request();
+    }
+
+    /// Full names of the signals in the view that are shifted in time, each
+    /// only once even if the signal is in the viewer more than once
+    fn get_view_offsets(&self, node: &BrowserNode<SignalNode>) -> Vec<(String, i64)> {
+        let mut signals = Vec::new();
+        collect_view_signals(node, &mut signals);
+        let mut offsets: Vec<(String, i64)> = Vec::new();
+        for (full_name, _, idcode) in signals {
+            let Some(offset) = self.offsets.get(&idcode) else {
+                continue;
+            };
+            if offsets.iter().all(|(name, _)| *name != full_name) {
+                offsets.push((full_name, *offset));
+            }
+        }
+        offsets
+    }
+
+    /// Collects the full names of the signals for the waveform viewer to add
+    /// its zoom range to
+    fn save_view(&self, name: String) {
+        self.messages
+            .push(WaveformViewerMessage::SaveView(self.get_view(name)));
+    }
+
+    /// Selects the first signal with the full name, looking inside of groups
+    /// but not the bits of vectors
+    fn select_signal(&mut self, full_name: &str) {
+        let mut path = Vec::new();
+        if find_signal_path(&self.node, full_name, &mut path) {
+            self.browser
+                .select_visible_path(&self.node, &BrowserNodePath::new(path));
+            self.push_request();
+        } else {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "No signal named {full_name} in the viewer"
+            )));
+        }
+    }
+
+    fn export(&self, path: String) {
+        let list = render_signal_list(&collect_list_entries(&self.node));
+        if let Err(err) = std::fs::write(&path, list) {
+            log::error!("Export Error: {err:?}");
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "Could not write {path}: {err}"
+            )));
+        }
+    }
+
+    fn get_view(&self, name: String) -> SignalView {
+        SignalView {
+            name,
+            signals: collect_view_entries(&self.node),
+            offsets: self.get_view_offsets(&self.node),
+            range: None,
+        }
+    }
+
+    /// Saves the signals of a group as a view named after it in a python file
+    /// of its own, which can be shared and imported into other scripts, by
+    /// default the file the group was appended from
+    fn save_group(&mut self, group: String, path: Option<String>) {
+        let Some(node) = find_group(&self.node, &group) else {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "No group named {group} in the viewer"
+            )));
+            return;
+        };
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match self.group_sources.get(&group) {
+                Some(path) => path.clone(),
+                None => {
+                    self.messages.push(NaluMessage::PaletteError(format!(
+                        "Give a python file to save {group} to"
+                    )));
+                    return;
+                }
+            },
+        };
+        let view = SignalView {
+            signals: collect_view_entries(node),
+            offsets: self.get_view_offsets(node),
+            name: group,
+            range: None,
+        };
+        match save_view_file(&path, &view) {
+            Ok(()) => {
+                self.group_sources.insert(view.name.clone(), path);
+                self.messages.push(NaluMessage::GroupSaved(view));
+            }
+            Err(err) => {
+                log::error!("Saving Group Error: {err:?}");
+                self.messages.push(NaluMessage::PaletteError(format!(
+                    "Could not write {}: {err}",
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    /// Appends the signals of a view loaded from another python file as an
+    /// expanded group named after the view, keeping the signals already here
+    fn append_view(
+        &mut self,
+        name: String,
+        signals: Vec<ViewNode>,
+        offsets: Vec<(usize, i64)>,
+        path: PathBuf,
+    ) {
+        let nodes = self.create_view_nodes(signals);
+        self.offsets
+            .extend(offsets.into_iter().filter(|(_, offset)| *offset != 0));
+        self.node
+            .get_children_mut()
+            .push(BrowserNode::from_expanded(
+                Some(SignalNode::Group(name.clone())),
+                true,
+                nodes,
+            ));
+        self.group_sources.insert(name, path);
+        self.push_request();
+    }
+
+    /// Hands over the signals to find again in a different file if they are
+    /// kept, pinned signals are always dropped
+    fn open_vcd(&mut self, path: PathBuf, keep: bool) {
+        let signals = match keep {
+            true => collect_view_entries(&self.node),
+            false => Vec::new(),
+        };
+        self.take_undo();
+        self.messages.push(NaluMessage::OpenVcd(path, signals));
+    }
+
+    /// Moves the signals out of the viewer to be restored by an undo
+    fn take_undo(&mut self) {
+        self.undo = Some((
+            std::mem::take(self.node.get_children_mut()),
+            std::mem::take(&mut self.pinned),
+            std::mem::take(&mut self.offsets),
+        ));
+        self.update_height();
+    }
+
+    /// Swaps the signals from before the last load back in, so undoing twice
+    /// goes back to the loaded signals
+    fn undo_load(&mut self) {
+        let Some((children, pinned, offsets)) = self.undo.take() else {
+            self.messages
+                .push(NaluMessage::PaletteError(String::from("Nothing to undo")));
+            return;
+        };
+        self.take_undo();
+        *self.node.get_children_mut() = children;
+        self.pinned = pinned;
+        self.offsets = offsets;
+        let mut signals = Vec::new();
+        collect_signals(&self.node, &mut signals);
+        for (_, idcode, _) in signals {
+            self.messages.push(NaluMessage::LoadSignal(idcode));
+        }
+        for pinned in &self.pinned {
+            if let SignalNode::VectorSignal(_, variable, _, _) = pinned {
+                self.messages
+                    .push(NaluMessage::LoadSignal(variable.get_idcode()));
+            }
+        }
+        self.update_height();
+        self.browser.select_relative(&self.node, 0, true);
+        self.push_request();
+    }
+
+    /// Creates the signals of a view along with its groups, which start out
+    /// expanded
+    fn create_view_nodes(&mut self, signals: Vec<ViewNode>) -> Vec<BrowserNode<SignalNode>> {
+        let mut nodes = Vec::new();
+        for signal in signals {
+            match signal {
+                ViewNode::Signal(path, variable, radix, None) => {
+                    self.messages
+                        .push(NaluMessage::LoadSignal(variable.get_idcode()));
+                    nodes.push(create_variable_node(path, variable, radix));
+                }
+                ViewNode::Signal(path, variable, radix, Some(index)) => {
+                    self.messages
+                        .push(NaluMessage::LoadSignal(variable.get_idcode()));
+                    let bit = SignalNode::VectorSignal(path, variable, radix, Some(index));
+                    nodes.push(BrowserNode::new(Some(bit)));
+                }
+                ViewNode::Group(name, signals) => {
+                    let children = self.create_view_nodes(signals);
+                    nodes.push(BrowserNode::from_expanded(
+                        Some(SignalNode::Group(name)),
+                        true,
+                        children,
+                    ));
+                }
+            }
+        }
+        nodes
+    }
+
+    fn load_view(&mut self, signals: Vec<ViewNode>, offsets: Vec<(usize, i64)>, undo: bool) {
+        // Stay on the selected signal when the view has it too
+        let selected = self
+            .node
+            .get_full_name(&self.browser.get_primary_selected_path(&self.node));
+        if undo {
+            self.undo = Some((
+                std::mem::take(self.node.get_children_mut()),
+                self.pinned.clone(),
+                self.offsets.clone(),
+            ));
+        }
+        self.offsets = offsets.into_iter().filter(|(_, o)| *o != 0).collect();
+        *self.node.get_children_mut() = self.create_view_nodes(signals);
+        let path = self.node.find_full_name(&selected);
+        self.browser.select_visible_path(&self.node, &path);
+        self.push_request();
+    }
+
+    /// Shifts every variable with a selected signal (or bit of it) in time,
+    /// which applies wherever else the variable is in the viewer too
+    fn set_offset(&mut self, offset: i64) {
+        let idcodes = self
+            .browser
+            .get_selected_paths(&self.node, false)
+            .iter()
+            .filter_map(|path| match self.node.get_node(path)?.get_entry() {
+                Some(SignalNode::VectorSignal(_, variable, _, _)) => Some(variable.get_idcode()),
+                _ => None,
+            })
+            .collect::<Vec<usize>>();
+        if idcodes.is_empty() {
+            self.messages.push(NaluMessage::PaletteError(String::from(
+                "No signal selected",
+            )));
+            return;
+        }
+        for idcode in idcodes {
+            match offset {
+                0 => self.offsets.remove(&idcode),
+                offset => self.offsets.insert(idcode, offset),
+            };
+        }
+        self.push_request();
+    }
+
+    fn get_offset(&self, variable: &VcdVariable) -> i64 {
+        self.offsets
+            .get(&variable.get_idcode())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn get_selected_signal(&self) -> Option<(String, usize, Option<usize>)> {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        match self.node.get_node(&path)?.get_entry() {
+            Some(entry @ SignalNode::VectorSignal(_, variable, _, index)) => {
+                Some((entry.to_string(), variable.get_idcode(), *index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Picks the signals to check for glitches, which the top level state
+    /// scans since it holds the waveform
+    fn find_glitches(&self, width: u64, all: bool) {
+        let mut signals = Vec::new();
+        if all {
+            collect_signals(&self.node, &mut signals);
+        } else {
+            signals.extend(self.get_selected_signal());
+        }
+        self.messages
+            .push(NaluMessage::FindGlitches(signals, width));
+    }
+
+    /// Sends the selected signals in order for the waveform viewer to add
+    /// the visible range to
+    fn measure_delays(&self) {
+        let signals = self
+            .browser
+            .get_selected_paths(&self.node, false)
+            .iter()
+            .filter_map(|path| match self.node.get_node(path)?.get_entry() {
+                Some(entry @ SignalNode::VectorSignal(_, variable, _, index)) => {
+                    Some((entry.to_string(), variable.get_idcode(), *index))
+                }
+                _ => None,
+            })
+            .collect();
+        self.messages
+            .push(WaveformViewerMessage::MeasureDelays(signals));
+    }
+
+    fn measure_period(&self, cursors: bool) {
+        match self.get_selected_signal() {
+            Some((name, idcode, index)) => self.messages.push(
+                WaveformViewerMessage::MeasurePeriod(name, idcode, index, cursors),
+            ),
+            None => self.messages.push(NaluMessage::PaletteError(String::from(
+                "Select a single-bit signal to measure its period",
+            ))),
+        }
+    }
+
+    fn trace_unknown(&self, all: bool) {
+        let mut others = Vec::new();
+        if all {
+            collect_signals(&self.node, &mut others);
+        }
+        self.messages.push(NaluMessage::TraceUnknown(
+            self.get_selected_signal(),
+            others,
+        ));
+    }
+
+    fn list_unknown(&self, all: bool) {
+        let mut signals = Vec::new();
+        collect_signal_names(&self.node, &mut signals);
+        self.messages
+            .push(NaluMessage::ListUnknownSignals(signals, all));
+    }
+
     pub fn set_size(&mut self, size: &Rect, border_width: u16) {
         // Handle extra room above/below hierarchy in browser
         let margin = border_width as isize * 2;
-        self.browser
-            .set_height((size.height as isize - margin).max(0));
-        self.browser.scroll_relative(&self.node, 0);
+        self.height = (size.height as isize - margin).max(0);
+        self.update_height();
         self.push_request();
     }
 
+    /// Values at the cursor and at the marker of every visible signal, padded
+    /// to line up in columns
+    fn get_marker_values(&self, marker: u64) -> HashMap<(usize, Option<usize>), (String, bool)> {
+        let mut values = HashMap::new();
+        for path in self.browser.get_visible_paths(&self.node) {
+            let Some(SignalNode::VectorSignal(_, variable, radix, index)) = self
+                .node
+                .get_node(&path)
+                .and_then(|node| node.get_entry().as_ref())
+            else {
+                continue;
+            };
+            let get_value = |timestamp| {
+                render_value(
+                    &self.waveform,
+                    variable.get_idcode(),
+                    *index,
+                    *radix,
+                    is_real(variable),
+                    timestamp,
+                )
+                .unwrap_or(String::from("-"))
+            };
+            values.insert(
+                (variable.get_idcode(), *index),
+                (get_value(self.cursor), get_value(marker)),
+            );
+        }
+        let width = values
+            .values()
+            .map(|(cursor, marker)| cursor.len().max(marker.len()))
+            .max()
+            .unwrap_or(0);
+        values
+            .into_iter()
+            .map(|(key, (cursor, marker))| {
+                let column = format!("{cursor:>width$} {marker:>width$}");
+                (key, (column, cursor != marker))
+            })
+            .collect()
+    }
+
+    /// Typing ahead takes every key, even the ones of global actions
+    pub fn is_typing_ahead(&self) -> bool {
+        self.type_ahead.is_active()
+    }
+
     pub fn get_browser(&self) -> Browser<'_, SignalNode> {
-        Browser::new(&self.browser, &self.node)
+        let browser = Browser::new(&self.browser, &self.node)
+            .spacing(MINIMAP_HEIGHT as u16)
+            .pinned(self.pinned.iter().map(|p| p.to_string()).collect())
+            .suffix(|node| match node {
+                SignalNode::VectorSignal(_, variable, _, None) => match self.get_offset(variable) {
+                    0 => String::new(),
+                    offset => format!("({})", render_offset(offset, self.timescale)),
+                },
+                _ => String::new(),
+            })
+            .icon(|node| match node {
+                SignalNode::Spacer | SignalNode::Separator(_) => String::new(),
+                SignalNode::Group(_) => String::from(GROUP_ICON),
+                SignalNode::VectorSignal(_, variable, _, index) => {
+                    get_variable_icon(variable, *index)
+                }
+                SignalNode::VirtualVector(..) => String::from("≡"),
+            });
+        let Some(marker) = self.marker else {
+            return browser;
+        };
+        let values = Arc::new(self.get_marker_values(marker));
+        let highlights = values.clone();
+        browser
+            .column(move |node| match node {
+                SignalNode::VectorSignal(_, variable, _, index) => values
+                    .get(&(variable.get_idcode(), *index))
+                    .map(|(column, _)| column.clone())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            })
+            .column_highlight(move |node| match node {
+                SignalNode::VectorSignal(_, variable, _, index) => highlights
+                    .get(&(variable.get_idcode(), *index))
+                    .is_some_and(|(_, changed)| *changed),
+                _ => false,
+            })
     }
 
     pub fn get_browser_state(&self) -> &BrowserState {
@@ -164,48 +1177,517 @@ impl SignalViewerState {
         // };
 
         match action {
-            ListAction::Group => {}
-            ListAction::Delete => {}
+            ListAction::Group => self.messages.push(NaluMessage::OpenForm(Form::new(
+                "Group Selected",
+                FormKind::GroupName,
+                vec![
+                    FormField::Text(String::from("Name"), String::from("group")),
+                    FormField::Button(String::from("Group")),
+                    FormField::Button(String::from("Cancel")),
+                ],
+            ))),
+            ListAction::Delete => self.delete_selected(),
             ListAction::Expand => {
                 let path = self.browser.get_primary_selected_path(&self.node);
                 if let Some(node) = self.node.get_node_mut(&path) {
+                    create_bits(node);
                     node.set_expanded(!node.is_expanded());
                 }
             }
+            ListAction::Pin => self.toggle_pin(),
+        }
+        self.push_request();
+    }
+
+    /// Removes the selected signals and groups, except for the bits of vectors
+    /// and the rows of plots which only go away along with their signal
+    fn delete_selected(&mut self) {
+        let paths = self.browser.get_selected_paths(&self.node, true);
+        for path in paths.into_iter().rev() {
+            let mut path = path.to_vec();
+            let Some(index) = path.pop() else {
+                continue;
+            };
+            let parent = match path.is_empty() {
+                true => &mut self.node,
+                false => match self.node.get_node_mut(&BrowserNodePath::new(path)) {
+                    Some(parent) => parent,
+                    None => continue,
+                },
+            };
+            if matches!(
+                parent.get_entry(),
+                Some(SignalNode::VectorSignal(_, _, _, _) | SignalNode::VirtualVector(_, _, _, _))
+            ) {
+                continue;
+            }
+            if index < parent.get_children().len() {
+                parent.get_children_mut().remove(index);
+            }
+        }
+        self.browser.select_relative(&self.node, 0, true);
+    }
+
+    /// Keeps the first of each signal in the list, in whatever group it is in
+    fn remove_duplicates(&mut self) {
+        remove_duplicates(&mut self.node, &mut HashSet::new());
+        self.browser.select_relative(&self.node, 0, true);
+        self.push_request();
+    }
+
+    /// Asks for a new name for the selected group, or a new label for the
+    /// selected separator
+    fn open_rename(&self) {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        let entry = self
+            .node
+            .get_node(&path)
+            .and_then(|n| n.get_entry().as_ref());
+        let (title, name) = match entry {
+            Some(SignalNode::Group(name)) => ("Rename Group", name),
+            Some(SignalNode::Separator(label)) => ("Rename Separator", label),
+            _ => return,
+        };
+        self.messages.push(NaluMessage::OpenForm(Form::new(
+            title,
+            FormKind::Rename,
+            vec![
+                FormField::Text(String::from("Name"), name.clone()),
+                FormField::Button(String::from("Rename")),
+                FormField::Button(String::from("Cancel")),
+            ],
+        )));
+    }
+
+    fn rename_selected(&mut self, name: String) {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        let entry = self
+            .node
+            .get_node_mut(&path)
+            .and_then(|n| n.get_entry_mut().as_mut());
+        match entry {
+            Some(SignalNode::Group(group)) => *group = name,
+            Some(SignalNode::Separator(label)) => *label = name,
+            _ => return,
+        }
+        self.push_request();
+    }
+
+    /// Moves the selected signals and groups into a new expanded group, which
+    /// takes the place of the first of them
+    fn group_selected(&mut self, name: String) {
+        let paths = self
+            .browser
+            .get_selected_paths(&self.node, true)
+            .into_iter()
+            .filter(|path| !self.is_in_signal(path))
+            .collect::<Vec<BrowserNodePath>>();
+        let Some(first) = paths.first().cloned() else {
+            return;
+        };
+        // Removing from the back keeps the paths before each one the same
+        let mut nodes = Vec::new();
+        for path in paths.into_iter().rev() {
+            let mut path = path.to_vec();
+            let Some(index) = path.pop() else {
+                continue;
+            };
+            let parent = match path.is_empty() {
+                true => &mut self.node,
+                false => match self.node.get_node_mut(&BrowserNodePath::new(path)) {
+                    Some(parent) => parent,
+                    None => continue,
+                },
+            };
+            if index < parent.get_children().len() {
+                nodes.push(parent.get_children_mut().remove(index));
+            }
+        }
+        nodes.reverse();
+        let mut path = first.to_vec();
+        let index = path.pop().unwrap_or(0);
+        let parent = match path.is_empty() {
+            true => &mut self.node,
+            false => match self.node.get_node_mut(&BrowserNodePath::new(path.clone())) {
+                Some(parent) => parent,
+                None => return,
+            },
+        };
+        let index = index.min(parent.get_children().len());
+        parent.get_children_mut().insert(
+            index,
+            BrowserNode::from_expanded(Some(SignalNode::Group(name)), true, nodes),
+        );
+        path.push(index);
+        self.browser
+            .select_path(&self.node, &BrowserNodePath::new(path));
+    }
+
+    /// Copies the selected signals and groups along with everything inside of
+    /// them, leaving out the bits of vectors and the rows of plots like delete
+    fn yank_selected(&mut self) {
+        let mut yanked = Vec::new();
+        for path in self.browser.get_selected_paths(&self.node, true) {
+            if self.is_in_signal(&path) {
+                continue;
+            }
+            if let Some(node) = self.node.get_node(&path) {
+                yanked.push(node.clone());
+            }
+        }
+        if !yanked.is_empty() {
+            self.yanked = yanked;
+        }
+    }
+
+    /// Inserts another copy of the yanked nodes after the selected one, which
+    /// can be inside of a different group than they were yanked from
+    fn paste_yanked(&mut self) {
+        if self.yanked.is_empty() {
+            self.messages
+                .push(NaluMessage::PaletteError(String::from("Nothing yanked")));
+            return;
+        }
+        for node in self.yanked.clone() {
+            self.insert_node(node);
+        }
+    }
+
+    /// Changes the radix of every selected signal along with its bits, and of
+    /// every signal inside the selected groups
+    fn set_radix(&mut self, radix: BitVectorRadix) {
+        for path in self.browser.get_selected_paths(&self.node, true) {
+            let Some(node) = self.node.get_node_mut(&path) else {
+                continue;
+            };
+            node.visit_mut(&mut |node| {
+                if let Some(
+                    SignalNode::VectorSignal(_, _, entry_radix, _)
+                    | SignalNode::VirtualVector(_, _, _, entry_radix),
+                ) = node.get_entry_mut()
+                {
+                    *entry_radix = radix;
+                }
+                true
+            });
+        }
+    }
+
+    /// Makes every selected signal (or bit of one) take up this many rows,
+    /// keeping the selection on the same signal
+    fn set_height(&mut self, height: usize) {
+        let primary = self.browser.get_primary_selected_path(&self.node);
+        let paths = self.browser.get_selected_paths(&self.node, false);
+        let mut changed = false;
+        for path in paths {
+            let Some(node) = self.node.get_node_mut(&path) else {
+                continue;
+            };
+            if let Some(
+                SignalNode::VectorSignal(_, _, _, _) | SignalNode::VirtualVector(_, _, _, _),
+            ) = node.get_entry()
+            {
+                node.set_height(height);
+                changed = true;
+            }
+        }
+        if !changed {
+            self.messages.push(NaluMessage::PaletteError(String::from(
+                "No signal selected",
+            )));
+            return;
+        }
+        self.browser.select_visible_path(&self.node, &primary);
+    }
+
+    fn copy_path(&self) {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        let Some(SignalNode::VectorSignal(path, variable, _, index)) = self
+            .node
+            .get_node(&path)
+            .and_then(|node| node.get_entry().as_ref())
+        else {
+            return;
+        };
+        let mut full_name = path[..path.len().saturating_sub(1)].to_vec();
+        full_name.push(variable.get_name().clone());
+        let mut full_name = full_name.join(".");
+        if let Some(index) = index {
+            full_name.push_str(&format!("[{index}]"));
+        }
+        copy_to_clipboard(&full_name);
+    }
+
+    /// Expands or collapses every group under the selected one (or in the whole
+    /// tree), where vectors are only ever collapsed since expanding them all
+    /// could add thousands of rows of bits
+    fn set_expanded_below(&mut self, all: bool, expanded: bool) {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        let node = match all {
+            true => Some(&mut self.node),
+            false => self.node.get_node_mut(&path),
+        };
+        let Some(node) = node else {
+            return;
+        };
+        node.visit_mut(&mut |node| match node.get_entry() {
+            Some(SignalNode::VectorSignal(_, _, _, _) | SignalNode::VirtualVector(_, _, _, _)) => {
+                if !expanded {
+                    node.set_expanded(false);
+                }
+                false
+            }
+            Some(_) => {
+                node.set_expanded(expanded);
+                true
+            }
+            // The root has no entry and always stays expanded
+            None => true,
+        });
+        self.browser.select_visible_path(&self.node, &path);
+    }
+
+    fn is_in_signal(&self, path: &BrowserNodePath) -> bool {
+        let mut parent = path.clone().to_vec();
+        parent.pop();
+        matches!(
+            self.node
+                .get_node(&BrowserNodePath::new(parent))
+                .and_then(|node| node.get_entry().as_ref()),
+            Some(SignalNode::VectorSignal(_, _, _, _) | SignalNode::VirtualVector(_, _, _, _))
+        )
+    }
+
+    /// Offers the actions that apply to the selected entry, like the radix
+    /// for vectors with more than one bit
+    fn open_menu(&self) {
+        let path = self.browser.get_primary_selected_path(&self.node);
+        let Some(node) = self.node.get_node(&path) else {
+            return;
+        };
+        let Some(entry) = node.get_entry() else {
+            return;
+        };
+        let mut actions = Vec::new();
+        if node.is_parent() {
+            actions.push(MenuAction::Expand);
+        }
+        match entry {
+            SignalNode::VectorSignal(_, variable, _, None) => {
+                if variable.get_bit_width() > 1 && !is_real(variable) {
+                    actions.extend(RADIXES.map(MenuAction::Radix));
+                }
+                actions.extend([MenuAction::Pin, MenuAction::Delete, MenuAction::CopyPath]);
+            }
+            SignalNode::VectorSignal(_, _, _, Some(_)) => {
+                actions.extend([MenuAction::Pin, MenuAction::CopyPath])
+            }
+            // Spacers inside of a real signal are the rows of its plot
+            SignalNode::Spacer if self.is_in_signal(&path) => {}
+            SignalNode::Spacer => actions.push(MenuAction::Delete),
+            SignalNode::Separator(_) => actions.extend([MenuAction::Rename, MenuAction::Delete]),
+            SignalNode::VirtualVector(_, _, _, _) => {
+                actions.extend(RADIXES.map(MenuAction::Radix));
+                actions.push(MenuAction::Delete);
+            }
+            SignalNode::Group(_) => {
+                actions.extend([
+                    MenuAction::Rename,
+                    MenuAction::ExpandAll,
+                    MenuAction::CollapseAll,
+                ]);
+                actions.extend(RADIXES.map(MenuAction::Radix));
+                actions.push(MenuAction::Delete);
+            }
+        }
+        self.messages.push(NaluMessage::OpenMenu(ContextMenu {
+            title: entry.to_string(),
+            target: MenuTarget::Signal,
+            actions,
+        }));
+    }
+
+    fn handle_menu_action(&mut self, action: MenuAction) {
+        match action {
+            MenuAction::Expand => self.modify(ListAction::Expand),
+            MenuAction::ExpandAll => self.set_expanded_below(false, true),
+            MenuAction::CollapseAll => self.set_expanded_below(false, false),
+            MenuAction::Radix(radix) => self.set_radix(radix),
+            MenuAction::Pin => self.modify(ListAction::Pin),
+            MenuAction::Delete => self.modify(ListAction::Delete),
+            MenuAction::CopyPath => self.copy_path(),
+            MenuAction::Rename => self.open_rename(),
+            _ => {}
         }
         self.push_request();
     }
 
+    /// Spacers below an expanded real signal continue its analog plot
+    fn get_plot_entry(
+        &self,
+        path: BrowserNodePath,
+        is_selected: bool,
+        is_primary: bool,
+    ) -> Option<SignalViewerEntry> {
+        let mut path = path.to_vec();
+        let spacer = path.pop()?;
+        let parent = self.node.get_node(&BrowserNodePath::new(path))?;
+        // The plot starts on the rows of the signal itself
+        let (row, rows) = (
+            parent.get_height() + spacer,
+            parent.get_height() + PLOT_ROWS - 1,
+        );
+        match parent.get_entry() {
+            Some(entry @ SignalNode::VectorSignal(_, variable, radix, None))
+                if is_real(variable) =>
+            {
+                Some(SignalViewerEntry {
+                    name: get_entry_name(entry),
+                    idcode: variable.get_idcode(),
+                    index: None,
+                    radix: *radix,
+                    is_real: true,
+                    offset: self.get_offset(variable),
+                    plot_row: Some((row, rows)),
+                    is_selected,
+                    is_primary,
+                })
+            }
+            _ => None,
+        }
+    }
+
     pub fn push_request(&mut self) {
+        let duplicates = count_duplicates(&self.node, &mut HashSet::new());
+        if duplicates != self.duplicates {
+            self.duplicates = duplicates;
+            self.messages.push(HeaderMessage::SetDuplicates(duplicates));
+        }
         let mut request = Vec::new();
-        for path in self.browser.get_visible_paths(&self.node) {
-            let is_selected = self.browser.get_primary_selected_path(&self.node) == path;
+        let primary_path = self.browser.get_primary_selected_path(&self.node);
+        let selected_paths = self.browser.get_selected_paths(&self.node, false);
+        let scroll = self.browser.get_scroll();
+        for (line, path) in self
+            .browser
+            .get_visible_paths(&self.node)
+            .into_iter()
+            .enumerate()
+        {
+            let is_primary = primary_path == path;
+            let is_selected = selected_paths.contains(&path);
             let Some(node) = self.node.get_node(&path) else {
                 request.push(None);
                 continue;
             };
+            // Which of the rows of a taller signal this is
+            let row = (scroll + line).saturating_sub(self.node.get_index(&path).unwrap_or(0));
             request.push(match node.get_entry().as_ref().unwrap() {
+                entry @ SignalNode::VectorSignal(_, vcd_variable, radix, index) => {
+                    let rows = match is_real(vcd_variable) && node.is_expanded() {
+                        true => node.get_height() + PLOT_ROWS - 1,
+                        false => node.get_height(),
+                    };
+                    Some(SignalViewerRow::Signal(SignalViewerEntry {
+                        name: get_entry_name(entry),
+                        idcode: vcd_variable.get_idcode(),
+                        index: *index,
+                        radix: *radix,
+                        is_real: is_real(vcd_variable),
+                        offset: self.get_offset(vcd_variable),
+                        plot_row: (rows > 1).then_some((row, rows)),
+                        is_selected,
+                        is_primary,
+                    }))
+                }
+                entry @ SignalNode::VirtualVector(_, idcode, _, radix) => {
+                    let rows = node.get_height();
+                    Some(SignalViewerRow::Signal(SignalViewerEntry {
+                        name: get_entry_name(entry),
+                        idcode: *idcode,
+                        index: None,
+                        radix: *radix,
+                        is_real: false,
+                        offset: 0,
+                        plot_row: (rows > 1).then_some((row, rows)),
+                        is_selected,
+                        is_primary,
+                    }))
+                }
+                SignalNode::Spacer => self
+                    .get_plot_entry(path, is_selected, is_primary)
+                    .map(SignalViewerRow::Signal),
+                SignalNode::Separator(label) => Some(SignalViewerRow::Separator(label.clone())),
+                SignalNode::Group(_) if !node.is_expanded() => {
+                    let mut signals = Vec::new();
+                    collect_group_signals(node, &self.offsets, &mut signals);
+                    Some(SignalViewerRow::Group(signals))
+                }
+                SignalNode::Group(_) => None,
+            });
+        }
+        self.messages.push(WaveformViewerMessage::UpdateSignals(
+            request.clone(),
+            self.browser.get_hidden_below(&self.node),
+        ));
+        let selected = match self
+            .node
+            .get_node(&primary_path)
+            .and_then(|n| n.get_entry().as_ref())
+        {
+            Some(SignalNode::VectorSignal(_, variable, radix, index)) => Some(InspectorSignal {
+                name: match index {
+                    Some(index) => format!("{} [{}]", variable, index),
+                    None => variable.to_string(),
+                },
+                idcode: variable.get_idcode(),
+                index: *index,
+                radix: *radix,
+                is_real: is_real(variable),
+            }),
+            _ => None,
+        };
+        self.messages.push(InspectorMessage::Select(selected));
+        let pinned = self
+            .pinned
+            .iter()
+            .filter_map(|p| match p {
                 SignalNode::VectorSignal(_, vcd_variable, radix, index) => {
                     Some(SignalViewerEntry {
+                        name: get_entry_name(p),
                         idcode: vcd_variable.get_idcode(),
                         index: *index,
                         radix: *radix,
-                        is_selected,
+                        is_real: is_real(vcd_variable),
+                        offset: self.get_offset(vcd_variable),
+                        plot_row: None,
+                        is_selected: false,
+                        is_primary: false,
                     })
                 }
                 _ => None,
-            });
-        }
+            })
+            .collect();
         self.messages
-            .push(WaveformViewerMessage::UpdateSignals(request.clone()));
+            .push(WaveformViewerMessage::UpdatePinned(pinned));
     }
 }
 
 impl ComponentWidget for SignalViewerState {
     fn handle_mouse(&mut self, _x: u16, y: u16, kind: MouseEventKind) -> bool {
         match kind {
-            MouseEventKind::Down(MouseButton::Left) => {
-                if self.browser.select_absolute(&self.node, y as isize, true) {
+            MouseEventKind::Down(button @ (MouseButton::Left | MouseButton::Right)) => {
+                // Ignore clicks next to the minimap and on the pinned signals
+                // and their separator
+                let top = (MINIMAP_HEIGHT + get_pinned_height(self.pinned.len())) as u16;
+                let y = match y {
+                    0 => 0,
+                    y if y > top => y - top,
+                    _ => return false,
+                };
+                let reselected = self.browser.select_absolute(&self.node, y as isize, true);
+                if button == MouseButton::Right {
+                    self.open_menu();
+                } else if reselected {
                     self.modify(ListAction::Expand);
                 }
                 self.push_request();
@@ -224,6 +1706,12 @@ impl ComponentWidget for SignalViewerState {
     }
 
     fn handle_key(&mut self, e: KeyEvent) -> bool {
+        if let Some((text, skip_selected)) = self.type_ahead.handle_key(&e) {
+            self.browser
+                .select_matching(&self.node, &text, skip_selected);
+            self.push_request();
+            return true;
+        }
         let shift = e.modifiers.contains(KeyModifiers::SHIFT);
         match e.code {
             KeyCode::Up => self.browser.select_relative(&self.node, -1, !shift),
@@ -232,6 +1720,18 @@ impl ComponentWidget for SignalViewerState {
             KeyCode::PageUp => self.browser.select_relative(&self.node, -20, !shift),
             KeyCode::Enter => self.modify(ListAction::Expand),
             KeyCode::Char('g') => self.modify(ListAction::Group),
+            KeyCode::F(2) => self.open_rename(),
+            KeyCode::Char('P') => self.modify(ListAction::Pin),
+            KeyCode::Char('y') => self.yank_selected(),
+            KeyCode::Char('v') => self.paste_yanked(),
+            KeyCode::Char('o') => self.messages.push(NaluMessage::OpenQuickAdd),
+            KeyCode::Char('c') | KeyCode::Menu => self.open_menu(),
+            KeyCode::Char('e') => self.set_expanded_below(false, true),
+            KeyCode::Char('E') => self.set_expanded_below(false, false),
+            KeyCode::Char('-') => self.insert_separator(None),
+            KeyCode::Char('_') => self
+                .messages
+                .push(NaluMessage::OpenPalette(String::from("separator "))),
             KeyCode::Char('f') => {
                 self.browser
                     .set_indent_enabled(!self.browser.is_full_name_enabled());
@@ -253,8 +1753,126 @@ impl ComponentWidget for SignalViewerState {
                     self.browser_request_append(path, variable, BitVectorRadix::Hexadecimal);
                     updated = true;
                 }
-                SignalViewerMessage::NetlistInsert(_, _) => {}
+                SignalViewerMessage::NetlistAppendGroup(name, signals) => {
+                    self.browser_request_append_group(name, signals, BitVectorRadix::Hexadecimal);
+                    updated = true;
+                }
+                SignalViewerMessage::AppendSignals(signals) => {
+                    for (path, variable, radix) in signals {
+                        self.browser_request_append(path, variable, radix);
+                    }
+                    updated = true;
+                }
+                SignalViewerMessage::AppendVirtualVector(full_name, idcode, bits) => {
+                    self.append_virtual_vector(full_name, idcode, bits);
+                    updated = true;
+                }
+                SignalViewerMessage::NetlistInsert(path, variable) => {
+                    self.browser_request_insert(path, variable, BitVectorRadix::Hexadecimal);
+                    updated = true;
+                }
                 SignalViewerMessage::WaveformKey(e) => updated |= self.handle_key(e),
+                SignalViewerMessage::SaveView(name) => self.save_view(name),
+                SignalViewerMessage::Export(path) => self.export(path),
+                SignalViewerMessage::SaveGroup(group, path) => self.save_group(group, path),
+                SignalViewerMessage::AppendView(name, signals, offsets, path) => {
+                    self.append_view(name, signals, offsets, path);
+                    updated = true;
+                }
+                SignalViewerMessage::Share => {
+                    let path = self.browser.get_primary_selected_path(&self.node);
+                    let signal = self
+                        .node
+                        .get_node(&path)
+                        .and_then(|node| node.get_entry().as_ref())
+                        .and_then(get_signal_full_name);
+                    self.messages.push(WaveformViewerMessage::Share(signal));
+                }
+                SignalViewerMessage::SelectSignal(full_name) => {
+                    self.select_signal(&full_name);
+                    updated = true;
+                }
+                SignalViewerMessage::Autosave => self.messages.push(
+                    WaveformViewerMessage::Autosave(self.get_view(String::from("recovery"))),
+                ),
+                SignalViewerMessage::LoadView(signals, offsets, undo) => {
+                    self.load_view(signals, offsets, undo);
+                    updated = true;
+                }
+                SignalViewerMessage::FindGlitches(width, all) => self.find_glitches(width, all),
+                SignalViewerMessage::TraceUnknown(all) => self.trace_unknown(all),
+                SignalViewerMessage::ListUnknown(all) => self.list_unknown(all),
+                SignalViewerMessage::MeasureDelays => self.measure_delays(),
+                SignalViewerMessage::MeasurePeriod(cursors) => self.measure_period(cursors),
+                SignalViewerMessage::UpdateWaveform(waveform, timescale) => {
+                    self.waveform = waveform;
+                    self.timescale = timescale;
+                }
+                SignalViewerMessage::SetCursor(cursor) => {
+                    self.cursor = cursor;
+                    updated = true;
+                }
+                SignalViewerMessage::SetMarker(marker) => {
+                    self.marker = marker;
+                    updated = true;
+                }
+                SignalViewerMessage::OpenVcd(path, keep) => {
+                    self.open_vcd(path, keep);
+                    updated = true;
+                }
+                SignalViewerMessage::UndoLoad => {
+                    self.undo_load();
+                    updated = true;
+                }
+                SignalViewerMessage::MenuAction(action) => {
+                    self.handle_menu_action(action);
+                    updated = true;
+                }
+                SignalViewerMessage::RemoveDuplicates => {
+                    self.remove_duplicates();
+                    updated = true;
+                }
+                SignalViewerMessage::RenameSelected(name) => {
+                    self.rename_selected(name);
+                    updated = true;
+                }
+                SignalViewerMessage::GroupSelected(name) => {
+                    self.group_selected(name);
+                    updated = true;
+                }
+                SignalViewerMessage::InsertSeparator(label) => {
+                    self.insert_separator(label);
+                    updated = true;
+                }
+                SignalViewerMessage::SetOffset(offset) => {
+                    self.set_offset(offset);
+                    updated = true;
+                }
+                SignalViewerMessage::SetRadix(radix) => {
+                    self.set_radix(radix);
+                    self.push_request();
+                    updated = true;
+                }
+                SignalViewerMessage::SetHeight(height) => {
+                    self.set_height(height);
+                    self.push_request();
+                    updated = true;
+                }
+                SignalViewerMessage::SetTree(tree) => {
+                    self.browser.set_tree_enabled(tree);
+                    updated = true;
+                }
+                SignalViewerMessage::ExpandAll(expanded) => {
+                    self.set_expanded_below(true, expanded);
+                    self.push_request();
+                    updated = true;
+                }
+                SignalViewerMessage::SetWaveformHeight(height) => {
+                    self.waveform_height = height;
+                    self.update_height();
+                    self.push_request();
+                    updated = true;
+                }
             }
         }
         updated
@@ -274,6 +1892,7 @@ impl ComponentWidget for SignalViewerState {
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) {
         self.get_browser()
+            .prompt(self.type_ahead.get_prompt())
             .style(Style::default().fg(Color::LightCyan))
             .render(area, buf);
     }
@@ -286,3 +1905,86 @@ impl ComponentWidget for SignalViewerState {
         self
     }
 }
+
+#[test]
+fn rename_group_test() {
+    use crate::fixture::FixtureBuilder;
+    use crate::python::script::run_views;
+
+    let fixture = FixtureBuilder::default()
+        .clock("clk", 10, 100)
+        .counter("data", 8, 10, 100)
+        .build();
+    let signal = |name: &str| {
+        let variable = fixture.header.get_variable(&format!("top.{name}")).unwrap();
+        let path = vec![String::from("top"), variable.to_string()];
+        ViewNode::Signal(path, variable.clone(), BitVectorRadix::Hexadecimal, None)
+    };
+    let mut state = SignalViewerState::new(Messages::new());
+    let group = ViewNode::Group(String::from("bus"), vec![signal("data")]);
+    state.load_view(vec![signal("clk"), group], Vec::new(), false);
+    state
+        .browser
+        .select_path(&state.node, &BrowserNodePath::new(vec![1]));
+    state.rename_selected(String::from("axi bus"));
+
+    // The group is saved under its new name and loads back the same way
+    let view = state.get_view(String::from("renamed"));
+    let data = ViewEntry::Signal(String::from("top.data"), BitVectorRadix::Hexadecimal, None);
+    assert_eq!(
+        view.signals,
+        vec![
+            ViewEntry::Signal(String::from("top.clk"), BitVectorRadix::Hexadecimal, None),
+            ViewEntry::Group(String::from("axi bus"), vec![data]),
+        ]
+    );
+    let path = std::env::temp_dir().join(format!("nalu_rename_group_{}.py", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    save_view_file(&path, &view).unwrap();
+    let views = run_views(&path);
+    std::fs::remove_file(&path).unwrap();
+    let views = views.unwrap();
+    assert_eq!(views.iter().find(|v| v.name == "renamed"), Some(&view));
+}
+
+#[test]
+fn save_group_test() {
+    use crate::fixture::FixtureBuilder;
+    use crate::python::script::run_views;
+
+    let fixture = FixtureBuilder::default()
+        .clock("clk", 10, 100)
+        .counter("data", 8, 10, 100)
+        .pattern("ready", &[(0, "0"), (50, "1")])
+        .build();
+    let signal = |name: &str, index| {
+        let radix = BitVectorRadix::Hexadecimal;
+        ViewEntry::Signal(format!("top.{name}"), radix, index)
+    };
+    let handshake = ViewEntry::Group(String::from("handshake"), vec![signal("ready", None)]);
+    let bus = vec![signal("data", None), signal("data", Some(2)), handshake];
+    let entries = [
+        signal("clk", None),
+        ViewEntry::Group(String::from("bus"), bus.clone()),
+    ];
+    let mut state = SignalViewerState::new(Messages::new());
+    let nodes = get_view_nodes(&fixture.header, "test", &entries);
+    state.load_view(nodes, Vec::new(), false);
+    assert_eq!(collect_view_entries(&state.node), entries);
+
+    // The group is saved with its bits and the groups inside of it
+    let path = std::env::temp_dir().join(format!("nalu_save_group_{}.py", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    state.save_group(String::from("bus"), Some(path.display().to_string()));
+    let views = run_views(&path);
+    std::fs::remove_file(&path).unwrap();
+    let views = views.unwrap();
+    let view = views.iter().find(|view| view.name == "bus").unwrap();
+    assert_eq!(view.signals, bus);
+
+    // Appending the view recreates the group as it was
+    let nodes = get_view_nodes(&fixture.header, "bus", &view.signals);
+    state.append_view(String::from("bus"), nodes, Vec::new(), path);
+    let appended = collect_view_entries(&state.node).pop();
+    assert_eq!(appended, Some(ViewEntry::Group(String::from("bus"), bus)));
+}
@@ -0,0 +1,395 @@
+use std::ops::Range;
+use std::path::Path;
+
+use makai_waveform_db::bitvector::BitVectorRadix;
+
+/// A named signal list and zoom range, saved to the python script as a
+/// `view_<name>()` function returning a dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalView {
+    pub name: String,
+    /// The signals and groups in order
+    pub signals: Vec<ViewEntry>,
+    /// Full names of the signals drawn shifted in time by some timestamps
+    pub offsets: Vec<(String, i64)>,
+    pub range: Option<Range<u64>>,
+}
+
+/// A signal of a view by its full name and radix, or a group of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViewEntry {
+    /// Full name and radix, with the index when it is a single bit of a vector
+    Signal(String, BitVectorRadix, Option<usize>),
+    Group(String, Vec<ViewEntry>),
+}
+
+fn collect_view_signals<'a>(entries: &'a [ViewEntry], signals: &mut Vec<&'a str>) {
+    for entry in entries {
+        match entry {
+            ViewEntry::Signal(name, _, _) => signals.push(name),
+            ViewEntry::Group(_, entries) => collect_view_signals(entries, signals),
+        }
+    }
+}
+
+impl SignalView {
+    /// Full names of the signals and bits, including the ones in groups
+    pub fn get_signals(&self) -> Vec<&str> {
+        let mut signals = Vec::new();
+        collect_view_signals(&self.signals, &mut signals);
+        signals
+    }
+}
+
+pub fn get_radix_name(radix: BitVectorRadix) -> &'static str {
+    match radix {
+        BitVectorRadix::Binary => "bin",
+        BitVectorRadix::Octal => "oct",
+        BitVectorRadix::Decimal => "dec",
+        BitVectorRadix::Hexadecimal => "hex",
+    }
+}
+
+pub fn parse_radix(radix: &str) -> Option<BitVectorRadix> {
+    match radix {
+        "bin" => Some(BitVectorRadix::Binary),
+        "oct" => Some(BitVectorRadix::Octal),
+        "dec" => Some(BitVectorRadix::Decimal),
+        "hex" => Some(BitVectorRadix::Hexadecimal),
+        _ => None,
+    }
+}
+
+/// Name of the python function a view is saved as, anything that is not
+/// alphanumeric is replaced so names like "memory bus" still work
+pub fn get_view_function(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .collect::<String>();
+    format!("view_{name}")
+}
+
+/// Writes the entries of a signal list, groups as dictionaries with the name of
+/// the group and its own signal list
+fn render_view_entries(entries: &[ViewEntry], indent: usize, source: &mut String) {
+    let pad = " ".repeat(indent);
+    for entry in entries {
+        match entry {
+            ViewEntry::Signal(signal, radix, None) => {
+                source.push_str(&format!(
+                    "{pad}({:?}, {:?}),\n",
+                    signal,
+                    get_radix_name(*radix)
+                ));
+            }
+            ViewEntry::Signal(signal, radix, Some(index)) => {
+                source.push_str(&format!(
+                    "{pad}({:?}, {:?}, {index}),\n",
+                    signal,
+                    get_radix_name(*radix)
+                ));
+            }
+            ViewEntry::Group(name, entries) => {
+                source.push_str(&format!(
+                    "{pad}{{\n{pad}    \"group\": {:?},\n{pad}    \"signals\": [\n",
+                    name
+                ));
+                render_view_entries(entries, indent + 8, source);
+                source.push_str(&format!("{pad}    ],\n{pad}}},\n"));
+            }
+        }
+    }
+}
+
+fn render_view(view: &SignalView) -> String {
+    let mut source = format!(
+        "def {}():\n    return {{\n        \"name\": {:?},\n        \"signals\": [\n",
+        get_view_function(&view.name),
+        view.name
+    );
+    render_view_entries(&view.signals, 12, &mut source);
+    source.push_str("        ],\n");
+    if !view.offsets.is_empty() {
+        source.push_str("        \"offsets\": {\n");
+        for (signal, offset) in &view.offsets {
+            source.push_str(&format!("            {:?}: {},\n", signal, offset));
+        }
+        source.push_str("        },\n");
+    }
+    if let Some(range) = &view.range {
+        source.push_str(&format!(
+            "        \"range\": ({}, {}),\n",
+            range.start, range.end
+        ));
+    }
+    source.push_str("    }\n");
+    source
+}
+
+/// The quoted name of a different view saved in the python source as the same
+/// function as the view, which saving the view would overwrite, like "a b"
+/// and "a_b" are both saved as `view_a_b()`
+pub fn find_view_collision(source: &str, view: &SignalView) -> Option<String> {
+    let function = get_view_function(&view.name);
+    let header = format!("def {function}(");
+    let mut lines = source.lines().skip_while(|line| !line.starts_with(&header));
+    lines.next()?;
+    // Views without a name are named after their function, like when loaded
+    let name = lines
+        .take_while(|l| l.is_empty() || l.starts_with(char::is_whitespace))
+        .find_map(|l| l.trim().strip_prefix("\"name\": "))
+        .map(|name| name.trim_end_matches(',').to_string())
+        .unwrap_or_else(|| format!("{:?}", &function["view_".len()..]));
+    (name != format!("{:?}", view.name)).then_some(name)
+}
+
+/// Replaces the function of the view in the python source, appending it to the
+/// end if the view has not been saved before
+pub fn replace_view(source: &str, view: &SignalView) -> String {
+    let header = format!("def {}(", get_view_function(&view.name));
+    let mut lines = source.lines().peekable();
+    let mut result = String::new();
+    while let Some(line) = lines.next() {
+        if !line.starts_with(&header) {
+            result.push_str(line);
+            result.push('\n');
+            continue;
+        }
+        // Skip the body of the old function up to the next top-level line
+        while lines
+            .peek()
+            .is_some_and(|l| l.is_empty() || l.starts_with(char::is_whitespace))
+        {
+            lines.next();
+        }
+    }
+    let result = result.trim_end();
+    if result.is_empty() {
+        render_view(view)
+    } else {
+        format!("{}\n\n\n{}", result, render_view(view))
+    }
+}
+
+/// Saves the view to a python file, replacing the view with the same name or
+/// creating the file if there is none yet, but never a different view saved
+/// as the same function
+pub fn save_view_file(path: &Path, view: &SignalView) -> std::io::Result<()> {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    if let Some(other) = find_view_collision(&source, view) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!(
+                "{} would replace view {other}",
+                get_view_function(&view.name)
+            ),
+        ));
+    }
+    std::fs::write(path, replace_view(&source, view))
+}
+
+/// An entry of the signal list written out by `export`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListEntry {
+    /// Full name and radix, with the index when it is a single bit of a vector
+    Signal(String, BitVectorRadix, Option<usize>),
+    /// Full name and radix of a virtual vector along with the full names of
+    /// its bits from the lowest one up
+    Virtual(String, BitVectorRadix, Vec<String>),
+    Group(String, Vec<ListEntry>),
+    Separator(String),
+    Spacer,
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn render_list_entries(entries: &[ListEntry], indent: usize, list: &mut String) {
+    let pad = " ".repeat(indent);
+    for entry in entries {
+        match entry {
+            ListEntry::Signal(name, radix, index) => {
+                list.push_str(&format!("{pad}- name: {}\n", quote(name)));
+                list.push_str(&format!("{pad}  radix: {}\n", get_radix_name(*radix)));
+                if let Some(index) = index {
+                    list.push_str(&format!("{pad}  index: {index}\n"));
+                }
+            }
+            ListEntry::Virtual(name, radix, bits) => {
+                list.push_str(&format!("{pad}- name: {}\n", quote(name)));
+                list.push_str(&format!("{pad}  radix: {}\n", get_radix_name(*radix)));
+                list.push_str(&format!("{pad}  bits:\n"));
+                for bit in bits {
+                    list.push_str(&format!("{pad}    - {}\n", quote(bit)));
+                }
+            }
+            ListEntry::Group(name, entries) if entries.is_empty() => {
+                list.push_str(&format!(
+                    "{pad}- group: {}\n{pad}  signals: []\n",
+                    quote(name)
+                ));
+            }
+            ListEntry::Group(name, entries) => {
+                list.push_str(&format!("{pad}- group: {}\n{pad}  signals:\n", quote(name)));
+                render_list_entries(entries, indent + 4, list);
+            }
+            ListEntry::Separator(label) => {
+                list.push_str(&format!("{pad}- separator: {}\n", quote(label)));
+            }
+            ListEntry::Spacer => list.push_str(&format!("{pad}- spacer\n")),
+        }
+    }
+}
+
+/// Renders the signal list as YAML, which other tools can read without
+/// knowing anything about the python script
+pub fn render_signal_list(entries: &[ListEntry]) -> String {
+    if entries.is_empty() {
+        return String::from("signals: []\n");
+    }
+    let mut list = String::from("signals:\n");
+    render_list_entries(entries, 2, &mut list);
+    list
+}
+
+#[test]
+fn render_signal_list_test() {
+    let entries = vec![
+        ListEntry::Signal(String::from("TOP.clk"), BitVectorRadix::Binary, None),
+        ListEntry::Separator(String::from("fetch \"unit\"")),
+        ListEntry::Group(
+            String::from("core"),
+            vec![
+                ListEntry::Signal(
+                    String::from("TOP.core.pc"),
+                    BitVectorRadix::Hexadecimal,
+                    Some(3),
+                ),
+                ListEntry::Spacer,
+            ],
+        ),
+        ListEntry::Group(String::from("empty"), Vec::new()),
+    ];
+    assert_eq!(
+        render_signal_list(&entries),
+        "signals:\n  - name: \"TOP.clk\"\n    radix: bin\n  \
+         - separator: \"fetch \\\"unit\\\"\"\n  - group: \"core\"\n    signals:\n      \
+         - name: \"TOP.core.pc\"\n        radix: hex\n        index: 3\n      - spacer\n  \
+         - group: \"empty\"\n    signals: []\n"
+    );
+    assert_eq!(render_signal_list(&[]), "signals: []\n");
+}
+
+#[test]
+fn replace_view_test() {
+    let view = SignalView {
+        name: String::from("fetch unit"),
+        signals: vec![ViewEntry::Signal(
+            String::from("TOP.core.pc"),
+            BitVectorRadix::Hexadecimal,
+            None,
+        )],
+        offsets: vec![(String::from("TOP.core.pc"), -2)],
+        range: Some(10..20),
+    };
+    let source = replace_view("def main():\n    pass\n", &view);
+    assert_eq!(
+        source,
+        "def main():\n    pass\n\n\n\
+         def view_fetch_unit():\n    return {\n        \"name\": \"fetch unit\",\n        \
+         \"signals\": [\n            (\"TOP.core.pc\", \"hex\"),\n        ],\n        \
+         \"offsets\": {\n            \"TOP.core.pc\": -2,\n        },\n        \
+         \"range\": (10, 20),\n    }\n"
+    );
+    let view = SignalView {
+        signals: Vec::new(),
+        offsets: Vec::new(),
+        range: None,
+        ..view
+    };
+    let source = replace_view(&format!("{source}\n\ndef other():\n    pass\n"), &view);
+    assert_eq!(source.matches("def view_fetch_unit").count(), 1);
+    assert!(source.contains("def main():"));
+    assert!(source.contains("def other():"));
+    assert!(!source.contains("TOP.core.pc"));
+    assert!(source.ends_with("\"signals\": [\n        ],\n    }\n"));
+    // Groups are written with their name around their own signal list
+    let pc = ViewEntry::Signal(
+        String::from("TOP.core.pc"),
+        BitVectorRadix::Hexadecimal,
+        None,
+    );
+    let view = SignalView {
+        signals: vec![ViewEntry::Group(
+            String::from("core"),
+            vec![pc, ViewEntry::Group(String::from("empty"), Vec::new())],
+        )],
+        ..view
+    };
+    assert!(render_view(&view).contains(
+        "        \"signals\": [\n            {\n                \"group\": \"core\",\n                \
+         \"signals\": [\n                    (\"TOP.core.pc\", \"hex\"),\n                    \
+         {\n                        \"group\": \"empty\",\n                        \
+         \"signals\": [\n                        ],\n                    },\n                \
+         ],\n            },\n        ],\n"
+    ));
+}
+
+#[test]
+fn save_view_file_test() {
+    let path = std::env::temp_dir().join(format!("nalu_save_view_{}.py", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let view = SignalView {
+        name: String::from("axi bus"),
+        signals: vec![ViewEntry::Signal(
+            String::from("TOP.axi.valid"),
+            BitVectorRadix::Binary,
+            None,
+        )],
+        offsets: Vec::new(),
+        range: None,
+    };
+    save_view_file(&path, &view).unwrap();
+    let other = SignalView {
+        name: String::from("apb"),
+        ..view.clone()
+    };
+    save_view_file(&path, &other).unwrap();
+    save_view_file(&path, &view).unwrap();
+    // A different name saved as the same function is not overwritten
+    let colliding = SignalView {
+        name: String::from("axi-bus"),
+        ..view.clone()
+    };
+    let err = save_view_file(&path, &colliding).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    let source = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(source.matches("def view_axi_bus():").count(), 1);
+    assert!(source.contains("def view_apb():"));
+    assert!(source.contains("\"name\": \"axi bus\""));
+    // Views written by hand without a name are named after their function
+    let source = "def view_a_b():\n    return {\"signals\": []}\n";
+    let view = SignalView {
+        name: String::from("a b"),
+        ..view
+    };
+    assert_eq!(
+        find_view_collision(source, &view),
+        Some(String::from("\"a_b\""))
+    );
+    let view = SignalView {
+        name: String::from("a_b"),
+        ..view
+    };
+    assert_eq!(find_view_collision(source, &view), None);
+}
@@ -1,40 +1,126 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{KeyCode, KeyEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEventKind};
 use makai::utils::messages::Messages;
 use makai_vcd_reader::parser::VcdHeader;
 use makai_waveform_db::Waveform;
 use tui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Color, Style},
-    text::Spans,
-    widgets::{Block, Paragraph, Widget},
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
 };
 use tui_tiling::component::ComponentWidget;
 
 use crate::{
-    state::signal_viewer::SignalViewerEntry,
+    analysis::{
+        find_transition, get_rising_edges, get_transitions, get_value_history, measure_periods,
+    },
+    loader::decode_real,
+    logging::FrameTimestamps,
+    python::{
+        color::ColorRule,
+        error::{PythonError, PythonRerun},
+        script::{run_annotations, run_color_rules, set_cursors, with_timeout},
+        vcd_header::VcdHeaderPy,
+    },
+    source::{VirtualVector, VirtualWaveform, WaveformSource},
+    state::form::{Form, FormField, FormKind},
+    state::header::HeaderMessage,
+    state::inspector::{render_value, InspectorMessage},
+    state::python_view::{PythonViewMessage, PYTHON_PANE},
+    state::share::SharedLocation,
     state::signal_viewer::SignalViewerMessage,
-    widgets::timescale::{Timescale, TimescaleState},
+    state::signal_viewer::{SignalViewerEntry, SignalViewerRow},
+    state::view::SignalView,
+    state::{NaluMessage, ValueHistory},
+    widgets::browser::get_pinned_height,
+    widgets::layout::LayoutMessage,
+    widgets::minimap::{
+        get_minimap_density, get_minimap_timestamp, ActivityLine, Minimap, MINIMAP_HEIGHT,
+    },
+    widgets::timescale::{
+        get_cursor_color, get_idle_breaks, render_time, Timescale, TimescaleState, ZoomAction,
+    },
     widgets::waveform::WaveformWidget,
 };
 
+/// How long the mouse has to rest over a signal before its tooltip shows
+const HOVER_DELAY: Duration = Duration::from_millis(500);
+/// The tooltip goes away on its own after this long, since moving the mouse
+/// over to another pane is not seen here
+const HOVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most values listed by the history, so a clock between a far away cursor and
+/// marker does not flood the table
+const HISTORY_LIMIT: usize = 1000;
+
 pub(crate) enum WaveformViewerMessage {
-    UpdateSignals(Vec<Option<SignalViewerEntry>>),
+    // Also how many rows the signal viewer has below the visible ones
+    UpdateSignals(Vec<Option<SignalViewerRow>>, usize),
+    UpdatePinned(Vec<SignalViewerEntry>),
     UpdateWaveform(Arc<Waveform>, Arc<VcdHeader>, i32, Option<PathBuf>),
+    ReplaceWaveform(Arc<Waveform>),
+    AddVirtualVector(VirtualVector),
+    SetEvents(Arc<HashSet<usize>>),
+    AddAnnotation(String),
+    RemoveAnnotation(usize),
+    SearchAnnotations(String),
+    // Places a named cursor at the timestamp, or at the cursor without one
+    SetNamedCursor(char, Option<u64>),
+    RemoveNamedCursor(char),
+    GotoTimestamp(u64),
+    SaveView(SignalView),
+    Autosave(SignalView),
+    // Shares the range and cursor along with the full name of the selected signal
+    Share(Option<String>),
+    // Runs the annotations and coloring rules of the script again
+    ReloadPython,
+    SetRange(Range<u64>),
+    // Shows this many timestamps around the cursor
+    ZoomAround(u64),
+    ZoomAll,
+    // Multiplies the steps of zoom and scroll keys held down for each repeat
+    SetAcceleration(f64),
+    MeasureDelays(Vec<(String, usize, Option<usize>)>),
+    // Measures the periods of a single bit in view, placing named cursors at
+    // the shortest and longest if asked to
+    MeasurePeriod(String, usize, Option<usize>, bool),
+    // Lists the values of the selected signal from the cursor to the marker
+    ShowHistory,
 }
 
 pub struct WaveformViewerState {
     width: usize,
     height: usize,
-    waveform: Arc<Waveform>,
+    waveform: VirtualWaveform,
     vcd_header: Arc<VcdHeader>,
     timescale_state: TimescaleState,
-    signal_entries: Vec<Option<SignalViewerEntry>>,
-    python_view: bool,
+    signal_entries: Vec<Option<SignalViewerRow>>,
+    hidden_entries: usize,
+    pinned_entries: Vec<SignalViewerEntry>,
+    // Idcodes of event variables, which are drawn as ticks
+    events: Arc<HashSet<usize>>,
+    // Draw single bits with braille dots for twice the resolution
+    high_resolution: bool,
+    // The text searched for in the annotations last
+    annotation_query: Option<String>,
     python_path: Option<PathBuf>,
+    // Coloring rules from the python script by idcode
+    color_rules: HashMap<usize, ColorRule>,
+    // Transitions in each column of the minimap, only counted again when the
+    // width or the displayed signals change
+    minimap_density: Vec<usize>,
+    minimap_signals: Option<Vec<(usize, Option<usize>, i64)>>,
+    // The range last sent to the header, which is only told about changes
+    header_range: Option<(Range<u64>, Range<u64>, i32)>,
+    header_follow: Option<bool>,
+    // Where the mouse last moved to in the pane and when
+    hover: Option<(u16, u16, Instant)>,
     messages: Messages,
 }
 
@@ -43,12 +129,22 @@ impl WaveformViewerState {
         Self {
             width: 0,
             height: 0,
-            waveform: Arc::new(Waveform::default()),
+            waveform: VirtualWaveform::default(),
             vcd_header: Arc::new(VcdHeader::default()),
             timescale_state: TimescaleState::new(),
             signal_entries: Vec::new(),
-            python_view: false,
+            hidden_entries: 0,
+            pinned_entries: Vec::new(),
+            events: Arc::new(HashSet::new()),
+            high_resolution: false,
+            annotation_query: None,
             python_path: None,
+            color_rules: HashMap::new(),
+            minimap_density: Vec::new(),
+            minimap_signals: None,
+            header_range: None,
+            header_follow: None,
+            hover: None,
             messages,
         }
     }
@@ -60,12 +156,387 @@ impl WaveformViewerState {
         timescale: i32,
         python_path: Option<PathBuf>,
     ) {
-        self.waveform = waveform;
+        self.waveform = VirtualWaveform::new(waveform);
         self.vcd_header = vcd_header;
         let range = self.waveform.get_timestamp_range();
         self.timescale_state
             .load_waveform(range.clone(), range.end, timescale);
         self.python_path = python_path;
+        self.minimap_signals = None;
+        self.load_annotations();
+        self.load_color_rules();
+        self.push_named_cursors();
+        self.push_cursor();
+        self.messages.push(SignalViewerMessage::SetMarker(
+            self.timescale_state.get_marker(),
+        ));
+    }
+
+    fn push_cursor(&self) {
+        let cursor = self.timescale_state.get_cursor();
+        self.messages.push(InspectorMessage::SetCursor(cursor));
+        self.messages.push(NaluMessage::SetCursor(cursor));
+        self.messages.push(SignalViewerMessage::SetCursor(cursor));
+        self.messages.push(PythonViewMessage::SetCursor(cursor));
+    }
+
+    fn push_range(&mut self) {
+        self.timescale_state.update_follow();
+        let follow = self.timescale_state.get_follow();
+        if follow != self.header_follow {
+            self.messages.push(HeaderMessage::UpdateFollow(follow));
+            self.header_follow = follow;
+        }
+        let range = (
+            self.timescale_state.get_range(),
+            self.waveform.get_timestamp_range(),
+            self.timescale_state.get_timescale(),
+        );
+        if self.header_range.as_ref() == Some(&range) {
+            return;
+        }
+        self.messages.push(HeaderMessage::SetRange(
+            range.0.clone(),
+            range.1.clone(),
+            range.2,
+        ));
+        self.header_range = Some(range);
+    }
+
+    fn set_marker(&mut self, marker: Option<u64>) {
+        self.timescale_state.set_marker(marker);
+        self.messages.push(SignalViewerMessage::SetMarker(marker));
+    }
+
+    /// Swaps in a rescan of the same file, keeping the current view unless
+    /// there was nothing to view before
+    fn replace_waveform(&mut self, waveform: Arc<Waveform>) {
+        self.waveform.replace(waveform);
+        self.minimap_signals = None;
+        if self.timescale_state.get_timestamp_max() == 0 {
+            let range = self.waveform.get_timestamp_range();
+            let timescale = self.timescale_state.get_timescale();
+            self.timescale_state
+                .load_waveform(range.clone(), range.end, timescale);
+        }
+    }
+
+    /// Moves the cursor to the nearest change of any displayed signal after
+    /// (or before) it
+    fn goto_activity(&mut self, forward: bool) {
+        let cursor = self.timescale_state.get_cursor();
+        let transitions = self.get_displayed_entries().filter_map(|entry| {
+            // Search in the time of the signal, which is shifted by its offset
+            let cursor = cursor as i64 - entry.offset;
+            if cursor < 0 && !forward {
+                return None;
+            }
+            let timestamp = find_transition(
+                &self.waveform,
+                entry.idcode,
+                entry.index,
+                cursor.max(0) as u64,
+                forward,
+            )?;
+            u64::try_from(timestamp as i64 + entry.offset).ok()
+        });
+        let nearest = if forward {
+            transitions.min()
+        } else {
+            transitions.max()
+        };
+        if let Some(timestamp) = nearest {
+            self.timescale_state.goto(timestamp);
+            self.push_cursor();
+        }
+    }
+
+    /// Zooms into the column with the most transitions of the selected signal,
+    /// the one nearest to the cursor out of the busiest, padded by a column of
+    /// time on each side
+    fn zoom_densest(&mut self) {
+        let Some(entry) = self.get_displayed_entries().find(|entry| entry.is_primary) else {
+            return;
+        };
+        let range = self.timescale_state.get_range();
+        let shift = |timestamp: u64| u64::try_from(timestamp as i64 - entry.offset).unwrap_or(0);
+        let transitions = get_transitions(
+            &self.waveform,
+            entry.idcode,
+            entry.index,
+            &(shift(range.start)..shift(range.end)),
+        );
+        let density = self.get_column_density(
+            transitions
+                .into_iter()
+                .filter_map(|timestamp| u64::try_from(timestamp as i64 + entry.offset).ok())
+                .filter(|timestamp| range.contains(timestamp)),
+        );
+        let cursor = self
+            .timescale_state
+            .get_timestamp_column(self.timescale_state.get_cursor(), self.width as u16)
+            .unwrap_or(0) as usize;
+        let Some((column, _)) = density
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 1)
+            .max_by_key(|(column, count)| (**count, std::cmp::Reverse(column.abs_diff(cursor))))
+        else {
+            return;
+        };
+        let columns = self.timescale_state.get_columns(self.width as u16);
+        let Some((range, _)) = columns.get(column) else {
+            return;
+        };
+        let pad = (range.end - range.start).max(1);
+        self.timescale_state
+            .set_range(range.start.saturating_sub(pad)..range.end + pad);
+    }
+
+    /// Moves the cursor to the next (or previous) annotation matching the last
+    /// search
+    fn goto_annotation(&mut self, forward: bool) {
+        let Some(query) = &self.annotation_query else {
+            self.messages
+                .push(NaluMessage::OpenPalette(String::from("search ")));
+            return;
+        };
+        let cursor = self.timescale_state.get_cursor();
+        match self.timescale_state.find_annotation(query, cursor, forward) {
+            Some(timestamp) => {
+                self.timescale_state.goto(timestamp);
+                self.push_cursor();
+            }
+            None => self.messages.push(NaluMessage::PaletteError(format!(
+                "No annotation matching \"{query}\" {}",
+                if forward {
+                    "after the cursor"
+                } else {
+                    "before the cursor"
+                }
+            ))),
+        }
+    }
+
+    fn get_displayed_entries(&self) -> impl Iterator<Item = &SignalViewerEntry> {
+        self.pinned_entries
+            .iter()
+            .chain(self.signal_entries.iter().filter_map(|row| match row {
+                Some(SignalViewerRow::Signal(entry)) => Some(entry),
+                _ => None,
+            }))
+            .filter(|entry| entry.plot_row.is_none_or(|(row, _)| row == 0))
+    }
+
+    /// Finds the stretches of the range where none of the signals in the
+    /// viewer change, including the ones in collapsed groups, to collapse
+    /// while compressing idle time
+    fn update_breaks(&mut self) {
+        if !self.timescale_state.is_compressed() {
+            return;
+        }
+        let range = self.timescale_state.get_range();
+        let grouped = self.signal_entries.iter().flat_map(|row| match row {
+            Some(SignalViewerRow::Group(signals)) => signals.clone(),
+            _ => Vec::new(),
+        });
+        let mut changes = self
+            .get_displayed_entries()
+            .map(|entry| (entry.idcode, entry.index, entry.offset))
+            .chain(grouped)
+            .flat_map(|(idcode, index, offset)| {
+                let shift = |timestamp: u64| u64::try_from(timestamp as i64 - offset).unwrap_or(0);
+                let shifted = shift(range.start)..shift(range.end);
+                get_transitions(&self.waveform, idcode, index, &shifted)
+                    .into_iter()
+                    .filter_map(move |timestamp| u64::try_from(timestamp as i64 + offset).ok())
+            })
+            .collect::<Vec<u64>>();
+        changes.sort_unstable();
+        changes.dedup();
+        let breaks = get_idle_breaks(&changes, &range);
+        self.timescale_state.set_breaks(breaks);
+    }
+
+    fn update_minimap(&mut self) {
+        let signals = self
+            .get_displayed_entries()
+            .map(|entry| (entry.idcode, entry.index, entry.offset))
+            .collect::<Vec<_>>();
+        if self.minimap_density.len() == self.width
+            && self.minimap_signals.as_ref() == Some(&signals)
+        {
+            return;
+        }
+        let range = self.waveform.get_timestamp_range();
+        let transitions = signals.iter().flat_map(|(idcode, index, offset)| {
+            get_transitions(&self.waveform, *idcode, *index, &range)
+                .into_iter()
+                .filter_map(move |timestamp| u64::try_from(timestamp as i64 + offset).ok())
+        });
+        self.minimap_density = get_minimap_density(transitions, &range, self.width);
+        self.minimap_signals = Some(signals);
+    }
+
+    /// Moves the visible range (and the cursor) to the time under a column of
+    /// the minimap, where dragging on from a click is a single step back
+    fn jump_minimap(&mut self, column: u16, drag: bool) {
+        let range = self.waveform.get_timestamp_range();
+        let timestamp = get_minimap_timestamp(column, &range, self.width as u16);
+        self.timescale_state.recenter(timestamp, !drag);
+        self.timescale_state.set_cursor(timestamp);
+        self.push_cursor();
+    }
+
+    fn load_annotations(&mut self) {
+        self.timescale_state.clear_annotations();
+        if let Some(python_path) = &self.python_path {
+            match run_annotations(python_path, VcdHeaderPy::new(self.vcd_header.clone())) {
+                Ok(annotations) => {
+                    for (timestamp, text) in annotations {
+                        self.timescale_state.add_annotation(timestamp, text);
+                    }
+                }
+                Err(err) => {
+                    log::error!("Python Annotations Error: {err:?}");
+                    self.messages
+                        .push(NaluMessage::PythonError(PythonError::new(
+                            "annotations()",
+                            &err,
+                            PythonRerun::Script,
+                        )));
+                }
+            }
+        }
+        self.push_annotations();
+    }
+
+    fn load_color_rules(&mut self) {
+        self.color_rules.clear();
+        let Some(python_path) = &self.python_path else {
+            return;
+        };
+        let rules = match run_color_rules(python_path) {
+            Ok(rules) => rules,
+            Err(err) => {
+                log::error!("Python Color Rules Error: {err:?}");
+                self.messages
+                    .push(NaluMessage::PythonError(PythonError::new(
+                        "color rules",
+                        &err,
+                        PythonRerun::Script,
+                    )));
+                return;
+            }
+        };
+        for (name, rule) in rules {
+            match self.vcd_header.get_variable(&name) {
+                Some(variable) => {
+                    self.color_rules
+                        .insert(variable.get_idcode(), ColorRule::new(rule));
+                }
+                None => log::warn!("Python Color Rules: No signal named {name}"),
+            }
+        }
+    }
+
+    fn push_named_cursors(&self) {
+        let cursors = self.timescale_state.get_named_cursors();
+        set_cursors(cursors.iter().map(|c| (c.name, c.timestamp)).collect());
+        self.messages
+            .push(NaluMessage::UpdateCursors(cursors.clone()));
+    }
+
+    /// Places the first free named cursor at the cursor
+    fn add_named_cursor(&mut self) {
+        let Some(name) = self.timescale_state.get_free_cursor_name() else {
+            self.messages.push(NaluMessage::PaletteError(String::from(
+                "Every named cursor is placed, remove one with K first",
+            )));
+            return;
+        };
+        let cursor = self.timescale_state.get_cursor();
+        self.timescale_state.set_named_cursor(name, cursor);
+        self.push_named_cursors();
+    }
+
+    /// Measures the periods between the rising edges of a single bit in view
+    /// and shows them in the inspector, optionally placing named cursors at the
+    /// shortest and longest of them
+    fn measure_period(&mut self, name: &str, idcode: usize, index: Option<usize>, cursors: bool) {
+        if index.is_none() && self.waveform.get_vector_width(idcode) != Some(1) {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "Select a single-bit signal to measure its period, {name} is a vector"
+            )));
+            return;
+        }
+        let range = self.timescale_state.get_range();
+        let edges = get_rising_edges(&self.waveform, idcode, index, &range);
+        let Some(statistics) = measure_periods(&edges) else {
+            self.messages.push(NaluMessage::PaletteError(format!(
+                "Fewer than two rising edges of {name} in view"
+            )));
+            return;
+        };
+        if cursors {
+            let mut timestamps = vec![statistics.min.1, statistics.max.1];
+            timestamps.dedup();
+            for timestamp in timestamps {
+                let Some(name) = self.timescale_state.get_free_cursor_name() else {
+                    self.messages.push(NaluMessage::PaletteError(String::from(
+                        "Every named cursor is placed, remove one with K first",
+                    )));
+                    break;
+                };
+                self.timescale_state.set_named_cursor(name, timestamp);
+            }
+            self.push_named_cursors();
+        }
+        self.messages
+            .push(InspectorMessage::SetPeriods(idcode, index, statistics));
+    }
+
+    /// Sends every value of the selected signal between the cursor and the
+    /// marker (including both) in the radix it is drawn in
+    fn show_history(&self) {
+        let Some(entry) = self.get_displayed_entries().find(|entry| entry.is_primary) else {
+            self.messages.push(NaluMessage::PaletteError(String::from(
+                "Select a signal to list its values",
+            )));
+            return;
+        };
+        let Some(marker) = self.timescale_state.get_marker() else {
+            self.messages.push(NaluMessage::PaletteError(String::from(
+                "Place a marker with m to list the values up to it",
+            )));
+            return;
+        };
+        let cursor = self.timescale_state.get_cursor();
+        let range = cursor.min(marker)..cursor.max(marker) + 1;
+        let history = get_value_history(&self.waveform, entry.idcode, entry.index, &range);
+        let values = history
+            .iter()
+            .take(HISTORY_LIMIT)
+            .map(|(timestamp, bitvector)| {
+                let value = match entry.is_real {
+                    true => format!("{}", decode_real(bitvector)),
+                    false => bitvector.to_string_radix(entry.radix),
+                };
+                (*timestamp, value)
+            })
+            .collect();
+        self.messages.push(NaluMessage::ShowHistory(ValueHistory {
+            name: entry.name.clone(),
+            range,
+            values,
+            truncated: history.len() > HISTORY_LIMIT,
+        }));
+    }
+
+    fn push_annotations(&self) {
+        self.messages.push(NaluMessage::UpdateAnnotations(
+            self.timescale_state.get_annotations().clone(),
+        ));
     }
 
     pub fn set_size(&mut self, size: &Rect, border_width: u16) {
@@ -77,74 +548,187 @@ impl WaveformViewerState {
         self.height = size.height as usize;
     }
 
+    fn get_signal_widget(&self, entry: &SignalViewerEntry) -> WaveformWidget<'_, VirtualWaveform> {
+        WaveformWidget::new(
+            &self.timescale_state,
+            &self.waveform,
+            entry.idcode,
+            entry.index,
+            entry.radix,
+            entry.is_selected,
+        )
+        .primary(entry.is_primary)
+        .real(entry.is_real)
+        .event(self.events.contains(&entry.idcode))
+        .plot_row(entry.plot_row)
+        .color_rule(self.color_rules.get(&entry.idcode))
+        .high_resolution(self.high_resolution)
+        .offset(entry.offset)
+    }
+
+    /// Counts the transitions of the signals in a collapsed group falling into
+    /// each visible column, where the signals are drawn shifted by the offsets
+    fn get_group_activity(&self, signals: &[(usize, Option<usize>, i64)]) -> Vec<usize> {
+        let range = self.timescale_state.get_range();
+        let transitions = signals.iter().flat_map(|(idcode, index, offset)| {
+            let shift = |timestamp: u64| u64::try_from(timestamp as i64 - offset).unwrap_or(0);
+            let shifted = shift(range.start)..shift(range.end);
+            get_transitions(&self.waveform, *idcode, *index, &shifted)
+                .into_iter()
+                .filter_map(move |timestamp| u64::try_from(timestamp as i64 + offset).ok())
+        });
+        self.get_column_density(transitions.filter(|timestamp| range.contains(timestamp)))
+    }
+
+    /// Counts the transitions in view falling into each visible column, which
+    /// are not all as wide when idle time is compressed
+    fn get_column_density(&self, transitions: impl Iterator<Item = u64>) -> Vec<usize> {
+        if !self.timescale_state.is_compressed() {
+            let range = self.timescale_state.get_range();
+            return get_minimap_density(transitions, &range, self.width);
+        }
+        let columns = self.timescale_state.get_columns(self.width as u16);
+        let mut density = vec![0; columns.len()];
+        for timestamp in transitions {
+            let column = columns.partition_point(|(range, _)| range.end <= timestamp);
+            if let Some(count) = density.get_mut(column) {
+                *count += 1;
+            }
+        }
+        density
+    }
+
     fn get_waveform_widget(&self) -> WaveformViewerWidget<'_> {
+        let pinned_widgets = self
+            .pinned_entries
+            .iter()
+            .map(|entry| self.get_signal_widget(entry))
+            .collect::<Vec<WaveformWidget<VirtualWaveform>>>();
         let signal_widgets = self
             .signal_entries
             .iter()
-            .map(|entry| {
-                entry.as_ref().map(|entry| {
-                    WaveformWidget::new(
-                        &self.timescale_state,
-                        &self.waveform,
-                        entry.idcode,
-                        entry.index,
-                        entry.radix,
-                        entry.is_selected,
-                    )
-                })
+            .map(|row| match row {
+                Some(SignalViewerRow::Signal(entry)) => {
+                    Some(WaveformRow::Signal(self.get_signal_widget(entry)))
+                }
+                Some(SignalViewerRow::Separator(label)) => {
+                    Some(WaveformRow::Separator(label.clone()))
+                }
+                Some(SignalViewerRow::Group(signals)) => Some(WaveformRow::Activity(
+                    ActivityLine::new(self.get_group_activity(signals)),
+                )),
+                None => None,
             })
-            .collect::<Vec<Option<WaveformWidget>>>();
+            .collect::<Vec<Option<WaveformRow>>>();
         WaveformViewerWidget {
+            timescale_state: &self.timescale_state,
             timescale_widget: Timescale::new(&self.timescale_state),
+            minimap_widget: Minimap::new(
+                &self.timescale_state,
+                self.waveform.get_timestamp_range(),
+                &self.minimap_density,
+            ),
+            pinned_widgets,
             signal_widgets,
+            hidden_signals: self.hidden_entries,
             block: None,
             style: Default::default(),
         }
     }
 
-    fn get_python_widget(&self) -> Paragraph<'_> {
-        use crate::python::{buffer::*, vcd_header::*, waveform::*};
-        use pyo3::prelude::*;
+    /// The signal drawn on a row of the pane, if there is one
+    fn get_row_entry(&self, y: u16) -> Option<&SignalViewerEntry> {
+        let top = 1 + MINIMAP_HEIGHT;
+        let pinned_height = get_pinned_height(self.pinned_entries.len());
+        let row = (y as usize).checked_sub(top)?;
+        if row < pinned_height {
+            return self.pinned_entries.get(row);
+        }
+        match self.signal_entries.get(row - pinned_height)? {
+            Some(SignalViewerRow::Signal(entry)) => Some(entry),
+            _ => None,
+        }
+    }
 
-        let Some(python_path) = self.python_path.clone() else {
-            return Paragraph::new("No python loaded!");
+    /// Draws a box next to the mouse with the name of the signal under it, the
+    /// time of the column and the whole value there, once the mouse rests
+    fn render_tooltip(&self, area: Rect, buf: &mut Buffer) {
+        let Some((x, y, time)) = self.hover else {
+            return;
         };
-
-        let result: PyResult<BufferPy> = Python::with_gil(|py| {
-            let nalu = PyModule::new(py, "nalu")?;
-            nalu.add_class::<crate::python::waveform::WaveformSearchModePy>()?;
-            py.import("sys")?
-                .getattr("modules")?
-                .set_item("nalu", nalu)?;
-
-            let python_bytes = std::fs::read(python_path)?;
-            let python_file = String::from_utf8_lossy(&python_bytes);
-            let main: Py<PyAny> = PyModule::from_code(py, &python_file, "", "")?
-                .getattr("main")?
-                .into();
-
-            let buffer = BufferPy::new(self.width as u16, self.height as u16);
-            let waveform = WaveformPy::new(self.waveform.clone());
-            let vcd_header = VcdHeaderPy::new(self.vcd_header.clone());
-            let cursor = self.timescale_state.get_cursor();
-            main.call1(py, (buffer, waveform, vcd_header, cursor))?
-                .extract::<BufferPy>(py)
-        });
-
-        match result {
-            Ok(buffer) => {
-                let mut spans = Vec::new();
-                for y in 0..buffer.get_height() {
-                    let mut string = String::new();
-                    for x in 0..buffer.get_width() {
-                        string.push(buffer.get_cell(x, y));
-                    }
-                    spans.push(Spans::from(string.trim().to_string()));
-                }
-                Paragraph::new(spans)
-            }
-            Err(err) => Paragraph::new(format!("{err:#?}")),
+        let elapsed = time.elapsed();
+        if !(HOVER_DELAY..HOVER_TIMEOUT).contains(&elapsed) || x >= area.width {
+            return;
         }
+        let Some(entry) = self.get_row_entry(y) else {
+            return;
+        };
+        let timestamp = self.timescale_state.get_column_timestamp(x, area.width);
+        let value = match u64::try_from(timestamp as i64 - entry.offset) {
+            Ok(timestamp) if self.waveform.has_history(entry.idcode) => render_value(
+                &self.waveform,
+                entry.idcode,
+                entry.index,
+                entry.radix,
+                entry.is_real,
+                timestamp,
+            )
+            .unwrap_or(String::from("(no value)")),
+            Ok(_) => String::from("(not loaded)"),
+            Err(_) => String::from("(no value)"),
+        };
+        let lines = [
+            entry.name.clone(),
+            format!(
+                "{} ({timestamp})",
+                render_time(timestamp, 1, self.timescale_state.get_timescale())
+            ),
+            value,
+        ];
+        let width = (lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) + 2)
+            .min(area.width as usize) as u16;
+        let inner = width.saturating_sub(2).max(1) as usize;
+        let height = (lines
+            .iter()
+            .map(|l| l.chars().count().max(1).div_ceil(inner))
+            .sum::<usize>()
+            + 2)
+        .min(area.height as usize) as u16;
+        // Below and to the right of the mouse, unless that runs off the pane
+        let left = match x + 1 + width > area.width {
+            true => area.width.saturating_sub(width),
+            false => x + 1,
+        };
+        let top = match y + 1 + height > area.height {
+            true => y.saturating_sub(height),
+            false => y + 1,
+        };
+        let tooltip = Rect {
+            x: area.x + left,
+            y: area.y + top,
+            width,
+            height,
+        };
+        Clear.render(tooltip, buf);
+        Paragraph::new(lines.join("\n"))
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .render(tooltip, buf);
+    }
+
+    /// Renders the waveforms the same as the component does, timing how long
+    /// the minimap, building the widgets and drawing them each take
+    pub fn render_timed(&mut self, area: Rect, buf: &mut Buffer, timestamps: &mut FrameTimestamps) {
+        self.update_minimap();
+        self.update_breaks();
+        timestamps.timestamp(String::from("minimap"));
+        let widget = self
+            .get_waveform_widget()
+            .style(Style::default().fg(Color::LightCyan));
+        timestamps.timestamp(String::from("widgets"));
+        widget.render(area, buf);
+        timestamps.timestamp(String::from("draw"));
     }
 
     // fn modify_list(&mut self, action: ListAction) {
@@ -173,9 +757,21 @@ impl WaveformViewerState {
     // }
 }
 
+/// A row drawn below the timescale and the pinned signals
+enum WaveformRow<'a> {
+    Signal(WaveformWidget<'a, VirtualWaveform>),
+    Separator(String),
+    Activity(ActivityLine),
+}
+
 pub struct WaveformViewerWidget<'a> {
+    timescale_state: &'a TimescaleState,
     timescale_widget: Timescale<'a>,
-    signal_widgets: Vec<Option<WaveformWidget<'a>>>,
+    minimap_widget: Minimap<'a>,
+    pinned_widgets: Vec<WaveformWidget<'a, VirtualWaveform>>,
+    signal_widgets: Vec<Option<WaveformRow<'a>>>,
+    /// Signals below the ones in view, shown as a count after the last row
+    hidden_signals: usize,
     /// A block to wrap the widget in
     block: Option<Block<'a>>,
     /// Widget style
@@ -217,34 +813,198 @@ impl<'a> Widget for WaveformViewerWidget<'a> {
             height: 1,
         };
         self.timescale_widget.render(area_line, buf);
-        for (i, signal_widget) in self.signal_widgets.into_iter().enumerate() {
-            if (i + 1) as u16 >= area.height {
+        if area.height > 1 {
+            area_line.y = area.y + 1;
+            self.minimap_widget.render(area_line, buf);
+        }
+        let top = 1 + MINIMAP_HEIGHT;
+        // Pinned signals are followed by a separator line
+        let pinned_height = get_pinned_height(self.pinned_widgets.len());
+        for (i, pinned_widget) in self.pinned_widgets.into_iter().enumerate() {
+            if (i + top) as u16 >= area.height {
                 break;
             }
-            area_line.y = area.y + (i + 1) as u16;
-            if let Some(signal_widget) = signal_widget {
-                signal_widget.render(area_line, buf);
+            area_line.y = area.y + (i + top) as u16;
+            pinned_widget.render(area_line, buf);
+        }
+        if pinned_height > 0 && ((top + pinned_height - 1) as u16) < area.height {
+            area_line.y = area.y + (top + pinned_height - 1) as u16;
+            Paragraph::new("─".repeat(area.width as usize)).render(area_line, buf);
+        }
+        // Leave the last row for the count of signals that did not fit
+        let rows = (area.height as usize).saturating_sub(top + pinned_height);
+        let mut hidden = self.hidden_signals;
+        if self.signal_widgets.len() + (hidden > 0) as usize > rows {
+            hidden += self.signal_widgets.len() + 1 - rows.max(1);
+            self.signal_widgets.truncate(rows.saturating_sub(1));
+        }
+        let shown = self.signal_widgets.len();
+        for (i, row) in self.signal_widgets.into_iter().enumerate() {
+            area_line.y = area.y + (i + top + pinned_height) as u16;
+            match row {
+                Some(WaveformRow::Signal(signal_widget)) => signal_widget.render(area_line, buf),
+                Some(WaveformRow::Separator(label)) => {
+                    let label = format!("── {label} ");
+                    let rule =
+                        "─".repeat((area.width as usize).saturating_sub(label.chars().count()));
+                    Paragraph::new(format!("{label}{rule}"))
+                        .style(Style::default().fg(Color::DarkGray))
+                        .render(area_line, buf);
+                }
+                Some(WaveformRow::Activity(activity)) => activity.render(area_line, buf),
+                None => {}
+            }
+        }
+        let offset = shown + top + pinned_height;
+        if hidden > 0 && (offset as u16) < area.height {
+            area_line.y = area.y + offset as u16;
+            Paragraph::new(format!("+{hidden} more ↓"))
+                .alignment(Alignment::Right)
+                .render(area_line, buf);
+        }
+
+        // Highlight the marker and then the cursor column over the signals
+        let marker = self.timescale_state.get_marker().and_then(|marker| {
+            self.timescale_state
+                .get_timestamp_column(marker, area.width)
+        });
+        if let Some(column) = marker {
+            for y in (area.y + top as u16)..(area.y + area.height) {
+                let cell = buf.get_mut(area.x + column, y);
+                cell.set_style(
+                    cell.style()
+                        .fg(Color::LightMagenta)
+                        .add_modifier(Modifier::REVERSED),
+                );
+            }
+        }
+        for named_cursor in self.timescale_state.get_named_cursors() {
+            let Some(column) = self
+                .timescale_state
+                .get_timestamp_column(named_cursor.timestamp, area.width)
+            else {
+                continue;
+            };
+            for y in (area.y + top as u16)..(area.y + area.height) {
+                let cell = buf.get_mut(area.x + column, y);
+                cell.set_style(
+                    cell.style()
+                        .fg(get_cursor_color(named_cursor.name))
+                        .add_modifier(Modifier::REVERSED),
+                );
+            }
+        }
+        let cursor = self.timescale_state.get_cursor();
+        if let Some(column) = self
+            .timescale_state
+            .get_timestamp_column(cursor, area.width)
+        {
+            for y in (area.y + top as u16)..(area.y + area.height) {
+                let cell = buf.get_mut(area.x + column, y);
+                cell.set_style(cell.style().add_modifier(Modifier::REVERSED));
             }
         }
     }
 }
 
 impl ComponentWidget for WaveformViewerState {
-    fn handle_mouse(&mut self, _x: u16, _y: u16, _kind: MouseEventKind) -> bool {
-        false
+    fn handle_mouse(&mut self, x: u16, y: u16, kind: MouseEventKind) -> bool {
+        self.hover = None;
+        match kind {
+            MouseEventKind::Moved => {
+                self.hover = Some((x, y, Instant::now()));
+                return true;
+            }
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
+                if (1..=MINIMAP_HEIGHT as u16).contains(&y) =>
+            {
+                self.jump_minimap(x, matches!(kind, MouseEventKind::Drag(_)));
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let cursor = self
+                    .timescale_state
+                    .get_column_timestamp(x, self.width as u16);
+                self.timescale_state.set_cursor(cursor);
+                self.push_cursor();
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                let marker = self
+                    .timescale_state
+                    .get_column_timestamp(x, self.width as u16);
+                self.set_marker(Some(marker));
+            }
+            _ => return false,
+        }
+        self.push_range();
+        true
     }
 
     fn handle_key(&mut self, e: KeyEvent) -> bool {
         match e.code {
-            KeyCode::Char('v') => self.python_view = !self.python_view,
-            KeyCode::Char('-') => self.timescale_state.zoom_out(false),
-            KeyCode::Char('=') => self.timescale_state.zoom_in(false),
-            KeyCode::Char('[') => self.timescale_state.zoom_left(false),
-            KeyCode::Char(']') => self.timescale_state.zoom_right(false),
-            KeyCode::Char('_') => self.timescale_state.zoom_out(true),
-            KeyCode::Char('+') => self.timescale_state.zoom_in(true),
-            KeyCode::Char('{') => self.timescale_state.zoom_left(true),
-            KeyCode::Char('}') => self.timescale_state.zoom_right(true),
+            KeyCode::Char('v') => self
+                .messages
+                .push(LayoutMessage::Toggle(String::from(PYTHON_PANE))),
+            KeyCode::Char('b') => self.high_resolution = !self.high_resolution,
+            KeyCode::Char('x') => {
+                let compressed = !self.timescale_state.is_compressed();
+                self.timescale_state.set_compressed(compressed);
+            }
+            KeyCode::Char(c @ ('-' | '=' | '[' | ']' | '_' | '+' | '{' | '}')) => {
+                let action = match c {
+                    '-' | '_' => ZoomAction::Out,
+                    '=' | '+' => ZoomAction::In,
+                    '[' | '{' => ZoomAction::Left,
+                    _ => ZoomAction::Right,
+                };
+                let cursor = matches!(c, '_' | '+' | '{' | '}');
+                self.timescale_state
+                    .zoom_repeated(action, cursor, Instant::now());
+            }
+            KeyCode::Char(',') => self.goto_activity(false),
+            KeyCode::Char('.') => self.goto_activity(true),
+            KeyCode::Char('m') => self.set_marker(Some(self.timescale_state.get_cursor())),
+            KeyCode::Char('M') => self.set_marker(None),
+            KeyCode::Char('n') => self
+                .messages
+                .push(NaluMessage::OpenPalette(String::from("annotate "))),
+            KeyCode::Char('N') => self.messages.push(NaluMessage::OpenAnnotations),
+            KeyCode::Char('/') => self
+                .messages
+                .push(NaluMessage::OpenPalette(String::from("search "))),
+            KeyCode::Char(';') => self.goto_annotation(true),
+            KeyCode::Char(':') => self.goto_annotation(false),
+            KeyCode::Char('t') => self.messages.push(NaluMessage::OpenForm(Form::new(
+                "Go to Time",
+                FormKind::GotoTime,
+                vec![
+                    FormField::Text(String::from("Time"), String::new()),
+                    FormField::Choice(
+                        String::from("From"),
+                        vec![String::from("start"), String::from("cursor")],
+                        0,
+                    ),
+                    FormField::Button(String::from("Go")),
+                    FormField::Button(String::from("Cancel")),
+                ],
+            ))),
+            KeyCode::Char('k') => self.add_named_cursor(),
+            KeyCode::Char('K') => self.messages.push(NaluMessage::OpenCursors),
+            KeyCode::Char('z') => self
+                .messages
+                .push(NaluMessage::OpenPalette(String::from("zoom "))),
+            KeyCode::Char('Z') => self.timescale_state.zoom_all(),
+            KeyCode::Char('#') => self.zoom_densest(),
+            KeyCode::Char('<') => {
+                self.timescale_state.history_back();
+            }
+            KeyCode::Char('>') => {
+                self.timescale_state.history_forward();
+            }
+            KeyCode::Char('F') => {
+                let follow = self.timescale_state.get_follow().is_none();
+                self.timescale_state.set_follow(follow);
+                self.messages.push(NaluMessage::SetFollow(follow));
+            }
             KeyCode::Up
             | KeyCode::Down
             | KeyCode::PageDown
@@ -252,11 +1012,13 @@ impl ComponentWidget for WaveformViewerState {
             | KeyCode::Enter
             | KeyCode::Char('g')
             | KeyCode::Char('f')
+            | KeyCode::Char('P')
             | KeyCode::Delete => {
                 self.messages.push(SignalViewerMessage::WaveformKey(e));
             }
             _ => return false,
         }
+        self.push_range();
         true
     }
 
@@ -264,8 +1026,12 @@ impl ComponentWidget for WaveformViewerState {
         let mut updated = false;
         for message in self.messages.get::<WaveformViewerMessage>() {
             match message {
-                WaveformViewerMessage::UpdateSignals(signals) => {
+                WaveformViewerMessage::UpdateSignals(signals, hidden) => {
                     self.signal_entries = signals;
+                    self.hidden_entries = hidden;
+                }
+                WaveformViewerMessage::UpdatePinned(pinned) => {
+                    self.pinned_entries = pinned;
                 }
                 WaveformViewerMessage::UpdateWaveform(
                     waveform,
@@ -275,13 +1041,88 @@ impl ComponentWidget for WaveformViewerState {
                 ) => {
                     self.load_waveform(waveform, vcd_header, timescale, python_path);
                 }
+                WaveformViewerMessage::ReplaceWaveform(waveform) => self.replace_waveform(waveform),
+                WaveformViewerMessage::AddVirtualVector(vector) => {
+                    self.waveform.add_vector(vector);
+                    self.minimap_signals = None;
+                }
+                WaveformViewerMessage::SetEvents(events) => self.events = events,
+                WaveformViewerMessage::AddAnnotation(text) => {
+                    let cursor = self.timescale_state.get_cursor();
+                    self.timescale_state.add_annotation(cursor, text);
+                    self.push_annotations();
+                }
+                WaveformViewerMessage::SearchAnnotations(query) => {
+                    self.annotation_query = Some(query);
+                    self.goto_annotation(true);
+                }
+                WaveformViewerMessage::RemoveAnnotation(index) => {
+                    self.timescale_state.remove_annotation(index);
+                    self.push_annotations();
+                }
+                WaveformViewerMessage::SetNamedCursor(name, timestamp) => {
+                    let timestamp = timestamp.unwrap_or(self.timescale_state.get_cursor());
+                    self.timescale_state.set_named_cursor(name, timestamp);
+                    self.push_named_cursors();
+                }
+                WaveformViewerMessage::RemoveNamedCursor(name) => {
+                    self.timescale_state.remove_named_cursor(name);
+                    self.push_named_cursors();
+                }
+                WaveformViewerMessage::GotoTimestamp(timestamp) => {
+                    self.timescale_state.goto(timestamp);
+                    self.push_cursor();
+                }
+                WaveformViewerMessage::Autosave(view) => {
+                    self.messages.push(NaluMessage::Autosave(SignalView {
+                        range: Some(self.timescale_state.get_range()),
+                        ..view
+                    }));
+                }
+                WaveformViewerMessage::SaveView(view) => {
+                    self.messages.push(NaluMessage::SaveView(SignalView {
+                        range: Some(self.timescale_state.get_range()),
+                        ..view
+                    }));
+                }
+                WaveformViewerMessage::Share(signal) => {
+                    self.messages.push(NaluMessage::Share(SharedLocation {
+                        file: None,
+                        range: self.timescale_state.get_range(),
+                        cursor: self.timescale_state.get_cursor(),
+                        signal,
+                    }));
+                }
+                WaveformViewerMessage::SetRange(range) => self.timescale_state.set_range(range),
+                WaveformViewerMessage::ZoomAround(width) => {
+                    self.timescale_state.zoom_around_cursor(width)
+                }
+                WaveformViewerMessage::ZoomAll => self.timescale_state.zoom_all(),
+                WaveformViewerMessage::SetAcceleration(acceleration) => {
+                    self.timescale_state.set_acceleration(acceleration)
+                }
+                WaveformViewerMessage::ReloadPython => {
+                    self.load_annotations();
+                    self.load_color_rules();
+                }
+                WaveformViewerMessage::MeasureDelays(signals) => self.messages.push(
+                    NaluMessage::MeasureDelays(signals, self.timescale_state.get_range()),
+                ),
+                WaveformViewerMessage::MeasurePeriod(name, idcode, index, cursors) => {
+                    self.measure_period(&name, idcode, index, cursors)
+                }
+                WaveformViewerMessage::ShowHistory => self.show_history(),
             }
             updated = true;
         }
+        self.push_range();
         updated
     }
 
     fn resize(&mut self, width: u16, height: u16) {
+        self.messages.push(SignalViewerMessage::SetWaveformHeight(
+            height.saturating_sub(2) as isize,
+        ));
         self.set_size(
             &Rect {
                 x: 0,
@@ -294,15 +1135,33 @@ impl ComponentWidget for WaveformViewerState {
     }
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        if self.python_view {
-            self.get_python_widget()
-                .style(Style::default().fg(Color::LightCyan))
-                .render(area, buf);
+        self.update_minimap();
+        self.update_breaks();
+        let widget = self
+            .get_waveform_widget()
+            .style(Style::default().fg(Color::LightCyan));
+        if self.color_rules.is_empty() {
+            widget.render(area, buf);
         } else {
-            self.get_waveform_widget()
-                .style(Style::default().fg(Color::LightCyan))
-                .render(area, buf);
+            // Values not seen before call into the coloring rules, which all
+            // share one watchdog for the frame
+            let result = with_timeout(|_| {
+                widget.render(area, buf);
+                Ok(())
+            });
+            if let Err(err) = result {
+                // The rules can't be watched, so draw without them from now on
+                log::error!("Python Color Rules Error: {err:?}");
+                self.color_rules.clear();
+                self.messages
+                    .push(NaluMessage::PythonError(PythonError::new(
+                        "color rules",
+                        &err,
+                        PythonRerun::Script,
+                    )));
+            }
         }
+        self.render_tooltip(area, buf);
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
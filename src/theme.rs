@@ -0,0 +1,192 @@
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+/// Returns if colors should be left off by default, following the `NO_COLOR`
+/// convention and treating dumb terminals as not supporting them
+pub fn is_color_disabled() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    no_color || dumb
+}
+
+/// Plain ASCII stand-ins for the symbols drawn by nalu and its borders, with
+/// anything else outside of ASCII shown as '?'
+const ASCII_GLYPHS: &[(&str, &str)] = &[
+    ("─", "-"),
+    ("━", "-"),
+    ("│", "|"),
+    ("┃", "|"),
+    ("╭", "+"),
+    ("╮", "+"),
+    ("╰", "+"),
+    ("╯", "+"),
+    ("┌", "+"),
+    ("┐", "+"),
+    ("└", "+"),
+    ("├", "+"),
+    ("┘", "+"),
+    ("█", "#"),
+    ("░", "."),
+    ("▒", ":"),
+    ("▓", "%"),
+    ("▀", "\""),
+    ("▄", "."),
+    ("↑", "^"),
+    ("⇑", "*"),
+    ("↓", "v"),
+    ("◆", "*"),
+    ("⏰", "~"),
+    ("…", "."),
+    ("≈", "~"),
+    ("▲", "^"),
+    ("▼", "v"),
+    ("▣", "#"),
+    ("∿", "~"),
+    ("•", "*"),
+    ("≡", "="),
+];
+
+fn get_ascii_glyph(symbol: &str) -> Option<&'static str> {
+    if symbol.is_ascii() {
+        return None;
+    }
+    match ASCII_GLYPHS.iter().find(|(glyph, _)| *glyph == symbol) {
+        Some((_, ascii)) => Some(ascii),
+        // Partially filled gauge cells
+        None if ('▉'..='▏').contains(&symbol.chars().next().unwrap_or(' ')) => Some("#"),
+        None if ('\u{2800}'..='\u{28ff}').contains(&symbol.chars().next().unwrap_or(' ')) => {
+            Some(get_ascii_braille(symbol.chars().next().unwrap()))
+        }
+        None => Some("?"),
+    }
+}
+
+/// Stand-ins for the braille dots of high resolution waveforms, following the
+/// characters of the regular rendering
+fn get_ascii_braille(braille: char) -> &'static str {
+    let dots = braille as u32 - 0x2800;
+    let top = dots & 0x09 != 0;
+    let middle = dots & 0x36 != 0;
+    let bottom = dots & 0xc0 != 0;
+    match (top, middle, bottom) {
+        (_, true, true) | (true, true, _) => "|",
+        (false, true, false) => "X",
+        (true, false, _) => "#",
+        (false, false, true) => "_",
+        (false, false, false) => " ",
+    }
+}
+
+/// Attributes standing in for the colors of a cell, so selections and accents
+/// can still be told apart without any colors
+fn get_monochrome_modifier(fg: Color, bg: Color) -> Modifier {
+    let background = match bg {
+        // Selections and banners
+        Color::White => Modifier::REVERSED | Modifier::BOLD,
+        Color::Gray | Color::Rgb(128, 128, 128) | Color::Yellow => Modifier::REVERSED,
+        // Selected waveform rows
+        Color::Rgb(80, 80, 80) => Modifier::UNDERLINED | Modifier::BOLD,
+        Color::Rgb(40, 40, 40) => Modifier::UNDERLINED,
+        _ => Modifier::empty(),
+    };
+    let foreground = match fg {
+        // Focused borders, markers and unknown values
+        Color::Green | Color::Yellow | Color::LightBlue | Color::Red => Modifier::BOLD,
+        // High impedance values and the marker column
+        Color::Blue | Color::LightMagenta => Modifier::DIM,
+        _ => Modifier::empty(),
+    };
+    background | foreground
+}
+
+/// Adjusts a finished frame for what the terminal can show, rendered on top of
+/// everything else
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Theme {
+    pub monochrome: bool,
+    pub ascii: bool,
+}
+
+impl Widget for Theme {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.monochrome && !self.ascii {
+            return;
+        }
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                if self.ascii {
+                    if let Some(ascii) = get_ascii_glyph(&cell.symbol) {
+                        cell.set_symbol(ascii);
+                    }
+                }
+                if !self.monochrome {
+                    continue;
+                }
+                let modifier = get_monochrome_modifier(cell.fg, cell.bg);
+                cell.set_style(
+                    Style::default()
+                        .fg(Color::Reset)
+                        .bg(Color::Reset)
+                        .add_modifier(modifier),
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn monochrome_test() {
+    let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+    buf.get_mut(0, 0).set_fg(Color::LightCyan);
+    buf.get_mut(1, 0).set_fg(Color::Black).set_bg(Color::White);
+    buf.get_mut(2, 0).set_fg(Color::Green);
+    Theme {
+        monochrome: true,
+        ascii: false,
+    }
+    .render(buf.area, &mut buf);
+    assert!(buf
+        .content
+        .iter()
+        .all(|c| c.fg == Color::Reset && c.bg == Color::Reset));
+    assert_eq!(buf.get(0, 0).modifier, Modifier::empty());
+    assert_eq!(buf.get(1, 0).modifier, Modifier::REVERSED | Modifier::BOLD);
+    assert_eq!(buf.get(2, 0).modifier, Modifier::BOLD);
+}
+
+#[test]
+fn ascii_test() {
+    let mut buf = Buffer::empty(Rect::new(0, 0, 5, 1));
+    buf.set_string(0, 0, "╭█a▌λ", Style::default().fg(Color::Green));
+    Theme {
+        monochrome: false,
+        ascii: true,
+    }
+    .render(buf.area, &mut buf);
+    assert_eq!(
+        buf.content
+            .iter()
+            .map(|c| c.symbol.as_str())
+            .collect::<String>(),
+        "+#a#?"
+    );
+    assert_eq!(buf.get(0, 0).fg, Color::Green);
+    assert_eq!(get_ascii_glyph("⣇"), Some("|"));
+    assert_eq!(get_ascii_glyph("⠉"), Some("#"));
+    // A cut off value does not end in what looks like an unknown bit
+    assert_eq!(get_ascii_glyph("…"), Some("."));
+    assert_eq!(
+        get_ascii_glyph(crate::widgets::timescale::BREAK_SYMBOL),
+        Some("~")
+    );
+    assert_eq!(get_ascii_glyph("▲"), Some("^"));
+    assert_eq!(get_ascii_glyph("▼"), Some("v"));
+    // The guides and icons of the netlist and signal viewer trees
+    let icons = ["├", "└", "│", "─", "▣", "∿", "•", "≡"].map(get_ascii_glyph);
+    assert_eq!(icons.map(Option::unwrap).concat(), "++|-#~*=");
+}
@@ -1,3 +1,7 @@
 pub mod browser;
+pub mod layout;
+pub mod minimap;
+pub mod overlay;
+pub mod resize;
 pub mod timescale;
 pub mod waveform;
@@ -1,13 +1,18 @@
 use std::cmp::Ordering;
+use std::time::{Duration, Instant};
 
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tui::{
     buffer::Buffer,
     layout::{Alignment, Rect},
-    style::{Color, Style},
-    text::Text,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
     widgets::{Block, Paragraph, Widget},
 };
 
+/// How long the type-ahead waits for the next key before it ends
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
 pub fn get_selected_style(is_selected: bool, is_primary: bool) -> Style {
     if is_selected {
         if is_primary {
@@ -22,12 +27,29 @@ pub fn get_selected_style(is_selected: bool, is_primary: bool) -> Style {
     }
 }
 
+/// Returns the number of lines taken up by pinned entries and their separator
+pub fn get_pinned_height(pinned: usize) -> usize {
+    if pinned == 0 {
+        0
+    } else {
+        pinned + 1
+    }
+}
+
+/// A tree of entries where every node can be expanded to show its children,
+/// with the root usually left without an entry
+#[derive(Clone)]
 pub struct BrowserNode<E> {
     entry: Option<E>,
     expanded: bool,
+    // Rows the entry takes up, where the rows after the first are left blank
+    height: usize,
     children: Vec<BrowserNode<E>>,
+    // Shown as expandable before the children are created on first expand
+    deferred: bool,
 }
 
+/// The indices of the children leading from the root to a node
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BrowserNodePath(Vec<usize>);
 
@@ -37,7 +59,9 @@ impl<E> BrowserNode<E> {
         Self {
             entry,
             expanded: false,
+            height: 1,
             children: Vec::new(),
+            deferred: false,
         }
     }
 
@@ -45,7 +69,9 @@ impl<E> BrowserNode<E> {
         Self {
             entry,
             expanded: false,
+            height: 1,
             children,
+            deferred: false,
         }
     }
 
@@ -53,12 +79,33 @@ impl<E> BrowserNode<E> {
         Self {
             entry,
             expanded,
+            height: 1,
             children,
+            deferred: false,
+        }
+    }
+
+    /// A node that can be expanded but whose children are only created by the
+    /// owner of the tree when it is first expanded, see [`Self::set_children`]
+    pub fn deferred(entry: Option<E>) -> Self {
+        Self {
+            deferred: true,
+            ..Self::new(entry)
         }
     }
 
     pub fn is_parent(&self) -> bool {
-        !self.children.is_empty()
+        !self.children.is_empty() || self.deferred
+    }
+
+    pub fn is_deferred(&self) -> bool {
+        self.deferred
+    }
+
+    /// Fills in the children of a deferred node
+    pub fn set_children(&mut self, children: Vec<BrowserNode<E>>) {
+        self.children = children;
+        self.deferred = false;
     }
 
     pub fn is_expanded(&self) -> bool {
@@ -69,6 +116,14 @@ impl<E> BrowserNode<E> {
         self.expanded = expanded;
     }
 
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height.max(1);
+    }
+
     pub fn get_children(&self) -> &Vec<BrowserNode<E>> {
         &self.children
     }
@@ -85,6 +140,16 @@ impl<E> BrowserNode<E> {
         &mut self.entry
     }
 
+    /// Calls the visitor on this node and then on its children, skipping the
+    /// children of any node the visitor returns false for
+    pub fn visit_mut(&mut self, visitor: &mut impl FnMut(&mut BrowserNode<E>) -> bool) {
+        if visitor(self) {
+            for child in &mut self.children {
+                child.visit_mut(visitor);
+            }
+        }
+    }
+
     pub fn get_render_len(&self) -> usize {
         (if self.expanded {
             self.children
@@ -93,16 +158,18 @@ impl<E> BrowserNode<E> {
                 .sum::<usize>()
         } else {
             0
-        }) + usize::from(self.entry.is_some())
+        }) + if self.entry.is_some() { self.height } else { 0 }
     }
 
+    /// The path of the node rendered at the row, where every row of a node
+    /// taller than one row has the same path
     pub fn get_path(&self, mut index: usize) -> BrowserNodePath {
         for (i, c) in self.children.iter().enumerate() {
-            if index == 0 {
+            if index < c.height {
                 return BrowserNodePath(vec![i]);
             } else if index < c.get_render_len() {
                 let mut v = vec![i];
-                v.append(&mut c.get_path(index - 1).0);
+                v.append(&mut c.get_path(index - c.height).0);
                 return BrowserNodePath(v);
             } else {
                 index -= c.get_render_len();
@@ -111,6 +178,24 @@ impl<E> BrowserNode<E> {
         BrowserNodePath(Vec::new())
     }
 
+    // Inverse of get_path, returns the render index of the (first row of the)
+    // path if it is visible (all parents expanded)
+    pub fn get_index(&self, path: &BrowserNodePath) -> Option<usize> {
+        let (first, rest) = path.0.split_first()?;
+        let child = self.children.get(*first)?;
+        let offset = self.children[..*first]
+            .iter()
+            .map(|c| c.get_render_len())
+            .sum::<usize>();
+        if rest.is_empty() {
+            Some(offset)
+        } else if child.is_expanded() {
+            Some(offset + child.height + child.get_index(&BrowserNodePath(rest.to_vec()))?)
+        } else {
+            None
+        }
+    }
+
     pub fn get_paths(&self, range: std::ops::Range<usize>, condense: bool) -> Vec<BrowserNodePath> {
         let mut paths: Vec<BrowserNodePath> = Vec::new();
         for index in range {
@@ -136,6 +221,15 @@ impl<E> BrowserNode<E> {
         }
     }
 
+    /// The first row of the node rendered at the row, and how many it takes up
+    pub fn get_rows(&self, index: usize) -> (usize, usize) {
+        let path = self.get_path(index);
+        match (self.get_index(&path), self.get_node(&path)) {
+            (Some(first), Some(node)) => (first, node.height),
+            _ => (index, 1),
+        }
+    }
+
     pub fn get_node_mut(&mut self, path: &BrowserNodePath) -> Option<&mut BrowserNode<E>> {
         if path.0.is_empty() {
             None
@@ -165,6 +259,23 @@ where
         name.append(&mut suffix);
         name
     }
+
+    /// Finds the node with the full name (below this node) after the tree was
+    /// built again, or else the closest parent of it that is still there
+    pub fn find_full_name(&self, full_name: &[String]) -> BrowserNodePath {
+        let mut path = Vec::new();
+        let mut node = self;
+        for name in full_name {
+            let Some(index) = node.children.iter().position(|child| {
+                child.entry.as_ref().map(|e| e.to_string()).as_ref() == Some(name)
+            }) else {
+                break;
+            };
+            path.push(index);
+            node = &node.children[index];
+        }
+        BrowserNodePath(path)
+    }
 }
 
 impl<E> std::fmt::Display for BrowserNode<E>
@@ -185,7 +296,9 @@ impl<E> Default for BrowserNode<E> {
         Self {
             entry: None,
             expanded: false,
+            height: 1,
             children: Vec::new(),
+            deferred: false,
         }
     }
 }
@@ -274,6 +387,8 @@ impl PartialOrd for BrowserNodePath {
     }
 }
 
+/// The scroll position and selection of a [`Browser`], as rows of the visible
+/// part of the tree
 pub struct BrowserState {
     // Enables display up and down arrows at the top and bottom of the component
     // to indicate if scrolling is available
@@ -282,6 +397,8 @@ pub struct BrowserState {
     indent_enabled: bool,
     // Enables displaying full hierarchical names
     full_name_enabled: bool,
+    // Enables guide lines between the indented entries and their icons
+    tree_enabled: bool,
     scroll: isize,
     cursor: isize,
     cursor_secondary: Option<isize>,
@@ -295,6 +412,7 @@ impl BrowserState {
             bounds_enabled,
             indent_enabled,
             full_name_enabled,
+            tree_enabled: false,
             scroll: 0,
             cursor: 0,
             cursor_secondary: None,
@@ -318,38 +436,61 @@ impl BrowserState {
         self.cursor as usize
     }
 
+    /// The row drawn at the top, below the scroll arrow
+    pub fn get_scroll(&self) -> usize {
+        self.scroll.max(0) as usize
+    }
+
     pub fn get_visible_paths<E>(&self, node: &BrowserNode<E>) -> Vec<BrowserNodePath> {
         node.get_paths(
-            self.scroll as usize..(self.scroll + self.height) as usize,
+            self.scroll as usize..(self.scroll + self.get_render_height()) as usize,
             false,
         )
     }
 
+    /// Entries rendered below the visible ones
+    pub fn get_hidden_below<E>(&self, node: &BrowserNode<E>) -> usize {
+        (node.get_render_len() as isize - self.scroll - self.get_render_height()).max(0) as usize
+    }
+
+    /// Rows left for entries after the scroll arrows
+    pub fn get_render_height(&self) -> isize {
+        (self.height - if self.bounds_enabled { 2 } else { 0 }).max(0)
+    }
+
     pub fn get_selected_paths<E>(
         &self,
         node: &BrowserNode<E>,
         condense: bool,
     ) -> Vec<BrowserNodePath> {
-        node.get_paths(self.get_selected_range(), condense)
+        let mut paths = node.get_paths(self.get_selected_range(), condense);
+        // Taller entries are selected once however many of their rows are
+        paths.dedup();
+        paths
     }
 
     pub fn get_primary_selected_path<E>(&self, node: &BrowserNode<E>) -> BrowserNodePath {
         node.get_path(self.get_primary_selected())
     }
 
-    fn clamp_scroll(&mut self, render_height: isize) {
+    /// Scrolls just far enough to show every row of the primary entry, or
+    /// only its first rows when it is taller than the browser
+    fn clamp_scroll<E>(&mut self, node: &BrowserNode<E>, render_height: isize) {
+        let last = (node.get_render_len() as isize - 1).max(0);
+        let (_, rows) = node.get_rows(self.cursor.clamp(0, last) as usize);
+        let rows = (rows as isize).min(render_height.max(1));
         if self.cursor < self.scroll {
             self.scroll = self.cursor;
-        } else if self.cursor > self.scroll + (render_height - 1) {
-            self.scroll = self.cursor - (render_height - 1);
+        } else if self.cursor + rows - 1 > self.scroll + (render_height - 1) {
+            self.scroll = self.cursor + rows - render_height;
         }
     }
 
     pub fn scroll_relative<E>(&mut self, node: &BrowserNode<E>, delta: isize) {
         let node_height = node.get_render_len();
-        let render_height = (self.height - if self.bounds_enabled { 2 } else { 0 }).max(0);
+        let render_height = self.get_render_height();
         self.scroll = (self.scroll + delta).clamp(0, (node_height as isize - 1).max(0));
-        self.clamp_scroll(render_height);
+        self.clamp_scroll(node, render_height);
     }
 
     pub fn select_absolute<E>(
@@ -358,7 +499,7 @@ impl BrowserState {
         render_offset: isize,
         primary: bool,
     ) -> bool {
-        let render_height = (self.height - if self.bounds_enabled { 2 } else { 0 }).max(0);
+        let render_height = self.get_render_height();
         let node_height = node.get_render_len();
         let offset = if self.bounds_enabled {
             if render_offset < 1 || render_offset > render_height {
@@ -372,6 +513,10 @@ impl BrowserState {
         if offset > node_height as isize {
             return false;
         }
+        let offset = match offset < node_height as isize {
+            true => node.get_rows(offset as usize).0 as isize,
+            false => offset,
+        };
         if primary {
             self.cursor_secondary = None;
             if self.cursor == offset {
@@ -386,16 +531,80 @@ impl BrowserState {
         }
     }
 
+    pub fn select_path<E>(&mut self, node: &BrowserNode<E>, path: &BrowserNodePath) -> bool {
+        let Some(index) = node.get_index(path) else {
+            return false;
+        };
+        self.select_relative(node, index as isize - self.cursor, true);
+        true
+    }
+
+    /// Selects the path, or its closest parent still showing if it was
+    /// collapsed away
+    pub fn select_visible_path<E>(&mut self, node: &BrowserNode<E>, path: &BrowserNodePath) {
+        let mut path = path.clone();
+        while !path.is_empty() && node.get_index(&path).is_none() {
+            path.0.pop();
+        }
+        if !self.select_path(node, &path) {
+            self.select_relative(node, 0, true);
+        }
+    }
+
+    /// Moves the cursor to the first visible entry from the cursor on (or
+    /// after it when skipping) whose name starts with the text, or else the
+    /// first that contains it, ignoring case
+    pub fn select_matching<E>(
+        &mut self,
+        node: &BrowserNode<E>,
+        text: &str,
+        skip_selected: bool,
+    ) -> bool
+    where
+        E: std::fmt::Display,
+    {
+        let len = node.get_render_len();
+        if len == 0 || text.is_empty() {
+            return false;
+        }
+        let text = text.to_lowercase();
+        let start = self.cursor.max(0) as usize + usize::from(skip_selected);
+        let names = (0..len)
+            .map(|i| (start + i) % len)
+            .filter_map(|i| {
+                let entry = node.get_node(&node.get_path(i))?.get_entry().as_ref()?;
+                Some((i, entry.to_string().to_lowercase()))
+            })
+            .collect::<Vec<(usize, String)>>();
+        let found = names
+            .iter()
+            .find(|(_, name)| name.starts_with(&text))
+            .or_else(|| names.iter().find(|(_, name)| name.contains(&text)));
+        let Some((index, _)) = found else {
+            return false;
+        };
+        self.select_relative(node, *index as isize - self.cursor, true);
+        true
+    }
+
     pub fn select_relative<E>(&mut self, node: &BrowserNode<E>, delta: isize, primary: bool) {
-        let render_height = (self.height - if self.bounds_enabled { 2 } else { 0 }).max(0);
+        let render_height = self.get_render_height();
         let node_height = node.get_render_len();
         self.cursor_secondary = if primary {
             None
         } else {
             Some(self.cursor_secondary.unwrap_or(self.cursor))
         };
-        self.cursor = (self.cursor + delta).clamp(0, (node_height as isize - 1).max(0));
-        self.clamp_scroll(render_height);
+        let last = (node_height as isize - 1).max(0);
+        let (start, rows) = node.get_rows(self.cursor.clamp(0, last) as usize);
+        let (target, _) = node.get_rows((self.cursor + delta).clamp(0, last) as usize);
+        // Moving down from a taller entry has to skip past all of its rows
+        self.cursor = match delta > 0 && target <= start {
+            true if (start + rows) as isize <= last => (start + rows) as isize,
+            true => start as isize,
+            false => target as isize,
+        };
+        self.clamp_scroll(node, render_height);
     }
 
     pub fn get_height(&self) -> isize {
@@ -429,13 +638,116 @@ impl BrowserState {
     pub fn set_full_name_enabled(&mut self, full_name_enabled: bool) {
         self.full_name_enabled = full_name_enabled
     }
+
+    pub fn is_tree_enabled(&self) -> bool {
+        self.tree_enabled
+    }
+
+    pub fn set_tree_enabled(&mut self, tree_enabled: bool) {
+        self.tree_enabled = tree_enabled
+    }
+}
+
+/// Lines leading from the parents of an entry down to it in place of the
+/// indent, like `│  ├─ `, where top level entries have none
+fn get_tree_guides<E>(node: &BrowserNode<E>, path: &BrowserNodePath) -> String {
+    let mut guides = String::new();
+    for depth in 1..path.0.len() {
+        let parent = BrowserNodePath(path.0[..depth].to_vec());
+        let siblings = node.get_node(&parent).map_or(0, |p| p.get_children().len());
+        let is_last = path.0[depth] + 1 >= siblings;
+        guides.push_str(match (depth + 1 == path.0.len(), is_last) {
+            (true, true) => "└─ ",
+            (true, false) => "├─ ",
+            (false, true) => "   ",
+            (false, false) => "│  ",
+        });
+    }
+    guides
+}
+
+/// Characters typed after a '/' to jump to an entry by name, collected until
+/// there is a pause between keys
+#[derive(Debug, Default)]
+pub struct TypeAhead {
+    buffer: String,
+    last_key: Option<Instant>,
 }
 
+impl TypeAhead {
+    pub fn is_active(&self) -> bool {
+        self.last_key
+            .is_some_and(|last_key| last_key.elapsed() < TYPE_AHEAD_TIMEOUT)
+    }
+
+    pub fn get_prompt(&self) -> Option<String> {
+        self.is_active().then(|| format!("/{}", self.buffer))
+    }
+
+    /// Takes the keys of the type-ahead, returning the text to jump to and if
+    /// the jump should skip past the selected entry (to the next match)
+    pub fn handle_key(&mut self, e: &KeyEvent) -> Option<(String, bool)> {
+        if e.modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return None;
+        }
+        let active = self.is_active();
+        match e.code {
+            // Typing '/' again jumps to the next match
+            KeyCode::Char('/') if active && !self.buffer.is_empty() => {
+                self.last_key = Some(Instant::now());
+                return Some((self.buffer.clone(), true));
+            }
+            KeyCode::Char('/') => self.buffer.clear(),
+            _ if !active => return None,
+            KeyCode::Char(c) => self.buffer.push(c),
+            KeyCode::Backspace => {
+                self.buffer.pop();
+            }
+            KeyCode::Enter => {
+                self.last_key = None;
+                return Some((String::new(), false));
+            }
+            _ => {
+                self.last_key = None;
+                return None;
+            }
+        }
+        self.last_key = Some(Instant::now());
+        Some((self.buffer.clone(), false))
+    }
+}
+
+/// Generates extra text to render alongside an entry
+type BrowserLabel<'a, E> = Box<dyn Fn(&E) -> String + 'a>;
+
+/// Picks out entries to render highlighted
+type BrowserHighlight<'a, E> = Box<dyn Fn(&E) -> bool + 'a>;
+
+/// Draws the visible part of a tree with the selection of a [`BrowserState`]
 pub struct Browser<'a, E> {
     /// The scroll and selection status of the component
     state: &'a BrowserState,
     /// The root node to render
     node: &'a BrowserNode<E>,
+    /// Blank lines above the pinned lines, lining up with another pane
+    spacing: u16,
+    /// Lines always rendered at the top of the browser, followed by a separator
+    pinned: Vec<String>,
+    /// Optional extra information rendered after the name of each entry
+    suffix: Option<BrowserLabel<'a, E>>,
+    /// Optional symbol for the type of each entry, rendered before its name
+    /// when the tree is drawn with guides
+    icon: Option<BrowserLabel<'a, E>>,
+    /// Optional extra information right-aligned on the line of each entry
+    column: Option<BrowserLabel<'a, E>>,
+    /// Optionally picks out entries whose column is drawn highlighted
+    column_highlight: Option<BrowserHighlight<'a, E>>,
+    /// Optionally picks out entries whose name is drawn highlighted
+    highlight: Option<BrowserHighlight<'a, E>>,
+    /// Rendered in place of the bottom bound, like the text of a type-ahead
+    prompt: Option<String>,
     /// A block to wrap the widget in
     block: Option<Block<'a>>,
     /// Widget style
@@ -447,11 +759,59 @@ impl<'a, E> Browser<'a, E> {
         Self {
             state,
             node,
+            spacing: 0,
+            pinned: Vec::new(),
+            suffix: None,
+            icon: None,
+            column: None,
+            column_highlight: None,
+            highlight: None,
+            prompt: None,
             block: None,
             style: Default::default(),
         }
     }
 
+    pub fn spacing(mut self, spacing: u16) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn pinned(mut self, pinned: Vec<String>) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    pub fn suffix(mut self, suffix: impl Fn(&E) -> String + 'a) -> Self {
+        self.suffix = Some(Box::new(suffix));
+        self
+    }
+
+    pub fn icon(mut self, icon: impl Fn(&E) -> String + 'a) -> Self {
+        self.icon = Some(Box::new(icon));
+        self
+    }
+
+    pub fn column(mut self, column: impl Fn(&E) -> String + 'a) -> Self {
+        self.column = Some(Box::new(column));
+        self
+    }
+
+    pub fn column_highlight(mut self, column_highlight: impl Fn(&E) -> bool + 'a) -> Self {
+        self.column_highlight = Some(Box::new(column_highlight));
+        self
+    }
+
+    pub fn highlight(mut self, highlight: impl Fn(&E) -> bool + 'a) -> Self {
+        self.highlight = Some(Box::new(highlight));
+        self
+    }
+
+    pub fn prompt(mut self, prompt: Option<String>) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
     pub fn block(mut self, block: Block<'a>) -> Self {
         self.block = Some(block);
         self
@@ -478,17 +838,32 @@ where
             area.height
         };
         let mut text = Text::raw("");
-        let line_range = if self.state.bounds_enabled {
+        if self.state.bounds_enabled {
             if self.state.scroll > 0 {
                 text.extend(Text::raw("↑".repeat(area.width as usize)));
             } else {
                 text.extend(Text::raw(" ".repeat(area.width as usize)));
             }
-            self.state.scroll..(self.state.scroll + height as isize - 2)
-        } else {
-            self.state.scroll..(self.state.scroll + height as isize)
-        };
-        for line_index in line_range {
+        }
+        for _ in 0..self.spacing {
+            text.extend(Text::raw(" ".repeat(area.width as usize)));
+        }
+        let pinned_height = get_pinned_height(self.pinned.len()) as u16;
+        for pinned in &self.pinned {
+            text.extend(Text::styled(
+                format!("{:width$}", pinned, width = area.width as usize),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+        }
+        if pinned_height > 0 {
+            text.extend(Text::raw("─".repeat(area.width as usize)));
+        }
+        // The state can hold fewer rows than fit, like when lining up with
+        // another pane
+        let height = height
+            .saturating_sub(self.spacing + pinned_height)
+            .min(self.state.get_render_height().max(0) as u16);
+        for line_index in self.state.scroll..(self.state.scroll + height as isize) {
             let path = self.node.get_path(line_index as usize);
             let sub_node = if let Some(sub_node) = self.node.get_node(&path) {
                 sub_node
@@ -496,10 +871,10 @@ where
                 text.extend(Text::raw("    "));
                 continue;
             };
-            let indents = if self.state.indent_enabled {
-                "    ".repeat(path.0.len() - 1)
-            } else {
-                String::new()
+            let indents = match (self.state.indent_enabled, self.state.tree_enabled) {
+                (true, true) => get_tree_guides(self.node, &path),
+                (true, false) => "    ".repeat(path.0.len() - 1),
+                (false, _) => String::new(),
             };
             let expander = if sub_node.is_parent() {
                 if sub_node.is_expanded() {
@@ -517,25 +892,71 @@ where
             } else {
                 String::new()
             };
+            let content = match (&self.icon, sub_node.get_entry()) {
+                (Some(icon), Some(entry)) if self.state.tree_enabled => match icon(entry) {
+                    icon if icon.is_empty() => content,
+                    icon => format!("{} {}", icon, content),
+                },
+                _ => content,
+            };
+            let content = match (&self.suffix, sub_node.get_entry()) {
+                (Some(suffix), Some(entry)) => match suffix(entry) {
+                    suffix if suffix.is_empty() => content,
+                    suffix => format!("{} {}", content, suffix),
+                },
+                _ => content,
+            };
             let node_raw = format!("{}{}{}", indents, expander, content);
-            let padding = String::from(" ").repeat(if node_raw.len() < area.width as usize {
-                area.width as usize - node_raw.len()
-            } else {
-                0
-            });
-            let node_raw = format!("{}{}", node_raw, padding);
-            let is_selected = self
-                .state
-                .get_selected_range()
-                .contains(&(line_index as usize));
-            let is_primary_selected = line_index == self.state.get_primary_selected() as isize;
+            let column = match (&self.column, sub_node.get_entry()) {
+                (Some(column), Some(entry)) => column(entry),
+                _ => String::new(),
+            };
+            // Rows after the first of a taller entry are selected along with it
+            let first = self.node.get_index(&path).unwrap_or(line_index as usize);
+            let is_selected = self.state.get_selected_range().contains(&first);
+            let is_primary_selected = first == self.state.get_primary_selected();
+            let style = get_selected_style(is_selected, is_primary_selected);
+            // Selections keep their colors so the text stays readable
+            let style = match (&self.highlight, sub_node.get_entry()) {
+                (Some(highlight), Some(entry)) if highlight(entry) && is_selected => {
+                    style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                }
+                (Some(highlight), Some(entry)) if highlight(entry) => {
+                    style.fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                }
+                _ => style,
+            };
+            if first != line_index as usize {
+                text.extend(Text::styled(" ".repeat(area.width as usize), style));
+                continue;
+            }
+            if column.is_empty() {
+                let padding = String::from(" ")
+                    .repeat((area.width as usize).saturating_sub(node_raw.chars().count()));
+                text.extend(Text::styled(format!("{}{}", node_raw, padding), style));
+                continue;
+            }
+            // Truncate the name to always leave room for the column
+            let width = (area.width as usize).saturating_sub(column.chars().count() + 1);
+            let node_raw: String = node_raw.chars().take(width).collect();
+            let column_style = match (&self.column_highlight, sub_node.get_entry()) {
+                (Some(highlight), Some(entry)) if highlight(entry) => {
+                    style.fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                }
+                _ => style,
+            };
+            text.extend(Text::from(Spans::from(vec![
+                Span::styled(format!("{:width$} ", node_raw, width = width), style),
+                Span::styled(column, column_style),
+            ])));
+        }
+        if let (true, Some(prompt)) = (self.state.bounds_enabled, &self.prompt) {
             text.extend(Text::styled(
-                node_raw,
-                get_selected_style(is_selected, is_primary_selected),
+                format!("{:width$}", prompt, width = area.width as usize),
+                Style::default().add_modifier(Modifier::BOLD),
             ));
-        }
-        if self.state.bounds_enabled {
-            if self.node.get_render_len() as isize - self.state.scroll > height as isize - 2 {
+        } else if self.state.bounds_enabled {
+            if self.node.get_render_len() as isize - self.state.scroll > height as isize {
                 text.extend(Text::raw("↓".repeat(area.width as usize)));
             } else {
                 text.extend(Text::raw(" ".repeat(area.width as usize)));
@@ -613,6 +1034,11 @@ fn browser_node_test() {
     nodes[0][0].set_expanded(true);
     assert_eq!(nodes.get_path(2), BrowserNodePath(vec![0, 0, 0]));
 
+    for index in 0..nodes.get_render_len() {
+        assert_eq!(nodes.get_index(&nodes.get_path(index)), Some(index));
+    }
+    assert_eq!(nodes.get_index(&BrowserNodePath(vec![2, 0])), None);
+
     assert!(!BrowserNodePath(vec![]).contains(&BrowserNodePath(vec![])));
     assert!(!BrowserNodePath(vec![0]).contains(&BrowserNodePath(vec![])));
     assert!(BrowserNodePath(vec![]).contains(&BrowserNodePath(vec![0])));
@@ -680,6 +1106,92 @@ fn browser_node_test() {
         nodes.get_full_name(&BrowserNodePath(vec![0, 1])),
         vec!["A", "2"]
     );
+    let full_name = |name: &[&str]| name.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+    assert_eq!(
+        nodes.find_full_name(&full_name(&["A", "1", "b"])),
+        BrowserNodePath(vec![0, 0, 1])
+    );
+    assert_eq!(
+        nodes.find_full_name(&full_name(&["C", "4"])),
+        BrowserNodePath(vec![2])
+    );
+    assert!(nodes.find_full_name(&full_name(&["D"])).is_empty());
+
+    nodes.visit_mut(&mut |node| {
+        node.set_expanded(true);
+        true
+    });
+    assert_eq!(nodes.get_render_len(), 11);
+    let mut state = BrowserState::new(true, true, false);
+    state.set_height(20);
+    state.select_path(&nodes, &BrowserNodePath(vec![2, 1]));
+    // Collapse everything but A and what is in it
+    nodes.visit_mut(&mut |node| {
+        if node.get_entry() == &Some("A") {
+            return false;
+        }
+        if node.get_entry().is_some() {
+            node.set_expanded(false);
+        }
+        true
+    });
+    assert_eq!(nodes.get_render_len(), 7);
+    state.select_visible_path(&nodes, &BrowserNodePath(vec![2, 1]));
+    assert_eq!(
+        state.get_primary_selected_path(&nodes),
+        BrowserNodePath(vec![2])
+    );
+
+    assert!(state.select_matching(&nodes, "b", false));
+    assert_eq!(
+        state.get_primary_selected_path(&nodes),
+        BrowserNodePath(vec![0, 0, 1])
+    );
+    assert!(state.select_matching(&nodes, "b", true));
+    assert_eq!(
+        state.get_primary_selected_path(&nodes),
+        BrowserNodePath(vec![1])
+    );
+    assert!(!state.select_matching(&nodes, "z", false));
+
+    // Taller entries take up more rows that all map back to them
+    nodes[1].set_height(3);
+    assert_eq!(nodes.get_render_len(), 9);
+    assert_eq!(nodes.get_path(6), BrowserNodePath(vec![1]));
+    assert_eq!(nodes.get_path(7), BrowserNodePath(vec![1]));
+    assert_eq!(nodes.get_path(8), BrowserNodePath(vec![2]));
+    assert_eq!(nodes.get_index(&BrowserNodePath(vec![2])), Some(8));
+    assert_eq!(nodes.get_rows(7), (5, 3));
+    state.select_relative(&nodes, 1, true);
+    assert_eq!(state.get_primary_selected(), 8);
+    state.select_relative(&nodes, -1, true);
+    assert_eq!(state.get_primary_selected(), 5);
+
+    // Selecting a taller entry scrolls until all of its rows are in view
+    let mut state = BrowserState::new(false, true, false);
+    state.set_height(6);
+    state.select_relative(&nodes, 5, true);
+    assert_eq!(state.get_scroll(), 2);
+    state.select_relative(&nodes, -5, true);
+    assert_eq!(state.get_scroll(), 0);
+}
+
+#[test]
+fn deferred_node_test() {
+    let mut nodes = BrowserNode::from_expanded(None, true, vec![BrowserNode::deferred(Some("A"))]);
+    assert!(nodes[0].is_parent());
+    assert!(nodes[0].get_children().is_empty());
+    nodes[0].set_expanded(true);
+    assert_eq!(nodes.get_render_len(), 1);
+    nodes[0].set_children(vec![
+        BrowserNode::new(Some("1")),
+        BrowserNode::new(Some("2")),
+    ]);
+    assert!(!nodes[0].is_deferred());
+    assert_eq!(nodes.get_render_len(), 3);
+    // A node without children left after creating them is no longer a parent
+    nodes[0].set_children(Vec::new());
+    assert!(!nodes[0].is_parent());
 }
 
 #[test]
@@ -719,8 +1231,70 @@ fn browser_render_test() {
 
     let browser = Browser::new(&browser_state, &nodes);
 
-    let _text = browser.render(
+    browser.render(
         Rect::new(0, 0, 10, 10),
         &mut Buffer::empty(Rect::new(0, 0, 10, 10)),
     );
 }
+
+#[test]
+fn tree_guides_test() {
+    let mut nodes = BrowserNode::from(
+        None,
+        vec![
+            BrowserNode::from(
+                Some("A"),
+                vec![
+                    BrowserNode::from(
+                        Some("1"),
+                        vec![
+                            BrowserNode::from(Some("a"), vec![]),
+                            BrowserNode::from(Some("b"), vec![]),
+                        ],
+                    ),
+                    BrowserNode::from(Some("2"), vec![]),
+                ],
+            ),
+            BrowserNode::from(Some("B"), vec![]),
+        ],
+    );
+    nodes.set_expanded(true);
+    nodes[0].set_expanded(true);
+    nodes[0][0].set_expanded(true);
+    assert_eq!(get_tree_guides(&nodes, &BrowserNodePath(vec![0])), "");
+    assert_eq!(get_tree_guides(&nodes, &BrowserNodePath(vec![0, 0])), "├─ ");
+    assert_eq!(
+        get_tree_guides(&nodes, &BrowserNodePath(vec![0, 0, 1])),
+        "│  └─ "
+    );
+    assert_eq!(get_tree_guides(&nodes, &BrowserNodePath(vec![0, 1])), "└─ ");
+
+    let mut state = BrowserState::new(false, true, false);
+    state.set_height(6);
+    state.set_tree_enabled(true);
+    let area = Rect::new(0, 0, 12, 6);
+    let mut buf = Buffer::empty(area);
+    Browser::new(&state, &nodes)
+        .icon(|entry| match *entry {
+            "B" => String::new(),
+            _ => String::from("*"),
+        })
+        .render(area, &mut buf);
+    let rows = (0..area.height)
+        .map(|y| {
+            let row = (0..area.width).map(|x| buf.get(x, y).symbol.clone());
+            row.collect::<String>().trim_end().to_string()
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(
+        rows,
+        vec![
+            "[-] * A",
+            "├─ [-] * 1",
+            "│  ├─ * a",
+            "│  └─ * b",
+            "└─ * 2",
+            "B"
+        ]
+    );
+}
@@ -0,0 +1,305 @@
+use makai::utils::messages::Messages;
+use tui::{
+    layout::{Alignment, Direction},
+    style::{Color, Style},
+};
+use tui_tiling::{
+    component::{simple::ComponentWidgetSimple, Component},
+    container::{list::ContainerList, Container, ContainerChild},
+    Focus,
+};
+
+use crate::{
+    state::NaluMessage,
+    widgets::resize::{get_container_mut, get_direction, is_fixed, reset_sizes},
+};
+
+/// Name of the blank panes created by splitting
+const EMPTY_PANE: &str = "empty";
+
+/// Cells across the smallest pane that resizing from the palette leaves
+const MIN_SIZE: i32 = 3;
+
+pub(crate) enum LayoutMessage {
+    // Opens a closed pane with this name in the new half instead of a blank one
+    Split(Direction, Option<String>),
+    Close,
+    // Opens the closed pane with this name below the focused one, or closes it
+    // when it is already open
+    Toggle(String),
+    // Grows the focused pane by this many cells in the direction, taking them
+    // from the pane next to it
+    Resize(Direction, i16),
+    // Resets the panes around the focused one back to equal sizes
+    EqualSizes,
+}
+
+/// Child indices leading from the root to the focused component
+fn get_focused_path(container: &dyn Container) -> Option<Vec<usize>> {
+    for (i, child) in container.get_children().iter().enumerate() {
+        if child.as_base().get_focus() == Focus::None {
+            continue;
+        }
+        let mut path = vec![i];
+        if let ContainerChild::Container(child) = child {
+            path.extend(get_focused_path(child.as_container())?);
+        }
+        return Some(path);
+    }
+    None
+}
+
+/// Child indices leading from the root to the component with this name
+fn get_named_path(container: &dyn Container, name: &str) -> Option<Vec<usize>> {
+    for (i, child) in container.get_children().iter().enumerate() {
+        let path = match child {
+            ContainerChild::Container(child) => get_named_path(child.as_container(), name),
+            ContainerChild::Component(_) => (child.as_base().get_name() == name).then(Vec::new),
+        };
+        if let Some(path) = path {
+            return Some([vec![i], path].concat());
+        }
+    }
+    None
+}
+
+/// Focuses the pane at the position ahead of a right click, which is only sent
+/// to an already focused pane as only left clicks change the focus
+pub fn focus_at(container: &mut dyn Container, x: u16, y: u16) {
+    let rects = container.get_children_rectangles();
+    for (child, rect) in container.get_children_mut().iter_mut().zip(rects) {
+        if x < rect.x || y < rect.y || x >= rect.right() || y >= rect.bottom() {
+            continue;
+        }
+        match child {
+            ContainerChild::Container(child) => {
+                focus_at(child.as_container_mut(), x - rect.x, y - rect.y)
+            }
+            ContainerChild::Component(component) => component.set_focus(Focus::Focus),
+        }
+    }
+}
+
+fn get_empty_pane() -> ContainerChild {
+    ContainerChild::from(Component::new(
+        String::from(EMPTY_PANE),
+        1,
+        Box::new(
+            ComponentWidgetSimple::new()
+                .text(String::from(
+                    "Empty pane (run close in the palette to remove)",
+                ))
+                .style(Style::default().fg(Color::LightCyan))
+                .alignment(Alignment::Center),
+        ),
+    ))
+}
+
+fn get_size(child: &ContainerChild) -> (u16, u16) {
+    (child.as_base().get_width(), child.as_base().get_height())
+}
+
+/// Grows (or shrinks) the focused pane, or the split holding it, in the
+/// nearest resizable container laid out in the direction, like dragging the
+/// border after it (or before it for the last pane)
+fn resize(tui: &mut dyn Container, direction: Direction, cells: i16) -> Result<(), String> {
+    let mut path = get_focused_path(tui).ok_or("No pane is focused")?;
+    while let Some(index) = path.pop() {
+        let parent = get_container_mut(tui, &path).unwrap();
+        let rects = parent.get_children_rectangles();
+        if !parent.is_resizable() || rects.len() < 2 || get_direction(&rects) != direction {
+            continue;
+        }
+        let children = parent.get_children_mut();
+        if is_fixed(&children[index], &direction) {
+            continue;
+        }
+        let Some(neighbour) = (index + 1..children.len())
+            .chain((0..index).rev())
+            .find(|i| !is_fixed(&children[*i], &direction))
+        else {
+            continue;
+        };
+        let size = |child: &ContainerChild| match direction {
+            Direction::Horizontal => child.as_base().get_width(),
+            Direction::Vertical => child.as_base().get_height(),
+        };
+        // Neither pane gets smaller than its borders and a row in between
+        let (own, other) = (
+            size(&children[index]) as i32,
+            size(&children[neighbour]) as i32,
+        );
+        let moved = (cells as i32).clamp(-(own - MIN_SIZE).max(0), (other - MIN_SIZE).max(0));
+        if moved == 0 {
+            return Err(String::from("Not enough room to resize the focused pane"));
+        }
+        // The pane getting smaller goes first to make room for the other one
+        let mut sizes = [(index, own + moved), (neighbour, other - moved)];
+        if moved > 0 {
+            sizes.reverse();
+        }
+        for (i, new_size) in sizes {
+            let new_size = new_size as u16;
+            let (width, height) = match direction {
+                Direction::Horizontal => (new_size, rects[i].height),
+                Direction::Vertical => (rects[i].width, new_size),
+            };
+            children[i]
+                .as_base_mut()
+                .resize(width, height)
+                .map_err(|err| format!("Not enough room to resize ({err:?})"))?;
+        }
+        return Ok(());
+    }
+    Err(String::from(
+        "Nothing next to the focused pane can be resized that way",
+    ))
+}
+
+/// Reshapes the layout by splitting and closing panes, where closed panes are
+/// kept around (and kept up to date) so they can be opened again later
+pub struct PaneLayout {
+    closed: Vec<ContainerChild>,
+    messages: Messages,
+}
+
+impl PaneLayout {
+    /// Starts with panes that are not part of the layout yet but can be opened
+    pub fn new(messages: Messages, closed: Vec<ContainerChild>) -> Self {
+        Self { closed, messages }
+    }
+
+    pub fn handle_update(&mut self, tui: &mut dyn Container) {
+        for message in self.messages.get::<LayoutMessage>() {
+            let result = match message {
+                LayoutMessage::Split(direction, pane) => self.split(tui, direction, pane),
+                LayoutMessage::Close => match get_focused_path(tui) {
+                    Some(path) => self.close(tui, &path),
+                    None => Err(String::from("No pane is focused")),
+                },
+                LayoutMessage::Toggle(name) => match get_named_path(tui, &name) {
+                    Some(path) => self.close(tui, &path),
+                    None => self.split(tui, Direction::Vertical, Some(name)),
+                },
+                LayoutMessage::Resize(direction, cells) => resize(tui, direction, cells),
+                LayoutMessage::EqualSizes => match get_focused_path(tui) {
+                    Some(path) => match get_container_mut(tui, &path[..path.len() - 1]) {
+                        Some(parent) if parent.is_resizable() => {
+                            reset_sizes(parent);
+                            Ok(())
+                        }
+                        _ => Err(String::from("The focused pane cannot be resized")),
+                    },
+                    None => Err(String::from("No pane is focused")),
+                },
+            };
+            if let Err(err) = result {
+                self.messages.push(NaluMessage::PaletteError(err));
+            }
+        }
+        // Closed panes still have to see their messages or they would pile up
+        for pane in &mut self.closed {
+            pane.as_base_mut().handle_update();
+        }
+    }
+
+    /// Replaces the focused pane with a container holding it and a new pane
+    fn split(
+        &mut self,
+        tui: &mut dyn Container,
+        direction: Direction,
+        pane: Option<String>,
+    ) -> Result<(), String> {
+        let path = get_focused_path(tui).ok_or("No pane is focused")?;
+        let new_child = match pane {
+            Some(name) => {
+                let index = self
+                    .closed
+                    .iter()
+                    .position(|c| c.as_base().get_name() == name)
+                    .ok_or_else(|| {
+                        format!(
+                            "No closed pane named {name} (closed: {})",
+                            self.get_closed().join(", ")
+                        )
+                    })?;
+                self.closed.remove(index)
+            }
+            None => get_empty_pane(),
+        };
+        let (index, parent_path) = path.split_last().unwrap();
+        let parent = get_container_mut(tui, parent_path).unwrap();
+        let child = parent.get_children_mut().remove(*index);
+        let (width, height) = get_size(&child);
+        let name = child.as_base().get_name();
+        let mut split = ContainerList::new(format!("{name}_split"), direction, true, 0, 0);
+        split.get_children_mut().extend([child, new_child]);
+        if let Err(err) = split.as_base_mut().resize(width, height) {
+            let mut children = std::mem::take(split.get_children_mut());
+            let new_child = children.pop().unwrap();
+            parent
+                .get_children_mut()
+                .insert(*index, children.pop().unwrap());
+            if new_child.as_base().get_name() != EMPTY_PANE {
+                self.closed.push(new_child);
+            }
+            return Err(format!("Not enough room to split {name} ({err:?})"));
+        }
+        parent
+            .get_children_mut()
+            .insert(*index, ContainerChild::from(split));
+        Ok(())
+    }
+
+    /// Removes the pane at the path, giving its space to the nearest pane next
+    /// to it and unwrapping its container if only one pane is left in there
+    fn close(&mut self, tui: &mut dyn Container, path: &[usize]) -> Result<(), String> {
+        let (index, parent_path) = path.split_last().unwrap();
+        let parent = get_container_mut(tui, parent_path).unwrap();
+        let direction = get_direction(&parent.get_children_rectangles());
+        let children = parent.get_children_mut();
+        let neighbour = (0..*index)
+            .rev()
+            .chain(index + 1..children.len())
+            .find(|i| !is_fixed(&children[*i], &direction))
+            .ok_or("Cannot close the last pane")?;
+        let mut child = children.remove(*index);
+        let neighbour = if neighbour > *index {
+            neighbour - 1
+        } else {
+            neighbour
+        };
+        let (width, height) = get_size(&child);
+        let (neighbour_width, neighbour_height) = get_size(&children[neighbour]);
+        let (width, height) = match direction {
+            Direction::Horizontal => (neighbour_width + width, neighbour_height),
+            Direction::Vertical => (neighbour_width, neighbour_height + height),
+        };
+        let _ = children[neighbour].as_base_mut().resize(width, height);
+        let remaining = children.len();
+        if let ContainerChild::Component(component) = &mut child {
+            component.set_focus(Focus::None);
+        }
+        if child.as_base().get_name() != EMPTY_PANE {
+            self.closed.push(child);
+        }
+        if remaining > 1 {
+            return Ok(());
+        }
+        if let Some((index, grandparent_path)) = parent_path.split_last() {
+            let grandparent = get_container_mut(tui, grandparent_path).unwrap();
+            let children = grandparent.get_children_mut();
+            let (width, height) = get_size(&children[*index]);
+            if let ContainerChild::Container(container) = &mut children[*index] {
+                let mut only = container.get_children_mut().remove(0);
+                let _ = only.as_base_mut().resize(width, height);
+                children[*index] = only;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_closed(&self) -> Vec<String> {
+        self.closed.iter().map(|c| c.as_base().get_name()).collect()
+    }
+}
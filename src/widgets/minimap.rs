@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+use crate::widgets::timescale::TimescaleState;
+
+/// Rows taken up by the minimap below the timescale, which the signal viewer
+/// leaves blank to stay lined up with the waveforms
+pub const MINIMAP_HEIGHT: usize = 1;
+
+const MINIMAP_SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Returns the column a timestamp falls into when the whole range is squeezed
+/// into the columns
+fn get_minimap_column(timestamp: u64, range: &Range<u64>, columns: usize) -> usize {
+    let width = range.end.saturating_sub(range.start).max(1);
+    let offset = timestamp.saturating_sub(range.start).min(width - 1);
+    ((offset as u128 * columns as u128) / width as u128) as usize
+}
+
+/// Returns the timestamp in the middle of a column of the minimap
+pub fn get_minimap_timestamp(column: u16, range: &Range<u64>, columns: u16) -> u64 {
+    let width = range.end.saturating_sub(range.start) as u128;
+    let columns = columns.max(1) as u128;
+    let offset = (width * (column as u128 * 2 + 1)) / (columns * 2);
+    range.start + offset as u64
+}
+
+/// Counts the transitions falling into each column of the minimap
+pub fn get_minimap_density(
+    transitions: impl Iterator<Item = u64>,
+    range: &Range<u64>,
+    columns: usize,
+) -> Vec<usize> {
+    let mut density = vec![0; columns];
+    if columns == 0 {
+        return density;
+    }
+    for timestamp in transitions {
+        density[get_minimap_column(timestamp, range, columns)] += 1;
+    }
+    density
+}
+
+/// Picks the shade of a column relative to the busiest column, where any
+/// activity at all stays visible
+fn get_minimap_shade(count: usize, most: usize) -> char {
+    if count == 0 || most == 0 {
+        return MINIMAP_SHADES[0];
+    }
+    let levels = MINIMAP_SHADES.len() - 1;
+    MINIMAP_SHADES[(count * levels).div_ceil(most).clamp(1, levels)]
+}
+
+/// The whole simulation in one line, shaded by how many transitions of the
+/// displayed signals fall into each column with the visible range highlighted
+pub struct Minimap<'a> {
+    state: &'a TimescaleState,
+    /// The timestamps of the whole simulation
+    range: Range<u64>,
+    /// Transitions in each column
+    density: &'a [usize],
+}
+
+impl<'a> Minimap<'a> {
+    pub fn new(state: &'a TimescaleState, range: Range<u64>, density: &'a [usize]) -> Self {
+        Self {
+            state,
+            range,
+            density,
+        }
+    }
+}
+
+impl<'a> Widget for Minimap<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height < 1 {
+            return;
+        }
+        let columns = area.width as usize;
+        let visible = self.state.get_range();
+        let first = get_minimap_column(visible.start, &self.range, columns);
+        let last = get_minimap_column(visible.end.saturating_sub(1), &self.range, columns);
+        let most = self.density.iter().copied().max().unwrap_or(0);
+        for column in 0..columns {
+            let count = self.density.get(column).copied().unwrap_or(0);
+            let cell = buf.get_mut(area.x + column as u16, area.y);
+            cell.set_char(get_minimap_shade(count, most));
+            if (first..=last).contains(&column) {
+                cell.set_style(Style::default().fg(Color::Black).bg(Color::Gray));
+            }
+        }
+    }
+}
+
+/// The row of a collapsed group, shaded by how many transitions of the signals
+/// inside of it fall into each visible column and flat where nothing happens
+pub struct ActivityLine {
+    density: Vec<usize>,
+}
+
+impl ActivityLine {
+    pub fn new(density: Vec<usize>) -> Self {
+        Self { density }
+    }
+}
+
+impl Widget for ActivityLine {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height < 1 {
+            return;
+        }
+        let most = self.density.iter().copied().max().unwrap_or(0);
+        for (column, count) in self.density.iter().take(area.width as usize).enumerate() {
+            let cell = buf.get_mut(area.x + column as u16, area.y);
+            if *count == 0 {
+                cell.set_char('─')
+                    .set_style(Style::default().fg(Color::DarkGray));
+            } else {
+                cell.set_char(get_minimap_shade(*count, most))
+                    .set_style(Style::default().fg(Color::LightYellow));
+            }
+        }
+    }
+}
+
+#[test]
+fn minimap_test() {
+    let range = 0..100;
+    let density = get_minimap_density([0, 5, 10, 99, 150].into_iter(), &range, 10);
+    assert_eq!(density, vec![2, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+    assert_eq!(get_minimap_shade(0, 2), ' ');
+    assert_eq!(get_minimap_shade(1, 2), '▒');
+    assert_eq!(get_minimap_shade(2, 2), '█');
+    assert_eq!(get_minimap_shade(1, 1000), '░');
+    assert_eq!(get_minimap_timestamp(0, &range, 10), 5);
+    assert_eq!(get_minimap_timestamp(9, &range, 10), 95);
+    assert!(get_minimap_density([1].into_iter(), &range, 0).is_empty());
+}
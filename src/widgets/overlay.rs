@@ -0,0 +1,176 @@
+use crossterm::event::KeyCode;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::Text,
+    widgets::{Block, BorderType, Borders, Paragraph, Widget, Wrap},
+};
+
+/// Rows of text in most overlays, which is also how far the page keys move
+pub const OVERLAY_ROWS: u16 = 8;
+
+/// How the overlay index picks the rows of an overlay that are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayScroll {
+    /// Always shows the first rows
+    None,
+    /// The selected row, which is kept in view
+    Select(usize),
+    /// The first row shown
+    Offset(usize),
+}
+
+/// Centers a box of this height across the screen, leaving a column free on
+/// either side
+pub fn get_overlay_rect(frame_rect: Rect, overlay_height: u16) -> Rect {
+    let (y, height) = if frame_rect.height <= overlay_height {
+        (0, frame_rect.height)
+    } else {
+        ((frame_rect.height - overlay_height) / 2, overlay_height)
+    };
+    let (x, width) = if frame_rect.width <= 4 {
+        (0, frame_rect.width)
+    } else {
+        (1, frame_rect.width - 2)
+    };
+    Rect::new(x, y, width, height)
+}
+
+/// The first of the rows of text shown when only some of them fit
+fn get_first_row(scroll: OverlayScroll, len: usize, rows: usize) -> usize {
+    match scroll {
+        OverlayScroll::None => 0,
+        OverlayScroll::Select(selected) => selected.saturating_sub(rows.saturating_sub(1)),
+        OverlayScroll::Offset(offset) => offset.min(len.saturating_sub(1)),
+    }
+}
+
+/// Moves the overlay index for the arrow, page, home and end keys without
+/// going past the last row, or gives nothing for any other key
+pub fn scroll_index(code: KeyCode, index: usize, last: usize) -> Option<usize> {
+    let page = OVERLAY_ROWS as usize;
+    Some(match code {
+        KeyCode::Up => index.saturating_sub(1),
+        KeyCode::Down => (index + 1).min(last),
+        KeyCode::PageUp => index.saturating_sub(page),
+        KeyCode::PageDown => (index + page).min(last),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        _ => return None,
+    })
+}
+
+/// A titled box of text drawn over the panes, which scrolls through the rows
+/// that do not fit and marks the border where more are hidden
+pub struct Overlay<'a> {
+    title: String,
+    text: Text<'a>,
+    rows: u16,
+    scroll: OverlayScroll,
+    style: Style,
+    wrap: bool,
+}
+
+impl<'a> Overlay<'a> {
+    pub fn new<T: Into<Text<'a>>>(title: impl Into<String>, text: T) -> Self {
+        Self {
+            title: title.into(),
+            text: text.into(),
+            rows: OVERLAY_ROWS,
+            scroll: OverlayScroll::None,
+            style: Style::default().fg(Color::LightCyan),
+            wrap: false,
+        }
+    }
+
+    /// Rows of text that fit when the screen is tall enough
+    pub fn rows(mut self, rows: u16) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: OverlayScroll) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Wraps long lines instead of cutting them off, for overlays that do not
+    /// scroll
+    pub fn wrap(mut self) -> Self {
+        self.wrap = true;
+        self
+    }
+
+    /// Where the overlay goes on a screen of this size
+    pub fn get_rect(&self, frame_rect: Rect) -> Rect {
+        get_overlay_rect(frame_rect, self.rows.saturating_add(2))
+    }
+}
+
+impl Widget for Overlay<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let len = self.text.height();
+        let rows = area.height.saturating_sub(2) as usize;
+        let first = get_first_row(self.scroll, len, rows);
+        let mut paragraph = Paragraph::new(self.text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(Color::White))
+                    .border_type(BorderType::Rounded)
+                    .title(self.title),
+            )
+            .style(self.style)
+            .scroll((first as u16, 0));
+        if self.wrap {
+            paragraph = paragraph.wrap(Wrap { trim: true });
+        }
+        paragraph.render(area, buf);
+        if self.scroll == OverlayScroll::None || area.width < 4 || area.height < 2 {
+            return;
+        }
+        let style = Style::default().fg(Color::White);
+        if first > 0 {
+            buf.set_string(area.right() - 2, area.top(), "▲", style);
+        }
+        if first + rows < len {
+            buf.set_string(area.right() - 2, area.bottom() - 1, "▼", style);
+        }
+    }
+}
+
+#[test]
+fn overlay_scroll_test() {
+    assert_eq!(scroll_index(KeyCode::Down, 3, 3), Some(3));
+    assert_eq!(scroll_index(KeyCode::PageDown, 0, 20), Some(8));
+    assert_eq!(scroll_index(KeyCode::PageUp, 5, 20), Some(0));
+    assert_eq!(scroll_index(KeyCode::End, 0, 20), Some(20));
+    assert_eq!(scroll_index(KeyCode::Enter, 0, 20), None);
+
+    let text = (0..10)
+        .map(|i| format!("row {i}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let area = Rect::new(0, 0, 12, 6);
+    let mut buf = Buffer::empty(area);
+    Overlay::new("List", text.clone())
+        .scroll(OverlayScroll::Select(5))
+        .render(area, &mut buf);
+    // Rows 2 to 5 fit with the selected one last, leaving rows on either side
+    assert_eq!(buf.get(1, 1).symbol, "r");
+    assert_eq!(buf.get(5, 1).symbol, "2");
+    assert_eq!(buf.get(10, 0).symbol, "▲");
+    assert_eq!(buf.get(10, 5).symbol, "▼");
+    let mut buf = Buffer::empty(area);
+    Overlay::new("List", text)
+        .scroll(OverlayScroll::Offset(6))
+        .render(area, &mut buf);
+    assert_eq!(buf.get(5, 1).symbol, "6");
+    assert_eq!(buf.get(10, 5).symbol, "─");
+}
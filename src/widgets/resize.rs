@@ -0,0 +1,265 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{MouseButton, MouseEventKind};
+use tui::{
+    buffer::Buffer,
+    layout::{Direction, Rect},
+    style::Color,
+    widgets::Widget,
+};
+use tui_tiling::container::{Container, ContainerChild};
+
+/// How close together two clicks on a handle have to be to reset the sizes
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// A draggable border between two children of a resizable container
+#[derive(Debug, Clone)]
+struct ResizeHandle {
+    /// Child indices leading from the root to the resizable container
+    path: Vec<usize>,
+    /// Index of the child before the handle
+    index: usize,
+    direction: Direction,
+    /// Border cells of the children on either side of the handle
+    before: Rect,
+    after: Rect,
+}
+
+impl ResizeHandle {
+    fn is_same(&self, path: &[usize], index: usize) -> bool {
+        self.path == path && self.index == index
+    }
+
+    fn contains(&self, x: u16, y: u16) -> bool {
+        [self.before, self.after]
+            .iter()
+            .any(|r| x >= r.x && y >= r.y && x < r.x + r.width && y < r.y + r.height)
+    }
+}
+
+pub(crate) fn get_direction(rects: &[Rect]) -> Direction {
+    match rects {
+        [first, second, ..] if second.x > first.x => Direction::Horizontal,
+        _ => Direction::Vertical,
+    }
+}
+
+pub(crate) fn is_fixed(child: &ContainerChild, direction: &Direction) -> bool {
+    match direction {
+        Direction::Horizontal => child.as_base().is_fixed_width(),
+        Direction::Vertical => child.as_base().is_fixed_height(),
+    }
+}
+
+fn collect_handles(
+    container: &dyn Container,
+    x: u16,
+    y: u16,
+    path: &mut Vec<usize>,
+    handles: &mut Vec<ResizeHandle>,
+) {
+    let rects = container.get_children_rectangles();
+    let children = container.get_children();
+    let direction = get_direction(&rects);
+    for i in 0..children.len() {
+        if let ContainerChild::Container(child) = &children[i] {
+            path.push(i);
+            collect_handles(
+                child.as_container(),
+                x + rects[i].x,
+                y + rects[i].y,
+                path,
+                handles,
+            );
+            path.pop();
+        }
+        // Borders next to fixed size children cannot be dragged
+        if !container.is_resizable()
+            || i + 1 >= children.len()
+            || is_fixed(&children[i], &direction)
+            || is_fixed(&children[i + 1], &direction)
+        {
+            continue;
+        }
+        let (before, after) = (rects[i], rects[i + 1]);
+        let (before, after) = match direction {
+            Direction::Horizontal => (
+                Rect::new(x + before.right() - 1, y + before.y, 1, before.height),
+                Rect::new(x + after.x, y + after.y, 1, after.height),
+            ),
+            Direction::Vertical => (
+                Rect::new(x + before.x, y + before.bottom() - 1, before.width, 1),
+                Rect::new(x + after.x, y + after.y, after.width, 1),
+            ),
+        };
+        handles.push(ResizeHandle {
+            path: path.clone(),
+            index: i,
+            direction: direction.clone(),
+            before,
+            after,
+        });
+    }
+}
+
+fn get_handles(tui: &dyn Container) -> Vec<ResizeHandle> {
+    let mut handles = Vec::new();
+    collect_handles(tui, 0, 0, &mut Vec::new(), &mut handles);
+    handles
+}
+
+pub(crate) fn get_container_mut<'a>(
+    container: &'a mut dyn Container,
+    path: &[usize],
+) -> Option<&'a mut dyn Container> {
+    let Some((index, path)) = path.split_first() else {
+        return Some(container);
+    };
+    match container.get_children_mut().get_mut(*index)? {
+        ContainerChild::Container(child) => get_container_mut(child.as_container_mut(), path),
+        ContainerChild::Component(_) => None,
+    }
+}
+
+/// Splits the total size of the children that are not fixed equally between
+/// them, with any remainder going to the last one
+fn get_equal_sizes(sizes: &[Option<u16>]) -> Vec<u16> {
+    let total = sizes.iter().flatten().sum::<u16>();
+    let count = sizes.iter().flatten().count() as u16;
+    let last = sizes.iter().rposition(|s| s.is_some());
+    sizes
+        .iter()
+        .enumerate()
+        .map(|(i, size)| match size {
+            None => 0,
+            Some(_) if Some(i) == last => total - (total / count) * (count - 1),
+            Some(_) => total / count,
+        })
+        .collect()
+}
+
+/// Resets all of the resizable children of the container back to equal sizes
+pub(crate) fn reset_sizes(container: &mut dyn Container) {
+    let rects = container.get_children_rectangles();
+    let direction = get_direction(&rects);
+    let sizes = container
+        .get_children()
+        .iter()
+        .zip(rects.iter())
+        .map(|(child, rect)| match direction {
+            _ if is_fixed(child, &direction) => None,
+            Direction::Horizontal => Some(rect.width),
+            Direction::Vertical => Some(rect.height),
+        })
+        .collect::<Vec<Option<u16>>>();
+    let equal_sizes = get_equal_sizes(&sizes);
+    for (i, child) in container.get_children_mut().iter_mut().enumerate() {
+        if sizes[i].is_none() {
+            continue;
+        }
+        let (width, height) = match direction {
+            Direction::Horizontal => (equal_sizes[i], rects[i].height),
+            Direction::Vertical => (rects[i].width, equal_sizes[i]),
+        };
+        if let Err(err) = child.as_base_mut().resize(width, height) {
+            log::warn!("Could not reset sizes ({err:?})");
+            return;
+        }
+    }
+}
+
+/// Tracks drags and double clicks on the borders between resizable children,
+/// which the containers themselves handle the actual resizing of
+#[derive(Default)]
+pub struct ResizeHandles {
+    dragging: Option<(Vec<usize>, usize)>,
+    last_click: Option<(Vec<usize>, usize, Instant)>,
+}
+
+impl ResizeHandles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle_mouse(&mut self, tui: &mut dyn Container, x: u16, y: u16, kind: MouseEventKind) {
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => {}
+            MouseEventKind::Drag(MouseButton::Left) | MouseEventKind::Moved => return,
+            _ => {
+                self.dragging = None;
+                return;
+            }
+        }
+        let handles = get_handles(tui);
+        let Some(handle) = handles.iter().find(|h| h.contains(x, y)) else {
+            self.dragging = None;
+            self.last_click = None;
+            return;
+        };
+        let double_click = self.last_click.as_ref().is_some_and(|(path, index, time)| {
+            handle.is_same(path, *index) && time.elapsed() < DOUBLE_CLICK
+        });
+        if double_click {
+            if let Some(container) = get_container_mut(tui, &handle.path) {
+                reset_sizes(container);
+            }
+            self.dragging = None;
+            self.last_click = None;
+        } else {
+            self.dragging = Some((handle.path.clone(), handle.index));
+            self.last_click = Some((handle.path.clone(), handle.index, Instant::now()));
+        }
+    }
+
+    pub fn get_widget(&self, tui: &dyn Container) -> ResizeHandlesWidget {
+        ResizeHandlesWidget {
+            handles: get_handles(tui),
+            dragging: self.dragging.clone(),
+        }
+    }
+}
+
+/// Marks the middle of every draggable border with a handle, highlighting the
+/// whole border while it is being dragged
+pub struct ResizeHandlesWidget {
+    handles: Vec<ResizeHandle>,
+    dragging: Option<(Vec<usize>, usize)>,
+}
+
+impl Widget for ResizeHandlesWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for handle in &self.handles {
+            let dragging = self
+                .dragging
+                .as_ref()
+                .is_some_and(|(path, index)| handle.is_same(path, *index));
+            for rect in [handle.before, handle.after] {
+                let rect = rect.intersection(area);
+                if rect.area() == 0 {
+                    continue;
+                }
+                if dragging {
+                    for x in rect.left()..rect.right() {
+                        for y in rect.top()..rect.bottom() {
+                            buf.get_mut(x, y).set_fg(Color::LightBlue);
+                        }
+                    }
+                }
+                let (x, y, symbol) = match handle.direction {
+                    Direction::Horizontal => (rect.x, rect.y + rect.height / 2, "┃"),
+                    Direction::Vertical => (rect.x + rect.width / 2, rect.y, "━"),
+                };
+                buf.get_mut(x, y).set_symbol(symbol);
+            }
+        }
+    }
+}
+
+#[test]
+fn equal_sizes_test() {
+    assert_eq!(
+        get_equal_sizes(&[Some(10), Some(30), Some(21)]),
+        vec![20, 20, 21]
+    );
+    assert_eq!(get_equal_sizes(&[Some(7), None, Some(9)]), vec![8, 0, 8]);
+}
@@ -1,13 +1,117 @@
 use std::ops::Range;
+use std::time::{Duration, Instant};
 
 use tui::{
     buffer::Buffer,
     layout::{Alignment, Rect},
+    style::{Color, Style},
     text::{Span, Spans, Text},
     widgets::{Paragraph, Widget},
 };
 
-fn render_time(timestamp: u64, resolution: u64, timescale: i32) -> String {
+/// Converts a time given as digits and the power of ten in seconds they are in
+/// (like 15 and -10 for 1.5ns) into timestamps of the timescale, rounding down
+pub fn get_timestamps(value: u64, exponent: i32, timescale: i32) -> u64 {
+    let power = exponent + timescale;
+    if power >= 0 {
+        value.saturating_mul(10u64.saturating_pow(power as u32))
+    } else {
+        value / 10u64.pow((-power).min(19) as u32)
+    }
+}
+
+/// Stretches without changes are collapsed when at least this fraction of the
+/// range in view long
+const IDLE_FRACTION: u64 = 16;
+
+/// Fewest columns left to draw the time between the breaks in, where the
+/// columns are spread evenly over the whole range instead
+const MIN_ACTIVE_COLUMNS: u64 = 16;
+
+/// The symbol drawn in the column of each break
+pub const BREAK_SYMBOL: &str = "≈";
+
+/// Finds the stretches of a range without any of the sorted timestamps of
+/// changes in them long enough to collapse into breaks, leaving some time on
+/// either side of each change to draw its value in
+pub fn get_idle_breaks(changes: &[u64], range: &Range<u64>) -> Vec<Range<u64>> {
+    let idle = range.end.saturating_sub(range.start) / IDLE_FRACTION;
+    let mut breaks = Vec::new();
+    if idle < 2 || !changes.iter().any(|timestamp| range.contains(timestamp)) {
+        return breaks;
+    }
+    let mut start = range.start;
+    for timestamp in changes.iter().filter(|timestamp| range.contains(timestamp)) {
+        let end = timestamp.saturating_sub(idle / 2);
+        if end >= start.saturating_add(idle) {
+            breaks.push(start..end);
+        }
+        start = start.max(timestamp.saturating_add(idle / 2));
+    }
+    if range.end >= start.saturating_add(idle) {
+        breaks.push(start..range.end);
+    }
+    breaks
+}
+
+/// Splits a range into the time drawn in each column, where each of the
+/// sorted breaks takes up a single column and the rest of the columns are
+/// spread evenly over the time between them, unless that leaves too few
+fn compress_columns(
+    range: &Range<u64>,
+    breaks: &[Range<u64>],
+    columns: u16,
+) -> Option<Vec<(Range<u64>, bool)>> {
+    let breaks = breaks
+        .iter()
+        .map(|b| b.start.max(range.start)..b.end.min(range.end))
+        .filter(|b| !b.is_empty())
+        .collect::<Vec<Range<u64>>>();
+    let idle = breaks.iter().map(|b| b.end - b.start).sum::<u64>();
+    let active = range
+        .end
+        .saturating_sub(range.start)
+        .checked_sub(idle)
+        .filter(|active| *active > 0)?;
+    let active_columns = (columns as u64)
+        .checked_sub(breaks.len() as u64)
+        .filter(|active_columns| *active_columns >= MIN_ACTIVE_COLUMNS)?;
+    let mut segments = Vec::new();
+    let mut start = range.start;
+    for b in breaks {
+        if b.start > start {
+            segments.push((start..b.start, false));
+        }
+        start = b.end;
+        segments.push((b, true));
+    }
+    if start < range.end {
+        segments.push((start..range.end, false));
+    }
+    // Rounds to the nearest column so every stretch between breaks gets some
+    let get_column = |time: u64| {
+        ((time as u128 * active_columns as u128 + active as u128 / 2) / active as u128) as u64
+    };
+    let mut compressed = Vec::with_capacity(columns as usize);
+    let mut before = 0;
+    for (segment, is_break) in segments {
+        if is_break {
+            compressed.push((segment, true));
+            continue;
+        }
+        let width = segment.end - segment.start;
+        let count = get_column(before + width) - get_column(before);
+        if count == 0 {
+            return None;
+        }
+        let split = |i: u64| segment.start + ((i as u128 * width as u128) / count as u128) as u64;
+        compressed.extend((0..count).map(|i| (split(i)..split(i + 1), false)));
+        before += width;
+    }
+    Some(compressed)
+}
+
+pub fn render_time(timestamp: u64, resolution: u64, timescale: i32) -> String {
     let mut timestamp = timestamp;
     let mut resolution = resolution;
     let mut offset = 0i32;
@@ -52,10 +156,20 @@ fn render_time(timestamp: u64, resolution: u64, timescale: i32) -> String {
     };
 
     if timestamp_offset > 0 {
+        // Keep the leading zeros of the fraction but not the trailing ones
+        let fraction = format!(
+            "{:0width$}",
+            timestamp % timestamp_msb_divider,
+            width = timestamp_offset as usize
+        );
+        let fraction = match fraction.trim_end_matches('0') {
+            "" => "0",
+            fraction => fraction,
+        };
         format!(
             "{}.{}{}",
             timestamp / timestamp_msb_divider,
-            timestamp % timestamp_msb_divider,
+            fraction,
             timescale_str
         )
     } else {
@@ -63,28 +177,372 @@ fn render_time(timestamp: u64, resolution: u64, timescale: i32) -> String {
     }
 }
 
+/// Renders how far the second timestamp is after (or before) the first
+pub fn render_delta(from: u64, to: u64, timescale: i32) -> String {
+    if to >= from {
+        format!("+{}", render_time(to - from, 1, timescale))
+    } else {
+        format!("-{}", render_time(from - to, 1, timescale))
+    }
+}
+
+/// Colors of the named cursors, picked by their letter
+const CURSOR_COLORS: [Color; 6] = [
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::Cyan,
+    Color::Magenta,
+];
+
+pub fn get_cursor_color(name: char) -> Color {
+    let index = (name as usize).wrapping_sub('A' as usize) % CURSOR_COLORS.len();
+    CURSOR_COLORS[index]
+}
+
+/// An extra cursor named with a letter, for comparing several points in time
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedCursor {
+    pub name: char,
+    pub timestamp: u64,
+}
+
+/// A note placed at a point in time, drawn above the timescale
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimescaleAnnotation {
+    pub timestamp: u64,
+    pub text: String,
+}
+
+/// How many earlier views are kept to go back to
+const HISTORY_LIMIT: usize = 100;
+
+/// Zooms and scrolls closer together than this count as holding the key down
+const REPEAT_INTERVAL: Duration = Duration::from_millis(300);
+
+/// The most steps one repeated zoom or scroll takes at once
+const REPEAT_STEPS_MAX: u64 = 8;
+
+/// How much faster zooming and scrolling gets per repeat by default
+pub const DEFAULT_ACCELERATION: f64 = 1.2;
+
+/// Timestamps shown for a dump with fewer than two of them, which has no
+/// stretch of time of its own to fit the view to
+pub const EMPTY_DUMP_WIDTH: u64 = 10;
+
+/// The keys that zoom and scroll, which speed up while held down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoomAction {
+    In,
+    Out,
+    Left,
+    Right,
+}
+
+/// The range of a dump in view along with the cursors and annotations, shared
+/// by the timescale and every waveform drawn below it
 pub struct TimescaleState {
     range: Range<u64>,
-    _cursor: u64,
+    cursor: u64,
+    // Second point in time to compare the cursor against
+    marker: Option<u64>,
     timescale: i32,
+    timestamp_min: u64,
     timestamp_max: u64,
+    // Sorted by timestamp
+    annotations: Vec<TimescaleAnnotation>,
+    // Sorted by name
+    named_cursors: Vec<NamedCursor>,
+    // Ranges before each zoom, newest last, and the ones gone back from
+    back: Vec<Range<u64>>,
+    forward: Vec<Range<u64>>,
+    // Keeps the end of the dump at the right edge as more of it is loaded,
+    // until paused by moving the range away from the end
+    follow: bool,
+    follow_paused: bool,
+    // The range as it was when following was last updated
+    follow_range: Range<u64>,
+    // What the steps of a repeated zoom are multiplied by for every repeat
+    acceleration: f64,
+    // The last zoom or scroll, when it was and how many times it repeated
+    repeat: Option<(ZoomAction, Instant, i32)>,
+    // Collapses the stretches where nothing changes into a column each
+    compressed: bool,
+    // Sorted stretches without changes in the range, found while drawing
+    breaks: Vec<Range<u64>>,
 }
 
 impl TimescaleState {
     pub fn new() -> Self {
         Self {
             range: 0..1000000, // Actual time is timestamp*10^(-timescale)
-            _cursor: 0,
+            cursor: 0,
+            marker: None,
             timescale: 6,
+            timestamp_min: 0,
             timestamp_max: 1000000,
+            annotations: Vec::new(),
+            named_cursors: Vec::new(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            follow: false,
+            follow_paused: false,
+            follow_range: 0..1000000,
+            acceleration: DEFAULT_ACCELERATION,
+            repeat: None,
+            compressed: false,
+            breaks: Vec::new(),
         }
     }
 
+    /// Shows the range of a newly loaded dump, where the timescale is the
+    /// power of ten below a second each timestamp counts
     pub fn load_waveform(&mut self, new_range: Range<u64>, timestamp_max: u64, timescale: i32) {
+        if self.follow && timescale == self.timescale && new_range.start == self.timestamp_min {
+            // More of the same dump while following, which keeps the cursors
+            self.timestamp_max = timestamp_max;
+            if !self.follow_paused {
+                self.anchor_end();
+            }
+            self.follow_range = self.range.clone();
+            return;
+        }
         self.timescale = timescale;
         // TODO: Keep old timescale range if it still makes sense and timescales same
-        self.range = new_range;
+        self.cursor = new_range.start;
+        self.marker = None;
+        self.named_cursors.clear();
+        self.back.clear();
+        self.forward.clear();
+        self.breaks.clear();
+        self.timestamp_min = new_range.start;
         self.timestamp_max = timestamp_max;
+        self.range = match new_range.end.saturating_sub(new_range.start) {
+            0 | 1 => self.get_full_range(),
+            _ => new_range,
+        };
+        self.follow_range = self.range.clone();
+    }
+
+    pub fn add_annotation(&mut self, timestamp: u64, text: String) {
+        let index = self
+            .annotations
+            .partition_point(|a| a.timestamp <= timestamp);
+        self.annotations
+            .insert(index, TimescaleAnnotation { timestamp, text });
+    }
+
+    pub fn remove_annotation(&mut self, index: usize) -> Option<TimescaleAnnotation> {
+        if index < self.annotations.len() {
+            Some(self.annotations.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear_annotations(&mut self) {
+        self.annotations.clear();
+    }
+
+    pub fn get_annotations(&self) -> &Vec<TimescaleAnnotation> {
+        &self.annotations
+    }
+
+    /// Finds the nearest annotation after (or before) a timestamp with the
+    /// query somewhere in its text, ignoring case, like the byte of a decoded
+    /// transaction
+    pub fn find_annotation(&self, query: &str, timestamp: u64, forward: bool) -> Option<u64> {
+        let query = query.to_lowercase();
+        let is_match = |a: &&TimescaleAnnotation| a.text.to_lowercase().contains(&query);
+        if forward {
+            let start = self
+                .annotations
+                .partition_point(|a| a.timestamp <= timestamp);
+            self.annotations[start..].iter().find(is_match)
+        } else {
+            let end = self
+                .annotations
+                .partition_point(|a| a.timestamp < timestamp);
+            self.annotations[..end].iter().rev().find(is_match)
+        }
+        .map(|a| a.timestamp)
+    }
+
+    /// Places the named cursor at the timestamp, moving it if already placed
+    pub fn set_named_cursor(&mut self, name: char, timestamp: u64) {
+        match self.named_cursors.binary_search_by_key(&name, |c| c.name) {
+            Ok(index) => self.named_cursors[index].timestamp = timestamp,
+            Err(index) => self
+                .named_cursors
+                .insert(index, NamedCursor { name, timestamp }),
+        }
+    }
+
+    pub fn remove_named_cursor(&mut self, name: char) {
+        self.named_cursors.retain(|c| c.name != name);
+    }
+
+    /// Returns the first letter not used by a named cursor yet
+    pub fn get_free_cursor_name(&self) -> Option<char> {
+        ('A'..='Z').find(|name| self.named_cursors.iter().all(|c| c.name != *name))
+    }
+
+    pub fn get_named_cursors(&self) -> &Vec<NamedCursor> {
+        &self.named_cursors
+    }
+
+    /// Moves the cursor to the timestamp and recenters the range around it if
+    /// the timestamp is not already visible, without going past either end of
+    /// the dump
+    pub fn goto(&mut self, timestamp: u64) {
+        self.cursor = timestamp;
+        if self.range.contains(&timestamp) {
+            return;
+        }
+        let width = self.get_width();
+        let start = self.clamp_start(timestamp.saturating_sub(width / 2), width);
+        self.range = start..(start + width);
+    }
+
+    /// Moves the start of a range this wide so the range stays inside the
+    /// dump, or starts with the dump when it is wider
+    fn clamp_start(&self, start: u64, width: u64) -> u64 {
+        let last = self.timestamp_max.saturating_sub(width);
+        start.clamp(self.timestamp_min, last.max(self.timestamp_min))
+    }
+
+    /// Moves the range to be centered on the timestamp, keeping its width and
+    /// staying inside the dump. The range before goes into the history unless
+    /// this continues the last jump, like dragging across the minimap does.
+    pub fn recenter(&mut self, timestamp: u64, history: bool) {
+        let width = self.get_width();
+        let start = self.clamp_start(timestamp.saturating_sub(width / 2), width);
+        if start == self.range.start {
+            return;
+        }
+        if history {
+            self.push_history();
+        }
+        self.range = start..(start + width);
+    }
+
+    pub fn set_range(&mut self, range: Range<u64>) {
+        if range.start < range.end && range != self.range {
+            self.push_history();
+            self.range = range;
+        }
+    }
+
+    pub fn set_cursor(&mut self, cursor: u64) {
+        self.cursor = cursor;
+    }
+
+    pub fn set_marker(&mut self, marker: Option<u64>) {
+        self.marker = marker;
+    }
+
+    /// Starts (or stops) collapsing the stretches where none of the signals
+    /// change, so sparse activity over a long range fits on one screen
+    pub fn set_compressed(&mut self, compressed: bool) {
+        self.compressed = compressed;
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Sets the stretches without changes to collapse, from [`get_idle_breaks`]
+    pub fn set_breaks(&mut self, breaks: Vec<Range<u64>>) {
+        self.breaks = breaks;
+    }
+
+    fn get_compressed_columns(&self, columns: u16) -> Option<Vec<(Range<u64>, bool)>> {
+        if !self.compressed || self.breaks.is_empty() {
+            return None;
+        }
+        compress_columns(&self.range, &self.breaks, columns)
+    }
+
+    /// Returns the time drawn in each column for a given render width, along
+    /// with whether the column is a break standing in for a stretch of time
+    pub fn get_columns(&self, columns: u16) -> Vec<(Range<u64>, bool)> {
+        if let Some(compressed) = self.get_compressed_columns(columns) {
+            return compressed;
+        }
+        (0..columns)
+            .map(|column| {
+                let start = self.get_column_timestamp(column, columns);
+                let end = self.get_column_timestamp(column + 1, columns);
+                (start..end, false)
+            })
+            .collect()
+    }
+
+    /// Returns the timestamp at the start of a column for a given render width
+    pub fn get_column_timestamp(&self, column: u16, columns: u16) -> u64 {
+        if columns == 0 {
+            return self.range.start;
+        }
+        if let Some(compressed) = self.get_compressed_columns(columns) {
+            return compressed
+                .get(column as usize)
+                .map_or(self.range.end, |(range, _)| range.start);
+        }
+        self.range.start + (column as u64 * self.get_width()) / columns as u64
+    }
+
+    /// Returns the column a timestamp is rendered in, if visible
+    pub fn get_timestamp_column(&self, timestamp: u64, columns: u16) -> Option<u16> {
+        if !self.range.contains(&timestamp) {
+            return None;
+        }
+        if let Some(compressed) = self.get_compressed_columns(columns) {
+            let column = compressed.partition_point(|(range, _)| range.end <= timestamp);
+            return Some(column as u16);
+        }
+        let column = ((timestamp - self.range.start) * columns as u64) / self.get_width();
+        Some(column as u16)
+    }
+
+    /// Sets how quickly repeated zooms and scrolls speed up, where 1.0 keeps
+    /// every one of them a single step
+    pub fn set_acceleration(&mut self, acceleration: f64) {
+        self.acceleration = acceleration.max(1.0);
+    }
+
+    /// How many steps a zoom or scroll at this time takes, which grows by the
+    /// acceleration every time the same one is repeated in quick succession
+    pub fn get_repeat_steps(&mut self, action: ZoomAction, now: Instant) -> u64 {
+        let repeats = match self.repeat {
+            Some((last, time, repeats))
+                if last == action && now.saturating_duration_since(time) <= REPEAT_INTERVAL =>
+            {
+                repeats.saturating_add(1)
+            }
+            _ => 0,
+        };
+        self.repeat = Some((action, now, repeats));
+        (self.acceleration.powi(repeats) as u64).clamp(1, REPEAT_STEPS_MAX)
+    }
+
+    /// Zooms or scrolls as many steps as the key repeating calls for, where
+    /// all the steps of a zoom are a single step back in the history
+    pub fn zoom_repeated(&mut self, action: ZoomAction, cursor: bool, now: Instant) {
+        let steps = self.get_repeat_steps(action, now);
+        match action {
+            ZoomAction::In if self.get_width() >= 2 => self.push_history(),
+            ZoomAction::Out => self.push_history(),
+            _ => {}
+        }
+        for _ in 0..steps {
+            match action {
+                ZoomAction::In => self.zoom_in_step(),
+                ZoomAction::Out => self.zoom_out_step(),
+                ZoomAction::Left => self.zoom_left(cursor),
+                ZoomAction::Right => self.zoom_right(cursor),
+            }
+        }
     }
 
     pub fn zoom_left(&mut self, _cursor: bool) {
@@ -106,6 +564,17 @@ impl TimescaleState {
     }
 
     pub fn zoom_in(&mut self, _cursor: bool) {
+        if self.get_width() < 2 {
+            return;
+        }
+        self.push_history();
+        self.zoom_in_step();
+    }
+
+    fn zoom_in_step(&mut self) {
+        if self.get_width() < 2 {
+            return;
+        }
         // TODO: Support zooming in around cursor
         // Find the center of the timestamp range and then average start/end with the center
         let center = self.get_center();
@@ -113,6 +582,11 @@ impl TimescaleState {
     }
 
     pub fn zoom_out(&mut self, _cursor: bool) {
+        self.push_history();
+        self.zoom_out_step();
+    }
+
+    fn zoom_out_step(&mut self) {
         // TODO: Support zooming out around cursor
         let center = self.get_center();
         let width = self.get_width();
@@ -123,6 +597,103 @@ impl TimescaleState {
         }
     }
 
+    /// Shows this many timestamps around the cursor, moved inside the dump
+    /// when the cursor is close to either end of it
+    pub fn zoom_around_cursor(&mut self, width: u64) {
+        let width = width.max(1);
+        if width >= self.timestamp_max.saturating_sub(self.timestamp_min) {
+            self.zoom_all();
+            return;
+        }
+        self.push_history();
+        let start = self.clamp_start(self.cursor.saturating_sub(width / 2), width);
+        self.range = start..(start + width);
+    }
+
+    pub fn zoom_all(&mut self) {
+        self.push_history();
+        self.range = self.get_full_range();
+    }
+
+    /// The whole dump, or a few timestamps from its start when it has fewer
+    /// than two of them
+    fn get_full_range(&self) -> Range<u64> {
+        let end = match self.timestamp_max.saturating_sub(self.timestamp_min) {
+            0 | 1 => self.timestamp_min + EMPTY_DUMP_WIDTH,
+            _ => self.timestamp_max,
+        };
+        self.timestamp_min..end
+    }
+
+    /// Remembers the range before it is zoomed so it can be gone back to,
+    /// which forgets the ranges gone back from like a browser does
+    fn push_history(&mut self) {
+        if self.back.last() != Some(&self.range) {
+            self.back.push(self.range.clone());
+            if self.back.len() > HISTORY_LIMIT {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    /// Goes back to the range before the last zoom, returning if there was one
+    pub fn history_back(&mut self) -> bool {
+        let Some(range) = self.back.pop() else {
+            return false;
+        };
+        self.forward.push(std::mem::replace(&mut self.range, range));
+        true
+    }
+
+    /// Redoes the last zoom gone back from, returning if there was one
+    pub fn history_forward(&mut self) -> bool {
+        let Some(range) = self.forward.pop() else {
+            return false;
+        };
+        self.back.push(std::mem::replace(&mut self.range, range));
+        true
+    }
+
+    /// Starts (or stops) keeping the end of the dump at the right edge
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        self.follow_paused = false;
+        if follow {
+            self.anchor_end();
+        }
+        self.follow_range = self.range.clone();
+    }
+
+    /// Returns if the end of the dump is followed, and if so whether it is
+    /// paused because the range was moved away from it
+    pub fn get_follow(&self) -> Option<bool> {
+        self.follow.then_some(self.follow_paused)
+    }
+
+    /// Catches up with the range being changed since the last update, where
+    /// zooming keeps the end at the right edge, moving away from the end
+    /// pauses following and moving back to it resumes
+    pub fn update_follow(&mut self) {
+        if !self.follow || self.range == self.follow_range {
+            return;
+        }
+        let zoomed = self.get_width() != self.follow_range.end - self.follow_range.start;
+        if !zoomed || self.follow_paused {
+            self.follow_paused = self.range.end < self.timestamp_max;
+        }
+        if !self.follow_paused {
+            self.anchor_end();
+        }
+        self.follow_range = self.range.clone();
+    }
+
+    fn anchor_end(&mut self) {
+        let width = self.get_width();
+        let end = self.timestamp_max.max(self.timestamp_min + 1);
+        self.range = end.saturating_sub(width)..end;
+    }
+
     fn get_width(&self) -> u64 {
         if self.range.start < self.range.end {
             self.range.end - self.range.start
@@ -140,8 +711,11 @@ impl TimescaleState {
     }
 
     pub fn get_cursor(&self) -> u64 {
-        // TODO: Implement actual cursor
-        self.range.start
+        self.cursor
+    }
+
+    pub fn get_marker(&self) -> Option<u64> {
+        self.marker
     }
 
     pub fn get_timescale(&self) -> i32 {
@@ -159,6 +733,7 @@ impl Default for TimescaleState {
     }
 }
 
+/// The time axis above the waveforms, labelled in the timescale of the dump
 pub struct Timescale<'a> {
     /// The timescale range and cursor position to render
     state: &'a TimescaleState,
@@ -172,8 +747,24 @@ impl<'a> Timescale<'a> {
 
 impl<'a> Widget for Timescale<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        let compressed = self.state.get_compressed_columns(area.width);
         let text = if self.state.range.start == self.state.range.end {
             Text::raw(format!("|{}|", self.state.range.start))
+        } else if let Some(columns) = &compressed {
+            // Labels the time at the start of each label, which no longer
+            // grows evenly across the breaks
+            let mut text = Spans::from(Vec::new());
+            let resolution = columns
+                .iter()
+                .find(|(_, is_break)| !is_break)
+                .map_or(1, |(range, _)| range.end - range.start);
+            while let Some((range, _)) = columns.get(text.width()) {
+                text.0.push(Span::from(format!(
+                    "|{}",
+                    render_time(range.start, resolution, self.state.timescale)
+                )));
+            }
+            Text::from(text)
         } else {
             let mut text = Spans::from(Vec::new());
             let mut timestamp_current = self.state.range.start;
@@ -200,6 +791,279 @@ impl<'a> Widget for Timescale<'a> {
 
         Paragraph::new(text)
             .alignment(Alignment::Left)
-            .render(area, buf)
+            .render(area, buf);
+        for (x, (_, is_break)) in compressed.iter().flatten().enumerate() {
+            if *is_break {
+                buf.get_mut(area.x + x as u16, area.y)
+                    .set_symbol(BREAK_SYMBOL)
+                    .set_style(Style::default().fg(Color::DarkGray));
+            }
+        }
+
+        // Overlay annotation markers on top of the rendered times
+        for annotation in &self.state.annotations {
+            if let Some(column) = self
+                .state
+                .get_timestamp_column(annotation.timestamp, area.width)
+            {
+                buf.get_mut(area.x + column, area.y)
+                    .set_char('◆')
+                    .set_style(Style::default().fg(Color::Yellow));
+            }
+        }
+        for cursor in &self.state.named_cursors {
+            if let Some(column) = self
+                .state
+                .get_timestamp_column(cursor.timestamp, area.width)
+            {
+                buf.get_mut(area.x + column, area.y)
+                    .set_char(cursor.name)
+                    .set_style(Style::default().fg(get_cursor_color(cursor.name)));
+            }
+        }
     }
 }
+
+#[test]
+fn render_time_test() {
+    assert_eq!(render_time(10, 1, 9), "10.0ns");
+    assert_eq!(render_time(12050, 1, 9), "12.05us");
+    assert_eq!(render_time(12500, 1, 9), "12.5us");
+    assert_eq!(render_time(2000000, 100000, 9), "2.0ms");
+}
+
+#[test]
+fn get_timestamps_test() {
+    assert_eq!(get_timestamps(15, -7, 9), 1500);
+    assert_eq!(get_timestamps(15, -10, 9), 1);
+    assert_eq!(get_timestamps(2, -9, 12), 2000);
+}
+
+#[test]
+fn named_cursor_test() {
+    let mut state = TimescaleState::new();
+    assert_eq!(state.get_free_cursor_name(), Some('A'));
+    state.set_named_cursor('B', 30);
+    state.set_named_cursor('A', 10);
+    state.set_named_cursor('B', 20);
+    assert_eq!(
+        state.get_named_cursors(),
+        &vec![
+            NamedCursor {
+                name: 'A',
+                timestamp: 10
+            },
+            NamedCursor {
+                name: 'B',
+                timestamp: 20
+            }
+        ]
+    );
+    assert_eq!(state.get_free_cursor_name(), Some('C'));
+    state.remove_named_cursor('A');
+    assert_eq!(state.get_free_cursor_name(), Some('A'));
+    assert_eq!(render_delta(30, 10, 9), "-20.0ns");
+}
+
+#[test]
+fn zoom_around_cursor_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(100..1100, 1100, 9);
+    state.set_cursor(600);
+    state.zoom_around_cursor(100);
+    assert_eq!(state.get_range(), 550..650);
+    state.set_cursor(120);
+    state.zoom_around_cursor(100);
+    assert_eq!(state.get_range(), 100..200);
+    state.set_cursor(1090);
+    state.zoom_around_cursor(100);
+    assert_eq!(state.get_range(), 1000..1100);
+    state.zoom_around_cursor(5000);
+    assert_eq!(state.get_range(), 100..1100);
+}
+
+#[test]
+fn empty_dump_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(0..0, 0, 9);
+    assert_eq!(state.get_range(), 0..EMPTY_DUMP_WIDTH);
+    assert_eq!(state.get_columns(4).len(), 4);
+    state.zoom_all();
+    assert_eq!(state.get_range(), 0..EMPTY_DUMP_WIDTH);
+    // A single timestamp keeps the view starting at it
+    state.load_waveform(5..6, 6, 9);
+    assert_eq!(state.get_range(), 5..5 + EMPTY_DUMP_WIDTH);
+    assert_eq!(state.get_cursor(), 5);
+    state.zoom_around_cursor(2);
+    assert_eq!(state.get_range(), 5..5 + EMPTY_DUMP_WIDTH);
+    // Zooming in stops at a single timestamp instead of an empty range
+    for _ in 0..8 {
+        state.zoom_in(false);
+    }
+    assert_eq!(state.get_range().end - state.get_range().start, 1);
+}
+
+#[test]
+fn zoom_history_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(0..1000, 1000, 9);
+    assert!(!state.history_back());
+    state.zoom_in(false);
+    assert_eq!(state.get_range(), 250..750);
+    state.zoom_left(false);
+    assert_eq!(state.get_range(), 0..500);
+    state.set_range(100..200);
+    assert!(state.history_back());
+    assert_eq!(state.get_range(), 0..500);
+    assert!(state.history_back());
+    assert_eq!(state.get_range(), 0..1000);
+    assert!(!state.history_back());
+    assert!(state.history_forward());
+    assert_eq!(state.get_range(), 0..500);
+    // Zooming again forgets where it went back from
+    state.zoom_all();
+    assert!(!state.history_forward());
+    assert!(state.history_back());
+    assert_eq!(state.get_range(), 0..500);
+}
+
+#[test]
+fn find_annotation_test() {
+    let mut state = TimescaleState::new();
+    for (timestamp, text) in [
+        (10, "tx 0x55"),
+        (20, "rx 0x12"),
+        (30, "TX 0x55"),
+        (30, "idle"),
+    ] {
+        state.add_annotation(timestamp, String::from(text));
+    }
+    assert_eq!(state.find_annotation("tx 0x55", 0, true), Some(10));
+    assert_eq!(state.find_annotation("tx 0x55", 10, true), Some(30));
+    assert_eq!(state.find_annotation("tx 0x55", 30, true), None);
+    assert_eq!(state.find_annotation("0x55", 30, false), Some(10));
+    assert_eq!(state.find_annotation("idle", 31, false), Some(30));
+    assert_eq!(state.find_annotation("rx", 10, false), None);
+}
+
+#[test]
+fn follow_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(0..1000, 1000, 9);
+    state.zoom_in(false);
+    state.set_follow(true);
+    assert_eq!(state.get_range(), 500..1000);
+    assert_eq!(state.get_follow(), Some(false));
+    // Zooming keeps the end at the right edge
+    state.zoom_in(false);
+    state.update_follow();
+    assert_eq!(state.get_range(), 750..1000);
+    state.load_waveform(0..1500, 1500, 9);
+    assert_eq!(state.get_range(), 1250..1500);
+    // Scrolling away pauses following until scrolled back to the end
+    state.zoom_left(false);
+    state.update_follow();
+    assert_eq!(state.get_follow(), Some(true));
+    state.load_waveform(0..2000, 2000, 9);
+    assert_eq!(state.get_range(), 1125..1375);
+    state.set_range(1800..2100);
+    state.update_follow();
+    assert_eq!(state.get_follow(), Some(false));
+    assert_eq!(state.get_range(), 1700..2000);
+    state.set_follow(false);
+    state.load_waveform(0..3000, 3000, 9);
+    assert_eq!(state.get_range(), 0..3000);
+}
+
+#[test]
+fn zoom_repeated_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(0..1 << 20, 1 << 20, 9);
+    state.set_acceleration(2.0);
+    let start = Instant::now();
+    let steps = [0, 100, 200, 300, 400]
+        .map(|ms| state.get_repeat_steps(ZoomAction::In, start + Duration::from_millis(ms)));
+    assert_eq!(steps, [1, 2, 4, 8, 8]);
+    // Waiting too long or switching keys starts over
+    let later = start + Duration::from_millis(1000);
+    assert_eq!(state.get_repeat_steps(ZoomAction::In, later), 1);
+    assert_eq!(state.get_repeat_steps(ZoomAction::Out, later), 1);
+    state.zoom_repeated(ZoomAction::In, false, later);
+    assert_eq!(state.get_width(), 1 << 19);
+    state.zoom_repeated(ZoomAction::In, false, later);
+    assert_eq!(state.get_width(), 1 << 17);
+    state.zoom_repeated(ZoomAction::In, false, later);
+    assert_eq!(state.get_width(), 1 << 13);
+    state.set_acceleration(1.0);
+    state.zoom_repeated(ZoomAction::In, false, later);
+    assert_eq!(state.get_width(), 1 << 12);
+    // Each keypress is a single step back, however many steps it zoomed
+    assert!(state.history_back());
+    assert_eq!(state.get_width(), 1 << 13);
+    assert!(state.history_back());
+    assert_eq!(state.get_width(), 1 << 17);
+}
+
+#[test]
+fn compress_columns_test() {
+    let range = 0..1600;
+    assert_eq!(get_idle_breaks(&[], &range), vec![]);
+    assert_eq!(
+        get_idle_breaks(&[100, 120, 1000], &range),
+        vec![170..950, 1050..1600]
+    );
+    let breaks = get_idle_breaks(&[500, 640], &range);
+    assert_eq!(breaks, vec![0..450, 690..1600]);
+    let columns = compress_columns(&range, &breaks, 32).unwrap();
+    assert_eq!(columns.len(), 32);
+    assert_eq!(columns[0], (0..450, true));
+    assert_eq!(columns[1], (450..458, false));
+    assert_eq!(columns[31], (690..1600, true));
+    // Too few columns left between the breaks
+    assert_eq!(compress_columns(&range, &breaks, 17), None);
+
+    let mut state = TimescaleState::new();
+    state.load_waveform(range, 1600, 9);
+    state.set_breaks(breaks);
+    assert_eq!(state.get_timestamp_column(600, 32), Some(12));
+    state.set_compressed(true);
+    assert_eq!(state.get_timestamp_column(100, 32), Some(0));
+    assert_eq!(state.get_timestamp_column(600, 32), Some(19));
+    assert_eq!(state.get_column_timestamp(19, 32), 594);
+    assert_eq!(state.get_timestamp_column(1599, 32), Some(31));
+}
+
+#[test]
+fn goto_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(100..1100, 1100, 9);
+    state.set_range(400..500);
+    state.goto(450);
+    assert_eq!(state.get_range(), 400..500);
+    state.goto(700);
+    assert_eq!(state.get_range(), 650..750);
+    // Going near either end keeps the range inside the dump
+    state.goto(1080);
+    assert_eq!((state.get_cursor(), state.get_range()), (1080, 1000..1100));
+    state.goto(110);
+    assert_eq!(state.get_range(), 100..200);
+    state.set_range(0..5000);
+    state.goto(6000);
+    assert_eq!(state.get_range(), 100..5100);
+}
+
+#[test]
+fn recenter_test() {
+    let mut state = TimescaleState::new();
+    state.load_waveform(100..1100, 1100, 9);
+    state.set_range(400..500);
+    state.recenter(700, true);
+    assert_eq!(state.get_range(), 650..750);
+    // Jumping close to the end stops there, and one step back undoes the
+    // whole jump however far it went on
+    state.recenter(900, false);
+    state.recenter(1090, false);
+    assert_eq!(state.get_range(), 1000..1100);
+    assert!(state.history_back());
+    assert_eq!(state.get_range(), 400..500);
+}
@@ -8,32 +8,58 @@ use makai_waveform_db::{
 use tui::{
     buffer::Buffer,
     layout::{Alignment, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
     widgets::{Paragraph, Widget},
 };
 
-use super::timescale::TimescaleState;
+use crate::{
+    analysis::get_transitions, loader::decode_real, python::color::ColorRule,
+    source::WaveformSource,
+};
+
+use super::timescale::{TimescaleState, BREAK_SYMBOL};
 
-pub struct WaveformWidget<'a> {
+/// One row of a signal over the range of a [`TimescaleState`], drawn as the
+/// values of a vector, the level of a single bit, the occurrences of an event
+/// or a row of an analog plot. The values come from any [`WaveformSource`],
+/// which is usually the [`Waveform`] a dump was loaded into.
+pub struct WaveformWidget<'a, W: WaveformSource + ?Sized = Waveform> {
     /// The timescale range and cursor position to render
     timescale_state: &'a TimescaleState,
     /// The waveform container to query
-    waveform: &'a Waveform,
+    waveform: &'a W,
     /// The idcode of the signal to render
     idcode: usize,
     /// Optionally what bit-index of a multi-bit vector to render
     bit_index: Option<usize>,
     /// How to render the signal values
     radix: BitVectorRadix,
+    /// If the signal is a real stored as the bits of a float
+    is_real: bool,
+    /// If the signal is an event, which only has occurrences and no values
+    is_event: bool,
     /// If the signal itself is selected
     is_selected: bool,
+    /// If the signal is the primary selection (otherwise part of a range)
+    is_primary: bool,
+    /// Which row out of how many of an analog plot to render instead of the
+    /// values for real signals, or of the rows other signals are drawn over
+    plot_row: Option<(usize, usize)>,
+    /// A coloring rule from python that picks the color of each value
+    color_rule: Option<&'a ColorRule>,
+    /// If single bits are drawn with braille dots for more columns per cell
+    high_resolution: bool,
+    /// Timestamps the signal is drawn later by (or earlier when negative),
+    /// for lining up signals sampled at different points of a pipeline
+    offset: i64,
 }
 
-impl<'a> WaveformWidget<'a> {
+impl<'a, W: WaveformSource + ?Sized> WaveformWidget<'a, W> {
+    /// Draws the signal with the idcode from the waveform, or one bit of it
     pub fn new(
         timescale_state: &'a TimescaleState,
-        waveform: &'a Waveform,
+        waveform: &'a W,
         idcode: usize,
         bit_index: Option<usize>,
         radix: BitVectorRadix,
@@ -45,11 +71,148 @@ impl<'a> WaveformWidget<'a> {
             idcode,
             bit_index,
             radix,
+            is_real: false,
+            is_event: false,
             is_selected,
+            is_primary: true,
+            plot_row: None,
+            color_rule: None,
+            high_resolution: false,
+            offset: 0,
         }
     }
+
+    pub fn primary(mut self, is_primary: bool) -> Self {
+        self.is_primary = is_primary;
+        self
+    }
+
+    pub fn real(mut self, is_real: bool) -> Self {
+        self.is_real = is_real;
+        self
+    }
+
+    pub fn event(mut self, is_event: bool) -> Self {
+        self.is_event = is_event;
+        self
+    }
+
+    pub fn plot_row(mut self, plot_row: Option<(usize, usize)>) -> Self {
+        self.plot_row = plot_row;
+        self
+    }
+
+    pub fn color_rule(mut self, color_rule: Option<&'a ColorRule>) -> Self {
+        self.color_rule = color_rule;
+        self
+    }
+
+    pub fn high_resolution(mut self, high_resolution: bool) -> Self {
+        self.high_resolution = high_resolution;
+        self
+    }
+
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = offset;
+        self
+    }
 }
 
+/// Returns the background color of a waveform row, accented when selected to
+/// match the selection styles of the browser
+fn get_background(is_selected: bool, is_primary: bool) -> Color {
+    match (is_selected, is_primary) {
+        (true, true) => Color::Rgb(80, 80, 80),
+        (true, false) => Color::Rgb(40, 40, 40),
+        (false, _) => Color::Black,
+    }
+}
+
+/// Moves a range of displayed time to the time of the signal values drawn
+/// there, where anything before the start of the dump ends up at zero
+fn get_offset_range(range: Range<u64>, offset: i64) -> Range<u64> {
+    let shift = |timestamp: u64| match offset {
+        offset if offset < 0 => timestamp.saturating_add(offset.unsigned_abs()),
+        offset => timestamp.saturating_sub(offset as u64),
+    };
+    shift(range.start)..shift(range.end)
+}
+
+/// Draws a squiggle over the columns standing in for stretches of time where
+/// nothing changes, keeping the colors of the row
+fn render_breaks(columns: &[(Range<u64>, bool)], area: Rect, buf: &mut Buffer) {
+    for (x, (_, is_break)) in columns.iter().enumerate() {
+        if *is_break && x < area.width as usize {
+            let cell = buf.get_mut(area.x + x as u16, area.y);
+            cell.set_symbol(BREAK_SYMBOL).set_fg(Color::DarkGray);
+        }
+    }
+}
+
+/// Drawn across the row of a signal without a single value in the dump, so
+/// it is not mistaken for one that is still loading or drawn wrong
+const NO_DATA: &str = "no data";
+
+fn render_no_data(area: Rect, buf: &mut Buffer, background: Color, is_middle: bool) {
+    buf.set_style(area, Style::default().bg(background));
+    if is_middle {
+        let style = Style::default().fg(Color::DarkGray).bg(background);
+        buf.set_stringn(area.x, area.y, NO_DATA, area.width as usize, style);
+    }
+}
+
+/// Finds the lowest and highest value a real signal takes in each column of
+/// the timestamp range
+fn get_envelope(
+    waveform: &(impl WaveformSource + ?Sized),
+    idcode: usize,
+    range: Range<u64>,
+    columns: usize,
+) -> Vec<Option<(f64, f64)>> {
+    let mut envelope: Vec<Option<(f64, f64)>> = vec![None; columns];
+    if columns == 0 || range.is_empty() {
+        return envelope;
+    }
+    let mut include = |column: usize, value: f64| {
+        envelope[column] = Some(match envelope[column] {
+            Some((low, high)) => (low.min(value), high.max(value)),
+            None => (value, value),
+        });
+    };
+    let get_column = |timestamp: u64| {
+        (((timestamp - range.start) as u128 * columns as u128) / (range.end - range.start) as u128)
+            as usize
+    };
+    // Starts from the value held going into the range
+    let mut value = None;
+    let mut column = 0;
+    for (timestamp, bitvector) in waveform.get_vector_changes(idcode, range.start) {
+        if timestamp >= range.end {
+            break;
+        } else if timestamp < range.start {
+            value = Some(decode_real(&bitvector));
+            continue;
+        }
+        let next_column = get_column(timestamp);
+        if let Some(value) = value {
+            (column..=next_column).for_each(|c| include(c, value));
+        }
+        column = next_column;
+        value = Some(decode_real(&bitvector));
+    }
+    if let Some(value) = value {
+        (column..columns).for_each(|c| include(c, value));
+    }
+    envelope
+}
+
+/// Columns of time drawn in each cell by the high resolution mode, one for
+/// each column of braille dots
+const BRAILLE_COLUMNS: u64 = 2;
+
+/// Braille dots from top to bottom in the left and right columns of a cell
+const BRAILLE_DOTS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
 #[derive(Clone, Debug)]
 enum WaveformQuery {
     SingleEdge(WaveformValueResult, usize),
@@ -59,8 +222,56 @@ enum WaveformQuery {
     None(usize),
 }
 
+/// Fills a run of columns with several edges each, putting how many
+/// transitions there are in the middle when it fits with a `#` on each side
+fn label_edges(width: usize, transitions: usize) -> String {
+    let count = transitions.to_string();
+    if transitions < 2 || count.len() + 2 > width {
+        return "#".repeat(width);
+    }
+    let left = (width - count.len()) / 2;
+    format!(
+        "{}{count}{}",
+        "#".repeat(left),
+        "#".repeat(width - count.len() - left)
+    )
+}
+
+/// Pads the text out to the width, or cuts it down to the width with an
+/// ellipsis in the last column to show something is missing
+fn fit_text(raw: &str, width: usize) -> String {
+    let chars = raw.chars().count();
+    if chars <= width {
+        format!("{raw}{}", " ".repeat(width - chars))
+    } else if width < 2 {
+        raw.chars().take(width).collect()
+    } else {
+        format!("{}…", raw.chars().take(width - 1).collect::<String>())
+    }
+}
+
 impl WaveformQuery {
-    fn get_span(&self, radix: BitVectorRadix, _is_selected: bool) -> (String, Style) {
+    fn get_width(&self) -> usize {
+        match self {
+            Self::SingleEdge(_, width)
+            | Self::MultipleEdge(width)
+            | Self::Static(_, width)
+            | Self::StaticVoid(_, width)
+            | Self::None(width) => *width,
+        }
+    }
+
+    /// The text of one row out of how many the signal is drawn over, where
+    /// high bits fill every row, low bits only the last and values are written
+    /// in the middle one, which can be longer than the query is wide
+    fn get_span(
+        &self,
+        radix: BitVectorRadix,
+        is_real: bool,
+        background: Color,
+        color_rule: Option<&ColorRule>,
+        (row, rows): (usize, usize),
+    ) -> (String, Style) {
         let (value, width, is_void, is_delta) = match self {
             Self::Static(value, width) => (value, width, false, false),
             Self::StaticVoid(value, width) => (value, width, true, false),
@@ -74,7 +285,7 @@ impl WaveformQuery {
             Self::None(width) => {
                 return (
                     " ".repeat(*width),
-                    Style::default().fg(Color::White).bg(Color::Black),
+                    Style::default().fg(Color::White).bg(background),
                 )
             }
         };
@@ -82,22 +293,35 @@ impl WaveformQuery {
         let style = if is_void {
             Style::default().fg(Color::Gray).bg(Color::Gray)
         } else if value.is_unknown() {
-            Style::default().fg(Color::Red).bg(Color::Black)
+            Style::default().fg(Color::Red).bg(background)
         } else if value.is_high_impedance() {
-            Style::default().fg(Color::Blue).bg(Color::Black)
+            Style::default().fg(Color::Blue).bg(background)
         } else {
-            Style::default().fg(Color::White).bg(Color::Black)
+            let color = color_rule.and_then(|rule| rule.get_color(value, is_real));
+            Style::default()
+                .fg(color.unwrap_or(Color::White))
+                .bg(background)
         };
 
         let raw = match value {
+            WaveformValueResult::Vector(bv, _) if is_real => {
+                if is_delta {
+                    format!("|{}", decode_real(bv))
+                } else {
+                    format!("{}", decode_real(bv))
+                }
+            }
             WaveformValueResult::Vector(bv, _) => {
                 if bv.get_bit_width() <= 1 {
                     match bv.get_bit(0) {
+                        Logic::Zero if row + 1 < rows => " ".repeat(*width),
                         Logic::Zero => "_".repeat(*width),
                         Logic::One => "█".repeat(*width),
                         Logic::Unknown => "X".repeat(*width),
                         Logic::HighImpedance => "Z".repeat(*width),
                     }
+                } else if row != (rows - 1) / 2 {
+                    String::from(if is_delta { "|" } else { "" })
                 } else if is_delta {
                     format!("|{}", bv.to_string_radix(radix))
                 } else {
@@ -113,39 +337,27 @@ impl WaveformQuery {
             }
         };
 
-        let mut sized = String::with_capacity(raw.as_bytes().len());
-        let mut chars = 0;
-        for (i, c) in raw.chars().enumerate() {
-            if i < *width {
-                sized.push(c);
-                chars += 1;
-            }
-        }
-        if chars < *width {
-            for _ in 0..(*width - chars) {
-                sized.push(' ');
-            }
-        }
-        (sized, style)
+        (raw, style)
     }
 }
 
-impl<'a> WaveformWidget<'a> {
+impl<'a, W: WaveformSource + ?Sized> WaveformWidget<'a, W> {
     fn get_query(&self, timestamp_range: Range<u64>) -> WaveformQuery {
+        let timestamp_range = get_offset_range(timestamp_range, self.offset);
         // Find the timestamp indices that are contained by the timestamp range
-        if timestamp_range.end == 0 {
+        if timestamp_range.end == 0 || !self.waveform.has_history(self.idcode) {
             return WaveformQuery::None(1);
         }
-        let Some(timestamp_index_start) = self.waveform.search_timestamp(
-            timestamp_range.start,
-            WaveformSearchMode::After
-        ) else {
+        let Some(timestamp_index_start) = self
+            .waveform
+            .search_timestamp(timestamp_range.start, WaveformSearchMode::After)
+        else {
             return WaveformQuery::None(1);
         };
-        let Some(timestamp_index_end) = self.waveform.search_timestamp(
-            timestamp_range.end - 1,
-            WaveformSearchMode::Before
-        ) else {
+        let Some(timestamp_index_end) = self
+            .waveform
+            .search_timestamp(timestamp_range.end - 1, WaveformSearchMode::Before)
+        else {
             return WaveformQuery::None(1);
         };
         // Check if there is a value available
@@ -173,7 +385,7 @@ impl<'a> WaveformWidget<'a> {
             self.idcode,
             result.get_timestamp_index() - 1,
             WaveformSearchMode::Before,
-            self.bit_index
+            self.bit_index,
         ) else {
             return WaveformQuery::SingleEdge(result, 1);
         };
@@ -185,23 +397,202 @@ impl<'a> WaveformWidget<'a> {
     }
 }
 
-impl<'a> Widget for WaveformWidget<'a> {
+impl<'a, W: WaveformSource + ?Sized> WaveformWidget<'a, W> {
+    /// Draws one row of an analog plot scaled to the lowest and highest values
+    /// in view, with each cell split into two levels using half blocks
+    fn render_plot(
+        &self,
+        columns: &[(Range<u64>, bool)],
+        area: Rect,
+        buf: &mut Buffer,
+        row: usize,
+        rows: usize,
+    ) {
+        let is_compressed = columns.iter().any(|(_, is_break)| *is_break);
+        let envelope: Vec<Option<(f64, f64)>> = if is_compressed {
+            // Columns no longer cover even stretches of time when compressed
+            columns
+                .iter()
+                .map(|(range, _)| {
+                    let range = get_offset_range(range.clone(), self.offset);
+                    get_envelope(self.waveform, self.idcode, range, 1)[0]
+                })
+                .collect()
+        } else {
+            // Columns shifted from before the start of the dump stay empty
+            let range = self.timescale_state.get_range();
+            let width = range.end - range.start;
+            let before = (self.offset.max(0) as u64).saturating_sub(range.start);
+            let empty =
+                ((before.min(width) as u128 * area.width as u128) / width.max(1) as u128) as usize;
+            let mut envelope = vec![None; empty];
+            envelope.extend(get_envelope(
+                self.waveform,
+                self.idcode,
+                get_offset_range(range, self.offset),
+                area.width as usize - empty,
+            ));
+            envelope
+        };
+        let low = envelope.iter().flatten().map(|(l, _)| *l).reduce(f64::min);
+        let high = envelope.iter().flatten().map(|(_, h)| *h).reduce(f64::max);
+        let background = get_background(self.is_selected, self.is_primary);
+        buf.set_style(area, Style::default().fg(Color::LightGreen).bg(background));
+        let (Some(low), Some(high)) = (low, high) else {
+            return;
+        };
+        let levels = rows * 2;
+        let get_level = |value: f64| match high - low {
+            span if span > 0.0 => ((value - low) / span * (levels - 1) as f64).round() as usize,
+            _ => levels / 2,
+        };
+        // Levels of the top and bottom half of the cells in this row
+        let top = levels - 1 - row * 2;
+        let bottom = top - 1;
+        for (x, column) in envelope.iter().enumerate() {
+            let Some((column_low, column_high)) = column else {
+                continue;
+            };
+            let covered = get_level(*column_low)..=get_level(*column_high);
+            let symbol = match (covered.contains(&top), covered.contains(&bottom)) {
+                (true, true) => "█",
+                (true, false) => "▀",
+                (false, true) => "▄",
+                (false, false) => continue,
+            };
+            buf.get_mut(area.x + x as u16, area.y).set_symbol(symbol);
+        }
+        // Label the scale in the top and bottom rows
+        let label = match row {
+            0 => format!("{high}"),
+            r if r + 1 == rows => format!("{low}"),
+            _ => return,
+        };
+        buf.set_stringn(
+            area.x,
+            area.y,
+            label,
+            area.width as usize,
+            Style::default().fg(Color::White).bg(background),
+        );
+    }
+}
+
+impl<'a, W: WaveformSource + ?Sized> WaveformWidget<'a, W> {
+    /// Draws a single bit with two columns of braille dots per cell, the top
+    /// dot when high, the bottom dot when low and the whole column for edges,
+    /// so pulses narrower than a cell still show up in the right place
+    fn render_high_resolution(
+        &self,
+        cell_columns: &[(Range<u64>, bool)],
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let background = get_background(self.is_selected, self.is_primary);
+        buf.set_style(area, Style::default().fg(Color::White).bg(background));
+        let range = self.timescale_state.get_range();
+        let columns = area.width as u64 * BRAILLE_COLUMNS;
+        let width = range.end - range.start;
+        let is_compressed = cell_columns.iter().any(|(_, is_break)| *is_break);
+        for x in 0..area.width {
+            let mut dots = 0;
+            let mut style = Style::default().fg(Color::White).bg(background);
+            for (i, column_dots) in BRAILLE_DOTS.iter().enumerate() {
+                let column = x as u64 * BRAILLE_COLUMNS + i as u64;
+                let query = match cell_columns.get(x as usize) {
+                    Some((cell, _)) if is_compressed => {
+                        let split =
+                            |i: u64| cell.start + i * (cell.end - cell.start) / BRAILLE_COLUMNS;
+                        self.get_query(split(i as u64)..split(i as u64 + 1))
+                    }
+                    _ => self.get_query(
+                        range.start + column * width / columns
+                            ..range.start + (column + 1) * width / columns,
+                    ),
+                };
+                let value = match &query {
+                    WaveformQuery::Static(value, _) => value,
+                    WaveformQuery::StaticVoid(_, _) => {
+                        style = style.bg(Color::Gray);
+                        continue;
+                    }
+                    WaveformQuery::SingleEdge(_, _) | WaveformQuery::MultipleEdge(_) => {
+                        dots |= column_dots.iter().fold(0, |dots, dot| dots | dot);
+                        continue;
+                    }
+                    WaveformQuery::None(_) => continue,
+                };
+                let WaveformValueResult::Vector(bv, _) = value else {
+                    continue;
+                };
+                dots |= match bv.get_bit(0) {
+                    Logic::One => column_dots[0],
+                    Logic::Zero => column_dots[3],
+                    Logic::Unknown => {
+                        style = style.fg(Color::Red);
+                        column_dots[1] | column_dots[2]
+                    }
+                    Logic::HighImpedance => {
+                        style = style.fg(Color::Blue);
+                        column_dots[1] | column_dots[2]
+                    }
+                };
+            }
+            let symbol = char::from_u32(0x2800 + dots as u32).unwrap_or(' ');
+            buf.get_mut(area.x + x, area.y)
+                .set_symbol(&symbol.to_string())
+                .set_style(style);
+        }
+    }
+
+    /// Draws a tick in every column an event occurred in, marking columns
+    /// with more than one occurrence differently
+    fn render_events(&self, queries: &[WaveformQuery], area: Rect, buf: &mut Buffer) {
+        let background = get_background(self.is_selected, self.is_primary);
+        buf.set_style(area, Style::default().fg(Color::LightYellow).bg(background));
+        for (x, query) in queries.iter().enumerate() {
+            let symbol = match query {
+                WaveformQuery::SingleEdge(_, _) => "↑",
+                WaveformQuery::MultipleEdge(_) => "⇑",
+                _ => continue,
+            };
+            buf.get_mut(area.x + x as u16, area.y).set_symbol(symbol);
+        }
+    }
+}
+
+impl<'a, W: WaveformSource + ?Sized> Widget for WaveformWidget<'a, W> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let timestamp_width =
-            self.timescale_state.get_range().end - self.timescale_state.get_range().start;
+        if !self.waveform.has_history(self.idcode) {
+            let (row, rows) = self.plot_row.unwrap_or((0, 1));
+            let background = get_background(self.is_selected, self.is_primary);
+            render_no_data(area, buf, background, row == (rows - 1) / 2);
+            return;
+        }
+        let columns = self.timescale_state.get_columns(area.width);
+        if let (Some((row, rows)), true) = (self.plot_row, self.is_real) {
+            self.render_plot(&columns, area, buf, row, rows);
+            render_breaks(&columns, area, buf);
+            return;
+        }
+        let is_bit =
+            self.bit_index.is_some() || self.waveform.get_vector_width(self.idcode) == Some(1);
+        let line = self.plot_row.unwrap_or((0, 1));
+        if self.high_resolution && is_bit && !self.is_real && !self.is_event && line.1 == 1 {
+            self.render_high_resolution(&columns, area, buf);
+            render_breaks(&columns, area, buf);
+            return;
+        }
         // Create list of queries, one for each character on the screen
-        let queries = (0..area.width as u64)
-            .map(|i| {
-                (i * timestamp_width / area.width as u64)
-                    ..((i + 1) * timestamp_width / area.width as u64)
-            })
-            .map(|range| {
-                range.start + self.timescale_state.get_range().start
-                    ..range.end + self.timescale_state.get_range().start
-            })
-            .map(|range| self.get_query(range))
+        let queries = columns
+            .iter()
+            .map(|(range, _)| self.get_query(range.clone()))
             .collect::<Vec<WaveformQuery>>();
-
+        if self.is_event {
+            self.render_events(&queries, area, buf);
+            render_breaks(&columns, area, buf);
+            return;
+        }
         // Merge queries together when possible
         let mut queries_compressed: Vec<WaveformQuery> = Vec::with_capacity(queries.len());
         for query in queries.into_iter() {
@@ -238,154 +629,300 @@ impl<'a> Widget for WaveformWidget<'a> {
 
         // Render queries into a set of styled spans
         let mut spans = Vec::new();
+        let mut column = 0;
+        let background = get_background(self.is_selected, self.is_primary);
+        let cursor = self
+            .timescale_state
+            .get_timestamp_column(self.timescale_state.get_cursor(), area.width)
+            .map(usize::from);
+        // The value under the cursor when it did not fit, written out in full
+        let mut expanded = None;
         for query in queries_compressed {
-            let (string, style) = query.get_span(self.radix, self.is_selected);
+            let (raw, style) =
+                query.get_span(self.radix, self.is_real, background, self.color_rule, line);
+            let width = query.get_width();
+            let mut string = fit_text(&raw, width);
+            let is_middle = line.0 == (line.1 - 1) / 2;
+            let under_cursor = cursor.is_some_and(|c| (column..column + width).contains(&c));
+            if self.is_primary && under_cursor && raw.chars().count() > width {
+                expanded = Some((column, raw, style));
+            }
+            // Only count the transitions of runs wide enough to show them, on
+            // the middle row like the values
+            if matches!(query, WaveformQuery::MultipleEdge(_)) && width >= 4 && is_middle {
+                let range = columns[column].0.start..columns[column + width - 1].0.end;
+                let transitions = get_transitions(
+                    self.waveform,
+                    self.idcode,
+                    self.bit_index,
+                    &get_offset_range(range, self.offset),
+                );
+                string = label_edges(width, transitions.len());
+            }
+            column += width;
             spans.push(Span::styled(string, style));
         }
 
         Paragraph::new(Text::from(Spans::from(spans)))
             .alignment(Alignment::Left)
-            .render(area, buf)
+            .render(area, buf);
+        // Drawn over the values on either side, moved left to fit if needed
+        if let Some((column, raw, style)) = expanded {
+            let chars = raw.chars().count();
+            let x = column.min((area.width as usize).saturating_sub(chars)) as u16;
+            buf.set_stringn(
+                area.x + x,
+                area.y,
+                raw,
+                (area.width - x) as usize,
+                style.add_modifier(Modifier::BOLD),
+            );
+        }
+        render_breaks(&columns, area, buf);
     }
 }
 
 #[test]
-fn signal_render_test() {
-    use std::sync::{Arc, Mutex};
-    use std::thread;
-    // use waveform_db::*;
-
-    let fname = "res/gecko.vcd";
-
-    // Read VCD file header and build out waveform structure
-    let bytes = std::fs::read_to_string(fname).unwrap();
-    let status = Arc::new(Mutex::new((0, 0)));
-    let handle = makai_vcd_reader::utils::load_multi_threaded(bytes, 4, status.clone());
-    loop {
-        let (pos, total) = *status.lock().unwrap();
-        if pos >= total && total > 0 {
-            break;
-        }
-        thread::sleep(std::time::Duration::from_millis(10));
+fn envelope_test() {
+    use crate::loader::encode_real;
+
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 64);
+    for (timestamp, value) in [(0, 1.0), (10, 2.0), (12, -1.0), (30, 4.0)] {
+        waveform.insert_timestamp(timestamp).unwrap();
+        waveform.update_vector(0, encode_real(value)).unwrap();
     }
-    let (header, waveform) = handle.join().unwrap().unwrap();
-
-    let timestamp_range = 0u64..100u64;
-
-    let idcode = header.get_variable("TOP.clk").unwrap().get_idcode();
-
-    let mut timescale_state = TimescaleState::new();
-    timescale_state.load_waveform(
-        timestamp_range.clone(),
-        waveform.get_timestamp_range().end,
-        header.get_timescale().unwrap(),
+    assert_eq!(decode_real(&encode_real(-1.5)), -1.5);
+    assert_eq!(
+        get_envelope(&waveform, 0, 0..40, 4),
+        vec![
+            Some((1.0, 1.0)),
+            Some((-1.0, 2.0)),
+            Some((-1.0, -1.0)),
+            Some((-1.0, 4.0))
+        ]
     );
-
-    println!("Timescale: {}", header.get_timescale().unwrap());
-    println!("Timestamp range: {:?}", waveform.get_timestamp_range());
-
-    timescale_state.load_waveform(
-        0..100,
-        waveform.get_timestamp_range().end,
-        header.get_timescale().unwrap(),
+    assert_eq!(
+        get_envelope(&waveform, 0, 15..25, 2),
+        vec![Some((-1.0, -1.0)), Some((-1.0, -1.0))]
     );
+    assert_eq!(get_envelope(&waveform, 1, 0..40, 2), vec![None, None]);
+}
 
-    let rect = Rect::new(0, 0, 50, 1);
-    let mut buffer = Buffer::empty(rect.clone());
-    WaveformWidget::new(
-        &timescale_state,
-        &waveform,
-        idcode,
-        None,
-        BitVectorRadix::Hexadecimal,
-        false,
-    )
-    .render(rect, &mut buffer);
-    for x in 0..rect.width {
-        print!("{}", buffer.get(x, 0).symbol);
-    }
-    println!();
+#[test]
+fn label_edges_test() {
+    assert_eq!(label_edges(4, 12), "#12#");
+    assert_eq!(label_edges(7, 12), "##12###");
+    assert_eq!(label_edges(3, 12), "###");
+    assert_eq!(label_edges(5, 1), "#####");
+}
 
-    let rect = Rect::new(0, 0, 100, 1);
-    let mut buffer = Buffer::empty(rect.clone());
-    WaveformWidget::new(
-        &timescale_state,
-        &waveform,
-        idcode,
-        None,
-        BitVectorRadix::Hexadecimal,
-        false,
-    )
-    .render(rect, &mut buffer);
-    for x in 0..rect.width {
-        print!("{}", buffer.get(x, 0).symbol);
+#[test]
+fn tall_render_test() {
+    use makai_waveform_db::bitvector::BitVector;
+
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    waveform.initialize_vector(1, 8);
+    for timestamp in 0..2u64 {
+        waveform.insert_timestamp(timestamp * 4).unwrap();
+        let bit = match timestamp {
+            0 => BitVector::new_zero_bit(),
+            _ => BitVector::new_one_bit(),
+        };
+        waveform.update_vector(0, bit).unwrap();
+        waveform
+            .update_vector(1, BitVector::from_bits_two_state(8, 10 + timestamp as u8))
+            .unwrap();
     }
-    println!();
+    waveform.insert_timestamp(8).unwrap();
+    let mut timescale_state = TimescaleState::new();
+    timescale_state.load_waveform(0..8, 9, 9);
+    let render = |idcode, row| {
+        let rect = Rect::new(0, 0, 8, 1);
+        let mut buffer = Buffer::empty(rect);
+        WaveformWidget::new(
+            &timescale_state,
+            &waveform,
+            idcode,
+            None,
+            BitVectorRadix::Hexadecimal,
+            false,
+        )
+        .plot_row(row)
+        .high_resolution(true)
+        .render(rect, &mut buffer);
+        (0..rect.width)
+            .map(|x| buffer.get(x, 0).symbol.as_str())
+            .collect::<String>()
+    };
+    assert_eq!(render(0, Some((0, 3))), "    ████");
+    assert_eq!(render(0, Some((2, 3))), "____████");
+    assert_eq!(render(1, Some((0, 3))), "|   |   ");
+    assert_eq!(render(1, Some((1, 3))), "|h0A|h0B");
+}
 
-    timescale_state.load_waveform(
-        0..500,
-        waveform.get_timestamp_range().end,
-        header.get_timescale().unwrap(),
-    );
+#[test]
+fn fit_text_test() {
+    assert_eq!(fit_text("h0A", 5), "h0A  ");
+    assert_eq!(fit_text("h0A", 3), "h0A");
+    assert_eq!(fit_text("h000A", 3), "h0…");
+    assert_eq!(fit_text("|h000A", 2), "|…");
+    assert_eq!(fit_text("|h000A", 1), "|");
+}
 
-    let rect = Rect::new(0, 0, 400, 1);
-    let mut buffer = Buffer::empty(rect.clone());
-    WaveformWidget::new(
-        &timescale_state,
-        &waveform,
-        idcode,
-        None,
-        BitVectorRadix::Hexadecimal,
-        false,
-    )
-    .render(rect, &mut buffer);
-    for x in 0..rect.width {
-        print!("{}", buffer.get(x, 0).symbol);
+#[test]
+fn truncated_render_test() {
+    use makai_waveform_db::bitvector::BitVector;
+
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 16);
+    for timestamp in 0..4u64 {
+        waveform.insert_timestamp(timestamp * 2).unwrap();
+        waveform
+            .update_vector(0, BitVector::from_bits_two_state(16, 10 + timestamp as u8))
+            .unwrap();
     }
-    println!();
-
-    let idcode = header.get_variable("TOP.rst").unwrap().get_idcode();
+    waveform.insert_timestamp(8).unwrap();
+    let mut timescale_state = TimescaleState::new();
+    timescale_state.load_waveform(0..8, 9, 9);
+    timescale_state.set_cursor(4);
+    let render = |is_primary| {
+        let rect = Rect::new(0, 0, 8, 1);
+        let mut buffer = Buffer::empty(rect);
+        WaveformWidget::new(
+            &timescale_state,
+            &waveform,
+            0,
+            None,
+            BitVectorRadix::Hexadecimal,
+            false,
+        )
+        .primary(is_primary)
+        .render(rect, &mut buffer);
+        (0..rect.width)
+            .map(|x| buffer.get(x, 0).symbol.as_str())
+            .collect::<String>()
+    };
+    assert_eq!(render(false), "|…|…|…|…");
+    // The value under the cursor is written out over its neighbours
+    assert_eq!(render(true), "|…|h000C");
+}
 
-    let rect = Rect::new(0, 0, 400, 1);
-    let mut buffer = Buffer::empty(rect.clone());
+#[test]
+fn compressed_render_test() {
+    use super::timescale::get_idle_breaks;
+    use makai_waveform_db::bitvector::BitVector;
+
+    let mut waveform = Waveform::new();
+    waveform.initialize_vector(0, 1);
+    waveform.insert_timestamp(0).unwrap();
+    waveform
+        .update_vector(0, BitVector::new_zero_bit())
+        .unwrap();
+    waveform.insert_timestamp(800).unwrap();
+    waveform.update_vector(0, BitVector::new_one_bit()).unwrap();
+    waveform.insert_timestamp(1600).unwrap();
+    let mut timescale_state = TimescaleState::new();
+    timescale_state.load_waveform(0..1600, 1600, 9);
+    timescale_state.set_breaks(get_idle_breaks(&[800], &(0..1600)));
+    timescale_state.set_compressed(true);
+    let rect = Rect::new(0, 0, 20, 1);
+    let mut buffer = Buffer::empty(rect);
     WaveformWidget::new(
         &timescale_state,
         &waveform,
-        idcode,
+        0,
         None,
         BitVectorRadix::Hexadecimal,
         false,
     )
     .render(rect, &mut buffer);
-    for x in 0..rect.width {
-        print!("{}", buffer.get(x, 0).symbol);
-    }
-    println!();
+    let render = (0..rect.width)
+        .map(|x| buffer.get(x, 0).symbol.as_str())
+        .collect::<String>();
+    assert_eq!(render, "≈_________█████████≈");
+}
 
-    let idcode = header
-        .get_variable("TOP.gecko_nano_wrapper.inst.core.data_request.write_enable")
-        .unwrap()
-        .get_idcode();
+#[test]
+fn signal_render_test() {
+    use crate::fixture::FixtureBuilder;
 
+    let fixture = FixtureBuilder::default()
+        .clock("clk", 10, 20)
+        .counter("count", 8, 4, 20)
+        .pattern("data", &[(0, "0"), (4, "x"), (8, "z"), (12, "1")])
+        .build();
+    let mut timescale_state = TimescaleState::new();
     timescale_state.load_waveform(
-        0..1000,
-        waveform.get_timestamp_range().end,
-        header.get_timescale().unwrap(),
+        0..16,
+        fixture.end + 1,
+        fixture.header.get_timescale().unwrap(),
     );
+    let render = |name| {
+        let rect = Rect::new(0, 0, 16, 1);
+        let mut buffer = Buffer::empty(rect);
+        WaveformWidget::new(
+            &timescale_state,
+            &fixture.waveform,
+            fixture.get_idcode(name),
+            None,
+            BitVectorRadix::Hexadecimal,
+            false,
+        )
+        .render(rect, &mut buffer);
+        (0..rect.width)
+            .map(|x| buffer.get(x, 0).symbol.as_str())
+            .collect::<String>()
+    };
+    assert_eq!(render("clk"), "_____█████_____█");
+    assert_eq!(render("count"), "|h00|h01|h02|h03");
+    assert_eq!(render("data"), "____XXXXZZZZ████");
+}
 
-    let rect = Rect::new(0, 0, 400, 1);
-    let mut buffer = Buffer::empty(rect.clone());
-    WaveformWidget::new(
-        &timescale_state,
-        &waveform,
-        idcode,
-        None,
-        BitVectorRadix::Hexadecimal,
-        false,
-    )
-    .render(rect, &mut buffer);
-    for x in 0..rect.width {
-        print!("{}", buffer.get(x, 0).symbol);
-    }
-    println!();
+#[test]
+fn empty_render_test() {
+    use crate::fixture::FixtureBuilder;
+
+    // A signal that never changes next to a dump of a single timestamp
+    let fixture = FixtureBuilder::default()
+        .pattern("idle", &[])
+        .pattern("data", &[(0, "1")])
+        .build();
+    assert_eq!(fixture.end, 0);
+    let range = fixture.waveform.get_timestamp_range();
+    let mut timescale_state = TimescaleState::new();
+    timescale_state.load_waveform(range.clone(), range.end, 9);
+    let render = |name, plot_row| {
+        let rect = Rect::new(0, 0, 10, 1);
+        let mut buffer = Buffer::empty(rect);
+        WaveformWidget::new(
+            &timescale_state,
+            &fixture.waveform,
+            fixture.get_idcode(name),
+            None,
+            BitVectorRadix::Hexadecimal,
+            false,
+        )
+        .plot_row(plot_row)
+        .render(rect, &mut buffer);
+        (0..rect.width)
+            .map(|x| buffer.get(x, 0).symbol.as_str())
+            .collect::<String>()
+    };
+    assert_eq!(render("idle", None), "no data   ");
+    assert_eq!(render("idle", Some((0, 3))), "          ");
+    assert_eq!(render("idle", Some((1, 3))), "no data   ");
+    // The value at the only timestamp is drawn, followed by the void after it
+    assert_eq!(render("data", None).chars().next(), Some('█'));
+}
+
+#[test]
+fn offset_range_test() {
+    assert_eq!(get_offset_range(10..20, 0), 10..20);
+    assert_eq!(get_offset_range(10..20, 5), 5..15);
+    assert_eq!(get_offset_range(10..20, -5), 15..25);
+    // Columns from before the start of the dump are empty
+    assert_eq!(get_offset_range(10..20, 30), 0..0);
+    assert_eq!(get_offset_range(10..20, 15), 0..5);
 }